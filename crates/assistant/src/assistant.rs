@@ -10,12 +10,13 @@ pub use assistant_panel::AssistantPanel;
 use assistant_settings::OpenAiModel;
 use chrono::{DateTime, Local};
 use collections::HashMap;
+use command_palette::CommandPaletteAssistant;
 use fs::Fs;
 use futures::StreamExt;
 use gpui::{actions, AppContext, SharedString};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Reverse, ffi::OsStr, path::PathBuf, sync::Arc};
+use std::{cmp::Reverse, ffi::OsStr, path::PathBuf, rc::Rc, sync::Arc};
 use util::paths::CONVERSATIONS_DIR;
 
 actions!(
@@ -119,6 +120,9 @@ impl SavedConversationMetadata {
 
 pub fn init(cx: &mut AppContext) {
     assistant_panel::init(cx);
+    cx.set_global(CommandPaletteAssistant(Rc::new(|prompt, workspace, cx| {
+        AssistantPanel::ask(workspace, prompt, cx)
+    })));
 }
 
 #[cfg(test)]
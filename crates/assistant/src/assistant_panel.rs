@@ -798,6 +798,38 @@ impl AssistantPanel {
         self.api_key_editor = Some(build_api_key_editor(cx));
     }
 
+    /// Asks the prompt given in a new conversation, focusing the panel with the answer streaming in.
+    /// Fails without asking if the completion provider has no credentials.
+    pub fn ask(
+        workspace: &mut Workspace,
+        prompt: String,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Task<Result<()>> {
+        let Some(assistant) = workspace.panel::<AssistantPanel>(cx) else {
+            return Task::ready(Err(anyhow!("the assistant panel is not loaded")));
+        };
+        cx.spawn(|workspace, mut cx| async move {
+            assistant
+                .update(&mut cx, |assistant, cx| assistant.load_credentials(cx))?
+                .await;
+            if !assistant.update(&mut cx, |assistant, _| assistant.has_credentials())? {
+                return Err(anyhow!("no assistant provider is configured"));
+            }
+            assistant.update(&mut cx, |assistant, cx| {
+                let conversation = assistant.new_conversation(cx);
+                conversation.update(cx, |conversation, cx| {
+                    conversation
+                        .editor
+                        .update(cx, |editor, cx| editor.insert(&prompt, cx));
+                    conversation.assist(&Assist, cx);
+                });
+            })?;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.focus_panel::<AssistantPanel>(cx);
+            })
+        })
+    }
+
     fn new_conversation(&mut self, cx: &mut ViewContext<Self>) -> View<ConversationEditor> {
         let editor = cx.new_view(|cx| {
             ConversationEditor::new(
@@ -131,6 +131,11 @@ pub enum Event {
         action: String,
         milliseconds_since_first_event: i64,
     },
+    CommandPaletteDismissed {
+        query_length: usize,
+        match_count: usize,
+        milliseconds_since_first_event: i64,
+    },
 }
 
 #[cfg(debug_assertions)]
@@ -450,6 +455,22 @@ impl Telemetry {
         self.report_event(event)
     }
 
+    /// Reports the command palette getting dismissed without running any command.
+    /// Only the query's length gets reported, not the query itself.
+    pub fn report_command_palette_dismissed_event(
+        self: &Arc<Self>,
+        query_length: usize,
+        match_count: usize,
+    ) {
+        let event = Event::CommandPaletteDismissed {
+            query_length,
+            match_count,
+            milliseconds_since_first_event: self.milliseconds_since_first_event(Utc::now()),
+        };
+
+        self.report_event(event)
+    }
+
     fn milliseconds_since_first_event(self: &Arc<Self>, date_time: DateTime<Utc>) -> i64 {
         let mut state = self.state.lock();
 
@@ -503,6 +524,17 @@ impl Telemetry {
         self.state.lock().is_staff
     }
 
+    /// The events reported since the last flush.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn queued_events(self: &Arc<Self>) -> Vec<Event> {
+        self.state
+            .lock()
+            .events_queue
+            .iter()
+            .map(|wrapper| wrapper.event.clone())
+            .collect()
+    }
+
     pub fn flush_events(self: &Arc<Self>) {
         let mut state = self.state.lock();
         state.first_event_date_time = None;
@@ -0,0 +1,239 @@
+//! Vim-style abbreviations of the command names: "gtd" for "editor: go to definition".
+
+use std::sync::Arc;
+
+use collections::{HashMap, HashSet};
+use fuzzy::StringMatch;
+use gpui::Global;
+
+use crate::Command;
+
+/// Queries longer than this are matched fuzzily only.
+const MAX_ABBREVIATION_LEN: usize = 4;
+/// Fuzzy matches of a lower quality are scattered enough for the abbreviations to outrank them.
+const WEAK_FUZZY_MATCH_QUALITY: f64 = 0.6;
+
+/// Initials of the command names, computed once per session.
+#[derive(Default)]
+pub(crate) struct CommandInitials(HashMap<String, Arc<str>>);
+
+impl Global for CommandInitials {}
+
+impl CommandInitials {
+    pub(crate) fn get(&mut self, command_name: &str) -> Arc<str> {
+        if let Some(initials) = self.0.get(command_name) {
+            return initials.clone();
+        }
+        let command_initials: Arc<str> = initials(command_name).into();
+        self.0
+            .insert(command_name.to_string(), command_initials.clone());
+        command_initials
+    }
+}
+
+/// Lowercased first letters of the command name's words, with the namespace excluded.
+pub(crate) fn initials(command_name: &str) -> String {
+    word_starts(command_name)
+        .flat_map(|(_, initial)| initial.to_lowercase())
+        .collect()
+}
+
+fn word_starts(command_name: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let words_start = command_name.find(": ").map_or(0, |ix| ix + 2);
+    command_name[words_start..]
+        .char_indices()
+        .scan(' ', |previous, (ix, char)| {
+            let is_word_start = *previous == ' ' && char != ' ';
+            *previous = char;
+            Some(is_word_start.then_some((words_start + ix, char)))
+        })
+        .flatten()
+}
+
+/// Puts the commands, abbreviated by the short query given, above the weak fuzzy matches:
+/// first the ones whose initials are exactly the query, then the ones whose initials start with
+/// its first letter and contain the rest in order.
+///
+/// Does nothing for longer queries, or when the fuzzy matching found a strong enough match.
+pub(crate) fn rank_abbreviations(
+    query: &str,
+    commands: &[&Command],
+    fuzzy_matches: Vec<StringMatch>,
+) -> Vec<StringMatch> {
+    if query.chars().count() > MAX_ABBREVIATION_LEN || query.contains(' ') {
+        return fuzzy_matches;
+    }
+    let has_strong_fuzzy_match = fuzzy_matches.iter().any(|fuzzy_match| {
+        // The fuzzy scores are divided by the string length, undo that to compare the matches' shape.
+        fuzzy_match.score * fuzzy_match.string.chars().count() as f64 >= WEAK_FUZZY_MATCH_QUALITY
+    });
+    if has_strong_fuzzy_match {
+        return fuzzy_matches;
+    }
+
+    let query = query.to_lowercase().chars().collect::<Vec<_>>();
+    let mut exact_matches = Vec::new();
+    let mut partial_matches = Vec::new();
+    for (ix, command) in commands.iter().enumerate() {
+        let Some(positions) = abbreviation_positions(&query, &command.name) else {
+            continue;
+        };
+        let abbreviation_match = StringMatch {
+            candidate_id: ix,
+            score: 1.0,
+            positions,
+            string: command.name.clone(),
+        };
+        if command.initials.chars().eq(query.iter().copied()) {
+            exact_matches.push(abbreviation_match);
+        } else {
+            partial_matches.push(abbreviation_match);
+        }
+    }
+    if exact_matches.is_empty() && partial_matches.is_empty() {
+        return fuzzy_matches;
+    }
+
+    let mut matches = exact_matches;
+    matches.extend(partial_matches);
+    let abbreviated = matches
+        .iter()
+        .map(|abbreviation_match| abbreviation_match.candidate_id)
+        .collect::<HashSet<_>>();
+    matches.extend(
+        fuzzy_matches
+            .into_iter()
+            .filter(|fuzzy_match| !abbreviated.contains(&fuzzy_match.candidate_id)),
+    );
+    matches
+}
+
+/// Positions of the command name's initials, abbreviated by the (lowercase) query, if it abbreviates the name.
+fn abbreviation_positions(query: &[char], command_name: &str) -> Option<Vec<usize>> {
+    let mut word_starts = word_starts(command_name)
+        .flat_map(|(ix, initial)| initial.to_lowercase().map(move |initial| (ix, initial)));
+    let (first_ix, first_initial) = word_starts.next()?;
+    if query.first() != Some(&first_initial) {
+        return None;
+    }
+    let mut positions = vec![first_ix];
+    for query_char in &query[1..] {
+        let (ix, _) = word_starts.find(|(_, initial)| initial == query_char)?;
+        positions.push(ix);
+    }
+    Some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use fuzzy::StringMatchCandidate;
+    use gpui::{Action, NoAction, TestAppContext};
+
+    use super::*;
+
+    fn command(name: &str) -> Command {
+        Command {
+            name: name.to_string(),
+            action: NoAction.boxed_clone(),
+            initials: initials(name).into(),
+            description: None,
+            ranking_key: None,
+            project_root: None,
+            available: true,
+        }
+    }
+
+    async fn fuzzy_matches(
+        query: &str,
+        commands: &[Command],
+        cx: &TestAppContext,
+    ) -> Vec<StringMatch> {
+        let candidates = commands
+            .iter()
+            .enumerate()
+            .map(|(ix, command)| StringMatchCandidate {
+                id: ix,
+                string: command.name.clone(),
+                char_bag: command.name.chars().collect(),
+            })
+            .collect::<Vec<_>>();
+        fuzzy::match_strings(
+            &candidates,
+            query,
+            true,
+            10000,
+            &Default::default(),
+            cx.executor(),
+        )
+        .await
+    }
+
+    async fn ranked_names(query: &str, commands: &[Command], cx: &TestAppContext) -> Vec<String> {
+        let fuzzy_matches = fuzzy_matches(query, commands, cx).await;
+        let commands = commands.iter().collect::<Vec<_>>();
+        rank_abbreviations(query, &commands, fuzzy_matches)
+            .into_iter()
+            .map(|ranked_match| ranked_match.string)
+            .collect()
+    }
+
+    #[test]
+    fn test_initials() {
+        assert_eq!(initials("editor: go to definition"), "gtd");
+        assert_eq!(initials("editor: toggle format on save"), "tfos");
+        assert_eq!(initials("workspace: save all"), "sa");
+        assert_eq!(initials("no namespace"), "nn");
+    }
+
+    #[gpui::test]
+    async fn test_abbreviation_ranking(cx: &mut TestAppContext) {
+        let commands = [
+            "editor: go to definition",
+            "editor: go to diagnostic",
+            "editor: go to type definition",
+            "editor: toggle format on save",
+            "editor: toggled fuzzy diff stats",
+            "project panel: toggle focus",
+            "workspace: toggle full screen",
+        ]
+        .map(command);
+
+        assert_eq!(
+            ranked_names("gtd", &commands, cx).await,
+            [
+                "editor: go to definition",
+                "editor: go to diagnostic",
+                "editor: go to type definition",
+            ]
+        );
+        assert_eq!(
+            ranked_names("tfs", &commands, cx).await,
+            [
+                "workspace: toggle full screen",
+                "editor: toggle format on save",
+                "editor: toggled fuzzy diff stats",
+                "project panel: toggle focus",
+            ],
+            "Exact initials should go first, then the partial ones, then the rest of the fuzzy matches"
+        );
+
+        let fuzzy_names = fuzzy_matches("togg", &commands, cx)
+            .await
+            .into_iter()
+            .map(|fuzzy_match| fuzzy_match.string)
+            .collect::<Vec<_>>();
+        assert_eq!(fuzzy_names.len(), 4);
+        assert_eq!(
+            ranked_names("togg", &commands, cx).await,
+            fuzzy_names,
+            "Strong fuzzy matches should be left intact"
+        );
+        assert!(
+            ranked_names("go to", &commands, cx)
+                .await
+                .iter()
+                .all(|name| name.contains("go to")),
+            "Longer queries should be matched fuzzily only"
+        );
+    }
+}
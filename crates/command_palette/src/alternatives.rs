@@ -0,0 +1,120 @@
+//! Alternatives in the palette's query, separated with `|`: `wrap|fold` matches the commands matching either of them.
+
+use collections::HashMap;
+use fuzzy::StringMatch;
+
+/// Splits the query into its alternatives at the `|`s not escaped as `\|`, unescaping the escaped ones.
+/// The alternatives of a split query get trimmed, and the blank ones dropped, leaving the empty query if there's nothing else.
+pub(crate) fn split_alternatives(query: &str) -> Vec<String> {
+    let mut alternatives = Vec::new();
+    let mut alternative = String::new();
+    let mut chars = query.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' if chars.peek() == Some(&'|') => {
+                alternative.push('|');
+                chars.next();
+            }
+            '|' => alternatives.push(std::mem::take(&mut alternative)),
+            _ => alternative.push(char),
+        }
+    }
+    alternatives.push(alternative);
+    if alternatives.len() == 1 {
+        return alternatives;
+    }
+    let mut alternatives = alternatives
+        .into_iter()
+        .map(|alternative| alternative.trim().to_string())
+        .filter(|alternative| !alternative.is_empty())
+        .collect::<Vec<_>>();
+    if alternatives.is_empty() {
+        alternatives.push(String::new());
+    }
+    alternatives
+}
+
+/// Merges the matches of every alternative into one list, ordered by the score.
+/// A candidate matched by several alternatives gets the best of their scores, and all of their highlighted positions.
+///
+/// The matches of a single alternative are left as they are, in the order they've been ranked in.
+pub(crate) fn merge_alternative_matches(
+    mut alternative_matches: Vec<Vec<StringMatch>>,
+) -> Vec<StringMatch> {
+    if alternative_matches.len() <= 1 {
+        return alternative_matches.pop().unwrap_or_default();
+    }
+    let mut merged = HashMap::<usize, StringMatch>::default();
+    for string_match in alternative_matches.into_iter().flatten() {
+        match merged.get_mut(&string_match.candidate_id) {
+            Some(merged_match) => {
+                merged_match.score = merged_match.score.max(string_match.score);
+                merged_match.positions.extend(string_match.positions);
+                merged_match.positions.sort_unstable();
+                merged_match.positions.dedup();
+            }
+            None => {
+                merged.insert(string_match.candidate_id, string_match);
+            }
+        }
+    }
+    let mut matches = merged.into_values().collect::<Vec<_>>();
+    // The candidate ids follow the commands' usage ranking, which breaks the ties.
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.candidate_id.cmp(&b.candidate_id))
+    });
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids_and_scores(matches: &[StringMatch]) -> Vec<(usize, f64)> {
+        matches
+            .iter()
+            .map(|string_match| (string_match.candidate_id, string_match.score))
+            .collect()
+    }
+
+    fn string_match(candidate_id: usize, score: f64, positions: &[usize]) -> StringMatch {
+        StringMatch {
+            candidate_id,
+            score,
+            positions: positions.to_vec(),
+            string: format!("command {candidate_id}"),
+        }
+    }
+
+    #[test]
+    fn test_split_alternatives() {
+        assert_eq!(split_alternatives(""), [""]);
+        assert_eq!(split_alternatives("soft wrap"), ["soft wrap"]);
+        assert_eq!(split_alternatives("wrap|fold"), ["wrap", "fold"]);
+        assert_eq!(split_alternatives("wrap || fold| "), ["wrap", "fold"]);
+        assert_eq!(split_alternatives("soft wrap "), ["soft wrap "]);
+        assert_eq!(split_alternatives("|"), [""]);
+        assert_eq!(split_alternatives(r"a\|b"), ["a|b"]);
+        assert_eq!(split_alternatives(r"a\|b|c"), ["a|b", "c"]);
+        assert_eq!(split_alternatives(r"a\b"), [r"a\b"]);
+    }
+
+    #[test]
+    fn test_merge_alternative_matches() {
+        let single = vec![string_match(3, 0.1, &[0]), string_match(1, 0.5, &[1])];
+        assert_eq!(
+            ids_and_scores(&merge_alternative_matches(vec![single])),
+            [(3, 0.1), (1, 0.5)],
+            "A single alternative's ranking should be kept"
+        );
+
+        let merged = merge_alternative_matches(vec![
+            vec![string_match(0, 0.9, &[5, 6]), string_match(2, 0.3, &[1])],
+            vec![string_match(1, 0.6, &[0]), string_match(2, 0.4, &[3, 1])],
+        ]);
+        assert_eq!(ids_and_scores(&merged), [(0, 0.9), (1, 0.6), (2, 0.4)]);
+        assert_eq!(merged[2].positions, [1, 3]);
+    }
+}
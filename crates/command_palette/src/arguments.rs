@@ -0,0 +1,133 @@
+//! Arguments typed in the palette's query after a command's name, e.g. `workspace: activate pane 2`,
+//! to run the actions with parameters with other values than their defaults.
+
+use gpui::{ActionBuilder, FocusHandle, SharedString, WindowContext};
+use serde_json::Value;
+
+use crate::{humanize_action_name, is_action_type_hidden, Command, CommandInterceptResult};
+
+/// The actions with parameters, listed once per palette session rather than on every keystroke:
+/// telling them apart takes building every action registered.
+#[derive(Default)]
+pub(crate) struct ArgumentParsers {
+    parsers: Vec<ArgumentParser>,
+}
+
+/// An action with parameters, not hidden from the palette, to build out of the argument typed after its command's name.
+struct ArgumentParser {
+    action_name: SharedString,
+    command_name: String,
+    build: ActionBuilder,
+    /// Whether the action is available to the element focused before the palette got opened, whatever its parameters.
+    available: bool,
+}
+
+impl ArgumentParsers {
+    /// Lists the actions with parameters, as available to the element focused before the palette got opened.
+    pub(crate) fn read(previous_focus_handle: &FocusHandle, cx: &WindowContext) -> Self {
+        let parsers = cx
+            .all_action_names()
+            .iter()
+            .filter_map(|action_name| {
+                let (action_type, build) = cx.action_builder(action_name)?;
+                // Unit actions are built out of any value, ignoring the argument, while the ones with parameters reject `null`.
+                if build(Value::Null).is_ok() || is_action_type_hidden(action_name, action_type, cx)
+                {
+                    return None;
+                }
+                Some(ArgumentParser {
+                    action_name: action_name.clone(),
+                    command_name: humanize_action_name(action_name),
+                    build,
+                    available: cx.is_action_type_available_in(action_type, previous_focus_handle),
+                })
+            })
+            .collect();
+        Self { parsers }
+    }
+
+    /// The action the query starts with, by its command's name or by the action's own one, and the argument after it.
+    /// The longest of the names matching wins, for `workspace: activate pane in direction left` not to match
+    /// `workspace: activate pane` with the `in direction left` argument.
+    fn split_argument<'a>(&self, query: &'a str) -> Option<(&ArgumentParser, &'a str)> {
+        let query = query.trim_start();
+        self.parsers
+            .iter()
+            .filter_map(|parser| {
+                [parser.command_name.as_str(), parser.action_name.as_ref()]
+                    .into_iter()
+                    .find_map(|name| {
+                        let prefix = query.get(..name.len())?;
+                        let argument = query[name.len()..]
+                            .strip_prefix(char::is_whitespace)?
+                            .trim();
+                        (prefix.eq_ignore_ascii_case(name) && !argument.is_empty())
+                            .then_some((name.len(), argument))
+                    })
+                    .map(|(name_len, argument)| (name_len, parser, argument))
+            })
+            .max_by_key(|(name_len, _, _)| *name_len)
+            .map(|(_, parser, argument)| (parser, argument))
+    }
+
+    /// The command to run for the query with an argument, if it names an action with parameters, available and not hidden,
+    /// and the reason the argument does not fit the action, if it does not.
+    ///
+    /// The actions getting an argument they do not fit fall back to their default values, when they have ones:
+    /// the commands of the actions without defaults are only offered with a fitting argument.
+    pub(crate) fn intercept(
+        &self,
+        query: &str,
+        commands: &[Command],
+    ) -> Option<(CommandInterceptResult, Option<String>)> {
+        let (parser, argument) = self.split_argument(query)?;
+        let command = commands
+            .iter()
+            .find(|command| command.action.name() == parser.action_name.as_ref());
+        let (action, error) = match (parser.build)(parse_argument(argument)) {
+            // The actions without defaults are not among the commands.
+            Ok(action) if command.is_some() || parser.available => (action, None),
+            Ok(_) => return None,
+            Err(error) => (
+                command?.action.boxed_clone(),
+                Some(error.root_cause().to_string()),
+            ),
+        };
+        let positions = (0..parser.command_name.len()).collect();
+        Some((
+            CommandInterceptResult {
+                action,
+                string: format!("{} {argument}", parser.command_name),
+                positions,
+                ranking_key: None,
+            },
+            error,
+        ))
+    }
+}
+
+/// The argument as JSON, or as a string if it's not valid JSON, for the bare words not to need quoting.
+pub(crate) fn parse_argument(argument: &str) -> Value {
+    serde_json::from_str(argument).unwrap_or_else(|_| Value::String(argument.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_argument() {
+        assert_eq!(parse_argument("2"), Value::from(2));
+        assert_eq!(parse_argument("true"), Value::from(true));
+        assert_eq!(
+            parse_argument(r#"{ "url": "zed://settings" }"#),
+            serde_json::json!({ "url": "zed://settings" })
+        );
+        assert_eq!(parse_argument(r#""Left""#), Value::from("Left"));
+        assert_eq!(
+            parse_argument("Left"),
+            Value::from("Left"),
+            "Bare words should not need quoting"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Short descriptions of what the commands do, shown under the names that do not tell it on their own.
+
+use collections::HashMap;
+use gpui::{AppContext, Global, SharedString};
+
+/// Descriptions of the commands whose names are the least self-explanatory, by the names of their actions.
+const BUILTIN_COMMAND_DESCRIPTIONS: &[(&str, &str)] = &[
+    (
+        "editor::ToggleSoftWrap",
+        "Wrap the long lines at the editor's width, or stop wrapping them",
+    ),
+    (
+        "editor::ToggleInlayHints",
+        "Show or hide the type and parameter hints of the language server",
+    ),
+    (
+        "editor::Format",
+        "Format the buffer with the formatter of its language",
+    ),
+    (
+        "editor::ExpandMacroRecursively",
+        "Show the full expansion of the Rust macro under the cursor",
+    ),
+    (
+        "editor::RestartLanguageServer",
+        "Restart the language servers of the buffer's language",
+    ),
+    (
+        "editor::SortLinesCaseSensitive",
+        "Sort the selected lines, uppercase letters first",
+    ),
+    (
+        "workspace::ToggleVimMode",
+        "Switch between Vim's modal editing and the default key bindings",
+    ),
+];
+
+/// Descriptions of the commands, by the names of their actions.
+#[derive(Default)]
+pub(crate) struct CommandDescriptions(HashMap<&'static str, SharedString>);
+
+impl Global for CommandDescriptions {}
+
+impl CommandDescriptions {
+    pub(crate) fn init(cx: &mut AppContext) {
+        let descriptions = cx.default_global::<Self>();
+        for &(action_name, description) in BUILTIN_COMMAND_DESCRIPTIONS {
+            descriptions
+                .0
+                .entry(action_name)
+                .or_insert(SharedString::from(description));
+        }
+    }
+
+    pub(crate) fn get(&self, action_name: &str) -> Option<SharedString> {
+        self.0.get(action_name).cloned()
+    }
+}
+
+/// Describes the action's command in the palette, in a line shown under its name and matched by the queries too.
+pub fn register_command_description(
+    action_name: &'static str,
+    description: impl Into<SharedString>,
+    cx: &mut AppContext,
+) {
+    cx.default_global::<CommandDescriptions>()
+        .0
+        .insert(action_name, description.into());
+}
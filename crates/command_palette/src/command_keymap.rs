@@ -0,0 +1,103 @@
+//! Opening the user keymap at the binding of a command, to change its keystrokes from the palette, or to bind it.
+
+use std::ops::Range;
+
+use anyhow::Context;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::ViewContext;
+use util::paths;
+use workspace::{create_and_open_local_file, Workspace};
+
+/// Opens the user keymap with the cursor at the user's binding of the action, if there's one.
+/// Otherwise, a binding of the action with no keystrokes gets added at the end, with the cursor where the keystrokes go.
+pub(crate) fn open_keymap_at_binding(
+    action_name: String,
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let open_keymap = create_and_open_local_file(&paths::KEYMAP, cx, || "".into());
+    cx.spawn(|_, mut cx| async move {
+        let editor = open_keymap
+            .await?
+            .downcast::<Editor>()
+            .context("keymap file is not opened in an editor")?;
+        editor.update(&mut cx, |editor, cx| {
+            let text = editor.text(cx);
+            let position = match binding_offset(&text, &action_name) {
+                Some(offset) => offset,
+                None => {
+                    let (range, template) = binding_template(&text, &action_name);
+                    let start = range.start;
+                    editor.edit([(range, template.clone())], cx);
+                    start + template.find("\"\"").unwrap_or(0) + 1
+                }
+            };
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([position..position])
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The offset of the keystrokes of the first binding of the action, as its name is quoted in the keymap,
+/// skipping the commented out lines.
+fn binding_offset(text: &str, action_name: &str) -> Option<usize> {
+    let quoted_name = format!("\"{action_name}\"");
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let indentation = line.len() - line.trim_start().len();
+        if !line.trim_start().starts_with("//") && line.contains(&quoted_name) {
+            return Some(offset + indentation);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The range to replace with a binding of the action with no keystrokes, as the keymap's last block, along with the text
+/// to replace it with.
+fn binding_template(text: &str, action_name: &str) -> (Range<usize>, String) {
+    let block = format!("  {{\n    \"bindings\": {{\n      \"\": \"{action_name}\"\n    }}\n  }}");
+    match text.rfind(']') {
+        Some(array_end) => {
+            let offset = text[..array_end].trim_end().len();
+            let separator = if text[..offset].ends_with('[') {
+                ""
+            } else {
+                ","
+            };
+            (offset..array_end, format!("{separator}\n{block}\n"))
+        }
+        None => (text.len()..text.len(), format!("[\n{block}\n]\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_template() {
+        let insert = |text: &str| {
+            let (range, template) = binding_template(text, "editor::Backspace");
+            format!("{}{template}{}", &text[..range.start], &text[range.end..])
+        };
+        let block = "  {\n    \"bindings\": {\n      \"\": \"editor::Backspace\"\n    }\n  }";
+        assert_eq!(insert(""), format!("[\n{block}\n]\n"));
+        assert_eq!(insert("[]\n"), format!("[\n{block}\n]\n"));
+        assert_eq!(
+            insert("[\n  {\n    \"bindings\": {}\n  }\n]\n"),
+            format!("[\n  {{\n    \"bindings\": {{}}\n  }},\n{block}\n]\n"),
+            "The new block should be separated from the last one"
+        );
+
+        let keymap = "// Zed keymap\n[\n  {\n    // \"cmd-b\": \"editor::Backspace\",\n    \"cmd-k b\": \"editor::Backspace\"\n  }\n]\n";
+        assert_eq!(
+            binding_offset(keymap, "editor::Backspace"),
+            keymap.find("\"cmd-k b\""),
+            "The commented out bindings should be skipped"
+        );
+        assert_eq!(binding_offset(keymap, "editor::Delete"), None);
+    }
+}
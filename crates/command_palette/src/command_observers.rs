@@ -0,0 +1,34 @@
+//! Observing the commands from outside of the palette, e.g. by the onboarding tour, waiting for the user to run the commands it suggests.
+
+use gpui::{AppContext, Subscription};
+
+use crate::{
+    is_hidden, is_namespace_hidden, resolve_action_name, CommandExecuted, LastConfirmedCommand,
+};
+
+/// Calls the callback for every command confirmed in any palette from now on, or executed by its name
+/// with [`zed_actions::ExecuteCommand`], until the subscription is dropped.
+pub fn on_command_executed(
+    cx: &mut AppContext,
+    callback: impl Fn(&CommandExecuted, &mut AppContext) + 'static,
+) -> Subscription {
+    cx.observe_global::<LastConfirmedCommand>(move |cx| {
+        let event = CommandExecuted {
+            command_name: cx.global::<LastConfirmedCommand>().name.clone(),
+        };
+        callback(&event, cx);
+    })
+}
+
+/// Whether the palette can list the command with the name given: its action's one, its humanized one or a synonym,
+/// as with [`zed_actions::ExecuteCommand`], unless the command is hidden from the palette.
+pub fn command_exists(name: &str, cx: &AppContext) -> bool {
+    let Some(action_name) = resolve_action_name(name, cx) else {
+        return false;
+    };
+    match cx.build_action(&action_name, None) {
+        Ok(action) => !is_hidden(&*action, cx),
+        // The actions with the required arguments are not built without them: only their namespaces can hide them.
+        Err(_) => !is_namespace_hidden(&action_name, cx),
+    }
+}
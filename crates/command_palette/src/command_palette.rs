@@ -1,91 +1,681 @@
-use std::{
-    cmp::{self, Reverse},
-    sync::Arc,
-};
+use std::{any::TypeId, cmp, iter, mem, ops::Range, path::Path, rc::Rc, sync::Arc, time::Duration};
 
+use anyhow::{anyhow, bail};
 use client::telemetry::Telemetry;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use copilot::CommandPaletteFilter;
+use editor::{scroll::Autoscroll, Bias, Editor};
+use feature_flags::{AssistantFeatureFlag, FeatureFlagAppExt};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
-    ParentElement, Render, Styled, View, ViewContext, VisualContext, WeakView,
+    actions, impl_actions, overlay, Action, AnchorCorner, AnyElement, AppContext, ClipboardItem,
+    DismissEvent, EventEmitter, FocusHandle, FocusableView, Global, Keystroke, Model,
+    ParentElement, Render, Styled, Subscription, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
 };
-use picker::{Picker, PickerDelegate};
-
+use picker::{Picker, PickerDelegate, PickerSettings};
+use project::{Project, Symbol};
 use release_channel::{parse_zed_link, ReleaseChannel};
-use ui::{h_flex, prelude::*, v_flex, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
+use serde::Deserialize;
+use settings::{Settings, SettingsStore};
+use ui::{
+    h_flex, prelude::*, tooltip_container, v_flex, ContextMenu, HighlightedLabel, KeyBinding,
+    ListItem, ListItemSpacing, Tooltip,
+};
 use util::ResultExt;
-use workspace::{ModalView, Workspace};
-use zed_actions::OpenZedUrl;
+use workspace::{notifications::NotifyResultExt, ModalView, Toast, Workspace, WorkspaceId};
+use zed_actions::{ExecuteCommand, OpenZedUrl};
+
+mod abbreviations;
+mod alternatives;
+mod arguments;
+mod command_descriptions;
+mod command_keymap;
+mod command_observers;
+mod command_palette_settings;
+mod command_settings;
+mod command_toggles;
+mod command_variants;
+mod explain_command;
+mod hit_counts;
+mod insert_at_cursor;
+mod intercepted_commands;
+mod key_query;
+mod last_command;
+mod namespace_prefix;
+mod persistence;
+mod pinned_commands;
+mod project_commands;
+mod published_actions;
+mod query_history;
+mod ranking;
+mod recent_commands;
+mod substring_fallback;
+mod synonyms;
+mod terms;
+
+use abbreviations::{initials, CommandInitials};
+use alternatives::split_alternatives;
+use arguments::ArgumentParsers;
+pub use command_descriptions::register_command_description;
+use command_descriptions::CommandDescriptions;
+use command_keymap::open_keymap_at_binding;
+pub use command_observers::{command_exists, on_command_executed};
+pub use command_palette_settings::{CommandPaletteSettings, ProjectCommand, UsageScope};
+pub use command_settings::register_command_setting;
+use command_settings::{open_setting, CommandSettings};
+pub use command_toggles::{register_toggle_probe, ToggleProbe};
+use command_toggles::{render_toggle_state, ToggleProbes};
+pub use command_variants::register_command_variants;
+use command_variants::{variant_name, CommandVariants};
+pub use explain_command::CommandPaletteAssistant;
+use explain_command::{explain_command, CommandDetails};
+use hit_counts::HitCounts;
+pub use insert_at_cursor::KeybindingFormat;
+use key_query::{binding_starting_with, parse_key_query, starts_with_keystrokes};
+pub use last_command::LastCommandIndicator;
+use namespace_prefix::{match_in_namespace, split_namespace_prefix};
+use pinned_commands::{boost_pinned, PinnedCommands};
+use project_commands::project_commands;
+use published_actions::resolve_deprecated_alias;
+use query_history::QueryHistory;
+use ranking::{
+    comparison_table, match_alternatives, RankingSnapshot, COMPARED_MATCHES, RANKING_PROFILES,
+};
+use recent_commands::RecentCommands;
+use substring_fallback::match_substrings;
+use synonyms::{normalize, resolve_synonym};
+
+actions!(
+    command_palette,
+    [
+        GoToActionDefinition,
+        ForgetRecent,
+        ForgetHitCount,
+        NeverShowInRecents,
+        OpenCommandSettings,
+        OpenCommandKeymap,
+        ExplainCommand,
+        DumpRankingComparison,
+        Repeat,
+        InsertActionNameAtCursor,
+        TogglePinned,
+        ResetHitCounts,
+        PreviousQuery,
+        NextQuery,
+        ToggleScoreDetails
+    ]
+);
 
-actions!(command_palette, [Toggle]);
+/// Toggles the command palette.
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Toggle {
+    /// Keystrokes, as in the keymap (e.g. `"cmd-k"`), to list only the commands whose bindings start with.
+    pub key_prefix: Option<String>,
+    /// Query to open the palette with. The command it names exactly gets run right away,
+    /// if it's the only such command and `command_palette.auto_confirm_exact_match` is on.
+    pub query: Option<String>,
+    /// Name of the command to emphasize in the list, e.g. the one a step of the onboarding tour asks to run:
+    /// its action's name or the one shown in the palette.
+    pub highlight: Option<String>,
+}
+
+/// Inserts the selected command's keybinding at the cursor of the editor the palette got opened from.
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+pub struct InsertKeybindingAtCursor {
+    /// Whether to write the keybinding in the platform's notation or as in the keymap.
+    #[serde(default)]
+    pub format: KeybindingFormat,
+}
+
+impl_actions!(command_palette, [Toggle, InsertKeybindingAtCursor]);
+
+const GO_TO_ACTION_DEFINITION_TOAST_ID: usize = 0x7a3b91c2;
+const REPEAT_TOAST_ID: usize = 0x7a3b91c3;
+const COPY_ACTION_NAME_TOAST_ID: usize = 0x7a3b91c5;
+const UNAVAILABLE_COMMAND_TOAST_ID: usize = 0x7a3b91c6;
+const FORGET_HIT_COUNT_TOAST_ID: usize = 0x7a3b91c7;
+
+/// How many commands the palette lists for a query at most, unless its delegate's `max_matches` says otherwise.
+pub const DEFAULT_MAX_MATCHES: usize = 10_000;
 
 pub fn init(cx: &mut AppContext) {
-    cx.set_global(HitCounts::default());
-    cx.set_global(CommandPaletteFilter::default());
+    CommandPaletteSettings::register(cx);
+    cx.set_global(HitCounts::load(cx));
+    cx.set_global(RecentCommands::load());
+    cx.set_global(PinnedCommands::load());
+    cx.set_global(QueryHistory::default());
+    cx.set_global(CommandInitials::default());
+    // The commands hidden before the palette got initialized stay hidden.
+    cx.default_global::<CommandPaletteFilter>();
+    CommandSettings::init(cx);
+    CommandDescriptions::init(cx);
+    ToggleProbes::init(cx);
+    CommandVariants::init(cx);
     cx.observe_new_views(CommandPalette::register).detach();
+    open_on_pending_prefix(cx);
+    cx.observe_global::<SettingsStore>(open_on_pending_prefix)
+        .detach();
+}
+
+/// Makes the pending keystrokes of a multi-keystroke binding open the palette, as the settings say.
+fn open_on_pending_prefix(cx: &mut AppContext) {
+    match CommandPaletteSettings::get_global(cx).open_on_pending_prefix_ms {
+        Some(delay_ms) => cx.on_pending_keystrokes(Duration::from_millis(delay_ms), |keystrokes| {
+            let key_prefix = keystrokes
+                .iter()
+                .map(Keystroke::unparse)
+                .collect::<Vec<_>>()
+                .join(" ");
+            Box::new(Toggle {
+                key_prefix: Some(key_prefix),
+                query: None,
+                highlight: None,
+            })
+        }),
+        None => cx.clear_pending_keystrokes_handler(),
+    }
 }
 
 impl ModalView for CommandPalette {}
 
 pub struct CommandPalette {
     picker: View<Picker<CommandPaletteDelegate>>,
+    workspace: WeakView<Workspace>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl CommandPalette {
     fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-        workspace.register_action(|workspace, _: &Toggle, cx| {
-            let Some(previous_focus_handle) = cx.focused() else {
-                return;
-            };
-            let telemetry = workspace.client().telemetry().clone();
-            workspace.toggle_modal(cx, move |cx| {
-                CommandPalette::new(previous_focus_handle, telemetry, cx)
+        workspace.register_action(|workspace, action: &Toggle, cx| {
+            let key_prefix = action.key_prefix.as_deref().and_then(|key_prefix| {
+                key_prefix
+                    .split_whitespace()
+                    .map(Keystroke::parse)
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .log_err()
             });
+            Self::toggle_with(
+                workspace,
+                None,
+                key_prefix,
+                action.query.clone(),
+                action.highlight.clone(),
+                cx,
+            )
+        });
+        workspace.register_action(|workspace, action: &ExecuteCommand, cx| {
+            execute_command(
+                workspace,
+                &action.name,
+                action.args.clone(),
+                action.include_hidden,
+                cx,
+            )
+            .notify_err(workspace, cx);
+        });
+        workspace.register_action(|workspace, _: &Repeat, cx| {
+            repeat_last_command(workspace, cx);
+        });
+        workspace.register_action(|_, _: &ResetHitCounts, cx| {
+            cx.update_global(|hit_counts: &mut HitCounts, cx| hit_counts.reset(cx));
+        });
+    }
+
+    /// Toggles the palette, opened with the query given, e.g. "theme" for a welcome screen's button to list the theme commands.
+    /// The query is selected, for the typing to replace it, and so is its first match.
+    pub fn toggle(workspace: &mut Workspace, initial_query: &str, cx: &mut ViewContext<Workspace>) {
+        let query = (!initial_query.is_empty()).then(|| initial_query.to_string());
+        Self::toggle_with(workspace, None, None, query, None, cx);
+    }
+
+    /// Toggles the palette, selecting the command with the name given once it's shown.
+    /// With the key prefix given, only the commands with the bindings starting with it are listed.
+    /// With the query given, the palette gets opened with it, auto-confirming its exact match if the settings say so.
+    /// The command to highlight gets emphasized whenever it's listed.
+    pub(crate) fn toggle_with(
+        workspace: &mut Workspace,
+        selected_command: Option<String>,
+        key_prefix: Option<Vec<Keystroke>>,
+        query: Option<String>,
+        highlight: Option<String>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(previous_focus_handle) = cx.focused() else {
+            return;
+        };
+        let telemetry = workspace.client().telemetry().clone();
+        let workspace_handle = workspace.weak_handle();
+        let workspace_id = workspace.database_id();
+        let project = workspace.project().clone();
+        workspace.toggle_modal(cx, move |cx| {
+            let commands = available_commands(&previous_focus_handle, key_prefix.as_deref(), cx);
+            CommandPalette::new(
+                commands,
+                previous_focus_handle,
+                telemetry,
+                workspace_handle,
+                project,
+                workspace_id,
+                selected_command,
+                key_prefix,
+                query,
+                highlight,
+                cx,
+            )
         });
     }
 
+    /// Lists the commands given along with the ones defined in the project's settings,
+    /// unless only the ones bound after a key prefix are listed.
     fn new(
+        mut commands: Vec<Command>,
         previous_focus_handle: FocusHandle,
         telemetry: Arc<Telemetry>,
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        workspace_id: WorkspaceId,
+        selected_command: Option<String>,
+        key_prefix: Option<Vec<Keystroke>>,
+        query: Option<String>,
+        highlight: Option<String>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
-        let filter = cx.try_global::<CommandPaletteFilter>();
-
-        let commands = cx
-            .available_actions()
-            .into_iter()
-            .filter_map(|action| {
-                let name = action.name();
-                let namespace = name.split("::").next().unwrap_or("malformed action name");
-                if filter.is_some_and(|f| {
-                    f.hidden_namespaces.contains(namespace)
-                        || f.hidden_action_types.contains(&action.type_id())
-                }) {
-                    return None;
-                }
-
-                Some(Command {
-                    name: humanize_action_name(&name),
-                    action,
-                })
-            })
-            .collect();
-
-        let delegate = CommandPaletteDelegate::new(
+        if key_prefix.is_none() {
+            commands.extend(project_commands(project.read(cx), cx));
+        }
+        let mut delegate = CommandPaletteDelegate::new(
             cx.view().downgrade(),
             commands,
             telemetry,
             previous_focus_handle,
+            workspace.clone(),
+            workspace_id,
+            cx,
         );
+        delegate.selected_command = selected_command;
+        delegate.key_prefix = key_prefix;
+        delegate.highlighted_command = highlight;
+        // Interactive users may type an exact name on their way to another command: only the programmatic queries get auto-confirmed.
+        if CommandPaletteSettings::get_global(cx).auto_confirm_exact_match {
+            delegate.auto_confirm_query = query.clone();
+        }
+
+        // Not a uniform list: the rows of the commands with descriptions are taller.
+        let picker = cx.new_view(|cx| Picker::list(delegate, cx));
+        if let Some(query) = query {
+            picker.update(cx, |picker, cx| {
+                picker.set_query(query, cx);
+                picker.select_query(cx);
+            });
+        }
+        let _subscriptions = vec![
+            cx.observe_global::<HitCounts>(Self::refresh_ranking),
+            cx.observe_global::<RecentCommands>(Self::refresh_ranking),
+            cx.observe_global::<PinnedCommands>(Self::refresh_ranking),
+            cx.observe_global::<SettingsStore>(Self::refresh_ranking),
+            cx.observe_global::<CommandPaletteFilter>(Self::reload_commands),
+            cx.on_focus_in(&picker.focus_handle(cx), Self::reload_commands),
+        ];
+        Self {
+            picker,
+            workspace,
+            _subscriptions,
+        }
+    }
+
+    /// Goes back to the query a command got confirmed with before the one gone back to last, selecting it for the typing to replace it.
+    fn previous_query(&mut self, _: &PreviousQuery, cx: &mut ViewContext<Self>) {
+        let ix = self
+            .picker
+            .read(cx)
+            .delegate
+            .history_ix
+            .map_or(0, |ix| ix + 1);
+        self.go_to_history_query(Some(ix), cx);
+    }
+
+    /// Goes forward to the query a command got confirmed with after the one gone back to last,
+    /// or to the empty query past the last one.
+    fn next_query(&mut self, _: &NextQuery, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.picker.read(cx).delegate.history_ix else {
+            return;
+        };
+        self.go_to_history_query(ix.checked_sub(1), cx);
+    }
+
+    fn go_to_history_query(&mut self, ix: Option<usize>, cx: &mut ViewContext<Self>) {
+        let query = match ix {
+            Some(ix) => match cx.global::<QueryHistory>().get(ix) {
+                Some(query) => query.to_string(),
+                None => return,
+            },
+            None => String::new(),
+        };
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.history_ix = ix;
+            picker.set_query(query, cx);
+            picker.select_query(cx);
+        });
+    }
+
+    /// Re-reads the ranking snapshot after the usage or the settings change, ranking the matches anew.
+    fn refresh_ranking(&mut self, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.refresh_ranking_snapshot(cx);
+            picker.refresh(cx);
+        });
+    }
+
+    /// Lists the commands again, matching the query anew if they changed, e.g. with the palette opened before the crates registering them
+    /// got initialized, or the commands hidden since. Runs when the palette's filter changes or the palette gets focused again,
+    /// not on every change of the query: listing the commands is too costly for that.
+    /// The actions get registered anew on every frame: until some are available, this runs after each frame the palette gets rendered in.
+    fn reload_commands(&mut self, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if picker.delegate.refresh_commands(cx) {
+                picker.refresh(cx);
+            }
+        });
+    }
+
+    /// Jumps to the Rust type of the selected action, looked up among the project symbols.
+    /// Meant for developing Zed itself, hence only available in the Dev channel.
+    fn go_to_action_definition(&mut self, _: &GoToActionDefinition, cx: &mut ViewContext<Self>) {
+        let Some(action_name) = self.picker.read(cx).delegate.selected_action_name() else {
+            return;
+        };
+        let type_name = action_name
+            .rsplit("::")
+            .next()
+            .unwrap_or(&action_name)
+            .to_string();
+        let workspace = self.workspace.clone();
+        cx.emit(DismissEvent);
+
+        if ReleaseChannel::try_global(cx) != Some(ReleaseChannel::Dev) {
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            GO_TO_ACTION_DEFINITION_TOAST_ID,
+                            "Going to action definitions is only available in Zed Dev",
+                        ),
+                        cx,
+                    )
+                })
+                .log_err();
+            return;
+        }
+
+        let Some(symbols) = workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .project()
+                    .update(cx, |project, cx| project.symbols(&type_name, cx))
+            })
+            .log_err()
+        else {
+            return;
+        };
+        cx.spawn(|_, mut cx| async move {
+            let mut symbols = symbols.await?;
+            symbols.retain(|symbol| symbol.name == type_name);
+            workspace.update(&mut cx, |workspace, cx| match symbols.len() {
+                0 => workspace.show_toast(
+                    Toast::new(
+                        GO_TO_ACTION_DEFINITION_TOAST_ID,
+                        format!("No definition found for action {action_name}"),
+                    ),
+                    cx,
+                ),
+                1 => open_symbol(workspace, symbols.remove(0), cx),
+                _ => project_symbols::toggle_with_query(workspace, &type_name, cx),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens the settings file at the setting the selected command corresponds to, if there's one.
+    fn open_command_settings(&mut self, _: &OpenCommandSettings, cx: &mut ViewContext<Self>) {
+        let Some(setting_key) = self
+            .picker
+            .read(cx)
+            .delegate
+            .selected_action_name()
+            .and_then(|action_name| cx.global::<CommandSettings>().get(&action_name))
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| open_setting(setting_key, workspace, cx))
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    /// Opens the user keymap at the selected command's binding, or at a new one for it, if the user has none.
+    fn open_command_keymap(&mut self, _: &OpenCommandKeymap, cx: &mut ViewContext<Self>) {
+        let Some(command) = self.picker.read(cx).delegate.selected_command() else {
+            return;
+        };
+        // The project commands' actions take the arguments of their projects' settings.
+        if command.project_root.is_some() {
+            return;
+        }
+        let action_name = command.action.name().to_string();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                open_keymap_at_binding(action_name, workspace, cx)
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    /// Asks the assistant what the selected command does, showing the answer in the assistant panel.
+    fn explain_command(&mut self, _: &ExplainCommand, cx: &mut ViewContext<Self>) {
+        if !cx.has_flag::<AssistantFeatureFlag>() {
+            return;
+        }
+        let Some(details) = self.picker.update(cx, |picker, cx| {
+            picker.delegate.selected_command_details(cx)
+        }) else {
+            return;
+        };
+        cx.emit(DismissEvent);
+        self.workspace
+            .update(cx, |workspace, cx| explain_command(details, workspace, cx))
+            .log_err();
+    }
+
+    /// Inserts the keybinding the selected command has in the context the palette got opened from.
+    fn insert_keybinding_at_cursor(
+        &mut self,
+        action: &InsertKeybindingAtCursor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let delegate = &self.picker.read(cx).delegate;
+        let Some(command) = delegate.selected_command() else {
+            return;
+        };
+        let text = cx
+            .bindings_for_action_in(&*command.action, &delegate.previous_focus_handle)
+            .last()
+            .map(|key_binding| action.format.format(key_binding))
+            .ok_or_else(|| format!("'{}' has no keybinding", command.name));
+        self.insert_at_cursor(text, cx);
+    }
+
+    fn insert_action_name_at_cursor(
+        &mut self,
+        _: &InsertActionNameAtCursor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(action_name) = self.picker.read(cx).delegate.selected_action_name() else {
+            return;
+        };
+        self.insert_at_cursor(Ok(action_name), cx);
+    }
+
+    /// Dismisses the palette, giving the focus back, and inserts the text into the editor it got opened from.
+    fn insert_at_cursor(&mut self, text: Result<String, String>, cx: &mut ViewContext<Self>) {
+        let previous_focus_handle = self.picker.read(cx).delegate.previous_focus_handle.clone();
+        cx.emit(DismissEvent);
+        cx.focus(&previous_focus_handle);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                insert_at_cursor::insert_text(workspace, &previous_focus_handle, text, cx)
+            })
+            .log_err();
+    }
+
+    /// Ranks the query with every ranking profile, opening the top matches of each side by side in a new buffer.
+    /// Only available in Zed Dev.
+    fn dump_ranking_comparison(&mut self, _: &DumpRankingComparison, cx: &mut ViewContext<Self>) {
+        if ReleaseChannel::try_global(cx) != Some(ReleaseChannel::Dev) {
+            return;
+        }
+        let picker = self.picker.read(cx);
+        let query = picker.query(cx);
+        let all_commands = picker.delegate.all_commands.clone();
+        let snapshot = picker.delegate.ranking_snapshot.clone();
+        let workspace = self.workspace.clone();
+        cx.emit(DismissEvent);
+
+        cx.spawn(|_, mut cx| async move {
+            let (order, _, _) = cx
+                .background_executor()
+                .spawn({
+                    let snapshot = snapshot.clone();
+                    async move { snapshot.usage_order() }
+                })
+                .await;
+            let commands = order
+                .iter()
+                .map(|&ix| &all_commands[ix])
+                .collect::<Vec<_>>();
+            let candidates = command_candidates(&commands);
+            let action_name_candidates = action_name_candidates(&commands);
+            let description_candidates = if snapshot.match_descriptions {
+                description_candidates(&commands)
+            } else {
+                Vec::new()
+            };
+            let alias_candidates =
+                alias_candidates(&commands, &snapshot.aliases, &snapshot.previous_names);
+            let alternatives = split_alternatives(&query);
+            let mut rankings = Vec::with_capacity(RANKING_PROFILES.len());
+            for profile in RANKING_PROFILES {
+                let (matches, _, _) = match_alternatives(
+                    &alternatives,
+                    &candidates,
+                    &action_name_candidates,
+                    &description_candidates,
+                    &alias_candidates,
+                    &commands,
+                    &snapshot,
+                    profile,
+                    DEFAULT_MAX_MATCHES,
+                    cx.background_executor().clone(),
+                )
+                .await;
+                let names = matches
+                    .into_iter()
+                    .take(COMPARED_MATCHES)
+                    .map(|string_match| string_match.string)
+                    .collect::<Vec<_>>();
+                rankings.push((profile.name, names));
+            }
+            let table = comparison_table(&query, &rankings);
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let buffer =
+                    project.update(cx, |project, cx| project.create_buffer(&table, None, cx))?;
+                let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+                workspace.add_item(Box::new(editor), cx);
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Helpers for asserting on the palette's matches by the commands' relative order,
+/// which, unlike their absolute positions, does not change with the commands' usage or the actions registered.
+#[cfg(any(test, feature = "test-support"))]
+impl CommandPalette {
+    /// The names of the commands shown for the current query, in their order.
+    pub fn match_names(&self, cx: &AppContext) -> Vec<String> {
+        self.picker
+            .read(cx)
+            .delegate
+            .matches
+            .iter()
+            .map(|string_match| string_match.string.clone())
+            .collect()
+    }
+
+    /// The position of the command with the name given among the ones shown, if it is.
+    pub fn position_of(&self, command_name: &str, cx: &AppContext) -> Option<usize> {
+        self.picker
+            .read(cx)
+            .delegate
+            .matches
+            .iter()
+            .position(|string_match| string_match.string == command_name)
+    }
 
-        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
-        Self { picker }
+    /// Panics unless all the commands given are shown, in the order given, regardless of the ones in between.
+    #[track_caller]
+    pub fn assert_shown_in_order(&self, command_names: &[&str], cx: &AppContext) {
+        let positions = command_names
+            .iter()
+            .map(|command_name| {
+                self.position_of(command_name, cx).unwrap_or_else(|| {
+                    panic!(
+                        "Command {command_name:?} is not shown, matches: {:?}",
+                        self.match_names(cx)
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            positions.windows(2).all(|pair| pair[0] < pair[1]),
+            "Commands {command_names:?} are shown at positions {positions:?}"
+        );
     }
 }
 
+fn open_symbol(workspace: &mut Workspace, symbol: Symbol, cx: &mut ViewContext<Workspace>) {
+    let buffer = workspace.project().update(cx, |project, cx| {
+        project.open_buffer_for_symbol(&symbol, cx)
+    });
+    cx.spawn(|workspace, mut cx| async move {
+        let buffer = buffer.await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            let position = buffer
+                .read(cx)
+                .clip_point_utf16(symbol.range.start, Bias::Left);
+            let editor = workspace.open_project_item::<Editor>(buffer, cx);
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([position..position])
+                });
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Emitted by the palette once it dispatched the action of the command confirmed.
+/// The observers of [`on_command_executed`] get it for the commands executed by their names too.
+pub struct CommandExecuted {
+    /// Humanized name of the command, as shown in the palette.
+    pub command_name: String,
+}
+
 impl EventEmitter<DismissEvent> for CommandPalette {}
+impl EventEmitter<CommandExecuted> for CommandPalette {}
 
 impl FocusableView for CommandPalette {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
@@ -94,231 +684,1197 @@ impl FocusableView for CommandPalette {
 }
 
 impl Render for CommandPalette {
-    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
-        v_flex().w(rems(34.)).child(self.picker.clone())
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.picker.read(cx).delegate.all_commands.is_empty() {
+            cx.defer(Self::reload_commands);
+        }
+        v_flex()
+            .key_context("CommandPalette")
+            .on_action(cx.listener(Self::go_to_action_definition))
+            .on_action(cx.listener(Self::open_command_settings))
+            .on_action(cx.listener(Self::open_command_keymap))
+            .on_action(cx.listener(Self::explain_command))
+            .on_action(cx.listener(Self::dump_ranking_comparison))
+            .on_action(cx.listener(Self::insert_keybinding_at_cursor))
+            .on_action(cx.listener(Self::insert_action_name_at_cursor))
+            .on_action(cx.listener(Self::previous_query))
+            .on_action(cx.listener(Self::next_query))
+            .on_action(cx.listener(|this, _: &ToggleScoreDetails, cx| {
+                let score_details = cx.default_global::<ScoreDetails>();
+                score_details.shown = !score_details.shown;
+                this.picker.update(cx, |_, cx| cx.notify());
+            }))
+            .on_action(cx.listener(|this, _: &ForgetRecent, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    let ix = picker.delegate.selected_ix;
+                    picker.delegate.forget_recent(ix, cx);
+                    picker.refresh(cx);
+                })
+            }))
+            .on_action(cx.listener(|this, _: &ForgetHitCount, cx| {
+                let forgotten = this.picker.update(cx, |picker, cx| {
+                    let ix = picker.delegate.selected_ix;
+                    let forgotten = picker.delegate.forget_hit_count(ix, cx);
+                    if forgotten {
+                        picker.refresh(cx);
+                    }
+                    forgotten
+                });
+                if forgotten {
+                    this.workspace
+                        .update(cx, |workspace, cx| {
+                            workspace.show_toast(
+                                Toast::new(FORGET_HIT_COUNT_TOAST_ID, "Removed from history"),
+                                cx,
+                            )
+                        })
+                        .log_err();
+                }
+            }))
+            .on_action(cx.listener(|this, _: &TogglePinned, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    let ix = picker.delegate.selected_ix;
+                    picker.delegate.toggle_pinned(ix, cx);
+                })
+            }))
+            .on_action(cx.listener(|this, _: &NeverShowInRecents, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    let ix = picker.delegate.selected_ix;
+                    picker.delegate.never_show_in_recents(ix, cx);
+                    picker.refresh(cx);
+                })
+            }))
+            .w(picker::modal_width(rems(34.), cx))
+            .child(self.picker.clone())
+            .children(
+                self.picker
+                    .read(cx)
+                    .delegate
+                    .match_count_label()
+                    .map(|label| {
+                        h_flex()
+                            .px_3()
+                            .py_1()
+                            .border_t_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .debug_selector(|| "match-count".into())
+                            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                    }),
+            )
     }
 }
 
-pub struct CommandPaletteInterceptor(
-    pub Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>,
-);
+/// Turns the palette's query into commands of its own.
+type Interceptor = Rc<dyn Fn(&str, &AppContext) -> Vec<CommandInterceptResult>>;
+
+/// Callbacks turning the palette's query into commands of their own, e.g. vim's `:w`, in the order of their registration.
+#[derive(Default)]
+pub struct CommandPaletteInterceptor {
+    interceptors: Vec<(InterceptorId, Interceptor)>,
+    next_id: usize,
+}
 
 impl Global for CommandPaletteInterceptor {}
 
+/// A registered interceptor, to unregister it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterceptorId(usize);
+
+impl CommandPaletteInterceptor {
+    /// Registers the interceptor, with its results listed after the ones of the interceptors registered before.
+    pub fn register(
+        cx: &mut AppContext,
+        intercept: impl Fn(&str, &AppContext) -> Vec<CommandInterceptResult> + 'static,
+    ) -> InterceptorId {
+        let interceptors = cx.default_global::<Self>();
+        let id = InterceptorId(interceptors.next_id);
+        interceptors.next_id += 1;
+        interceptors.interceptors.push((id, Rc::new(intercept)));
+        id
+    }
+
+    pub fn unregister(id: InterceptorId, cx: &mut AppContext) {
+        cx.default_global::<Self>()
+            .interceptors
+            .retain(|(interceptor_id, _)| *interceptor_id != id);
+    }
+
+    /// The interceptors registered, in the order of their registration, for a palette to intercept its queries with.
+    fn interceptors(cx: &AppContext) -> Rc<[Interceptor]> {
+        cx.try_global::<Self>()
+            .map_or(&[][..], |interceptors| interceptors.interceptors.as_slice())
+            .iter()
+            .map(|(_, intercept)| intercept.clone())
+            .collect()
+    }
+}
+
 pub struct CommandInterceptResult {
     pub action: Box<dyn Action>,
     pub string: String,
     pub positions: Vec<usize>,
+    /// A stable identity of the command, e.g. `vim::ex::write`, to record its usage by when confirmed,
+    /// ranking the results with the most used keys first. Results without one keep the interceptor's order.
+    pub ranking_key: Option<String>,
 }
 
 pub struct CommandPaletteDelegate {
     command_palette: WeakView<CommandPalette>,
-    all_commands: Vec<Command>,
-    commands: Vec<Command>,
+    /// All the commands available, never reordered: the matches refer to them by the candidate ids.
+    all_commands: Arc<[Command]>,
+    /// The commands the query got intercepted with, matched with the candidate ids past all the commands, in their order.
+    intercepted_commands: Vec<Command>,
+    /// The interceptors registered as of the palette's opening, the queries get intercepted with.
+    interceptors: Rc<[Interceptor]>,
+    /// The actions with parameters as of the commands' listing, to run with the argument typed after their commands' names.
+    argument_parsers: Arc<ArgumentParsers>,
+    /// Why the argument typed after the intercepted command's name does not fit its action, run with its default value then.
+    argument_error: Option<SharedString>,
     matches: Vec<StringMatch>,
+    /// The query the matches got updated for last.
+    query: String,
+    /// Index of the query of [`QueryHistory`] the palette went back to, if it did.
+    history_ix: Option<usize>,
+    /// Whether a command got confirmed or the palette's dismissal with none got reported already,
+    /// for the dismissal that follows the confirmation not to get reported as one with nothing run.
+    outcome_reported: bool,
+    /// How many commands get listed at most, the intercepted ones included.
+    max_matches: usize,
+    /// Whether more than `max_matches` commands matched, for the palette to tell that only the first of them are listed.
+    matches_truncated: bool,
+    /// Whether the matches contain the query's words as substrings, possibly mistyped, with no fuzzy matches for it, to be shown muted.
+    substring_fallback: bool,
     selected_ix: usize,
     telemetry: Arc<Telemetry>,
     previous_focus_handle: FocusHandle,
+    workspace: WeakView<Workspace>,
+    workspace_id: WorkspaceId,
+    /// The number of matches on top, coming from the pinned commands.
+    pinned_count: usize,
+    /// The number of matches after the pinned ones, coming from the recent commands.
+    recent_count: usize,
+    /// Synonyms the query matched, by the candidate ids of the commands they stand for.
+    matched_synonyms: HashMap<usize, SharedString>,
+    /// Aliases the query matched, by the candidate ids of the commands they stand for.
+    matched_aliases: HashMap<usize, SharedString>,
+    /// Name of the command to select once the matches get updated.
+    selected_command: Option<String>,
+    /// Name of the command to emphasize, or of its action, as given to [`Toggle`].
+    highlighted_command: Option<String>,
+    /// Keystrokes, which all the commands' bindings start with, if the palette got opened for a pending key prefix.
+    key_prefix: Option<Vec<Keystroke>>,
+    /// Keystrokes typed after `key:`, listing only the commands with the bindings starting with them.
+    key_query: Option<Vec<Keystroke>>,
+    /// Candidate ids of the matches, whose commands the query names exactly.
+    exact_matches: Vec<usize>,
+    /// The query the palette got opened with, to confirm the only exact match of, once matched.
+    auto_confirm_query: Option<String>,
+    /// Index of the match to confirm as soon as the matches get updated.
+    auto_confirm_ix: Option<usize>,
+    /// Bindings of the commands by the candidate ids, `None` for the unbound ones.
+    /// Resolved after the rows get rendered, not to hold the first paint of the matches.
+    key_bindings: HashMap<usize, Option<gpui::KeyBinding>>,
+    /// Resolves the binding shown for the command.
+    resolve_key_binding:
+        fn(&CommandPaletteDelegate, &Command, &mut WindowContext) -> Option<gpui::KeyBinding>,
+    /// What the matches get ranked by besides the query, read once per palette session and again when it changes.
+    ranking_snapshot: Arc<RankingSnapshot>,
+    /// Reads the ranking snapshot from the globals.
+    read_ranking_snapshot: fn(&CommandPaletteDelegate, &AppContext) -> RankingSnapshot,
+    /// Lists the commands available to the element focused before the palette got opened, for the given key prefix.
+    list_commands: fn(&FocusHandle, Option<&[Keystroke]>, &mut WindowContext) -> Vec<Command>,
+    /// The snapshot the matches got ranked with, for their score details to show the hit counts they got ranked by.
+    matches_snapshot: Arc<RankingSnapshot>,
+    /// Indices of all the commands ranked by their usage, with the numbers of the pinned and the recent ones, as of the palette's opening.
+    /// Kept while the usage changes, not to shift the commands between two updates of the empty query: the commands get ranked anew
+    /// once the query gets cleared, or the user pins or forgets a command. `None` until then.
+    usage_order: Option<Arc<(Vec<usize>, usize, usize)>>,
+    /// The candidates the matches got matched against last, reused for the next keystrokes while the usage order
+    /// and the ranking snapshot they got built for stay the same.
+    candidates: Option<Arc<CommandCandidates>>,
+    /// Range of the matches rendered last.
+    rendered_range: Range<usize>,
+    resolving_key_bindings: bool,
+    /// The context menu of the selected match, if deployed.
+    context_menu: Option<(View<ContextMenu>, Subscription)>,
+}
+
+/// The command confirmed in the palette last, or executed by its name, for [`Repeat`] to dispatch again.
+struct LastConfirmedCommand {
+    name: String,
+    action: Box<dyn Action>,
+}
+
+impl Global for LastConfirmedCommand {}
+
+/// Whether the palettes show how their matches got ranked, toggled with [`ToggleScoreDetails`] for the session.
+#[derive(Default)]
+struct ScoreDetails {
+    shown: bool,
 }
 
+impl Global for ScoreDetails {}
+
 struct Command {
     name: String,
     action: Box<dyn Action>,
+    /// Initials of the name's words, matched against the short queries.
+    initials: Arc<str>,
+    /// What the command does, shown under its name.
+    description: Option<SharedString>,
+    /// What the usage of the intercepted command gets recorded by, as supplied by its interceptor.
+    ranking_key: Option<String>,
+    /// Root of the worktree whose settings define the command, for the project commands.
+    project_root: Option<Arc<Path>>,
+    /// Whether the command's action is available to the element focused before the palette got opened.
+    /// The unavailable commands are only listed, greyed out, if `command_palette.show_unavailable_commands` is on.
+    available: bool,
 }
 
-impl Clone for Command {
-    fn clone(&self) -> Self {
-        Self {
-            name: self.name.clone(),
-            action: self.action.boxed_clone(),
+impl Command {
+    /// The name the command's usage gets recorded by, scoped to the worktree for the project commands:
+    /// the same names in different projects may run different things.
+    fn usage_key(&self) -> String {
+        if let Some(ranking_key) = &self.ranking_key {
+            return intercepted_commands::usage_key(ranking_key);
+        }
+        match &self.project_root {
+            Some(project_root) => project_commands::usage_key(project_root, &self.name),
+            None => self.name.clone(),
         }
     }
 }
 
-/// Hit count for each command in the palette.
-/// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
-/// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
-#[derive(Default)]
-struct HitCounts(HashMap<String, usize>);
-
-impl Global for HitCounts {}
-
 impl CommandPaletteDelegate {
     fn new(
         command_palette: WeakView<CommandPalette>,
         commands: Vec<Command>,
         telemetry: Arc<Telemetry>,
         previous_focus_handle: FocusHandle,
+        workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        cx: &WindowContext,
     ) -> Self {
+        let ranking_snapshot = Arc::new(RankingSnapshot::read(&commands, workspace_id, cx));
+        let argument_parsers = Arc::new(ArgumentParsers::read(&previous_focus_handle, cx));
+        let usage_order = Some(Arc::new(ranking_snapshot.usage_order()));
+        let matches_snapshot = ranking_snapshot.clone();
         Self {
             command_palette,
-            all_commands: commands.clone(),
+            all_commands: commands.into(),
+            intercepted_commands: Vec::new(),
+            interceptors: CommandPaletteInterceptor::interceptors(cx),
+            argument_parsers,
+            argument_error: None,
             matches: vec![],
-            commands,
+            query: String::new(),
+            history_ix: None,
+            outcome_reported: false,
+            max_matches: DEFAULT_MAX_MATCHES,
+            matches_truncated: false,
+            substring_fallback: false,
             selected_ix: 0,
             telemetry,
             previous_focus_handle,
+            workspace,
+            workspace_id,
+            pinned_count: 0,
+            recent_count: 0,
+            matched_synonyms: HashMap::default(),
+            matched_aliases: HashMap::default(),
+            selected_command: None,
+            highlighted_command: None,
+            key_prefix: None,
+            key_query: None,
+            exact_matches: Vec::new(),
+            auto_confirm_query: None,
+            auto_confirm_ix: None,
+            key_bindings: HashMap::default(),
+            resolve_key_binding,
+            ranking_snapshot,
+            read_ranking_snapshot,
+            list_commands: available_commands,
+            matches_snapshot,
+            usage_order,
+            candidates: None,
+            rendered_range: 0..0,
+            resolving_key_bindings: false,
+            context_menu: None,
         }
     }
-}
-
-impl PickerDelegate for CommandPaletteDelegate {
-    type ListItem = ListItem;
 
-    fn placeholder_text(&self) -> Arc<str> {
-        "Execute a command...".into()
+    /// How many of the commands the query matched, e.g. "3 of 420 commands", along with the intercepted commands, if any.
+    /// `None` while there are no commands, rather than "0 of 0".
+    fn match_count_label(&self) -> Option<String> {
+        if self.all_commands.is_empty() {
+            return None;
+        }
+        let command_count = self.all_commands.len();
+        let matched_count = self
+            .matches
+            .iter()
+            .filter(|string_match| string_match.candidate_id < command_count)
+            .count();
+        let mut label = format!("{matched_count} of {command_count} commands");
+        match self.intercepted_commands.len() {
+            0 => {}
+            1 => label.push_str(" +1 command suggestion"),
+            suggestion_count => {
+                label.push_str(&format!(" +{suggestion_count} command suggestions"))
+            }
+        }
+        Some(label)
     }
 
-    fn match_count(&self) -> usize {
-        self.matches.len()
+    fn refresh_ranking_snapshot(&mut self, cx: &AppContext) {
+        self.ranking_snapshot = Arc::new((self.read_ranking_snapshot)(self, cx));
     }
 
-    fn selected_index(&self) -> usize {
-        self.selected_ix
+    /// Lists the commands available in the context the palette got opened from again, e.g. once a dock finished loading
+    /// or a namespace got hidden, keeping the project's commands and the selected command. Returns whether they changed.
+    fn refresh_commands(&mut self, cx: &mut ViewContext<Picker<Self>>) -> bool {
+        // Resolved relative to the focus the palette got opened from: its own focus has none of the workspace's commands.
+        let mut commands =
+            (self.list_commands)(&self.previous_focus_handle, self.key_prefix.as_deref(), cx);
+        // No commands at all means the element focused before is not rendered anymore, rather than it has none.
+        if commands.is_empty() {
+            return false;
+        }
+        self.argument_parsers = Arc::new(ArgumentParsers::read(&self.previous_focus_handle, cx));
+        let old_commands = self.all_commands.clone();
+        let listed = old_commands
+            .iter()
+            .filter(|command| command.project_root.is_none())
+            .map(|command| (command.action.name(), command.available));
+        if commands
+            .iter()
+            .map(|command| (command.action.name(), command.available))
+            .eq(listed)
+        {
+            return false;
+        }
+        commands.extend(
+            old_commands
+                .iter()
+                .filter(|command| command.project_root.is_some())
+                .cloned(),
+        );
+
+        // Until the matches get updated, the ones still listed stay rendered, by the indices of their commands in the new list.
+        let new_ix = |old_ix: usize| -> Option<usize> {
+            match old_commands.get(old_ix) {
+                Some(old_command) => commands.iter().position(|command| {
+                    command.action.name() == old_command.action.name()
+                        && command.project_root == old_command.project_root
+                        && command.name == old_command.name
+                }),
+                None => Some(old_ix - old_commands.len() + commands.len()),
+            }
+        };
+        if self.selected_command.is_none() {
+            self.selected_command = self.selected_command().map(|command| command.name.clone());
+        }
+        self.matches = mem::take(&mut self.matches)
+            .into_iter()
+            .filter_map(|mut string_match| {
+                string_match.candidate_id = new_ix(string_match.candidate_id)?;
+                Some(string_match)
+            })
+            .collect();
+        self.selected_ix = self.selected_ix.min(self.matches.len().saturating_sub(1));
+        for matched in [&mut self.matched_synonyms, &mut self.matched_aliases] {
+            *matched = mem::take(matched)
+                .into_iter()
+                .filter_map(|(candidate_id, name)| Some((new_ix(candidate_id)?, name)))
+                .collect();
+        }
+        self.exact_matches = mem::take(&mut self.exact_matches)
+            .into_iter()
+            .filter_map(new_ix)
+            .collect();
+        self.all_commands = commands.into();
+        self.key_bindings.clear();
+        self.refresh_ranking_snapshot(cx);
+        self.usage_order = None;
+        true
     }
 
-    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
-        self.selected_ix = ix;
+    fn matches_in(&self, range: Range<usize>) -> &[StringMatch] {
+        let end = range.end.min(self.matches.len());
+        &self.matches[range.start.min(end)..end]
     }
 
-    fn update_matches(
+    /// Lists the commands with the bindings starting with the keystrokes typed after `key:`, the ones bound to exactly
+    /// those keystrokes first, showing those bindings. Unparsable keystrokes match nothing.
+    fn match_key_query(
         &mut self,
-        query: String,
+        key_query: Option<Vec<Keystroke>>,
         cx: &mut ViewContext<Picker<Self>>,
-    ) -> gpui::Task<()> {
-        let mut commands = self.all_commands.clone();
+    ) {
+        if self.key_query != key_query {
+            self.key_bindings.clear();
+        }
+        self.key_query = key_query;
+        let mut matches = Vec::new();
+        if let Some(keystrokes) = &self.key_query {
+            for (candidate_id, command) in self.all_commands.iter().enumerate() {
+                let binding = binding_starting_with(
+                    &*command.action,
+                    keystrokes,
+                    &self.previous_focus_handle,
+                    cx,
+                );
+                let Some(binding) = binding else {
+                    continue;
+                };
+                matches.push((binding.keystrokes().len() != keystrokes.len(), candidate_id));
+                self.key_bindings.insert(candidate_id, Some(binding));
+            }
+        }
+        matches.sort();
+        self.matches = matches
+            .into_iter()
+            .map(|(_, candidate_id)| StringMatch {
+                candidate_id,
+                score: 0.0,
+                positions: Vec::new(),
+                string: self.all_commands[candidate_id].name.clone(),
+            })
+            .collect();
+        self.intercepted_commands.clear();
+        self.argument_error = None;
+        self.matches_truncated = false;
+        self.substring_fallback = false;
+        self.pinned_count = 0;
+        self.recent_count = 0;
+        self.matched_synonyms.clear();
+        self.matched_aliases.clear();
+        self.exact_matches.clear();
+        self.selected_ix = 0;
+    }
 
-        cx.spawn(move |picker, mut cx| async move {
-            cx.read_global::<HitCounts, _>(|hit_counts, _| {
-                commands.sort_by_key(|action| {
-                    (
-                        Reverse(hit_counts.0.get(&action.name).cloned()),
-                        action.name.clone(),
-                    )
-                });
+    fn has_unresolved_key_bindings(&self, range: Range<usize>) -> bool {
+        self.matches_in(range)
+            .iter()
+            .any(|string_match| !self.key_bindings.contains_key(&string_match.candidate_id))
+    }
+
+    /// Resolves the bindings of the matches in the range given, returning whether any were not resolved before.
+    fn resolve_key_bindings(&mut self, range: Range<usize>, cx: &mut WindowContext) -> bool {
+        let resolved = self
+            .matches_in(range)
+            .iter()
+            .filter(|string_match| !self.key_bindings.contains_key(&string_match.candidate_id))
+            .filter_map(|string_match| {
+                let command = self.matched_command(string_match)?;
+                let key_binding = (self.resolve_key_binding)(self, command, cx);
+                Some((string_match.candidate_id, key_binding))
             })
-            .ok();
+            .collect::<Vec<_>>();
+        let any_resolved = !resolved.is_empty();
+        self.key_bindings.extend(resolved);
+        any_resolved
+    }
 
-            let candidates = commands
-                .iter()
-                .enumerate()
-                .map(|(ix, command)| StringMatchCandidate {
-                    id: ix,
-                    string: command.name.to_string(),
-                    char_bag: command.name.chars().collect(),
-                })
-                .collect::<Vec<_>>();
-            let mut matches = if query.is_empty() {
-                candidates
-                    .into_iter()
-                    .enumerate()
-                    .map(|(index, candidate)| StringMatch {
-                        candidate_id: index,
-                        string: candidate.string,
-                        positions: Vec::new(),
-                        score: 0.0,
-                    })
-                    .collect()
-            } else {
-                fuzzy::match_strings(
-                    &candidates,
-                    &query,
-                    true,
-                    10000,
-                    &Default::default(),
-                    cx.background_executor().clone(),
-                )
-                .await
-            };
+    /// Whether the command's toggle is on, if the command is a toggle with its state probe registered.
+    /// Probed only for the rendered rows.
+    fn toggle_state(&self, command: &Command, cx: &WindowContext) -> Option<bool> {
+        let probe = cx
+            .try_global::<ToggleProbes>()?
+            .get(command.action.name())?;
+        let workspace = self.workspace.upgrade()?;
+        probe(workspace.read(cx), cx)
+    }
 
-            let mut intercept_result = cx
-                .try_read_global(|interceptor: &CommandPaletteInterceptor, cx| {
-                    (interceptor.0)(&query, cx)
-                })
-                .flatten();
-            let release_channel = cx
-                .update(|cx| ReleaseChannel::try_global(cx))
-                .ok()
-                .flatten();
-            if release_channel == Some(ReleaseChannel::Dev) {
-                if parse_zed_link(&query).is_some() {
-                    intercept_result = Some(CommandInterceptResult {
-                        action: OpenZedUrl { url: query.clone() }.boxed_clone(),
-                        string: query.clone(),
-                        positions: vec![],
-                    })
-                }
-            }
+    /// The rows of the recents section.
+    fn recent_rows(&self) -> Range<usize> {
+        self.pinned_count..self.pinned_count + self.recent_count
+    }
 
-            if let Some(CommandInterceptResult {
-                action,
-                string,
-                positions,
-            }) = intercept_result
-            {
-                if let Some(idx) = matches
-                    .iter()
-                    .position(|m| commands[m.candidate_id].action.type_id() == action.type_id())
-                {
-                    matches.remove(idx);
-                }
-                commands.push(Command {
-                    name: string.clone(),
-                    action,
-                });
-                matches.insert(
-                    0,
+    /// The name of the command at the index given, if it's shown in the recents section.
+    fn recent_command_name(&self, ix: usize) -> Option<String> {
+        if !self.recent_rows().contains(&ix) {
+            return None;
+        }
+        let r#match = self.matches.get(ix)?;
+        Some(self.matched_command(r#match)?.usage_key())
+    }
+
+    /// The command the match stands for: one of all the commands or of the intercepted ones.
+    fn matched_command(&self, string_match: &StringMatch) -> Option<&Command> {
+        match self.all_commands.get(string_match.candidate_id) {
+            Some(command) => Some(command),
+            None => self
+                .intercepted_commands
+                .get(string_match.candidate_id - self.all_commands.len()),
+        }
+    }
+
+    fn forget_recent(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(command_name) = self.recent_command_name(ix) else {
+            return;
+        };
+        let usage_scope = CommandPaletteSettings::get_global(cx).usage_scope;
+        let workspace_id = self.workspace_id;
+        self.usage_order = None;
+        cx.update_global(|recent_commands: &mut RecentCommands, cx| {
+            recent_commands.forget(usage_scope, workspace_id, &command_name, cx);
+        });
+    }
+
+    /// Forgets the usage of the command at the index given, in all scopes, removing it from the recents too,
+    /// for it to stop ranking above the commands used less. Returns whether there was a command to forget.
+    fn forget_hit_count(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) -> bool {
+        let Some(command) = self
+            .matches
+            .get(ix)
+            .and_then(|r#match| self.matched_command(r#match))
+        else {
+            return false;
+        };
+        let usage_key = command.usage_key();
+        let usage_scope = CommandPaletteSettings::get_global(cx).usage_scope;
+        let workspace_id = self.workspace_id;
+        cx.update_global(|hit_counts: &mut HitCounts, cx| hit_counts.forget(&usage_key, cx));
+        cx.update_global(|recent_commands: &mut RecentCommands, cx| {
+            recent_commands.forget(usage_scope, workspace_id, &usage_key, cx);
+        });
+        self.refresh_ranking_snapshot(cx);
+        self.usage_order = None;
+        true
+    }
+
+    fn never_show_in_recents(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(command_name) = self.recent_command_name(ix) else {
+            return;
+        };
+        self.usage_order = None;
+        cx.update_global(|recent_commands: &mut RecentCommands, cx| {
+            recent_commands.never_promote(command_name, cx);
+        });
+    }
+
+    /// Pins the command at the index given to the top of the palette, or unpins it.
+    /// The intercepted commands are not pinned, as they get listed only for some queries.
+    fn toggle_pinned(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(r#match) = self.matches.get(ix) else {
+            return;
+        };
+        let Some(command) = self.all_commands.get(r#match.candidate_id) else {
+            return;
+        };
+        let usage_key = command.usage_key();
+        self.usage_order = None;
+        cx.update_global(|pinned_commands: &mut PinnedCommands, cx| {
+            pinned_commands.toggle(usage_key, cx);
+        });
+    }
+
+    /// How the match at the index given got ranked, shown with [`ToggleScoreDetails`]: where it comes from,
+    /// its score and the hit count it got ranked by, e.g. "alias · score 0.812 · 3 hits".
+    fn score_details(&self, ix: usize) -> Option<String> {
+        let string_match = self.matches.get(ix)?;
+        let command = self.matched_command(string_match)?;
+        let candidate_id = string_match.candidate_id;
+        let (source, hit_counts) = if candidate_id >= self.all_commands.len() {
+            ("intercepted", &self.matches_snapshot.intercepted_hit_counts)
+        } else if ix < self.pinned_count {
+            ("pinned", &self.matches_snapshot.hit_counts)
+        } else if self.recent_rows().contains(&ix) {
+            ("recent", &self.matches_snapshot.hit_counts)
+        } else if self.exact_matches.contains(&candidate_id) {
+            ("exact", &self.matches_snapshot.hit_counts)
+        } else if self.matched_aliases.contains_key(&candidate_id) {
+            ("alias", &self.matches_snapshot.hit_counts)
+        } else if self.matched_synonyms.contains_key(&candidate_id) {
+            ("synonym", &self.matches_snapshot.hit_counts)
+        } else {
+            ("fuzzy", &self.matches_snapshot.hit_counts)
+        };
+        let hit_count = hit_counts.get(&command.usage_key()).copied().unwrap_or(0);
+        Some(format!(
+            "{source} · score {:.3} · {hit_count} hits",
+            string_match.score
+        ))
+    }
+
+    /// What the row of a command matched by an alias tells about it: the user's alias, or the command's previous name.
+    fn alias_label(&self, alias: &str) -> String {
+        if self.ranking_snapshot.previous_names.contains_key(alias) {
+            format!("(previously '{alias}')")
+        } else {
+            format!("— alias: {alias}")
+        }
+    }
+
+    fn is_pinned(&self, string_match: &StringMatch, cx: &AppContext) -> bool {
+        self.all_commands
+            .get(string_match.candidate_id)
+            .map_or(false, |command| {
+                cx.global::<PinnedCommands>()
+                    .is_pinned(&command.usage_key())
+            })
+    }
+
+    fn selected_command(&self) -> Option<&Command> {
+        let r#match = self.matches.get(self.selected_ix)?;
+        self.matched_command(r#match)
+    }
+
+    fn selected_action_name(&self) -> Option<String> {
+        let command = self.selected_command()?;
+        Some(command.action.name().to_string())
+    }
+
+    /// Copies the selected command's action name, e.g. to bind a key to it, and dismisses the palette
+    /// without running the command or recording its usage.
+    fn copy_action_name(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(action_name) = self.selected_action_name() else {
+            return;
+        };
+        cx.write_to_clipboard(ClipboardItem::new(action_name.clone()));
+        self.dismissed(cx);
+        cx.focus(&self.previous_focus_handle);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        COPY_ACTION_NAME_TOAST_ID,
+                        format!("Copied {action_name} to the clipboard"),
+                    ),
+                    cx,
+                )
+            })
+            .log_err();
+    }
+
+    /// What to tell the assistant about the selected command, with the binding from the context the palette got opened from.
+    fn selected_command_details(&self, cx: &mut WindowContext) -> Option<CommandDetails> {
+        let r#match = self.matches.get(self.selected_ix)?;
+        let command = self.matched_command(r#match)?;
+        let action_name = command.action.name();
+        let key_binding = cx
+            .bindings_for_action_in(&*command.action, &self.previous_focus_handle)
+            .last()
+            .map(|key_binding| {
+                key_binding
+                    .keystrokes()
+                    .iter()
+                    .map(Keystroke::unparse)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            });
+        Some(CommandDetails {
+            action_name: action_name.to_string(),
+            command_name: command.name.clone(),
+            key_binding,
+            setting_key: cx.global::<CommandSettings>().get(action_name),
+        })
+    }
+
+    /// Selects the match given and shows what can be done with its command besides running it.
+    fn deploy_context_menu(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(command) = self
+            .matches
+            .get(ix)
+            .and_then(|r#match| self.matched_command(r#match))
+        else {
+            return;
+        };
+        let explain = cx.has_flag::<AssistantFeatureFlag>();
+        let open_settings = cx
+            .global::<CommandSettings>()
+            .get(command.action.name())
+            .is_some();
+        if !explain && !open_settings {
+            return;
+        }
+        self.selected_ix = ix;
+        let command_palette = self.command_palette.clone();
+        let context_menu = ContextMenu::build(cx, |mut menu, _| {
+            if explain {
+                let command_palette = command_palette.clone();
+                menu = menu.entry(
+                    "Explain with Assistant",
+                    Some(Box::new(ExplainCommand)),
+                    move |cx| {
+                        command_palette
+                            .update(cx, |palette, cx| {
+                                palette.explain_command(&ExplainCommand, cx)
+                            })
+                            .log_err();
+                    },
+                );
+            }
+            if open_settings {
+                menu = menu.entry(
+                    "Open Settings",
+                    Some(Box::new(OpenCommandSettings)),
+                    move |cx| {
+                        command_palette
+                            .update(cx, |palette, cx| {
+                                palette.open_command_settings(&OpenCommandSettings, cx)
+                            })
+                            .log_err();
+                    },
+                );
+            }
+            menu
+        });
+        cx.focus_view(&context_menu);
+        let subscription = cx.subscribe(&context_menu, |picker, _, _: &DismissEvent, cx| {
+            picker.delegate.context_menu = None;
+            picker.focus(cx);
+            cx.notify();
+        });
+        self.context_menu = Some((context_menu, subscription));
+        cx.notify();
+    }
+}
+
+impl PickerDelegate for CommandPaletteDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self) -> Arc<str> {
+        match &self.key_prefix {
+            Some(key_prefix) => {
+                let key_prefix = key_prefix
+                    .iter()
+                    .map(Keystroke::unparse)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("Execute a command bound after {key_prefix}...").into()
+            }
+            None => "Execute a command...".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.all_commands.is_empty() {
+            "No commands available in this context".into()
+        } else if self.key_query.is_some() {
+            "No commands bound to these keystrokes".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn separators_after_indices(&self) -> Vec<usize> {
+        let mut separators = Vec::new();
+        for end in [self.pinned_count, self.pinned_count + self.recent_count] {
+            if end > 0 && end < self.matches.len() && !separators.contains(&(end - 1)) {
+                separators.push(end - 1);
+            }
+        }
+        separators
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_ix
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_ix = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        if query.is_empty() && !self.query.is_empty() {
+            self.usage_order = None;
+        }
+        self.query = query.clone();
+        if let Some(key_query) = parse_key_query(&query) {
+            self.match_key_query(key_query.ok(), cx);
+            return gpui::Task::ready(());
+        }
+        if self.key_query.take().is_some() {
+            self.key_bindings.clear();
+        }
+        let all_commands = self.all_commands.clone();
+        let snapshot = self.ranking_snapshot.clone();
+        let interceptors = self.interceptors.clone();
+        let argument_parsers = self.argument_parsers.clone();
+        let max_matches = self.max_matches;
+        let usage_order = self
+            .usage_order
+            .get_or_insert_with(|| Arc::new(snapshot.usage_order()))
+            .clone();
+        let cached_candidates = self
+            .candidates
+            .clone()
+            .filter(|candidates| candidates.built_for(&usage_order, &snapshot));
+
+        cx.spawn(move |picker, mut cx| async move {
+            // Indices of all the commands, ranked by their usage. The candidate ids index into it while matching,
+            // for the equally scored matches to keep that ranking, and get mapped back to the commands' indices then.
+            let (order, pinned_count, recent_count) = &*usage_order;
+            let (pinned_count, recent_count) = (*pinned_count, *recent_count);
+            let commands = order
+                .iter()
+                .map(|&ix| &all_commands[ix])
+                .collect::<Vec<_>>();
+            let command_candidates = cached_candidates.unwrap_or_else(|| {
+                Arc::new(CommandCandidates::build(
+                    &commands,
+                    usage_order.clone(),
+                    snapshot.clone(),
+                ))
+            });
+            let candidates = &command_candidates.names;
+            let alternatives = split_alternatives(&query);
+            // One match more than listed tells whether the list got cut, when exactly `max_matches` commands match.
+            let match_limit = max_matches.saturating_add(1);
+            let (mut matches, matched_synonyms, matched_aliases) =
+                if let Some((namespace, rest)) = split_namespace_prefix(&query, &commands) {
+                    let matches = match_in_namespace(
+                        namespace,
+                        rest,
+                        &commands,
+                        match_limit,
+                        cx.background_executor().clone(),
+                    )
+                    .await;
+                    (matches, HashMap::default(), HashMap::default())
+                } else {
+                    match_alternatives(
+                        &alternatives,
+                        candidates,
+                        &command_candidates.action_names,
+                        &command_candidates.descriptions,
+                        &command_candidates.aliases,
+                        &commands,
+                        &snapshot,
+                        snapshot.profile,
+                        match_limit,
+                        cx.background_executor().clone(),
+                    )
+                    .await
+                };
+            let substring_fallback = matches.is_empty() && !query.is_empty();
+            if substring_fallback {
+                let command_candidates = command_candidates.clone();
+                let query = query.clone();
+                matches = cx
+                    .background_executor()
+                    .spawn(async move { match_substrings(&command_candidates.names, &query) })
+                    .await;
+            }
+            // The pinned commands come first in the usage order.
+            boost_pinned(&mut matches, |candidate_id| candidate_id < pinned_count);
+            for string_match in &mut matches {
+                string_match.candidate_id = order[string_match.candidate_id];
+            }
+            let exact_matches = alternatives
+                .iter()
+                .flat_map(|alternative| {
+                    exact_matches(alternative, &all_commands, &snapshot.user_synonyms)
+                })
+                .fold(Vec::new(), |mut exact_matches, candidate_id| {
+                    if !exact_matches.contains(&candidate_id) {
+                        exact_matches.push(candidate_id);
+                    }
+                    exact_matches
+                });
+            for &candidate_id in &exact_matches {
+                // The commands named by a synonym are not among the fuzzy matches.
+                if !matches
+                    .iter()
+                    .any(|string_match| string_match.candidate_id == candidate_id)
+                {
+                    matches.push(StringMatch {
+                        candidate_id,
+                        score: 1.0,
+                        positions: Vec::new(),
+                        string: all_commands[candidate_id].name.clone(),
+                    });
+                }
+            }
+            // Exact matches go first, keeping their order.
+            matches.sort_by_key(|string_match| !exact_matches.contains(&string_match.candidate_id));
+            let matched_synonyms = matched_synonyms
+                .into_iter()
+                .map(|(ix, synonym)| (order[ix], synonym))
+                .collect::<HashMap<_, _>>();
+            let matched_aliases = matched_aliases
+                .into_iter()
+                .map(|(ix, alias)| (order[ix], alias))
+                .collect::<HashMap<_, _>>();
+
+            let mut intercept_results = if interceptors.is_empty() {
+                Vec::new()
+            } else {
+                cx.update(|cx| {
+                    interceptors
+                        .iter()
+                        .flat_map(|intercept| intercept(&query, cx))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+            };
+            intercepted_commands::rank_intercept_results(
+                &mut intercept_results,
+                &snapshot.intercepted_hit_counts,
+            );
+            if snapshot.dev_channel {
+                if parse_zed_link(&query).is_some() {
+                    intercept_results.insert(
+                        0,
+                        CommandInterceptResult {
+                            action: OpenZedUrl { url: query.clone() }.boxed_clone(),
+                            string: query.clone(),
+                            positions: vec![],
+                            ranking_key: None,
+                        },
+                    );
+                }
+            }
+            let mut argument_error = None;
+            if intercept_results.is_empty() {
+                if let Some((result, error)) = argument_parsers.intercept(&query, &all_commands) {
+                    intercept_results.push(result);
+                    argument_error = error.map(SharedString::from);
+                }
+            }
+
+            // The intercepted commands go on top, in the order of their results, replacing the matches of their actions.
+            let mut intercepted_commands = Vec::new();
+            for (ix, result) in intercept_results.into_iter().enumerate() {
+                let CommandInterceptResult {
+                    action,
+                    string,
+                    positions,
+                    ranking_key,
+                } = result;
+                if let Some(idx) = matches.iter().position(|m| {
+                    all_commands.get(m.candidate_id).map_or(false, |command| {
+                        command.action.type_id() == action.type_id()
+                    })
+                }) {
+                    matches.remove(idx);
+                }
+                matches.insert(
+                    ix,
                     StringMatch {
-                        candidate_id: commands.len() - 1,
-                        string,
+                        candidate_id: all_commands.len() + ix,
+                        string: string.clone(),
                         positions,
                         score: 0.0,
                     },
-                )
+                );
+                intercepted_commands.push(Command {
+                    initials: initials(&string).into(),
+                    name: string,
+                    action,
+                    description: None,
+                    ranking_key,
+                    project_root: None,
+                    available: true,
+                });
             }
+            let intercepted = !intercepted_commands.is_empty();
 
+            let (pinned_count, recent_count) = if query.is_empty() && !intercepted {
+                (pinned_count, recent_count)
+            } else {
+                (0, 0)
+            };
+            // The unavailable commands go below the available ones, in the order they got matched in.
+            let first_ranked = (pinned_count + recent_count).min(matches.len());
+            matches[first_ranked..].sort_by_key(|string_match| {
+                all_commands
+                    .get(string_match.candidate_id)
+                    .map_or(false, |command| !command.available)
+            });
+            // The intercepted commands are on top, so it's the worst ranked matches that get cut.
+            let matches_truncated = matches.len() > max_matches;
+            matches.truncate(max_matches);
             picker
                 .update(&mut cx, |picker, _| {
                     let delegate = &mut picker.delegate;
-                    delegate.commands = commands;
+                    // The commands got listed anew while matching: the update matching them is on its way.
+                    if !Arc::ptr_eq(&delegate.all_commands, &all_commands) {
+                        return;
+                    }
+                    // The intercepted commands change with the query, while their candidate ids stay the same.
+                    let command_count = delegate.all_commands.len();
+                    delegate
+                        .key_bindings
+                        .retain(|&candidate_id, _| candidate_id < command_count);
+                    delegate.intercepted_commands = intercepted_commands;
+                    delegate.argument_error = argument_error;
                     delegate.matches = matches;
+                    delegate.matches_truncated = matches_truncated;
+                    delegate.substring_fallback = substring_fallback;
+                    delegate.candidates = Some(command_candidates);
+                    delegate.matches_snapshot = snapshot.clone();
+                    delegate.pinned_count = pinned_count;
+                    delegate.recent_count = recent_count;
+                    delegate.matched_synonyms = matched_synonyms;
+                    delegate.matched_aliases = matched_aliases;
+                    if delegate.auto_confirm_query.as_ref() == Some(&query) {
+                        delegate.auto_confirm_query = None;
+                        if exact_matches.len() == 1 {
+                            delegate.auto_confirm_ix =
+                                delegate.matches.iter().position(|string_match| {
+                                    exact_matches.contains(&string_match.candidate_id)
+                                });
+                        }
+                    }
+                    delegate.exact_matches = exact_matches;
                     if delegate.matches.is_empty() {
                         delegate.selected_ix = 0;
                     } else {
                         delegate.selected_ix =
                             cmp::min(delegate.selected_ix, delegate.matches.len() - 1);
                     }
+                    if let Some(selected_command) = delegate.selected_command.take() {
+                        if let Some(ix) = delegate
+                            .matches
+                            .iter()
+                            .position(|string_match| string_match.string == selected_command)
+                        {
+                            delegate.selected_ix = ix;
+                        }
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn take_auto_confirm_index(&mut self) -> Option<usize> {
+        self.auto_confirm_ix.take()
+    }
+
+    fn matches_rendered(
+        &mut self,
+        visible_range: Range<usize>,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        self.rendered_range = visible_range.clone();
+        let next_page = visible_range.end..visible_range.end + visible_range.len();
+        if self.resolving_key_bindings
+            || !(self.has_unresolved_key_bindings(visible_range)
+                || self.has_unresolved_key_bindings(next_page))
+        {
+            return;
+        }
+        // The visible rows get their bindings on the next frame. Once they're painted with them,
+        // the next page gets prefetched, so that scrolling shows the bindings right away.
+        self.resolving_key_bindings = true;
+        cx.spawn(|picker, mut cx| async move {
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.resolving_key_bindings = false;
+                    let visible_range = delegate.rendered_range.clone();
+                    let next_page = visible_range.end..visible_range.end + visible_range.len();
+                    if delegate.resolve_key_bindings(visible_range, cx) {
+                        cx.notify();
+                    } else {
+                        delegate.resolve_key_bindings(next_page, cx);
+                    }
                 })
                 .log_err();
         })
+        .detach();
     }
 
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        if !self.outcome_reported {
+            self.outcome_reported = true;
+            self.telemetry.report_command_palette_dismissed_event(
+                self.query.chars().count(),
+                self.matches.len(),
+            );
+        }
         self.command_palette
             .update(cx, |_, cx| cx.emit(DismissEvent))
             .log_err();
     }
 
-    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
         if self.matches.is_empty() {
             self.dismissed(cx);
             return;
         }
-        let action_ix = self.matches[self.selected_ix].candidate_id;
-        let command = self.commands.swap_remove(action_ix);
-
-        self.telemetry
-            .report_action_event("command palette", command.name.clone());
+        let Some(command) = self
+            .matches
+            .get(self.selected_ix)
+            .and_then(|r#match| self.matched_command(r#match))
+        else {
+            self.dismissed(cx);
+            return;
+        };
+        self.outcome_reported = true;
+        if secondary {
+            self.copy_action_name(cx);
+            return;
+        }
+        // The filter may have changed since the commands got listed, e.g. with a feature flag turned off.
+        // The intercepted commands are not, as they get listed anew for every query.
+        let listed = self.matches[self.selected_ix].candidate_id < self.all_commands.len();
+        if listed && is_hidden(&*command.action, cx) {
+            log::warn!(
+                "Not running {:?}: its action got hidden from the command palette",
+                command.name
+            );
+            self.dismissed(cx);
+            return;
+        }
+        if !command.available {
+            let message = format!(
+                "Command {:?} is not available here: focus what it acts on, e.g. an editor, and run it again",
+                command.name
+            );
+            self.dismissed(cx);
+            cx.focus(&self.previous_focus_handle);
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    workspace.show_toast(Toast::new(UNAVAILABLE_COMMAND_TOAST_ID, message), cx)
+                })
+                .log_err();
+            return;
+        }
+        let command_name = command.name.clone();
+        let action = command.action.boxed_clone();
+        let usage_key = command.usage_key();
+        // The project commands are named by the projects: only their action gets reported.
+        let reported_name = match command.project_root {
+            Some(_) => humanize_action_name(action.name()),
+            None => command_name.clone(),
+        };
 
         self.matches.clear();
-        self.commands.clear();
-        cx.update_global(|hit_counts: &mut HitCounts, _| {
-            *hit_counts.0.entry(command.name).or_default() += 1;
-        });
-        let action = command.action;
-        cx.focus(&self.previous_focus_handle);
+        // The action may toggle another modal: let the palette's dismissal and the focus restoration complete
+        // before dispatching it, so it neither races the modal layer nor lands on the palette being torn down.
         self.dismissed(cx);
-        cx.dispatch_action(action);
+        cx.focus(&self.previous_focus_handle);
+        // The command gets recorded once dispatched, if available to the element focused again:
+        // not at all, if the window closes before the dispatch.
+        // The palette is kept alive until then, for its observers to learn about the command.
+        let command_palette = self.command_palette.upgrade();
+        let run = CommandRun {
+            name: command_name.clone(),
+            action,
+            usage_key,
+            reported_name,
+            query: self.query.clone(),
+            workspace_id: self.workspace_id,
+            telemetry: self.telemetry.clone(),
+        };
+        cx.window_context().defer(move |cx| {
+            run_command(run, cx);
+            if let Some(command_palette) = command_palette {
+                command_palette.update(cx, |_, cx| cx.emit(CommandExecuted { command_name }));
+            }
+        });
     }
 
     fn render_match(
@@ -328,110 +1884,4478 @@ impl PickerDelegate for CommandPaletteDelegate {
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let r#match = self.matches.get(ix)?;
-        let command = self.commands.get(r#match.candidate_id)?;
+        let command = self.matched_command(r#match)?;
+        let picker_settings = PickerSettings::get_global(cx).clone();
+        let highlighted = self
+            .highlighted_command
+            .as_ref()
+            .map_or(false, |highlight| {
+                *highlight == command.name || highlight == command.action.name()
+            });
         Some(
-            ListItem::new(ix)
+            picker_settings
+                .style_match_item(ListItem::new(ix))
                 .inset(true)
                 .spacing(ListItemSpacing::Sparse)
                 .selected(selected)
+                .on_secondary_mouse_down(
+                    cx.listener(move |picker, _, cx| picker.delegate.deploy_context_menu(ix, cx)),
+                )
+                .when(self.recent_rows().contains(&ix), |item| {
+                    item.end_hover_slot(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new(("forget-recent", ix), IconName::Close)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| Tooltip::for_action("Forget", &ForgetRecent, cx))
+                                    .on_click(cx.listener(move |picker, _, cx| {
+                                        picker.delegate.forget_recent(ix, cx);
+                                        picker.refresh(cx);
+                                    })),
+                            )
+                            .child(
+                                IconButton::new(("never-show-in-recents", ix), IconName::XCircle)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Never Show in Recents",
+                                            &NeverShowInRecents,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(move |picker, _, cx| {
+                                        picker.delegate.never_show_in_recents(ix, cx);
+                                        picker.refresh(cx);
+                                    })),
+                            ),
+                    )
+                })
                 .child(
                     h_flex()
                         .w_full()
+                        .gap_2()
                         .justify_between()
-                        .child(HighlightedLabel::new(
-                            command.name.clone(),
-                            r#match.positions.clone(),
+                        .when(highlighted, |el| {
+                            el.debug_selector(|| "highlighted-command".to_string())
+                                .pl_1()
+                                .border_l_2()
+                                .border_color(cx.theme().colors().border_focused)
+                        })
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .overflow_hidden()
+                                .child(
+                                    h_flex()
+                                        .gap_2()
+                                        .child(
+                                            picker_settings
+                                                .style_match_label(HighlightedLabel::new(
+                                                    command.name.clone(),
+                                                    r#match.positions.clone(),
+                                                ))
+                                                .when(self.substring_fallback, |label| {
+                                                    label.color(Color::Muted)
+                                                })
+                                                .when(!command.available, |label| {
+                                                    label.color(Color::Disabled)
+                                                }),
+                                        )
+                                        .when(!command.available, |el| {
+                                            el.child(
+                                                Label::new("(not available here)")
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                            )
+                                        })
+                                        .children(
+                                            self.matched_aliases.get(&r#match.candidate_id).map(
+                                                |alias| {
+                                                    Label::new(self.alias_label(alias))
+                                                        .size(LabelSize::Small)
+                                                        .color(Color::Muted)
+                                                },
+                                            ),
+                                        )
+                                        .children(
+                                            self.matched_synonyms.get(&r#match.candidate_id).map(
+                                                |synonym| {
+                                                    Label::new(format!("matched '{synonym}'"))
+                                                        .size(LabelSize::Small)
+                                                        .color(Color::Muted)
+                                                },
+                                            ),
+                                        )
+                                        .when(
+                                            self.exact_matches.contains(&r#match.candidate_id),
+                                            |el| {
+                                                el.child(
+                                                    Label::new("exact")
+                                                        .size(LabelSize::Small)
+                                                        .color(Color::Accent),
+                                                )
+                                            },
+                                        )
+                                        .when(command.project_root.is_some(), |el| {
+                                            el.child(
+                                                Label::new("project")
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                            )
+                                        })
+                                        .when_some(
+                                            self.argument_error.clone().filter(|_| {
+                                                r#match.candidate_id == self.all_commands.len()
+                                            }),
+                                            |el, error| {
+                                                el.child(
+                                                    Label::new(error)
+                                                        .size(LabelSize::Small)
+                                                        .color(Color::Error),
+                                                )
+                                            },
+                                        ),
+                                )
+                                .children(command.description.clone().map(|description| {
+                                    Label::new(description)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted)
+                                })),
+                        )
+                        .when(
+                            cx.try_global::<ScoreDetails>()
+                                .map_or(false, |score_details| score_details.shown),
+                            |el| {
+                                el.children(self.score_details(ix).map(|details| {
+                                    Label::new(details)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted)
+                                }))
+                            },
+                        )
+                        .when(
+                            r#match.candidate_id < self.all_commands.len()
+                                && (selected || self.is_pinned(r#match, cx)),
+                            |el| {
+                                let pinned = self.is_pinned(r#match, cx);
+                                el.child(
+                                    IconButton::new(("toggle-pinned", ix), IconName::Pin)
+                                        .icon_size(IconSize::Small)
+                                        .icon_color(if pinned {
+                                            Color::Accent
+                                        } else {
+                                            Color::Muted
+                                        })
+                                        .tooltip(move |cx| {
+                                            Tooltip::for_action(
+                                                if pinned { "Unpin" } else { "Pin to Top" },
+                                                &TogglePinned,
+                                                cx,
+                                            )
+                                        })
+                                        .on_click(cx.listener(move |picker, _, cx| {
+                                            picker.delegate.toggle_pinned(ix, cx);
+                                        })),
+                                )
+                            },
+                        )
+                        .children(
+                            cx.global::<CommandSettings>()
+                                .get(command.action.name())
+                                .map(|_| {
+                                    IconButton::new(("command-settings", ix), IconName::Settings)
+                                        .icon_size(IconSize::Small)
+                                        .icon_color(Color::Muted)
+                                        .tooltip(|cx| {
+                                            Tooltip::for_action(
+                                                "Open Settings",
+                                                &OpenCommandSettings,
+                                                cx,
+                                            )
+                                        })
+                                        .on_click(cx.listener(move |picker, _, cx| {
+                                            picker.delegate.selected_ix = ix;
+                                            cx.dispatch_action(OpenCommandSettings.boxed_clone());
+                                        }))
+                                }),
+                        )
+                        .when(
+                            r#match.candidate_id < self.all_commands.len()
+                                && command.project_root.is_none(),
+                            |el| {
+                                el.child(
+                                    div().visible_on_hover("list_item").child(
+                                        IconButton::new(("command-keymap", ix), IconName::Command)
+                                            .icon_size(IconSize::Small)
+                                            .icon_color(Color::Muted)
+                                            .tooltip(|cx| {
+                                                Tooltip::for_action(
+                                                    "Edit Keybinding",
+                                                    &OpenCommandKeymap,
+                                                    cx,
+                                                )
+                                            })
+                                            .on_click(cx.listener(move |picker, _, cx| {
+                                                picker.delegate.selected_ix = ix;
+                                                cx.dispatch_action(OpenCommandKeymap.boxed_clone());
+                                            })),
+                                    ),
+                                )
+                            },
+                        )
+                        .child(render_key_binding(
+                            ix,
+                            self.key_bindings
+                                .get(&r#match.candidate_id)
+                                .cloned()
+                                .flatten()
+                                .map(KeyBinding::new),
+                            command.action.boxed_clone(),
+                            self.previous_focus_handle.clone(),
                         ))
-                        .children(KeyBinding::for_action_in(
-                            &*command.action,
-                            &self.previous_focus_handle,
-                            cx,
+                        .children(
+                            self.toggle_state(command, cx)
+                                .map(|on| render_toggle_state(on, cx)),
+                        )
+                        .children(self.context_menu.as_ref().filter(|_| selected).map(
+                            |(context_menu, _)| {
+                                overlay()
+                                    .anchor(AnchorCorner::TopRight)
+                                    .child(context_menu.clone())
+                            },
                         )),
                 ),
         )
     }
+
+    fn render_footer(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if !self.matches_truncated {
+            return None;
+        }
+        Some(
+            div()
+                .px_3()
+                .pb_2()
+                .debug_selector(|| "matches-truncated".into())
+                .child(
+                    Label::new(format!("Showing the first {} matches", self.matches.len()))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element(),
+        )
+    }
 }
 
-fn humanize_action_name(name: &str) -> String {
-    let capacity = name.len() + name.chars().filter(|c| c.is_uppercase()).count();
-    let mut result = String::with_capacity(capacity);
-    for char in name.chars() {
-        if char == ':' {
-            if result.ends_with(':') {
-                result.push(' ');
-            } else {
-                result.push(':');
-            }
-        } else if char == '_' {
-            result.push(' ');
-        } else if char.is_uppercase() {
-            if !result.ends_with(' ') {
-                result.push(' ');
+/// The commands of the actions available to the element focused before the palette got opened, sorted by their names,
+/// followed by the rest of the registered actions' commands, unavailable there, if `command_palette.show_unavailable_commands` is on.
+/// With the key prefix given, only the commands with the bindings starting with it.
+fn available_commands(
+    previous_focus_handle: &FocusHandle,
+    key_prefix: Option<&[Keystroke]>,
+    cx: &mut WindowContext,
+) -> Vec<Command> {
+    let mut invalid_actions = Vec::new();
+    let actions = cx
+        .available_actions_in(previous_focus_handle)
+        .into_iter()
+        .filter(|action| match validate_action(&**action) {
+            Ok(()) => true,
+            Err(error) => {
+                invalid_actions.push(format!("{:?}: {error}", action.name()));
+                false
             }
-            result.extend(char.to_lowercase());
-        } else {
-            result.push(char);
+        })
+        .filter(|action| !is_hidden(&**action, cx))
+        .collect::<Vec<_>>();
+    if !invalid_actions.is_empty() {
+        log::error!(
+            "Skipped {} actions the command palette cannot list: {}",
+            invalid_actions.len(),
+            invalid_actions.join(", ")
+        );
+        debug_assert!(
+            false,
+            "Actions the command palette cannot list: {invalid_actions:?}"
+        );
+    }
+    let actions = match key_prefix {
+        Some(key_prefix) => actions
+            .into_iter()
+            .filter(|action| {
+                keys_after_prefix(&**action, key_prefix, previous_focus_handle, cx).is_some()
+            })
+            .collect(),
+        None => actions,
+    };
+    let unavailable_actions = if key_prefix.is_none()
+        && CommandPaletteSettings::get_global(cx).show_unavailable_commands
+    {
+        let available_names = actions
+            .iter()
+            .map(|action| action.name())
+            .collect::<HashSet<_>>();
+        // Only the actions built with no arguments get listed: there's no telling what the others should run with.
+        cx.all_action_names()
+            .iter()
+            .filter(|action_name| !available_names.contains(action_name.as_ref()))
+            .filter_map(|action_name| cx.build_action(action_name, None).ok())
+            .filter(|action| validate_action(&**action).is_ok() && !is_hidden(&**action, cx))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let actions = actions
+        .into_iter()
+        .map(|action| (action, true))
+        .chain(
+            unavailable_actions
+                .into_iter()
+                .map(|action| (action, false)),
+        )
+        .collect::<Vec<_>>();
+    let descriptions = actions
+        .iter()
+        .map(|(action, _)| {
+            cx.try_global::<CommandDescriptions>()
+                .and_then(|descriptions| descriptions.get(action.name()))
+        })
+        .collect::<Vec<_>>();
+    let variants = actions
+        .iter()
+        .map(|(action, _)| {
+            cx.try_global::<CommandVariants>()
+                .map(|variants| variants.get(action.name()))
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let command_initials = cx.default_global::<CommandInitials>();
+    let mut commands = actions
+        .into_iter()
+        .zip(descriptions)
+        .zip(variants)
+        .flat_map(|(((action, available), description), variants)| {
+            let name = humanize_action_name(action.name());
+            let variants = variants
+                .into_iter()
+                .map(|(label, action)| (variant_name(&name, &label), action))
+                .collect::<Vec<_>>();
+            iter::once((name, action))
+                .chain(variants)
+                .map(|(name, action)| Command {
+                    initials: command_initials.get(&name),
+                    name,
+                    action,
+                    description: description.clone(),
+                    ranking_key: None,
+                    project_root: None,
+                    available,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // The available actions come in the order of their registration, which differs between the platforms.
+    // The variants stay after their actions' commands, in the order of their registration.
+    commands.sort_by(|a, b| {
+        (!a.available, namespace_and_name(a.action.name()))
+            .cmp(&(!b.available, namespace_and_name(b.action.name())))
+    });
+    commands
+}
+
+/// The candidates the query gets matched against, for the commands in the usage order, along with what they got built for.
+struct CommandCandidates {
+    usage_order: Arc<(Vec<usize>, usize, usize)>,
+    snapshot: Arc<RankingSnapshot>,
+    names: Vec<StringMatchCandidate>,
+    action_names: Vec<StringMatchCandidate>,
+    descriptions: Vec<StringMatchCandidate>,
+    aliases: Vec<StringMatchCandidate>,
+}
+
+impl CommandCandidates {
+    fn build(
+        commands: &[&Command],
+        usage_order: Arc<(Vec<usize>, usize, usize)>,
+        snapshot: Arc<RankingSnapshot>,
+    ) -> Self {
+        Self {
+            names: command_candidates(commands),
+            action_names: action_name_candidates(commands),
+            descriptions: if snapshot.match_descriptions {
+                description_candidates(commands)
+            } else {
+                Vec::new()
+            },
+            aliases: alias_candidates(commands, &snapshot.aliases, &snapshot.previous_names),
+            usage_order,
+            snapshot,
         }
     }
-    result
+
+    /// Whether the candidates got built for the commands in the usage order given, ranked with the snapshot given.
+    fn built_for(
+        &self,
+        usage_order: &Arc<(Vec<usize>, usize, usize)>,
+        snapshot: &Arc<RankingSnapshot>,
+    ) -> bool {
+        Arc::ptr_eq(&self.usage_order, usage_order) && Arc::ptr_eq(&self.snapshot, snapshot)
+    }
 }
 
-impl std::fmt::Debug for Command {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Command")
-            .field("name", &self.name)
-            .finish_non_exhaustive()
+fn command_candidates(commands: &[&Command]) -> Vec<StringMatchCandidate> {
+    commands
+        .iter()
+        .enumerate()
+        .map(|(ix, command)| StringMatchCandidate {
+            id: ix,
+            string: command.name.to_string(),
+            // The characters outside of ASCII are left out of the char bags,
+            // so the prefilter never skips the names in the other scripts for them.
+            char_bag: command.name.chars().collect(),
+        })
+        .collect()
+}
+
+/// The candidates to match the actions' names with, e.g. `editor::ToggleComments`, with the same ids as the commands' ones.
+fn action_name_candidates(commands: &[&Command]) -> Vec<StringMatchCandidate> {
+    commands
+        .iter()
+        .enumerate()
+        .map(|(ix, command)| StringMatchCandidate::new(ix, command.action.name().to_string()))
+        .collect()
+}
+
+fn description_candidates(commands: &[&Command]) -> Vec<StringMatchCandidate> {
+    commands
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, command)| {
+            let description = command.description.as_ref()?;
+            Some(StringMatchCandidate::new(ix, description.to_string()))
+        })
+        .collect()
+}
+
+/// The candidates to match the aliases with, with the same ids as the commands' ones, in the order of the aliases,
+/// followed by the previous names of the renamed commands. An alias names an action, or a command by its name,
+/// e.g. a Latin shorthand of a project command named in another script. The aliases of the actions and the commands
+/// not among the commands given are skipped.
+fn alias_candidates(
+    commands: &[&Command],
+    aliases: &HashMap<String, String>,
+    previous_names: &HashMap<String, String>,
+) -> Vec<StringMatchCandidate> {
+    let mut aliases = aliases.iter().collect::<Vec<_>>();
+    aliases.sort();
+    let mut previous_names = previous_names.iter().collect::<Vec<_>>();
+    previous_names.sort();
+    aliases.extend(previous_names);
+    aliases
+        .into_iter()
+        .filter_map(|(alias, target)| {
+            let ix = commands
+                .iter()
+                .position(|command| command.action.name() == target || command.name == *target)?;
+            Some(StringMatchCandidate::new(ix, alias.clone()))
+        })
+        .collect()
+}
+
+/// Indices of the commands the query names exactly, regardless of the case: by the humanized name (`"editor: format"`),
+/// the action's name (`"editor::Format"`) or a synonym (`"format document"`), the user synonyms taking precedence.
+fn exact_matches(
+    query: &str,
+    all_commands: &[Command],
+    user_synonyms: &HashMap<String, String>,
+) -> Vec<usize> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let synonym_action_name = resolve_synonym(&query, user_synonyms);
+    all_commands
+        .iter()
+        .enumerate()
+        .filter(|(_, command)| {
+            let action_name = command.action.name();
+            command.name == query
+                || action_name.to_lowercase() == query
+                || synonym_action_name.as_deref() == Some(action_name)
+        })
+        .map(|(ix, _)| ix)
+        .collect()
+}
+
+/// Checks that the action can be listed as a command: that it's named `namespace::Name`, as its command's name
+/// and its usage get derived from that, and that its clones, dispatched when confirmed, are the same action.
+fn validate_action(action: &dyn Action) -> anyhow::Result<()> {
+    let name = action.name();
+    let (namespace, type_name) = name
+        .rsplit_once("::")
+        .ok_or_else(|| anyhow!("the name has no namespace"))?;
+    if namespace.is_empty() || type_name.is_empty() {
+        bail!("the name has an empty namespace or type name");
+    }
+    if humanize_action_name(name).trim().is_empty() {
+        bail!("the name humanizes to an empty command name");
+    }
+    if !action.boxed_clone().partial_eq(action) {
+        bail!("the action's clone differs from it");
+    }
+    Ok(())
+}
+
+/// Whether the action's command is hidden from the palette by the [`CommandPaletteFilter`].
+fn is_hidden(action: &dyn Action, cx: &AppContext) -> bool {
+    is_action_type_hidden(action.name(), action.type_id(), cx)
+}
+
+/// Whether the commands of the action with the name and the type given are hidden from the palette.
+fn is_action_type_hidden(action_name: &str, action_type: TypeId, cx: &AppContext) -> bool {
+    let Some(filter) = cx.try_global::<CommandPaletteFilter>() else {
+        return false;
+    };
+    is_namespace_hidden(action_name, cx) || filter.is_action_type_hidden(action_type)
+}
+
+/// Whether the namespace of the action with the name given is hidden from the palette.
+fn is_namespace_hidden(action_name: &str, cx: &AppContext) -> bool {
+    let Some(filter) = cx.try_global::<CommandPaletteFilter>() else {
+        return false;
+    };
+    let namespace = action_name
+        .split("::")
+        .next()
+        .unwrap_or("malformed action name");
+    filter.is_namespace_hidden(namespace)
+}
+
+/// A command to run the way confirming it in the palette does, see [`run_command`].
+struct CommandRun {
+    /// The command's name, as listed in the palette.
+    name: String,
+    action: Box<dyn Action>,
+    usage_key: String,
+    /// The name the command gets reported to the telemetry by.
+    reported_name: String,
+    /// The query the command got confirmed with, or the name it got executed by.
+    query: String,
+    workspace_id: WorkspaceId,
+    telemetry: Arc<Telemetry>,
+}
+
+/// Runs the command: dispatches its action to the focused element, records it, and keeps it,
+/// along with its query, for [`Repeat`], the palette's history and the observers of [`on_command_executed`].
+/// The command only gets recorded if its action is available to the focused element: nothing handles it otherwise.
+fn run_command(run: CommandRun, cx: &mut WindowContext) {
+    let available = cx.is_action_available(&*run.action);
+    cx.dispatch_action(run.action.boxed_clone());
+    if available {
+        record_command(
+            run.usage_key,
+            run.reported_name,
+            run.workspace_id,
+            &run.telemetry,
+            cx,
+        );
+    }
+    cx.update_global(|history: &mut QueryHistory, _| history.record(&run.query));
+    cx.set_global(LastConfirmedCommand {
+        name: run.name,
+        action: run.action,
+    });
+}
+
+/// Records the command as confirmed in the palette: reports it to the telemetry and ranks it higher later.
+fn record_command(
+    usage_key: String,
+    reported_name: String,
+    workspace_id: WorkspaceId,
+    telemetry: &Arc<Telemetry>,
+    cx: &mut AppContext,
+) {
+    telemetry.report_action_event("command palette", reported_name);
+    let usage_scope = CommandPaletteSettings::get_global(cx).usage_scope;
+    cx.update_global(|recent_commands: &mut RecentCommands, cx| {
+        recent_commands.record(usage_scope, workspace_id, usage_key.clone(), cx);
+    });
+    cx.update_global(|hit_counts: &mut HitCounts, cx| {
+        hit_counts.record(usage_scope, workspace_id, usage_key, cx);
+    });
+}
+
+/// The name of the action, the command with the name given stands for. Besides the action's name (`"editor::Format"`),
+/// the name can be the command's one, as shown in the palette (`"editor: format"`), or its synonym (`"format document"`).
+/// The deprecated names of the renamed actions resolve to their new names.
+fn resolve_action_name(name: &str, cx: &AppContext) -> Option<String> {
+    let action_names = cx.all_action_names();
+    if let Some(action_name) = action_names
+        .iter()
+        .find(|action_name| action_name.as_ref() == name)
+    {
+        return Some(action_name.to_string());
+    }
+    if let Some(action_name) = resolve_deprecated_alias(name) {
+        return Some(action_name.to_string());
+    }
+    let command_name = normalize(name);
+    if let Some(action_name) = action_names
+        .iter()
+        .find(|action_name| humanize_action_name(action_name) == command_name)
+    {
+        return Some(action_name.to_string());
+    }
+    resolve_synonym(name, &CommandPaletteSettings::get_global(cx).synonyms)
+}
+
+/// Executes the command with the name given without showing the palette, the same way confirming it in the palette does:
+/// see [`run_command`], with the command's action built with the arguments given.
+/// Fails for the commands unavailable in the focused element's context, and the hidden ones, unless `include_hidden`.
+fn execute_command(
+    workspace: &mut Workspace,
+    name: &str,
+    args: Option<serde_json::Value>,
+    include_hidden: bool,
+    cx: &mut ViewContext<Workspace>,
+) -> anyhow::Result<()> {
+    let (command_name, action) = resolve_command(name, args, cx)?;
+    if !include_hidden && is_hidden(&*action, cx) {
+        bail!("command {name:?} is hidden from the command palette");
+    }
+    if !cx.is_action_available(&*action) {
+        bail!("command {name:?} is not available in the focused context");
+    }
+    run_command(
+        CommandRun {
+            name: command_name.clone(),
+            action,
+            usage_key: command_name.clone(),
+            reported_name: command_name,
+            query: name.to_string(),
+            workspace_id: workspace.database_id(),
+            telemetry: workspace.client().telemetry().clone(),
+        },
+        cx,
+    );
+    Ok(())
+}
+
+/// The command with the name given, named as the palette lists it, with its action built with the arguments given.
+/// Besides the names [`resolve_action_name`] resolves, the name can be a variant's one (`"vim: switch mode (visual line)"`),
+/// or an alias of the `command_palette.aliases` setting.
+fn resolve_command(
+    name: &str,
+    args: Option<serde_json::Value>,
+    cx: &AppContext,
+) -> anyhow::Result<(String, Box<dyn Action>)> {
+    let normalized_name = normalize(name);
+    let target = CommandPaletteSettings::get_global(cx)
+        .aliases
+        .iter()
+        .find(|(alias, _)| normalize(alias) == normalized_name)
+        .map_or(name, |(_, target)| target.as_str());
+    // The variants are built with their own arguments.
+    if args.is_none() {
+        if let Some(variant) = cx
+            .try_global::<CommandVariants>()
+            .and_then(|variants| variants.find(target))
+        {
+            return Ok(variant);
+        }
+    }
+    let action_name =
+        resolve_action_name(target, cx).ok_or_else(|| anyhow!("no command named {name:?}"))?;
+    let action = cx.build_action(&action_name, args)?;
+    Ok((humanize_action_name(&action_name), action))
+}
+
+/// Dispatches the command confirmed in the palette last again, without opening the palette, unless it got hidden since.
+/// Not recorded as a usage: like the commands run with their key bindings, repeated ones are already known to the user.
+fn repeat_last_command(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(last_command) = cx.try_global::<LastConfirmedCommand>() else {
+        workspace.show_toast(
+            Toast::new(REPEAT_TOAST_ID, "No command confirmed in the palette yet"),
+            cx,
+        );
+        return;
+    };
+    if is_hidden(&*last_command.action, cx) {
+        let message = format!(
+            "Command {:?} is hidden from the command palette",
+            last_command.name
+        );
+        workspace.show_toast(Toast::new(REPEAT_TOAST_ID, message), cx);
+        return;
+    }
+    let action = last_command.action.boxed_clone();
+    cx.dispatch_action(action);
+}
+
+/// Executes the command with the name given as confirming it in the palette would, for the tests and the automation.
+/// See [`ExecuteCommand`] for executing the hidden commands too.
+#[cfg(any(test, feature = "test-support"))]
+pub fn execute_by_name(
+    workspace: &mut Workspace,
+    name: &str,
+    args: Option<serde_json::Value>,
+    cx: &mut ViewContext<Workspace>,
+) -> anyhow::Result<()> {
+    execute_command(workspace, name, args, false, cx)
+}
+
+fn read_ranking_snapshot(delegate: &CommandPaletteDelegate, cx: &AppContext) -> RankingSnapshot {
+    RankingSnapshot::read(&delegate.all_commands, delegate.workspace_id, cx)
+}
+
+/// The binding shown for the command: the one starting with the keystrokes of a `key:` query, if any,
+/// the keystrokes left after the palette's key prefix, if any, or the last binding in the context the palette got opened from.
+fn resolve_key_binding(
+    delegate: &CommandPaletteDelegate,
+    command: &Command,
+    cx: &mut WindowContext,
+) -> Option<gpui::KeyBinding> {
+    if let Some(key_query) = &delegate.key_query {
+        return binding_starting_with(
+            &*command.action,
+            key_query,
+            &delegate.previous_focus_handle,
+            cx,
+        );
+    }
+    match &delegate.key_prefix {
+        Some(key_prefix) => keys_after_prefix(
+            &*command.action,
+            key_prefix,
+            &delegate.previous_focus_handle,
+            cx,
+        ),
+        None => binding_in_focus_chain(&*command.action, delegate, cx),
+    }
+}
+
+/// The action's binding of the highest precedence for the element focused before the palette got opened, falling back
+/// to the one for the workspace, then to the one with no context: the focused element may not be rendered anymore.
+fn binding_in_focus_chain(
+    action: &dyn Action,
+    delegate: &CommandPaletteDelegate,
+    cx: &mut WindowContext,
+) -> Option<gpui::KeyBinding> {
+    if let Some(binding) = cx
+        .bindings_for_action_in(action, &delegate.previous_focus_handle)
+        .pop()
+    {
+        return Some(binding);
+    }
+    if let Some(workspace) = delegate.workspace.upgrade() {
+        let workspace_focus_handle = workspace.focus_handle(cx);
+        if let Some(binding) = cx
+            .bindings_for_action_in(action, &workspace_focus_handle)
+            .pop()
+        {
+            return Some(binding);
+        }
+    }
+    cx.global_bindings_for_action(action).pop()
+}
+
+/// The keystrokes left to type after the prefix given, for the action's binding that starts with it.
+fn keys_after_prefix(
+    action: &dyn Action,
+    key_prefix: &[Keystroke],
+    focus_handle: &FocusHandle,
+    cx: &mut WindowContext,
+) -> Option<gpui::KeyBinding> {
+    cx.bindings_for_action_in(action, focus_handle)
+        .into_iter()
+        .find_map(|binding| {
+            let keystrokes = binding.keystrokes();
+            if keystrokes.len() == key_prefix.len()
+                || !starts_with_keystrokes(keystrokes, key_prefix)
+            {
+                return None;
+            }
+            let remaining_keys = keystrokes[key_prefix.len()..]
+                .iter()
+                .map(Keystroke::unparse)
+                .collect::<Vec<_>>()
+                .join(" ");
+            gpui::KeyBinding::load(&remaining_keys, action.boxed_clone(), None).log_err()
+        })
+}
+
+/// The number of keystrokes shown for a binding before its middle gets elided.
+const MAX_KEYSTROKES: usize = 4;
+
+/// Renders a binding in a fixed-width slot, so that long chords can't push the
+/// action name out of the row. Hovering the binding lists all of the action's bindings in full,
+/// along with the keymaps they come from, see [`KeyBindingSourcesTooltip`].
+/// The slot keeps the keys' height while the binding is not resolved yet, so the row's layout doesn't shift once it is.
+pub(crate) fn render_key_binding(
+    ix: usize,
+    key_binding: Option<KeyBinding>,
+    action: Box<dyn Action>,
+    focus_handle: FocusHandle,
+) -> impl IntoElement {
+    let bound = key_binding.is_some();
+    let key_binding = key_binding.map(|key_binding| key_binding.max_keystrokes(MAX_KEYSTROKES));
+
+    div()
+        .id(("key-binding", ix))
+        .flex_none()
+        .min_h(rems(14. / 16.))
+        .max_w(rems(16.))
+        .overflow_hidden()
+        .children(key_binding)
+        .when(bound, |el| {
+            el.tooltip(move |cx| {
+                let bindings = bindings_by_precedence(&*action, &focus_handle, cx);
+                cx.new_view(|_| KeyBindingSourcesTooltip { bindings })
+                    .into()
+            })
+        })
+}
+
+/// The action's bindings in the context of the focus given, the one taking precedence first.
+fn bindings_by_precedence(
+    action: &dyn Action,
+    focus_handle: &FocusHandle,
+    cx: &mut WindowContext,
+) -> Vec<gpui::KeyBinding> {
+    let mut bindings = cx.bindings_for_action_in(action, focus_handle);
+    bindings.reverse();
+    bindings
+}
+
+/// Where the binding got defined, e.g. `keymaps/vim.json` or the user's keymap file.
+fn binding_source(binding: &gpui::KeyBinding) -> SharedString {
+    binding
+        .source()
+        .cloned()
+        .unwrap_or_else(|| "built into Zed".into())
+}
+
+/// Lists the bindings of a command, the one shown in its row first, each with the keymap it comes from.
+struct KeyBindingSourcesTooltip {
+    bindings: Vec<gpui::KeyBinding>,
+}
+
+impl Render for KeyBindingSourcesTooltip {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        tooltip_container(cx, |el, _| {
+            el.gap_1().children(self.bindings.iter().map(|binding| {
+                h_flex()
+                    .gap_4()
+                    .justify_between()
+                    .child(KeyBinding::new(binding.clone()))
+                    .child(
+                        Label::new(binding_source(binding))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+            }))
+        })
+    }
+}
+
+fn humanize_action_name(name: &str) -> String {
+    humanize_with_offsets(name).0
+}
+
+/// The positions in the humanized action name of the action name's characters at the positions given,
+/// to highlight the matches of the action's name in its command's name.
+fn humanized_positions(action_name: &str, positions: &[usize]) -> Vec<usize> {
+    let (_, offsets) = humanize_with_offsets(action_name);
+    positions
+        .iter()
+        .filter_map(|position| {
+            let ix = offsets
+                .binary_search_by_key(position, |(offset, _)| *offset)
+                .ok()?;
+            Some(offsets[ix].1)
+        })
+        .collect()
+}
+
+/// The humanized action name, along with the offsets of the action name's characters and of the ones they became.
+/// Consecutive uppercase letters make one word, an acronym, ended by the uppercase letter starting the next word:
+/// `LSPLogView` becomes "lsp log view".
+fn humanize_with_offsets(name: &str) -> (String, Vec<(usize, usize)>) {
+    let capacity = name.len() + name.chars().filter(|c| c.is_uppercase()).count();
+    let mut result = String::with_capacity(capacity);
+    let mut offsets = Vec::with_capacity(name.len());
+    let mut chars = name.char_indices().peekable();
+    let mut previous_char = None;
+    while let Some((offset, char)) = chars.next() {
+        let next_char = chars.peek().map(|&(_, next_char)| next_char);
+        let in_acronym = previous_char.map_or(false, char::is_uppercase)
+            && !next_char.map_or(false, char::is_lowercase);
+        previous_char = Some(char);
+        if char == ':' {
+            offsets.push((offset, result.len()));
+            if result.ends_with(':') {
+                result.push(' ');
+            } else {
+                result.push(':');
+            }
+        } else if char == '_' {
+            offsets.push((offset, result.len()));
+            result.push(' ');
+        } else if char.is_uppercase() {
+            if !in_acronym && !result.ends_with(' ') {
+                result.push(' ');
+            }
+            offsets.push((offset, result.len()));
+            result.extend(char.to_lowercase());
+        } else {
+            offsets.push((offset, result.len()));
+            result.push(char);
+        }
+    }
+    (result, offsets)
+}
+
+/// Splits the action name into its namespace and the name within it.
+fn namespace_and_name(action_name: &str) -> (&str, &str) {
+    action_name.rsplit_once("::").unwrap_or(("", action_name))
+}
+
+impl Clone for Command {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            action: self.action.boxed_clone(),
+            initials: self.initials.clone(),
+            description: self.description.clone(),
+            ranking_key: self.ranking_key.clone(),
+            project_root: self.project_root.clone(),
+            available: self.available,
+        }
+    }
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{
+            atomic::{self, AtomicUsize},
+            Arc,
+        },
+    };
+
+    use super::*;
+    use editor::Editor;
+    use futures::StreamExt;
+    use go_to_line::GoToLine;
+    use gpui::{px, size, Modifiers, Task, TestAppContext, VisualTestContext};
+    use language::{
+        language_settings::{self, AllLanguageSettings},
+        FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, Point,
+    };
+    use project::Project;
+    use serde_json::json;
+    use settings::{KeymapFile, SettingsStore};
+    use std::path::Path;
+    use util::paths;
+    use workspace::{
+        notifications::simple_message_notification::MessageNotification, AppState, Workspace,
+    };
+
+    #[test]
+    fn test_humanize_action_name() {
+        assert_eq!(
+            humanize_action_name("editor::GoToDefinition"),
+            "editor: go to definition"
+        );
+        assert_eq!(
+            humanize_action_name("editor::Backspace"),
+            "editor: backspace"
+        );
+        assert_eq!(
+            humanize_action_name("go_to_line::Deploy"),
+            "go to line: deploy"
+        );
+        assert_eq!(humanize_action_name("zed::OpenURL"), "zed: open url");
+        assert_eq!(
+            humanize_action_name("editor::CopyHTTPSUrl"),
+            "editor: copy https url"
+        );
+        assert_eq!(
+            humanize_action_name("zed::ToggleUIFontSize"),
+            "zed: toggle ui font size"
+        );
+        assert_eq!(
+            humanize_action_name("lsp::RestartLSPServer"),
+            "lsp: restart lsp server"
+        );
+        assert_eq!(humanize_action_name("lsp::LSPLogView"), "lsp: lsp log view");
+    }
+
+    #[test]
+    fn test_humanized_positions() {
+        let action_name = "editor::GoToDefinition";
+        let command_name = humanize_action_name(action_name);
+        let highlighted = |positions: &[usize]| {
+            humanized_positions(action_name, positions)
+                .into_iter()
+                .map(|position| &command_name[position..position + 1])
+                .collect::<String>()
+        };
+        assert_eq!(highlighted(&[0, 1, 6, 7, 8, 9]), "ed: go");
+        assert_eq!(highlighted(&[10, 12]), "td");
+
+        let action_name = "editor::CopyHTTPSUrl";
+        let command_name = humanize_action_name(action_name);
+        let highlighted = |positions: &[usize]| {
+            humanized_positions(action_name, positions)
+                .into_iter()
+                .map(|position| &command_name[position..position + 1])
+                .collect::<String>()
+        };
+        assert_eq!(
+            highlighted(&[12, 13, 14, 15, 16, 17, 18]),
+            "httpsur",
+            "The acronym's letters should be highlighted where they got humanized"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_action_name_matches(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("editor::Backsp");
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            let top_match = &palette.delegate.matches[0];
+            assert_eq!(
+                top_match.string, "editor: backspace",
+                "The commands should be found by their actions' names"
+            );
+            let highlighted = top_match
+                .positions
+                .iter()
+                .map(|&position| &top_match.string[position..position + 1])
+                .collect::<String>();
+            assert_eq!(
+                highlighted, "editor: backsp",
+                "The action name's match should be highlighted in the command's name"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_palette(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            assert!(palette.match_names(cx).len() > 5);
+            palette.assert_shown_in_order(
+                &[
+                    "editor: backspace",
+                    "editor: select all",
+                    "workspace: new file",
+                ],
+                cx,
+            );
+        });
+
+        cx.simulate_input("bcksp");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("editor: backspace", cx), Some(0));
+        });
+
+        cx.simulate_keystrokes("enter");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            assert_eq!(editor.read(cx).text(cx), "ab")
+        });
+
+        // The commands used get ahead of the others, which keep their order.
+        open_palette(&workspace, cx);
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            palette.assert_shown_in_order(
+                &[
+                    "editor: backspace",
+                    "editor: delete",
+                    "editor: select all",
+                    "workspace: new file",
+                ],
+                cx,
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_query_terms_in_any_order(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("backspace editor");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("editor: backspace", cx), Some(0));
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(editor.read(cx).text(cx), "ab");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_mistyped_query(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("editor backpsace");
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert!(
+                palette.delegate.substring_fallback,
+                "The swapped letters should leave the fuzzy matching with nothing"
+            );
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+
+        palette.update(cx, |palette, cx| palette.set_query("editor backspace", cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert!(!palette.delegate.substring_fallback);
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+
+        palette.update(cx, |palette, cx| palette.set_query("qxzjv", cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert!(palette.delegate.matches.is_empty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_matches_truncated_at_max_matches(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("e");
+        let match_count = palette.update(cx, |palette, _| palette.delegate.matches.len());
+        assert!(match_count > 3);
+        assert!(
+            cx.debug_bounds("matches-truncated").is_none(),
+            "The matches under the cap should not be said to be truncated"
+        );
+
+        // Exactly as many matches as listed at most are not truncated.
+        palette.update(cx, |palette, cx| {
+            palette.delegate.max_matches = match_count;
+            palette.refresh(cx);
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches.len(), match_count);
+            assert!(!palette.delegate.matches_truncated);
+        });
+        assert!(cx.debug_bounds("matches-truncated").is_none());
+
+        palette.update(cx, |palette, cx| {
+            palette.delegate.max_matches = match_count - 1;
+            palette.refresh(cx);
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches.len(), match_count - 1);
+            assert!(palette.delegate.matches_truncated);
+        });
+
+        palette.update(cx, |palette, cx| {
+            palette.delegate.max_matches = 3;
+            palette.refresh(cx);
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches.len(), 3);
+            assert!(palette.delegate.matches_truncated);
+        });
+        assert!(
+            cx.debug_bounds("matches-truncated").is_some(),
+            "The palette should tell that only the first matches are listed"
+        );
+
+        palette.update(cx, |palette, cx| {
+            palette.delegate.max_matches = DEFAULT_MAX_MATCHES;
+            palette.refresh(cx);
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches.len(), match_count);
+        });
+        assert!(cx.debug_bounds("matches-truncated").is_none());
+    }
+
+    #[gpui::test]
+    async fn test_match_count(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| {
+            CommandPaletteInterceptor::register(cx, |query, _| {
+                if query == "bcksp" {
+                    vec![CommandInterceptResult {
+                        action: editor::actions::Delete.boxed_clone(),
+                        string: ":delete".to_string(),
+                        positions: Vec::new(),
+                        ranking_key: None,
+                    }]
+                } else {
+                    Vec::new()
+                }
+            });
+        });
+
+        let palette = open_palette(&workspace, cx);
+        cx.run_until_parked();
+        let command_count = palette.update(cx, |palette, _| palette.delegate.all_commands.len());
+        let label = |cx: &mut VisualTestContext| {
+            palette.update(cx, |palette, _| palette.delegate.match_count_label())
+        };
+        assert_eq!(
+            label(cx),
+            Some(format!("{command_count} of {command_count} commands"))
+        );
+        assert!(cx.debug_bounds("match-count").is_some());
+
+        cx.simulate_input("editor: backspace");
+        cx.run_until_parked();
+        let matched_count = palette.update(cx, |palette, _| palette.delegate.matches.len());
+        assert!(matched_count < command_count);
+        assert_eq!(
+            label(cx),
+            Some(format!("{matched_count} of {command_count} commands")),
+            "The count should follow the query"
+        );
+
+        palette.update(cx, |palette, cx| palette.set_query("bcksp", cx));
+        cx.run_until_parked();
+        let matched_count = palette.update(cx, |palette, _| palette.delegate.matches.len() - 1);
+        assert_eq!(
+            label(cx),
+            Some(format!(
+                "{matched_count} of {command_count} commands +1 command suggestion"
+            )),
+            "The intercepted commands should be counted on their own"
+        );
+
+        palette.update(cx, |palette, _| {
+            palette.delegate.all_commands = Vec::new().into();
+            assert_eq!(
+                palette.delegate.match_count_label(),
+                None,
+                "No count should be shown without any commands"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hiding_and_unhiding_namespace(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        cx.update(|cx| {
+            cx.set_global(CommandPaletteFilter::default());
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            })
+        });
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(palette.match_names(cx), Vec::<String>::new());
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.remove("editor");
+            })
+        });
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(
+                palette.position_of("editor: backspace", cx),
+                Some(0),
+                "The namespace's commands should match again once it's not hidden"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_filter_handles(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        let backspace_position = |cx: &mut VisualTestContext| {
+            cx.run_until_parked();
+            active_palette(&workspace, cx).update(cx, |palette, cx| {
+                palette.position_of("editor: backspace", cx)
+            })
+        };
+        assert_eq!(backspace_position(cx), Some(0));
+
+        let (namespace_handle, other_namespace_handle, action_handle) = cx.update(|cx| {
+            (
+                CommandPaletteFilter::hide_namespace(cx, "editor"),
+                CommandPaletteFilter::hide_namespace(cx, "editor"),
+                CommandPaletteFilter::hide_action_types(
+                    cx,
+                    [std::any::TypeId::of::<editor::actions::Backspace>()],
+                ),
+            )
+        });
+        assert_eq!(
+            backspace_position(cx),
+            None,
+            "The open palette should stop listing the commands hidden"
+        );
+
+        drop(namespace_handle);
+        drop(other_namespace_handle);
+        assert_eq!(
+            backspace_position(cx),
+            None,
+            "The command should stay hidden while any handle hiding it lives"
+        );
+        cx.update(|cx| {
+            let filter = cx.global::<CommandPaletteFilter>();
+            assert!(!filter.is_namespace_hidden("editor"));
+            assert!(filter.hidden_namespaces.is_empty());
+        });
+
+        drop(action_handle);
+        assert_eq!(
+            backspace_position(cx),
+            Some(0),
+            "Dropping the last handle should list the command again"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_commands_refreshed_while_open(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| cx.set_global(CommandPaletteFilter::default()));
+
+        static LISTINGS: AtomicUsize = AtomicUsize::new(0);
+        fn counting_lister(
+            previous_focus_handle: &FocusHandle,
+            key_prefix: Option<&[Keystroke]>,
+            cx: &mut WindowContext,
+        ) -> Vec<Command> {
+            LISTINGS.fetch_add(1, atomic::Ordering::SeqCst);
+            available_commands(previous_focus_handle, key_prefix, cx)
+        }
+
+        let picker = open_palette(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            picker.delegate.list_commands = counting_lister;
+        });
+        let listed_commands = picker.update(cx, |picker, _| picker.delegate.all_commands.clone());
+        cx.simulate_input("editor: ");
+        assert_eq!(
+            LISTINGS.load(atomic::Ordering::SeqCst),
+            0,
+            "Typing should not list the commands again"
+        );
+        picker.update(cx, |picker, _| {
+            assert!(Arc::ptr_eq(&picker.delegate.all_commands, &listed_commands));
+        });
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SelectNext);
+        let selected = picker.update(cx, |picker, _| {
+            picker.delegate.selected_command().unwrap().name.clone()
+        });
+        let workspace_commands = |cx: &mut VisualTestContext| {
+            picker.update(cx, |picker, _| {
+                picker
+                    .delegate
+                    .all_commands
+                    .iter()
+                    .filter(|command| command.action.name().starts_with("workspace::"))
+                    .count()
+            })
+        };
+        assert!(workspace_commands(cx) > 0);
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("workspace");
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(LISTINGS.load(atomic::Ordering::SeqCst), 1);
+        assert_eq!(
+            workspace_commands(cx),
+            0,
+            "The commands hidden while the palette is open should not be listed anymore"
+        );
+        picker.update(cx, |picker, _| {
+            assert_eq!(
+                picker.delegate.selected_command().unwrap().name,
+                selected,
+                "Listing the commands again should keep the selection"
+            );
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            })
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert!(picker
+                .delegate
+                .matches
+                .iter()
+                .all(|string_match| !string_match.string.starts_with("editor: ")));
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.clear();
+            })
+        });
+        cx.simulate_input("backspace");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches[0].string, "editor: backspace");
+        });
+        assert!(workspace_commands(cx) > 0);
+    }
+
+    #[gpui::test]
+    async fn test_dismiss_with_escape(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("escape");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            assert!(
+                editor.focus_handle(cx).is_focused(cx),
+                "Dismissing the palette should focus the editor it got opened from"
+            );
+            assert_eq!(
+                editor.read(cx).text(cx),
+                "abc",
+                "Dismissing the palette should not run the selected command"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_namespace_prefix(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        run_backspace(&workspace, cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("editor:");
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            let names = palette.match_names(cx);
+            assert!(names.len() > 5);
+            assert!(
+                names.iter().all(|name| name.starts_with("editor: ")),
+                "Only the editor commands should be listed: {names:?}"
+            );
+            assert_eq!(
+                palette.position_of("editor: backspace", cx),
+                Some(0),
+                "The namespace's commands should be ranked by their usage"
+            );
+        });
+
+        cx.simulate_input(" selall");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("editor: select all", cx), Some(0));
+            let positions = &palette.picker.read(cx).delegate.matches[0].positions;
+            assert_eq!(
+                positions.first(),
+                Some(&"editor: ".len()),
+                "The namespace should not be highlighted"
+            );
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        for query in ["go to line: tog", "go_to_line:tog"] {
+            open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            active_palette(&workspace, cx).update(cx, |palette, cx| {
+                assert_eq!(palette.match_names(cx), ["go to line: toggle"], "{query}");
+            });
+            cx.simulate_keystrokes("cmd-shift-p");
+        }
+
+        // Unknown namespaces are matched along with the rest of the query.
+        open_palette(&workspace, cx);
+        cx.simulate_input("edit: backspace");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("editor: backspace", cx), Some(0));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_descriptions(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| {
+            register_command_description("editor::Backspace", "Delete before the cursor", cx)
+        });
+
+        let picker = open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        picker.update(cx, |picker, _| {
+            let command = picker
+                .delegate
+                .matched_command(&picker.delegate.matches[0])
+                .unwrap();
+            assert_eq!(command.name, "editor: backspace");
+            assert_eq!(
+                command.description.as_deref(),
+                Some("Delete before the cursor")
+            );
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        let picker = open_palette(&workspace, cx);
+        cx.simulate_input("language");
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            palette.assert_shown_in_order(&["editor: restart language server", "editor: format"], cx);
+            let format_match = palette
+                .picker
+                .read(cx)
+                .delegate
+                .matches
+                .iter()
+                .find(|string_match| string_match.string == "editor: format")
+                .unwrap();
+            assert!(
+                format_match.positions.is_empty(),
+                "Nothing should be highlighted in the names of the commands matched by their descriptions"
+            );
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.match_descriptions = Some(false);
+                });
+            });
+        });
+        picker.update(cx, |picker, cx| picker.refresh(cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert!(palette
+                .position_of("editor: restart language server", cx)
+                .is_some());
+            assert_eq!(palette.position_of("editor: format", cx), None);
+        });
+    }
+
+    /// An action misbehaving by naming itself with no namespace, unlike the name it's registered with.
+    #[derive(Clone, PartialEq)]
+    struct MisnamedAction;
+
+    gpui::register_action!(MisnamedAction);
+
+    impl Action for MisnamedAction {
+        fn boxed_clone(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn partial_eq(&self, action: &dyn Action) -> bool {
+            action.as_any().downcast_ref::<Self>() == Some(self)
+        }
+
+        fn name(&self) -> &str {
+            "misnamed"
+        }
+
+        fn debug_name() -> &'static str {
+            "command_palette_test::MisnamedAction"
+        }
+
+        fn build(_: serde_json::Value) -> anyhow::Result<Box<dyn Action>> {
+            Ok(Box::new(Self))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_unavailable_commands(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let unavailable_backspace = |cx: &mut VisualTestContext| {
+            let picker = open_palette(&workspace, cx);
+            cx.simulate_input("editor: backspace");
+            picker.update(cx, |picker, _| {
+                let delegate = &picker.delegate;
+                delegate.matches.iter().find_map(|string_match| {
+                    let command = delegate.matched_command(string_match)?;
+                    (command.name == "editor: backspace").then_some(command.available)
+                })
+            })
+        };
+
+        assert_eq!(
+            unavailable_backspace(cx),
+            None,
+            "The editor's commands should not be listed with no editor focused"
+        );
+        cx.dispatch_action(menu::Cancel);
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.show_unavailable_commands = Some(true);
+                });
+            });
+        });
+        assert_eq!(unavailable_backspace(cx), Some(false));
+        let picker =
+            active_palette(&workspace, cx).read_with(cx, |palette, _| palette.picker.clone());
+        picker.update(cx, |picker, _| {
+            let delegate = &mut picker.delegate;
+            let ix = delegate
+                .matches
+                .iter()
+                .position(|string_match| string_match.string == "editor: backspace")
+                .unwrap();
+            assert!(
+                delegate.matches[..ix].iter().all(|string_match| delegate
+                    .matched_command(string_match)
+                    .map_or(false, |command| command.available)),
+                "The unavailable commands should be listed below the available ones"
+            );
+            delegate.selected_ix = ix;
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            assert!(workspace
+                .notification::<MessageNotification>(UNAVAILABLE_COMMAND_TOAST_ID)
+                .is_some());
+        });
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                None,
+                "The unavailable commands should not be recorded as used"
+            );
+        });
+
+        let editor = add_editor(&workspace, "abc", cx);
+        assert_eq!(unavailable_backspace(cx), Some(true));
+        cx.simulate_keystrokes("enter");
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab");
+    }
+
+    // The debug builds fail on them, for their authors to notice.
+    #[gpui::test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "Actions the command palette cannot list")
+    )]
+    async fn test_invalid_actions_are_skipped(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        workspace.update(cx, |workspace, _| {
+            workspace.register_action(|_, _: &MisnamedAction, _| {});
+        });
+        add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            let names = palette.match_names(cx);
+            assert!(names.contains(&"editor: backspace".to_string()));
+            assert!(
+                !names.iter().any(|name| name.contains("misnamed")),
+                "The misbehaving action should be skipped: {names:?}"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_confirming_command_hidden_after_listing(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("editor: backspace", cx), Some(0));
+        });
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            })
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "abc",
+            "The command hidden since it got listed should not run"
+        );
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                None
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_key_query(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| {
+            cx.bind_keys([
+                gpui::KeyBinding::new("ctrl-k ctrl-b", editor::actions::Backspace, None),
+                gpui::KeyBinding::new("ctrl-k", editor::actions::Delete, None),
+            ])
+        });
+        let shown_binding = |name: &str, palette: &CommandPalette, cx: &AppContext| {
+            let delegate = &palette.picker.read(cx).delegate;
+            let string_match = delegate
+                .matches
+                .iter()
+                .find(|string_match| string_match.string == name)?;
+            let binding = delegate
+                .key_bindings
+                .get(&string_match.candidate_id)?
+                .as_ref()?;
+            Some(
+                binding
+                    .keystrokes()
+                    .iter()
+                    .map(Keystroke::unparse)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        };
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("key:cmd-n");
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.match_names(cx), ["workspace: new file"]);
+        });
+
+        cx.simulate_keystrokes("cmd-shift-p");
+        open_palette(&workspace, cx);
+        cx.simulate_input("key:ctrl-k");
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            palette.assert_shown_in_order(&["editor: delete", "editor: backspace"], cx);
+            assert_eq!(palette.match_names(cx).len(), 2);
+            assert_eq!(
+                shown_binding("editor: backspace", palette, cx).as_deref(),
+                Some("ctrl-k ctrl-b"),
+                "The binding matched should be shown"
+            );
+        });
+
+        cx.simulate_input(" ctrl-b");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.match_names(cx), ["editor: backspace"]);
+        });
+
+        cx.simulate_keystrokes("cmd-shift-p");
+        open_palette(&workspace, cx);
+        cx.simulate_input("key:");
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            let names = palette.match_names(cx);
+            assert!(names.contains(&"editor: backspace".to_string()));
+            assert!(
+                !names.contains(&"editor: select all".to_string()),
+                "The commands with no binding should not be listed: {names:?}"
+            );
+        });
+
+        cx.simulate_input("ctrl-x");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.match_names(cx), Vec::<String>::new());
+            assert_eq!(
+                palette
+                    .picker
+                    .update(cx, |picker, cx| picker.delegate.no_matches_text(cx)),
+                "No commands bound to these keystrokes"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_high_contrast_selection(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let palette = open_palette(&workspace, cx);
+        cx.run_until_parked();
+        assert!(
+            cx.debug_bounds("list-item-emphasized-selection").is_none(),
+            "The selection should not be emphasized by default"
+        );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<PickerSettings>(cx, |settings| {
+                    settings.high_contrast_selection = Some(true);
+                });
+            });
+        });
+        palette.update(cx, |palette, cx| palette.refresh(cx));
+        cx.run_until_parked();
+        assert!(
+            cx.debug_bounds("list-item-emphasized-selection").is_some(),
+            "The selected row should be outlined with the high contrast selection"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_pinned_commands(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        run_backspace(&workspace, cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("del");
+        let palette = active_palette(&workspace, cx);
+        let unpinned_position = palette
+            .update(cx, |palette, cx| {
+                palette.position_of("editor: delete line", cx)
+            })
+            .unwrap();
+        assert!(unpinned_position > 0);
+        palette.update(cx, |palette, cx| {
+            palette.picker.update(cx, |picker, _| {
+                picker.delegate.selected_ix = unpinned_position;
+            })
+        });
+        cx.simulate_keystrokes("cmd-alt-p");
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            let pinned_position = palette.position_of("editor: delete line", cx).unwrap();
+            assert!(
+                pinned_position < unpinned_position,
+                "The pinned command should rank higher: {pinned_position} vs {unpinned_position}"
+            );
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.update(|cx| {
+            assert_eq!(
+                cx.global::<PinnedCommands>().pinned(),
+                ["editor: delete line"]
+            );
+            // Pinned commands no longer available are skipped.
+            cx.update_global(|pinned_commands: &mut PinnedCommands, cx| {
+                pinned_commands.toggle("removed: command".to_string(), cx);
+            });
+        });
+
+        let picker = open_palette(&workspace, cx);
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            let names = palette.match_names(cx);
+            assert_eq!(names[..2], ["editor: delete line", "editor: backspace"]);
+            assert!(!names.contains(&"removed: command".to_string()));
+        });
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.pinned_count, 1);
+            assert_eq!(picker.delegate.recent_count, 1);
+            assert_eq!(picker.delegate.separators_after_indices(), [0, 1]);
+        });
+
+        // Unpinning from the pinned row.
+        cx.simulate_keystrokes("cmd-alt-p");
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.pinned_count, 0);
+            assert_eq!(picker.delegate.matches[0].string, "editor: backspace");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_commands_order_is_deterministic(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        let mut orderings = Vec::new();
+        for _ in 0..2 {
+            let palette = open_palette(&workspace, cx);
+            orderings.push(palette.update(cx, |palette, _| {
+                palette
+                    .delegate
+                    .all_commands
+                    .iter()
+                    .map(|command| command.action.name().to_string())
+                    .collect::<Vec<_>>()
+            }));
+            cx.simulate_keystrokes("cmd-shift-p");
+        }
+
+        assert!(orderings[0].len() > 5);
+        assert_eq!(orderings[0], orderings[1]);
+        assert!(
+            orderings[0]
+                .windows(2)
+                .all(|pair| namespace_and_name(&pair[0]) <= namespace_and_name(&pair[1])),
+            "Commands should be ordered by their namespaces, then names"
+        );
+    }
+
+    #[gpui::test]
+    fn test_published_action_names(cx: &mut TestAppContext) {
+        let available = cx.update(|cx| {
+            cx.all_action_names()
+                .iter()
+                .map(|name| name.to_string())
+                .filter(|name| !published_actions::is_test_action(name))
+                .collect::<Vec<_>>()
+        });
+
+        let snapshot_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/published_actions.txt");
+        if std::env::var(published_actions::UPDATE_PUBLISHED_ACTIONS_VAR).is_ok() {
+            let snapshot =
+                published_actions::generate_snapshot(available.iter().map(String::as_str));
+            std::fs::write(&snapshot_path, snapshot).unwrap();
+            return;
+        }
+        let snapshot = std::fs::read_to_string(&snapshot_path).unwrap();
+        let problems = published_actions::check_published_names(
+            &published_actions::parse_snapshot(&snapshot),
+            &available.iter().map(String::as_str).collect(),
+            published_actions::DEPRECATED_ACTION_ALIASES,
+        );
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+
+    #[gpui::test]
+    async fn test_confirm_after_out_of_order_update(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_input("abc");
+        cx.simulate_keystrokes("cmd-shift-p");
+        let command_palette = workspace.update(cx, |workspace, cx| {
+            workspace.active_modal::<CommandPalette>(cx).unwrap()
+        });
+        let palette =
+            command_palette.update(cx, |command_palette, _| command_palette.picker.clone());
+        let executed_commands = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let executed_commands = executed_commands.clone();
+            cx.subscribe(&command_palette, move |_, event: &CommandExecuted, _| {
+                executed_commands
+                    .borrow_mut()
+                    .push(event.command_name.clone());
+            })
+            .detach();
+        });
+
+        cx.simulate_input("bcksp");
+        let stale_matches = palette.update(cx, |palette, _| palette.delegate.matches.clone());
+        assert_eq!(stale_matches[0].string, "editor: backspace");
+
+        // A newer update lands first, with the commands ranked differently by then...
+        let workspace_id = workspace.update(cx, |workspace, _| workspace.database_id());
+        cx.update(|cx| {
+            cx.update_global(|recent_commands: &mut RecentCommands, cx| {
+                recent_commands.record(
+                    UsageScope::Global,
+                    workspace_id,
+                    "editor: select all".to_string(),
+                    cx,
+                )
+            })
+        });
+        palette.update(cx, |palette, cx| palette.refresh(cx));
+        cx.run_until_parked();
+        // ...and the slow one with the previous ranking after it.
+        palette.update(cx, |palette, _| {
+            palette.delegate.matches = stale_matches;
+            palette.delegate.selected_ix = 0;
+        });
+
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(*executed_commands.borrow(), ["editor: backspace"]);
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(editor.read(cx).text(cx), "ab");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_abbreviations(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("gtd");
+
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        palette.update(cx, |palette, _| {
+            let first_match = &palette.delegate.matches[0];
+            assert_eq!(first_match.string, "editor: go to definition");
+            assert_eq!(first_match.positions, [8, 11, 14]);
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.abbreviations = Some(false);
+                });
+            });
+        });
+        palette.update(cx, |palette, cx| palette.refresh(cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert!(
+                palette
+                    .delegate
+                    .matches
+                    .iter()
+                    .all(|string_match| string_match.score < 1.0),
+                "No abbreviation matches should be made when disabled"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_alternatives(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        let picker = open_palette(&workspace, cx);
+        cx.simulate_input("wrap|fold");
+        picker.update(cx, |picker, _| {
+            let matches = &picker.delegate.matches;
+            let position = |name: &str| {
+                matches
+                    .iter()
+                    .position(|string_match| string_match.string == name)
+                    .unwrap_or_else(|| panic!("{name} should be matched"))
+            };
+            let soft_wrap = position("editor: toggle soft wrap");
+            let fold = position("editor: fold");
+            assert!(
+                matches
+                    .windows(2)
+                    .all(|pair| pair[0].score >= pair[1].score),
+                "The matches of both alternatives should be interleaved by their scores"
+            );
+            assert_eq!(
+                matches[soft_wrap].positions,
+                [20, 21, 22, 23],
+                "Only the alternative matching the row should be highlighted"
+            );
+            assert_eq!(matches[fold].positions, [8, 9, 10, 11]);
+        });
+
+        picker.update(cx, |picker, cx| picker.set_query("fold | edit", cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            let fold = picker
+                .delegate
+                .matches
+                .iter()
+                .find(|string_match| string_match.string == "editor: fold")
+                .unwrap();
+            assert_eq!(
+                fold.positions,
+                [0, 1, 2, 3, 8, 9, 10, 11],
+                "A row matched by several alternatives should highlight all of them"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_toggle_states(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        let rendered_toggle_states = |query: &str, cx: &mut VisualTestContext| {
+            open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            cx.run_until_parked();
+            let states = (
+                cx.debug_bounds("TOGGLE_STATE-on").is_some(),
+                cx.debug_bounds("TOGGLE_STATE-off").is_some(),
+            );
+            cx.simulate_keystrokes("cmd-shift-p");
+            states
+        };
+
+        assert_eq!(
+            rendered_toggle_states("editor: toggle soft wrap", cx),
+            (false, true)
+        );
+        cx.update(|cx| {
+            cx.update_global(|store: &mut SettingsStore, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.defaults.soft_wrap = Some(language_settings::SoftWrap::EditorWidth);
+                });
+            });
+        });
+        assert_eq!(
+            rendered_toggle_states("editor: toggle soft wrap", cx),
+            (true, false),
+            "The pill should follow the setting on the next palette open"
+        );
+
+        assert_eq!(
+            rendered_toggle_states("editor: backspace", cx),
+            (false, false),
+            "Commands without a probe should have no pill"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_synonyms(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        let first_match = |query: &str, cx: &mut VisualTestContext| {
+            palette.update(cx, |palette, cx| palette.set_query(query, cx));
+            cx.run_until_parked();
+            palette.update(cx, |palette, _| {
+                let first_match = &palette.delegate.matches[0];
+                (
+                    first_match.string.clone(),
+                    palette
+                        .delegate
+                        .matched_synonyms
+                        .get(&first_match.candidate_id)
+                        .map(|synonym| synonym.to_string()),
+                )
+            })
+        };
+
+        assert_eq!(
+            first_match("word wrap", cx),
+            (
+                "editor: toggle soft wrap".to_string(),
+                Some("word wrap".to_string())
+            )
+        );
+        assert_eq!(
+            first_match("Reformat", cx),
+            (
+                "editor: format".to_string(),
+                Some("reformat code".to_string())
+            ),
+            "Queries starting the synonym should match it, regardless of the case"
+        );
+        assert_eq!(
+            first_match("close tab", cx),
+            (
+                "pane: close active item".to_string(),
+                Some("close tab".to_string())
+            )
+        );
+        assert_eq!(
+            first_match("line comment", cx),
+            (
+                "editor: toggle comments".to_string(),
+                Some("line comment".to_string())
+            )
+        );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.synonyms = Some(HashMap::from_iter([
+                        ("beautify".to_string(), "editor::Format".to_string()),
+                        ("word wrap".to_string(), "go_to_line::Toggle".to_string()),
+                    ]));
+                });
+            });
+        });
+        assert_eq!(
+            first_match("beautify", cx),
+            ("editor: format".to_string(), Some("beautify".to_string()))
+        );
+        assert_eq!(
+            first_match("word wrap", cx),
+            (
+                "go to line: toggle".to_string(),
+                Some("word wrap".to_string())
+            ),
+            "User synonyms should take precedence over the built-in ones"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_previous_command_names(cx: &mut TestAppContext) {
+        fn reader_with_renames(
+            delegate: &CommandPaletteDelegate,
+            cx: &AppContext,
+        ) -> RankingSnapshot {
+            let mut snapshot = read_ranking_snapshot(delegate, cx);
+            snapshot.previous_names.insert(
+                "editor: delete backward".to_string(),
+                "editor::Backspace".to_string(),
+            );
+            snapshot
+        }
+
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+        let picker = open_palette(&workspace, cx);
+        picker.update(cx, |picker, cx| {
+            picker.delegate.read_ranking_snapshot = reader_with_renames;
+            picker.delegate.refresh_ranking_snapshot(cx);
+        });
+
+        cx.simulate_input("delete backward");
+        picker.update(cx, |picker, _| {
+            let first_match = &picker.delegate.matches[0];
+            assert_eq!(first_match.string, "editor: backspace");
+            assert_eq!(
+                picker
+                    .delegate
+                    .matched_aliases
+                    .get(&first_match.candidate_id)
+                    .map(|name| name.to_string()),
+                Some("editor: delete backward".to_string()),
+                "The renamed command should be found by its previous name"
+            );
+            assert_eq!(
+                picker.delegate.alias_label("editor: delete backward"),
+                "(previously 'editor: delete backward')"
+            );
+        });
+
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab");
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                Some(&1),
+                "The usage should be recorded for the new name"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_aliases(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.aliases = Some(HashMap::from_iter([
+                        ("fmt".to_string(), "editor::Format".to_string()),
+                        ("nope".to_string(), "unknown::Action".to_string()),
+                    ]));
+                });
+            });
+        });
+
+        cx.simulate_keystrokes("cmd-n");
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("fmt");
+        palette.update(cx, |palette, _| {
+            let first_match = &palette.delegate.matches[0];
+            assert_eq!(first_match.string, "editor: format");
+            assert!(first_match.positions.is_empty());
+            assert_eq!(
+                palette
+                    .delegate
+                    .matched_aliases
+                    .get(&first_match.candidate_id)
+                    .map(|alias| alias.to_string()),
+                Some("fmt".to_string())
+            );
+        });
+
+        palette.update(cx, |palette, cx| palette.set_query("nope", cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert!(
+                palette.delegate.matched_aliases.is_empty(),
+                "Aliases of unknown actions should be ignored"
+            );
+        });
+
+        palette.update(cx, |palette, cx| palette.set_query("fmt", cx));
+        cx.run_until_parked();
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.read(|cx| {
+            let hit_counts = &cx.global::<HitCounts>().global;
+            assert_eq!(hit_counts.get("editor: format"), Some(&1));
+            assert_eq!(hit_counts.get("fmt"), None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_key_bindings_resolved_after_render(cx: &mut TestAppContext) {
+        static RESOLUTIONS: AtomicUsize = AtomicUsize::new(0);
+        fn counting_resolver(
+            delegate: &CommandPaletteDelegate,
+            command: &Command,
+            cx: &mut WindowContext,
+        ) -> Option<gpui::KeyBinding> {
+            assert!(
+                !delegate.rendered_range.is_empty(),
+                "No binding should be resolved before the matches get rendered"
+            );
+            RESOLUTIONS.fetch_add(1, atomic::Ordering::SeqCst);
+            resolve_key_binding(delegate, command, cx)
+        }
+
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = cx.new_view(|cx| Editor::single_line(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(editor.clone()), cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+
+        let picker = workspace.update(cx, |workspace, cx| {
+            CommandPalette::toggle_with(workspace, None, None, None, None, cx);
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        picker.update(cx, |picker, _| {
+            assert!(picker.delegate.matches.is_empty());
+            picker.delegate.resolve_key_binding = counting_resolver;
+        });
+        cx.run_until_parked();
+
+        picker.update(cx, |picker, _| {
+            let delegate = &picker.delegate;
+            let rendered_range = delegate.rendered_range.clone();
+            assert!(!rendered_range.is_empty());
+            assert!(delegate.matches.len() > 2 * rendered_range.len());
+            for string_match in &delegate.matches[rendered_range.clone()] {
+                assert!(
+                    delegate
+                        .key_bindings
+                        .contains_key(&string_match.candidate_id),
+                    "The rendered rows should get their bindings once painted"
+                );
+            }
+            assert_eq!(
+                RESOLUTIONS.load(atomic::Ordering::SeqCst),
+                2 * rendered_range.len(),
+                "Only the rendered rows and the next page should get their bindings resolved"
+            );
+        });
+
+        cx.simulate_input("new file");
+        picker.update(cx, |picker, _| {
+            let delegate = &picker.delegate;
+            let new_file = delegate
+                .matches_in(delegate.rendered_range.clone())
+                .iter()
+                .find(|string_match| string_match.string == "workspace: new file")
+                .unwrap();
+            assert_eq!(
+                delegate.key_bindings[&new_file.candidate_id]
+                    .as_ref()
+                    .map(|key_binding| key_binding.keystrokes().to_vec()),
+                Some(vec![Keystroke::parse("cmd-n").unwrap()])
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_key_bindings_in_focus_chain(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| {
+            cx.bind_keys([gpui::KeyBinding::new(
+                "ctrl-k ctrl-l",
+                workspace::ToggleLeftDock,
+                Some("Workspace"),
+            )])
+        });
+        let picker = open_palette(&workspace, cx);
+        cx.run_until_parked();
+
+        picker.update(cx, |picker, cx| {
+            // The element focused before is not rendered anymore, e.g. closed while the palette was open.
+            picker.delegate.previous_focus_handle = cx.focus_handle();
+            let keystrokes = |command_name: &str, cx: &mut WindowContext| {
+                let command = picker
+                    .delegate
+                    .all_commands
+                    .iter()
+                    .find(|command| command.name == command_name)
+                    .unwrap();
+                resolve_key_binding(&picker.delegate, command, cx)
+                    .map(|key_binding| key_binding.keystrokes().to_vec())
+            };
+            assert_eq!(
+                keystrokes("workspace: new file", cx),
+                Some(vec![Keystroke::parse("cmd-n").unwrap()]),
+                "The bindings with no context should be shown"
+            );
+            assert_eq!(
+                keystrokes("workspace: toggle left dock", cx),
+                Some(vec![
+                    Keystroke::parse("ctrl-k").unwrap(),
+                    Keystroke::parse("ctrl-l").unwrap()
+                ]),
+                "The workspace's bindings should be shown"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_ranking_snapshot_read_once(cx: &mut TestAppContext) {
+        static READS: AtomicUsize = AtomicUsize::new(0);
+        fn counting_reader(delegate: &CommandPaletteDelegate, cx: &AppContext) -> RankingSnapshot {
+            READS.fetch_add(1, atomic::Ordering::SeqCst);
+            read_ranking_snapshot(delegate, cx)
+        }
+
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = workspace.update(cx, |workspace, _| workspace.database_id());
+        let picker = open_palette(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            picker.delegate.read_ranking_snapshot = counting_reader;
+        });
+        cx.run_until_parked();
+
+        cx.simulate_input("new");
+        cx.simulate_input(" file");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches[0].string, "workspace: new file");
+        });
+        assert_eq!(
+            READS.load(atomic::Ordering::SeqCst),
+            0,
+            "The keystrokes should be ranked with the snapshot read when the palette got opened"
+        );
+
+        cx.update(|cx| {
+            cx.update_global(|hit_counts: &mut HitCounts, cx| {
+                for command_name in ["workspace: new file", "no such: command"] {
+                    hit_counts.record(
+                        UsageScope::Global,
+                        workspace_id,
+                        command_name.to_string(),
+                        cx,
+                    );
+                }
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            READS.load(atomic::Ordering::SeqCst),
+            1,
+            "The snapshot should be read again once the hit counts change"
+        );
+        picker.update(cx, |picker, _| {
+            let hit_counts = &picker.delegate.ranking_snapshot.hit_counts;
+            assert_eq!(hit_counts.get("workspace: new file"), Some(&1));
+            assert!(
+                !hit_counts.contains_key("no such: command"),
+                "Only the hit counts of the palette's commands should be snapshotted"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_empty_query_order_is_stable(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = workspace.update(cx, |workspace, _| workspace.database_id());
+        add_editor(&workspace, "abc", cx);
+        let picker = open_palette(&workspace, cx);
+        cx.run_until_parked();
+        let palette = active_palette(&workspace, cx);
+        let names = palette.update(cx, |palette, cx| palette.match_names(cx));
+        let last_name = names.last().unwrap().clone();
+
+        cx.update(|cx| {
+            cx.update_global(|hit_counts: &mut HitCounts, cx| {
+                hit_counts.record(UsageScope::Global, workspace_id, last_name.clone(), cx);
+            })
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| picker.refresh(cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert_eq!(
+                palette.match_names(cx),
+                names,
+                "The commands should not move while the query stays empty"
+            );
+        });
+
+        cx.simulate_input("a");
+        picker.update(cx, |picker, cx| picker.set_query("", cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert_eq!(
+                palette.match_names(cx)[0],
+                last_name,
+                "The commands should be ranked anew once the query gets cleared"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_candidates_reused_between_keystrokes(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        let picker = open_palette(&workspace, cx);
+        let candidates = |cx: &mut VisualTestContext| {
+            picker.update(cx, |picker, _| picker.delegate.candidates.clone().unwrap())
+        };
+
+        cx.simulate_input("n");
+        cx.run_until_parked();
+        let first = candidates(cx);
+        cx.simulate_input("e");
+        cx.run_until_parked();
+        cx.simulate_input("w");
+        cx.run_until_parked();
+        assert!(
+            Arc::ptr_eq(&first, &candidates(cx)),
+            "The candidates should be reused while the commands and their ranking stay the same"
+        );
+
+        picker.update(cx, |picker, cx| {
+            picker.delegate.refresh_ranking_snapshot(cx);
+            picker.refresh(cx);
+        });
+        cx.run_until_parked();
+        assert!(
+            !Arc::ptr_eq(&first, &candidates(cx)),
+            "The candidates should be rebuilt for the new ranking snapshot"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_candidates_reused_with_thousands_of_commands(cx: &mut TestAppContext) {
+        const GENERATED_COMMAND_COUNT: usize = 5_000;
+        fn generated_commands(
+            previous_focus_handle: &FocusHandle,
+            key_prefix: Option<&[Keystroke]>,
+            cx: &mut WindowContext,
+        ) -> Vec<Command> {
+            let mut commands = available_commands(previous_focus_handle, key_prefix, cx);
+            commands.extend((0..GENERATED_COMMAND_COUNT).map(|ix| {
+                let name = format!("generated: command number {ix}");
+                Command {
+                    initials: initials(&name).into(),
+                    name,
+                    action: Box::new(gpui::NoAction),
+                    description: None,
+                    ranking_key: None,
+                    project_root: None,
+                    available: true,
+                }
+            }));
+            commands
+        }
+
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        cx.update(|cx| cx.set_global(CommandPaletteFilter::default()));
+        let picker = open_palette(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            picker.delegate.list_commands = generated_commands;
+        });
+        cx.update(|cx| cx.update_global::<CommandPaletteFilter, _>(|_, _| {}));
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert!(picker.delegate.all_commands.len() > GENERATED_COMMAND_COUNT);
+        });
+
+        cx.simulate_input("g");
+        cx.run_until_parked();
+        let first = picker.update(cx, |picker, _| picker.delegate.candidates.clone().unwrap());
+        assert_eq!(
+            first.names.len(),
+            picker.update(cx, |picker, _| picker.delegate.all_commands.len())
+        );
+        for keystroke in "enerated number 4999".chars() {
+            cx.simulate_input(&keystroke.to_string());
+            cx.run_until_parked();
+            picker.update(cx, |picker, _| {
+                assert!(
+                    Arc::ptr_eq(&first, picker.delegate.candidates.as_ref().unwrap()),
+                    "The candidates of thousands of commands should not be rebuilt between the keystrokes"
+                );
+            });
+        }
+        picker.update(cx, |picker, _| {
+            assert_eq!(
+                picker.delegate.matches[0].string,
+                "generated: command number 4999"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_variants(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| {
+            let variant = cx
+                .build_action(
+                    "editor::SelectToBeginningOfLine",
+                    Some(json!({ "stop_at_soft_wraps": true })),
+                )
+                .unwrap();
+            register_command_variants([("stop at soft wraps", variant)], cx);
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("select to beginning of line");
+        palette.update(cx, |palette, cx| {
+            let names = palette.match_names(cx);
+            assert!(names.contains(&"editor: select to beginning of line".to_string()));
+            assert!(
+                names.contains(
+                    &"editor: select to beginning of line (stop at soft wraps)".to_string()
+                ),
+                "The variant should be listed as a command of its own: {names:?}"
+            );
+        });
+        cx.simulate_input(" stop");
+        palette.update(cx, |palette, _| {
+            assert_eq!(
+                palette.delegate.matches[0].string,
+                "editor: select to beginning of line (stop at soft wraps)"
+            );
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.read(|cx| {
+            let hit_counts = &cx.global::<HitCounts>().global;
+            assert_eq!(
+                hit_counts.get("editor: select to beginning of line (stop at soft wraps)"),
+                Some(&1),
+                "The variant's usage should be recorded by its own name"
+            );
+            assert_eq!(hit_counts.get("editor: select to beginning of line"), None);
+        });
+
+        cx.update(|cx| {
+            let reloaded = cx.global::<HitCounts>().reloaded(cx);
+            cx.set_global(reloaded);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            top_command(&workspace, cx),
+            "editor: select to beginning of line (stop at soft wraps)",
+            "The variant's usage should be remembered after a restart"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_exact_matches(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.synonyms = Some(HashMap::from_iter([(
+                        "beautify".to_string(),
+                        "editor::Format".to_string(),
+                    )]));
+                    settings.auto_confirm_exact_match = Some(true);
+                });
+            });
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        let exact_matches = |query: &str, cx: &mut VisualTestContext| {
+            palette.update(cx, |palette, cx| palette.set_query(query, cx));
+            cx.run_until_parked();
+            palette.update(cx, |palette, _| {
+                let delegate = &palette.delegate;
+                delegate
+                    .matches
+                    .iter()
+                    .take_while(|string_match| {
+                        delegate.exact_matches.contains(&string_match.candidate_id)
+                    })
+                    .map(|string_match| string_match.string.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(exact_matches("editor: format", cx), ["editor: format"]);
+        assert_eq!(
+            exact_matches("Editor::Format", cx),
+            ["editor: format"],
+            "Action names should match exactly, regardless of the case"
+        );
+        assert_eq!(
+            exact_matches("beautify", cx),
+            ["editor: format"],
+            "User synonyms should match exactly"
+        );
+        assert_eq!(
+            exact_matches("format  document", cx),
+            ["editor: format"],
+            "Built-in synonyms should match exactly"
+        );
+        assert_eq!(exact_matches("editor: form", cx), Vec::<String>::new());
+        workspace.update(cx, |workspace, cx| {
+            assert!(
+                workspace.active_modal::<CommandPalette>(cx).is_some(),
+                "Typed queries should never be auto-confirmed"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_toggle_with_initial_query(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        workspace.update(cx, |workspace, cx| {
+            CommandPalette::toggle(workspace, "new file", cx)
+        });
+        cx.run_until_parked();
+        let palette = active_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            let picker = palette.picker.read(cx);
+            assert_eq!(picker.query(cx), "new file");
+            assert_eq!(picker.delegate.selected_ix, 0);
+            assert_eq!(palette.match_names(cx)[0], "workspace: new file");
+        });
+
+        cx.simulate_input("backsp");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(
+                palette.picker.read(cx).query(cx),
+                "backsp",
+                "Typing should replace the initial query"
+            );
+            assert_eq!(palette.match_names(cx)[0], "editor: backspace");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_auto_confirm_exact_match(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text("abc", cx);
+            editor
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(editor.clone()), cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+        let toggle_with_query = |query: &str, cx: &mut VisualTestContext| {
+            cx.dispatch_action(Toggle {
+                key_prefix: None,
+                query: Some(query.to_string()),
+                highlight: None,
+            });
+            cx.run_until_parked();
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .active_modal::<CommandPalette>(cx)
+                    .map(|palette| palette.read(cx).picker.read(cx).query(cx))
+            })
+        };
+
+        assert_eq!(
+            toggle_with_query("editor: backspace", cx),
+            Some("editor: backspace".to_string()),
+            "Exact matches should not be auto-confirmed unless the settings say so"
+        );
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "abc");
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.auto_confirm_exact_match = Some(true);
+                });
+            });
+        });
+        assert_eq!(toggle_with_query("Editor::Backspace", cx), None);
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab");
+
+        assert_eq!(
+            toggle_with_query("editor: back", cx),
+            Some("editor: back".to_string()),
+            "Queries without an exact match should leave the palette open"
+        );
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab");
+    }
+
+    #[gpui::test]
+    async fn test_last_command(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.show_last_command = Some(true);
+                });
+            });
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let indicator = workspace.update(cx, |workspace, cx| {
+            let indicator = cx.new_view(|cx| LastCommandIndicator::new(workspace, cx));
+            workspace.status_bar().update(cx, |status_bar, cx| {
+                status_bar.add_right_item(indicator.clone(), cx)
+            });
+            indicator
+        });
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        indicator.update(cx, |indicator, _| {
+            assert_eq!(indicator.last_command.as_deref(), Some("editor: backspace"));
+        });
+
+        let bounds = cx.debug_bounds("last-command").unwrap();
+        cx.simulate_click(bounds.center(), Modifiers::none());
+        cx.run_until_parked();
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .expect("Clicking the last command should open the palette")
+                .read(cx)
+                .picker
+                .clone()
+        });
+        palette.update(cx, |palette, _| {
+            let selected_match = &palette.delegate.matches[palette.delegate.selected_ix];
+            assert_eq!(selected_match.string, "editor: backspace");
+        });
+        cx.dispatch_action(menu::Cancel);
+
+        cx.executor()
+            .advance_clock(last_command::SHOWN_FOR + last_command::FADE_STEP * 2);
+        cx.run_until_parked();
+        indicator.update(cx, |indicator, _| {
+            assert_eq!(indicator.last_command, None, "The command should fade out");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_tour_step(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        cx.update(|cx| {
+            assert!(command_exists("editor::Backspace", cx));
+            assert!(command_exists("editor: backspace", cx));
+            assert!(!command_exists("editor: fly away", cx));
+            cx.set_global(CommandPaletteFilter::default());
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            });
+            assert!(
+                !command_exists("editor: backspace", cx),
+                "The hidden commands should not be offered by the tour"
+            );
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.remove("editor");
+            });
+        });
+
+        // The tour's step shows the palette, pointing at its command, and waits for the user to run it.
+        let executed = Rc::new(RefCell::new(Vec::new()));
+        let subscription = cx.update(|cx| {
+            let executed = executed.clone();
+            on_command_executed(cx, move |event, _| {
+                executed.borrow_mut().push(event.command_name.clone());
+            })
+        });
+        cx.dispatch_action(Toggle {
+            key_prefix: None,
+            query: Some("editor: ba".to_string()),
+            highlight: Some("editor::Backspace".to_string()),
+        });
+        cx.run_until_parked();
+        let highlighted = cx
+            .debug_bounds("highlighted-command")
+            .expect("The step's command should be highlighted");
+        cx.simulate_click(highlighted.center(), Modifiers::none());
+        cx.run_until_parked();
+        assert_eq!(*executed.borrow(), ["editor: backspace"]);
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab");
+
+        open_palette(&workspace, cx);
+        cx.run_until_parked();
+        assert!(
+            cx.debug_bounds("highlighted-command").is_none(),
+            "Only the tour's palette should highlight a command"
+        );
+        cx.dispatch_action(menu::Cancel);
+
+        drop(subscription);
+        run_backspace(&workspace, cx);
+        assert_eq!(
+            executed.borrow().len(),
+            1,
+            "The tour should stop observing the commands once done"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_key_binding_sources(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| {
+            KeymapFile::parse(
+                r#"[{ "context": "Editor", "bindings": { "cmd-k b": "editor::Backspace" } }]"#,
+            )
+            .unwrap()
+            .add_to_cx_with_source(Some("keymaps/default.json"), cx)
+            .unwrap();
+            KeymapFile::parse(
+                r#"[{ "context": "Editor", "bindings": { "cmd-k x": "editor::Backspace" } }]"#,
+            )
+            .unwrap()
+            .add_to_cx_with_source(Some("~/.config/zed/keymap.json"), cx)
+            .unwrap();
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        let bindings = cx.update(|cx| {
+            let focus_handle = editor.focus_handle(cx);
+            bindings_by_precedence(&editor::actions::Backspace, &focus_handle, cx)
+                .iter()
+                .map(|binding| {
+                    let keystrokes = binding
+                        .keystrokes()
+                        .iter()
+                        .map(Keystroke::unparse)
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    (keystrokes, binding_source(binding))
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            bindings[..2],
+            [
+                ("cmd-k x".to_string(), SharedString::from("~/.config/zed/keymap.json")),
+                ("cmd-k b".to_string(), SharedString::from("keymaps/default.json")),
+            ],
+            "All the bindings should be listed with their keymaps, the one shown in the palette first"
+        );
+        assert!(
+            bindings[2..]
+                .iter()
+                .all(|(_, source)| source.as_ref() == "built into Zed"),
+            "The bindings bound in code should have no keymap"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_on_pending_prefix(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| {
+            KeymapFile::parse(
+                r#"[
+                    {
+                        "context": "Editor",
+                        "bindings": {
+                            "cmd-k b": "editor::Backspace",
+                            "cmd-k d": "editor::Delete",
+                            "cmd-k cmd-d": "editor::DeleteLine"
+                        }
+                    }
+                ]"#,
+            )
+            .unwrap()
+            .add_to_cx(cx)
+            .unwrap();
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_input("ab");
+        cx.simulate_keystrokes("cmd-k");
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(
+                workspace.active_modal::<CommandPalette>(cx).is_none(),
+                "The palette should not be opened for the pending prefix by default"
+            );
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.open_on_pending_prefix_ms = Some(500);
+                });
+            });
+        });
+        cx.simulate_keystrokes("cmd-k");
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.run_until_parked();
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .expect("The palette should be opened for the pending prefix")
+                .read(cx)
+                .picker
+                .clone()
+        });
+        palette.update(cx, |palette, cx| {
+            let names = palette
+                .delegate
+                .matches
+                .iter()
+                .map(|command_match| command_match.string.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                ["editor: backspace", "editor: delete", "editor: delete line"]
+            );
+            assert!(palette.delegate.placeholder_text().contains("cmd-k"));
+            let remaining_keys = keys_after_prefix(
+                &editor::actions::DeleteLine,
+                palette.delegate.key_prefix.as_ref().unwrap(),
+                &palette.delegate.previous_focus_handle,
+                cx,
+            )
+            .unwrap();
+            assert_eq!(remaining_keys.keystrokes()[0].unparse(), "cmd-d");
+        });
+
+        cx.simulate_keystrokes("enter");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(editor.read(cx).text(cx), "a");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_query_editor_keybindings(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| KeymapFile::load_asset("keymaps/default.json", cx).unwrap());
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+
+        cx.simulate_input("editor backspace");
+        cx.simulate_keystrokes("alt-backspace");
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), "editor "));
+
+        cx.simulate_keystrokes("cmd-z");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.query(cx), "editor backspace")
+        });
+
+        cx.simulate_keystrokes("cmd-left");
+        cx.simulate_input("go ");
+        cx.simulate_keystrokes("cmd-right");
+        cx.simulate_input("!");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.query(cx), "go editor backspace!")
+        });
+
+        // Home and end move the query's cursor, the selection jumps to the ends of the list with ctrl.
+        cx.simulate_keystrokes("home");
+        cx.simulate_input("(");
+        cx.simulate_keystrokes("end");
+        cx.simulate_input(")");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.query(cx), "(go editor backspace!)")
+        });
+
+        cx.simulate_keystrokes("cmd-a");
+        cx.simulate_input("editor");
+        cx.run_until_parked();
+        let match_count = palette.update(cx, |palette, _| palette.delegate.matches.len());
+        assert!(match_count > 1);
+        cx.simulate_keystrokes("ctrl-end");
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.selected_index(), match_count - 1)
+        });
+        cx.simulate_keystrokes("ctrl-home");
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.selected_index(), 0)
+        });
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), "editor"));
+
+        // Saving is scoped out of the query editor.
+        cx.simulate_keystrokes("cmd-s");
+        assert!(!cx.did_prompt_for_new_path());
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_some());
+        });
+
+        // Toggling the palette from its own query editor closes it instead of reopening.
+        cx.simulate_keystrokes("cmd-shift-p");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_usage_scope(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let first_window =
+            cx.add_window(|cx| Workspace::new(1, project.clone(), app_state.clone(), cx));
+        let second_window =
+            cx.add_window(|cx| Workspace::new(2, project.clone(), app_state.clone(), cx));
+        let first_workspace = first_window.root_view(cx).unwrap();
+        let second_workspace = second_window.root_view(cx).unwrap();
+        let first_cx = &mut VisualTestContext::from_window(*first_window, cx);
+        first_cx.simulate_keystrokes("cmd-n");
+        let second_cx = &mut VisualTestContext::from_window(*second_window, cx);
+        second_cx.simulate_keystrokes("cmd-n");
+
+        let alphabetical_top = top_command(&first_workspace, first_cx);
+        assert_ne!(alphabetical_top, "editor: backspace");
+
+        // Global scope ranks the usage in every workspace.
+        run_backspace(&first_workspace, first_cx);
+        assert_eq!(top_command(&first_workspace, first_cx), "editor: backspace");
+        assert_eq!(
+            top_command(&second_workspace, second_cx),
+            "editor: backspace"
+        );
+        cx.read_global::<HitCounts, _>(|hit_counts, _| {
+            assert_eq!(hit_counts.global.get("editor: backspace"), Some(&1));
+            assert!(hit_counts.by_workspace.is_empty());
+        });
+
+        // Workspace scope keeps the usage to the workspace where the command was confirmed.
+        set_usage_scope(UsageScope::Workspace, cx);
+        assert_eq!(top_command(&first_workspace, first_cx), alphabetical_top);
+        run_backspace(&first_workspace, first_cx);
+        assert_eq!(top_command(&first_workspace, first_cx), "editor: backspace");
+        assert_eq!(top_command(&second_workspace, second_cx), alphabetical_top);
+        cx.read_global::<HitCounts, _>(|hit_counts, _| {
+            assert_eq!(hit_counts.global.get("editor: backspace"), Some(&1));
+            assert_eq!(
+                hit_counts.by_workspace[&1].get("editor: backspace"),
+                Some(&1)
+            );
+            assert!(!hit_counts.by_workspace.contains_key(&2));
+        });
+
+        // Off neither ranks nor records the usage.
+        set_usage_scope(UsageScope::Off, cx);
+        assert_eq!(top_command(&first_workspace, first_cx), alphabetical_top);
+        run_backspace(&first_workspace, first_cx);
+        assert_eq!(top_command(&first_workspace, first_cx), alphabetical_top);
+        cx.read_global::<HitCounts, _>(|hit_counts, _| {
+            assert_eq!(hit_counts.global.get("editor: backspace"), Some(&1));
+            assert_eq!(
+                hit_counts.by_workspace[&1].get("editor: backspace"),
+                Some(&1)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_workspace_first_usage_scope(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        set_usage_scope(UsageScope::WorkspaceFirst, cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let first_window =
+            cx.add_window(|cx| Workspace::new(1, project.clone(), app_state.clone(), cx));
+        let second_window =
+            cx.add_window(|cx| Workspace::new(2, project.clone(), app_state.clone(), cx));
+        let first_workspace = first_window.root_view(cx).unwrap();
+        let second_workspace = second_window.root_view(cx).unwrap();
+        let first_cx = &mut VisualTestContext::from_window(*first_window, cx);
+        first_cx.simulate_keystrokes("cmd-n");
+        let second_cx = &mut VisualTestContext::from_window(*second_window, cx);
+        second_cx.simulate_keystrokes("cmd-n");
+
+        run_backspace(&first_workspace, first_cx);
+        assert_eq!(
+            top_command(&second_workspace, second_cx),
+            "editor: backspace",
+            "The workspace with no usage should be ranked by the global one"
+        );
+
+        open_palette(&second_workspace, second_cx);
+        second_cx.simulate_input("new file");
+        second_cx.simulate_keystrokes("enter");
+        assert_eq!(top_command(&first_workspace, first_cx), "editor: backspace");
+        assert_eq!(
+            top_command(&second_workspace, second_cx),
+            "workspace: new file",
+            "Every workspace should rank its own usage first"
+        );
+        cx.read_global::<HitCounts, _>(|hit_counts, _| {
+            assert_eq!(hit_counts.global.get("editor: backspace"), Some(&1));
+            assert_eq!(hit_counts.global.get("workspace: new file"), Some(&1));
+            assert_eq!(
+                hit_counts.by_workspace[&1].get("editor: backspace"),
+                Some(&1)
+            );
+            assert_eq!(hit_counts.by_workspace[&1].get("workspace: new file"), None);
+            assert_eq!(
+                hit_counts.by_workspace[&2].get("workspace: new file"),
+                Some(&1)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_query_history(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        run_backspace(&workspace, cx);
+        open_palette(&workspace, cx);
+        cx.simulate_input("new file");
+        cx.simulate_keystrokes("enter");
+
+        let picker = open_palette(&workspace, cx);
+        let query = |cx: &mut VisualTestContext| picker.update(cx, |picker, cx| picker.query(cx));
+        assert_eq!(query(cx), "");
+        cx.simulate_keystrokes("alt-up");
+        assert_eq!(query(cx), "new file");
+        cx.simulate_keystrokes("alt-up");
+        assert_eq!(query(cx), "bcksp");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.simulate_keystrokes("alt-up");
+        assert_eq!(
+            query(cx),
+            "bcksp",
+            "Going back past the oldest query should keep it"
+        );
+
+        cx.simulate_keystrokes("alt-down");
+        assert_eq!(query(cx), "new file");
+        cx.simulate_keystrokes("alt-down");
+        assert_eq!(query(cx), "");
+
+        cx.simulate_keystrokes("alt-up");
+        cx.simulate_input("x");
+        assert_eq!(
+            query(cx),
+            "x",
+            "Typing should replace the query gone back to"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_score_details(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        run_backspace(&workspace, cx);
+
+        let picker = open_palette(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(
+                picker.delegate.score_details(0),
+                Some("recent · score 0.000 · 1 hits".to_string())
+            );
+        });
+        cx.simulate_input("bcksp");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches[0].string, "editor: backspace");
+            let details = picker.delegate.score_details(0).unwrap();
+            assert!(details.starts_with("fuzzy · score "), "{details}");
+            assert!(details.ends_with(" · 1 hits"), "{details}");
+        });
+
+        cx.read(|cx| assert!(cx.try_global::<ScoreDetails>().is_none()));
+        cx.dispatch_action(ToggleScoreDetails);
+        cx.read(|cx| assert!(cx.global::<ScoreDetails>().shown));
+        cx.dispatch_action(ToggleScoreDetails);
+        cx.read(|cx| assert!(!cx.global::<ScoreDetails>().shown));
+    }
+
+    #[gpui::test]
+    async fn test_forget_hit_count(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| KeymapFile::load_asset("keymaps/default.json", cx).unwrap());
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        run_backspace(&workspace, cx);
+        run_backspace(&workspace, cx);
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 1);
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+
+        // Editing the query does not forget anything.
+        cx.simulate_input("editor backsp");
+        cx.simulate_keystrokes("alt-backspace ctrl-backspace backspace cmd-backspace");
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.query(cx), "");
+            assert_eq!(palette.delegate.recent_count, 1);
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.read(|cx| {
+            assert!(cx
+                .global::<HitCounts>()
+                .global
+                .get("editor: backspace")
+                .is_some());
+        });
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notification::<MessageNotification>(FORGET_HIT_COUNT_TOAST_ID)
+                .is_none());
+        });
+
+        cx.simulate_keystrokes("cmd-alt-backspace");
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.delegate.recent_count, 0);
+            assert!(palette.delegate.ranking_snapshot.hit_counts.is_empty());
+            assert_eq!(palette.query(cx), "", "The query should stay as it was");
+        });
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                None
+            );
+            assert!(cx
+                .global::<RecentCommands>()
+                .recent(UsageScope::Global, workspace.read(cx).database_id())
+                .is_empty());
+        });
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_some());
+            assert!(workspace
+                .notification::<MessageNotification>(FORGET_HIT_COUNT_TOAST_ID)
+                .is_some());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_recent_commands(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        run_backspace(&workspace, cx);
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 1);
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+            assert_eq!(palette.delegate.separators_after_indices(), vec![0]);
+        });
+
+        cx.simulate_keystrokes("cmd-shift-backspace");
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 0);
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        // The forgotten command is gone from the recents, but its hit count is intact and it still matches.
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 0);
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.simulate_input("bcksp");
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                Some(&1)
+            );
+            assert!(cx
+                .global::<RecentCommands>()
+                .recent(UsageScope::Global, workspace.read(cx).database_id())
+                .is_empty());
+        });
+
+        // Commands never shown in the recents are neither recorded, nor ranked by their hit counts.
+        run_backspace(&workspace, cx);
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.delegate.recent_count, 1);
+            palette.delegate.never_show_in_recents(0, cx);
+            palette.refresh(cx);
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 0);
+            assert_ne!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        run_backspace(&workspace, cx);
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.recent_count, 0);
+            assert_ne!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_reset_hit_counts(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        run_backspace(&workspace, cx);
+        cx.run_until_parked();
+        let palette = open_palette(&workspace, cx);
+        palette.update(cx, |palette, _| {
+            assert_eq!(
+                palette
+                    .delegate
+                    .ranking_snapshot
+                    .hit_counts
+                    .get("editor: backspace"),
+                Some(&1)
+            );
+        });
+
+        cx.dispatch_action(ResetHitCounts);
+        cx.run_until_parked();
+        cx.read(|cx| assert_eq!(cx.global::<HitCounts>(), &HitCounts::default()));
+        palette.update(cx, |palette, _| {
+            assert!(
+                palette.delegate.ranking_snapshot.hit_counts.is_empty(),
+                "The open palette should stop ranking by the forgotten usage"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_secondary_confirm_copies_action_name(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("cmd-enter");
+        cx.run_until_parked();
+
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            assert!(workspace
+                .notification::<MessageNotification>(COPY_ACTION_NAME_TOAST_ID)
+                .is_some());
+        });
+        cx.read(|cx| {
+            assert_eq!(
+                cx.read_from_clipboard().map(|item| item.text().to_owned()),
+                Some("editor::Backspace".to_string())
+            );
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                None,
+                "Copying the action name should not record the command's usage"
+            );
+        });
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "abc",
+            "Copying the action name should not run the command"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_repeat(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_input("abcd");
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        let take_toast = |cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                let shown = workspace
+                    .notification::<MessageNotification>(REPEAT_TOAST_ID)
+                    .is_some();
+                workspace.dismiss_toast(REPEAT_TOAST_ID, cx);
+                shown
+            })
+        };
+
+        cx.dispatch_action(Repeat);
+        cx.run_until_parked();
+        assert!(
+            take_toast(cx),
+            "Repeating with no command confirmed yet should tell so"
+        );
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "abcd");
+
+        run_backspace(&workspace, cx);
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "abc");
+        cx.dispatch_action(Repeat);
+        cx.run_until_parked();
+        cx.dispatch_action(Repeat);
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "a");
+        assert!(!take_toast(cx));
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                Some(&1),
+                "Only the palette's confirmations should be counted"
+            );
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            })
+        });
+        cx.dispatch_action(Repeat);
+        cx.run_until_parked();
+        assert!(
+            take_toast(cx),
+            "The commands hidden since should not be repeated"
+        );
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "a");
+    }
+
+    #[gpui::test]
+    async fn test_insert_at_cursor(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        let insert = |query: &str, action: Box<dyn Action>, cx: &mut VisualTestContext| {
+            open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            cx.run_until_parked();
+            cx.dispatch_action(action);
+            cx.run_until_parked();
+            assert!(
+                workspace.update(cx, |workspace, cx| workspace
+                    .active_modal::<CommandPalette>(cx)
+                    .is_none()),
+                "Inserting should dismiss the palette"
+            );
+            cx.simulate_input(" ");
+        };
+
+        insert(
+            "workspace: new file",
+            Box::new(InsertKeybindingAtCursor {
+                format: KeybindingFormat::Keymap,
+            }),
+            cx,
+        );
+        insert(
+            "workspace: new file",
+            Box::new(InsertKeybindingAtCursor::default()),
+            cx,
+        );
+        insert(
+            "workspace: new file",
+            Box::new(InsertActionNameAtCursor),
+            cx,
+        );
+        let platform_binding = if cfg!(target_os = "macos") {
+            "⌘N"
+        } else {
+            "Super+N"
+        };
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            format!("cmd-n {platform_binding} workspace::NewFile ")
+        );
+
+        insert(
+            "go to line: toggle",
+            Box::new(InsertKeybindingAtCursor::default()),
+            cx,
+        );
+        assert!(
+            workspace.update(cx, |workspace, _| workspace
+                .notification::<MessageNotification>(insert_at_cursor::INSERT_AT_CURSOR_TOAST_ID)
+                .is_some()),
+            "Commands without a keybinding should tell so"
+        );
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            format!("cmd-n {platform_binding} workspace::NewFile  ")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_arguments(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        editor.update(cx, |editor, cx| {
+            editor.set_text("one\ntwo\nthree", cx);
+            editor.move_to_end(&editor::actions::MoveToEnd, cx);
+        });
+        let top_match = |query: &str, cx: &mut VisualTestContext| {
+            let palette = open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            cx.run_until_parked();
+            palette.update(cx, |palette, _| {
+                let delegate = &palette.delegate;
+                (
+                    delegate.matches.first().map(|m| m.string.clone()),
+                    delegate.argument_error.clone(),
+                )
+            })
+        };
+
+        assert_eq!(
+            top_match(r#"editor: move up by lines {"lines": 2}"#, cx),
+            (
+                Some(r#"editor: move up by lines {"lines": 2}"#.to_string()),
+                None
+            )
+        );
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor
+                .selections
+                .newest::<Point>(cx)
+                .head()
+                .row),
+            0,
+            "The action should be built with the argument"
+        );
+
+        let (top_command, argument_error) = top_match("editor: move up by lines 2", cx);
+        assert_eq!(
+            top_command.as_deref(),
+            Some("editor: move up by lines 2"),
+            "The command should stay runnable with the argument not fitting it"
+        );
+        assert!(
+            argument_error.is_some(),
+            "The reason the argument does not fit should be shown"
+        );
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        assert_eq!(
+            workspace.update(cx, |workspace, _| workspace.panes().len()),
+            1
+        );
+        assert_eq!(
+            top_match("workspace::ActivatePane 1", cx),
+            (Some("workspace: activate pane 1".to_string()), None),
+            "The actions without defaults should be offered with a fitting argument"
+        );
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(
+            workspace.update(cx, |workspace, _| workspace.panes().len()),
+            2
+        );
+
+        let (top_command, _) = top_match("workspace: activate pane first", cx);
+        assert_ne!(
+            top_command.as_deref(),
+            Some("workspace: activate pane first"),
+            "The actions without defaults cannot fall back to them"
+        );
+        cx.simulate_keystrokes("cmd-shift-p");
+        let (top_command, _) = top_match("editor: backspace 3", cx);
+        assert_ne!(
+            top_command.as_deref(),
+            Some("editor: backspace 3"),
+            "The actions without parameters should not take arguments"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_interceptors(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let intercepted = |string: &str, action: Box<dyn Action>| CommandInterceptResult {
+            action,
+            string: string.to_string(),
+            positions: Vec::new(),
+            ranking_key: None,
+        };
+        let (first, second) = cx.update(|cx| {
+            let first = CommandPaletteInterceptor::register(cx, move |query, _| {
+                if query == "bksp" {
+                    vec![
+                        intercepted(":first", editor::actions::Backspace.boxed_clone()),
+                        intercepted(":first again", editor::actions::Backspace.boxed_clone()),
+                    ]
+                } else {
+                    Vec::new()
+                }
+            });
+            let second = CommandPaletteInterceptor::register(cx, move |_, _| {
+                vec![intercepted(
+                    ":second",
+                    editor::actions::Delete.boxed_clone(),
+                )]
+            });
+            (first, second)
+        });
+        let matches = |query: &str, cx: &mut VisualTestContext| {
+            let palette = open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            cx.run_until_parked();
+            let matches = palette.update(cx, |palette, _| {
+                palette
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|m| m.string.clone())
+                    .collect::<Vec<_>>()
+            });
+            cx.simulate_keystrokes("cmd-shift-p");
+            matches
+        };
+
+        let bksp_matches = matches("bksp", cx);
+        assert_eq!(
+            bksp_matches[..3],
+            [":first", ":first again", ":second"],
+            "The results of the interceptors registered first should rank first"
+        );
+        assert!(
+            !bksp_matches.contains(&"editor: backspace".to_string()),
+            "The commands of the intercepted actions should not be listed twice"
+        );
+
+        cx.update(|cx| CommandPaletteInterceptor::unregister(first, cx));
+        assert_eq!(matches("bksp", cx)[0], ":second");
+        cx.update(|cx| CommandPaletteInterceptor::unregister(second, cx));
+        assert_eq!(matches("bksp", cx)[0], "editor: backspace");
+    }
+
+    #[gpui::test]
+    async fn test_interceptors_snapshot(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+        let top_match = |palette: &View<Picker<CommandPaletteDelegate>>,
+                         query: &str,
+                         cx: &mut VisualTestContext| {
+            palette.update(cx, |palette, cx| palette.set_query(query, cx));
+            cx.run_until_parked();
+            palette.update(cx, |palette, _| {
+                palette.delegate.matches.first().map(|m| m.string.clone())
+            })
+        };
+
+        let palette = open_palette(&workspace, cx);
+        let interceptor = cx.update(|cx| {
+            CommandPaletteInterceptor::register(cx, |_, _| {
+                vec![CommandInterceptResult {
+                    action: editor::actions::Delete.boxed_clone(),
+                    string: ":delete".to_string(),
+                    positions: Vec::new(),
+                    ranking_key: None,
+                }]
+            })
+        });
+        assert_eq!(
+            top_match(&palette, "bksp", cx).as_deref(),
+            Some("editor: backspace"),
+            "The interceptors registered after the palette got opened should not intercept its queries"
+        );
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        let palette = open_palette(&workspace, cx);
+        cx.update(|cx| CommandPaletteInterceptor::unregister(interceptor, cx));
+        assert_eq!(
+            top_match(&palette, "bksp", cx).as_deref(),
+            Some(":delete"),
+            "The interceptors registered as of the palette's opening should intercept its queries"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_intercepted_commands_ranking(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abcd", cx);
+        let intercepted = |string: &str, action: Box<dyn Action>| CommandInterceptResult {
+            action,
+            string: string.to_string(),
+            positions: Vec::new(),
+            ranking_key: Some(format!("test::{}", &string[1..])),
+        };
+        cx.update(|cx| {
+            CommandPaletteInterceptor::register(cx, move |query, _| {
+                let mut results = vec![
+                    intercepted(":delete", editor::actions::Delete.boxed_clone()),
+                    intercepted(":backspace", editor::actions::Backspace.boxed_clone()),
+                ];
+                results.retain(|result| result.string.starts_with(query));
+                results
+            })
+        });
+        let intercepted_matches = |query: &str, cx: &mut VisualTestContext| {
+            let palette = open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            let matches = palette.update(cx, |palette, _| {
+                palette
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|m| m.string.clone())
+                    .take(2)
+                    .collect::<Vec<_>>()
+            });
+            cx.simulate_keystrokes("cmd-shift-p");
+            matches
+        };
+
+        assert_eq!(
+            intercepted_matches(":", cx),
+            [":delete", ":backspace"],
+            "With no usage recorded, the interceptor's order should be kept"
+        );
+        for _ in 0..2 {
+            open_palette(&workspace, cx);
+            cx.simulate_input(":b");
+            cx.simulate_keystrokes("enter");
+            cx.run_until_parked();
+        }
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>()
+                    .global
+                    .get("[intercepted] test::backspace"),
+                Some(&2)
+            );
+        });
+        assert_eq!(
+            intercepted_matches(":", cx),
+            [":backspace", ":delete"],
+            "The intercepted commands confirmed more should rank first"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_execute_by_name(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_input("abcd");
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        let usage = |cx: &mut VisualTestContext| {
+            cx.update(|cx| {
+                let workspace_id = workspace.read(cx).database_id();
+                (
+                    cx.global::<HitCounts>().global.clone(),
+                    cx.global::<RecentCommands>()
+                        .recent(UsageScope::Global, workspace_id)
+                        .to_vec(),
+                )
+            })
+        };
+
+        run_backspace(&workspace, cx);
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "abc");
+        let usage_after_confirm = usage(cx);
+
+        cx.update(|cx| {
+            cx.set_global(HitCounts::default());
+            cx.set_global(RecentCommands::default());
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                execute_by_name(workspace, "editor: backspace", None, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "ab");
+        assert_eq!(
+            usage(cx),
+            usage_after_confirm,
+            "Executing a command should record it the same way confirming it in the palette does"
+        );
+
+        workspace
+            .update(cx, |workspace, cx| {
+                execute_by_name(workspace, "editor::Backspace", None, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "a");
+        assert_eq!(usage(cx).0.get("editor: backspace"), Some(&2));
+
+        workspace.update(cx, |workspace, cx| {
+            assert!(execute_by_name(workspace, "editor: no such command", None, cx).is_err());
+            assert!(
+                execute_by_name(
+                    workspace,
+                    "command_palette::Toggle",
+                    Some(json!({ "keyPrefix": 1 })),
+                    cx
+                )
+                .is_err(),
+                "Actions failing to build with the arguments given should not be executed"
+            );
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
+                filter.hidden_namespaces.insert("editor");
+            })
+        });
+        workspace.update(cx, |workspace, cx| {
+            assert!(execute_by_name(workspace, "editor: backspace", None, cx).is_err());
+        });
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "a");
+
+        cx.dispatch_action(ExecuteCommand {
+            name: "editor: backspace".to_string(),
+            args: None,
+            include_hidden: true,
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "",
+            "Hidden commands should be executed when asked for explicitly"
+        );
+        assert_eq!(usage(cx).0.get("editor: backspace"), Some(&3));
+    }
+
+    #[gpui::test]
+    async fn test_unhandled_commands_not_recorded(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        add_editor(&workspace, "abc", cx);
+
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        // The editor the palette got opened from goes away, e.g. closed by a collaborator, with nothing to handle its commands.
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_pane()
+                .update(cx, |pane, cx| pane.remove_item(0, false, cx));
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                None,
+                "The commands nothing handled should not be counted"
+            );
+        });
+
+        add_editor(&workspace, "abc", cx);
+        run_backspace(&workspace, cx);
+        cx.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("editor: backspace"),
+                Some(&1)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_execute_by_name_as_confirmed(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor = add_editor(&workspace, "abc", cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.aliases = Some(HashMap::from_iter([(
+                        "close!".to_string(),
+                        "pane: close active item (skip saving)".to_string(),
+                    )]));
+                });
+            });
+        });
+        let executed = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let executed = executed.clone();
+            on_command_executed(cx, move |event, _| {
+                executed.borrow_mut().push(event.command_name.clone());
+            })
+        });
+
+        workspace
+            .update(cx, |workspace, cx| {
+                execute_by_name(workspace, "editor::Backspace", None, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        cx.dispatch_action(Repeat);
+        cx.run_until_parked();
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "a",
+            "The command executed by its name should be repeated"
+        );
+        assert_eq!(*executed.borrow(), ["editor: backspace"]);
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<QueryHistory>().get(0),
+                Some("editor::Backspace")
+            );
+        });
+
+        workspace
+            .update(cx, |workspace, cx| {
+                execute_by_name(workspace, "Close!", None, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert!(
+            workspace.update(cx, |workspace, cx| workspace.active_item(cx).is_none()),
+            "The aliased variant should run with its own arguments, closing the edited editor without saving it"
+        );
+        assert_eq!(
+            *executed.borrow(),
+            ["editor: backspace", "pane: close active item (skip saving)"]
+        );
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>()
+                    .global
+                    .get("pane: close active item (skip saving)"),
+                Some(&1),
+                "The variant's usage should be recorded by its own name"
+            );
+        });
+    }
+
+    fn set_usage_scope(usage_scope: UsageScope, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.usage_scope = Some(usage_scope);
+                });
+            });
+        });
+    }
+
+    #[gpui::test]
+    async fn test_project_commands(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(runnables_ui::init);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/project",
+                json!({
+                    ".zed": {
+                        "settings.json": r#"{
+                            "command_palette": {
+                                "project_commands": {
+                                    "lint": { "command": "npm run lint" }
+                                }
+                            }
+                        }"#
+                    },
+                }),
+            )
+            .await;
+        let project = Project::test(app_state.fs.clone(), ["/project".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned = spawned.clone();
+            cx.subscribe(&workspace, move |_, event, _| {
+                if let workspace::Event::SpawnRunnable(spawn_in_terminal) = event {
+                    spawned.borrow_mut().push(spawn_in_terminal.label.clone());
+                }
+            })
+            .detach();
+        });
+        cx.run_until_parked();
+        cx.simulate_keystrokes("cmd-n");
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("lint");
+        active_palette(&workspace, cx).update(cx, |palette, cx| {
+            assert_eq!(palette.position_of("lint", cx), Some(0));
+        });
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert!(
+            cx.has_pending_prompt(),
+            "The project commands should only run once the worktree is trusted"
+        );
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(spawned.take(), ["npm run lint"]);
+
+        let hit_counts = cx.update(|cx| cx.global::<HitCounts>().global.clone());
+        assert_eq!(hit_counts.get("[project] /project: lint"), Some(&1));
+        assert_eq!(
+            hit_counts.get("lint"),
+            None,
+            "The project commands' usage should be scoped to their project"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_non_latin_command_names(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/project",
+                json!({
+                    ".zed": {
+                        "settings.json": r#"{
+                            "command_palette": {
+                                "project_commands": {
+                                    "Собрать проект": { "command": "cargo build" },
+                                    "テストを実行": { "command": "cargo test" },
+                                    "İstanbul'a dağıt": { "command": "cargo deploy" }
+                                }
+                            }
+                        }"#
+                    },
+                }),
+            )
+            .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<CommandPaletteSettings>(cx, |settings| {
+                    settings.aliases = Some(HashMap::from_iter([(
+                        "sobrat".to_string(),
+                        "Собрать проект".to_string(),
+                    )]));
+                });
+            });
+        });
+        let project = Project::test(app_state.fs.clone(), ["/project".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+        cx.simulate_keystrokes("cmd-n");
+
+        let palette = open_palette(&workspace, cx);
+        for (query, command_name, highlighted) in [
+            ("собрать", "Собрать проект", "Собрать"),
+            ("СОБР", "Собрать проект", "Собр"),
+            ("テスト", "テストを実行", "テスト"),
+            ("dağıt", "İstanbul'a dağıt", "dağıt"),
+            ("istanbul", "İstanbul'a dağıt", "İstanbul"),
+        ] {
+            palette.update(cx, |palette, cx| palette.set_query(query, cx));
+            cx.run_until_parked();
+            palette.update(cx, |palette, _| {
+                let first_match = &palette.delegate.matches[0];
+                assert_eq!(first_match.string, command_name, "{query:?} should match");
+                assert_eq!(
+                    first_match
+                        .ranges()
+                        .map(|range| &first_match.string[range])
+                        .collect::<String>(),
+                    highlighted,
+                    "The characters of {query:?} should be highlighted"
+                );
+            });
+        }
+
+        palette.update(cx, |palette, cx| palette.set_query("sobrat", cx));
+        cx.run_until_parked();
+        palette.update(cx, |palette, _| {
+            let first_match = &palette.delegate.matches[0];
+            assert_eq!(first_match.string, "Собрать проект");
+            assert_eq!(
+                palette
+                    .delegate
+                    .matched_aliases
+                    .get(&first_match.candidate_id)
+                    .map(|alias| alias.to_string()),
+                Some("sobrat".to_string()),
+                "A Latin alias should match the command named in another script"
+            );
+        });
+    }
+
+    fn open_palette(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<CommandPaletteDelegate>> {
+        cx.simulate_keystrokes("cmd-shift-p");
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn active_palette(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<CommandPalette> {
+        workspace.update(cx, |workspace, cx| {
+            workspace.active_modal::<CommandPalette>(cx).unwrap()
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Arc;
+    /// Adds a single line editor with the text given to the workspace, focused.
+    fn add_editor(
+        workspace: &View<Workspace>,
+        text: &str,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text(text, cx);
+            editor
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(editor.clone()), cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+        editor
+    }
 
-    use super::*;
-    use editor::Editor;
-    use go_to_line::GoToLine;
-    use gpui::TestAppContext;
-    use language::Point;
-    use project::Project;
-    use settings::KeymapFile;
-    use workspace::{AppState, Workspace};
+    fn top_command(workspace: &View<Workspace>, cx: &mut VisualTestContext) -> String {
+        let palette = open_palette(workspace, cx);
+        let top_command =
+            palette.update(cx, |palette, _| palette.delegate.matches[0].string.clone());
+        cx.simulate_keystrokes("cmd-shift-p");
+        top_command
+    }
 
-    #[test]
-    fn test_humanize_action_name() {
-        assert_eq!(
-            humanize_action_name("editor::GoToDefinition"),
-            "editor: go to definition"
-        );
-        assert_eq!(
-            humanize_action_name("editor::Backspace"),
-            "editor: backspace"
-        );
-        assert_eq!(
-            humanize_action_name("go_to_line::Deploy"),
-            "go to line: deploy"
-        );
+    fn run_backspace(workspace: &View<Workspace>, cx: &mut VisualTestContext) {
+        open_palette(workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("enter");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
     }
 
     #[gpui::test]
-    async fn test_command_palette(cx: &mut TestAppContext) {
+    async fn test_no_commands_available(cx: &mut TestAppContext) {
         let app_state = init_test(cx);
         let project = Project::test(app_state.fs.clone(), [], cx).await;
         let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
-
-        let editor = cx.new_view(|cx| {
-            let mut editor = Editor::single_line(cx);
-            editor.set_text("abc", cx);
-            editor
-        });
-
+        let editor = cx.new_view(|cx| Editor::single_line(cx));
         workspace.update(cx, |workspace, cx| {
             workspace.add_item(Box::new(editor.clone()), cx);
             editor.update(cx, |editor, cx| editor.focus(cx))
         });
 
-        cx.simulate_keystrokes("cmd-shift-p");
-
+        // Nothing is bound after the prefix yet, so no commands are available until a binding appears.
+        let key_prefix = vec![Keystroke::parse("ctrl-alt-z").unwrap()];
+        workspace.update(cx, |workspace, cx| {
+            let previous_focus_handle = cx.focused().unwrap();
+            let telemetry = workspace.client().telemetry().clone();
+            let workspace_handle = workspace.weak_handle();
+            let workspace_id = workspace.database_id();
+            workspace.toggle_modal(cx, |cx| {
+                CommandPalette::new(
+                    Vec::new(),
+                    previous_focus_handle,
+                    telemetry,
+                    workspace_handle,
+                    workspace.project().clone(),
+                    workspace_id,
+                    None,
+                    Some(key_prefix),
+                    None,
+                    cx,
+                )
+            });
+        });
+        cx.run_until_parked();
         let palette = workspace.update(cx, |workspace, cx| {
             workspace
                 .active_modal::<CommandPalette>(cx)
@@ -440,49 +6364,73 @@ mod tests {
                 .picker
                 .clone()
         });
-
-        palette.update(cx, |palette, _| {
-            assert!(palette.delegate.commands.len() > 5);
-            let is_sorted =
-                |actions: &[Command]| actions.windows(2).all(|pair| pair[0].name <= pair[1].name);
-            assert!(is_sorted(&palette.delegate.commands));
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.delegate.match_count(), 0);
+            assert_eq!(
+                palette.delegate.no_matches_text(cx),
+                "No commands available in this context"
+            );
         });
+        assert!(cx.debug_bounds("PICKER-empty-state").is_some());
 
-        cx.simulate_input("bcksp");
-
-        palette.update(cx, |palette, _| {
-            assert_eq!(palette.delegate.matches[0].string, "editor: backspace");
+        cx.update(|cx| {
+            cx.bind_keys([gpui::KeyBinding::new(
+                "ctrl-alt-z n",
+                workspace::NewFile,
+                None,
+            )]);
+            cx.refresh();
+        });
+        cx.run_until_parked();
+        palette.update(cx, |palette, cx| {
+            assert_eq!(
+                palette
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|m| m.string.as_str())
+                    .collect::<Vec<_>>(),
+                ["workspace: new file"],
+                "The commands should get listed as soon as they are available"
+            );
+            assert_eq!(palette.delegate.no_matches_text(cx), "No matches");
         });
+        assert!(cx.debug_bounds("PICKER-empty-state").is_none());
+    }
 
-        cx.simulate_keystrokes("enter");
+    #[gpui::test]
+    async fn test_palette_size_on_small_windows(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
 
+        cx.simulate_keystrokes("cmd-shift-p");
         workspace.update(cx, |workspace, cx| {
-            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
-            assert_eq!(editor.read(cx).text(cx), "ab")
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_some());
         });
 
-        // Add namespace filter, and redeploy the palette
-        cx.update(|cx| {
-            cx.set_global(CommandPaletteFilter::default());
-            cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
-                filter.hidden_namespaces.insert("editor");
-            })
-        });
+        cx.simulate_resize(size(px(1200.), px(800.)));
+        cx.update(|cx| cx.refresh());
+        let bounds = cx.debug_bounds("PICKER-matches").unwrap();
+        assert_eq!(bounds.size, size(px(544.), px(288.)));
 
-        cx.simulate_keystrokes("cmd-shift-p");
-        cx.simulate_input("bcksp");
+        cx.simulate_resize(size(px(500.), px(400.)));
+        cx.update(|cx| cx.refresh());
+        let bounds = cx.debug_bounds("PICKER-matches").unwrap();
+        assert_eq!(
+            bounds.size,
+            size(px(500. - 64.), px(400. - 144.)),
+            "The palette should shrink to fit into the window"
+        );
 
-        let palette = workspace.update(cx, |workspace, cx| {
-            workspace
-                .active_modal::<CommandPalette>(cx)
-                .unwrap()
-                .read(cx)
-                .picker
-                .clone()
-        });
-        palette.update(cx, |palette, _| {
-            assert!(palette.delegate.matches.is_empty())
-        });
+        cx.simulate_resize(size(px(200.), px(150.)));
+        cx.update(|cx| cx.refresh());
+        let bounds = cx.debug_bounds("PICKER-matches").unwrap();
+        assert_eq!(
+            bounds.size,
+            size(px(320.), px(96.)),
+            "The palette should not shrink below its minimum size"
+        );
     }
 
     #[gpui::test]
@@ -517,6 +6465,409 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_dismissed_telemetry(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let telemetry = workspace.update(cx, |workspace, _| workspace.client().telemetry().clone());
+        let dismissed_events = || {
+            telemetry
+                .queued_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    client::telemetry::Event::CommandPaletteDismissed {
+                        query_length,
+                        match_count,
+                        ..
+                    } => Some((query_length, match_count)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let palette = open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        let match_count = palette.update(cx, |palette, _| palette.delegate.matches.len());
+        cx.simulate_keystrokes("escape");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
+        assert_eq!(
+            dismissed_events(),
+            vec![(5, match_count)],
+            "Dismissing the palette should be reported once, with the query's length only"
+        );
+
+        // Running a command, or copying its action's name, is not.
+        run_backspace(&workspace, cx);
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("cmd-enter");
+        cx.run_until_parked();
+        assert_eq!(dismissed_events().len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_toggling_modals_through_palette(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.simulate_keystrokes("cmd-n");
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+
+        for i in 0..50 {
+            cx.simulate_keystrokes("cmd-shift-p");
+            cx.simulate_input("go to line: Toggle");
+            cx.simulate_keystrokes("enter");
+
+            let go_to_line = workspace.update(cx, |workspace, cx| {
+                assert!(
+                    workspace.active_modal::<CommandPalette>(cx).is_none(),
+                    "Palette should be dismissed on iteration {i}"
+                );
+                workspace
+                    .active_modal::<GoToLine>(cx)
+                    .unwrap_or_else(|| panic!("Go to line modal is missing on iteration {i}"))
+            });
+            go_to_line.update(cx, |go_to_line, cx| {
+                assert!(
+                    go_to_line.focus_handle(cx).contains_focused(cx),
+                    "Go to line modal is not focused on iteration {i}"
+                );
+            });
+
+            cx.dispatch_action(menu::Cancel);
+            workspace.update(cx, |workspace, cx| {
+                assert!(!workspace.has_active_modal(cx));
+            });
+            editor.update(cx, |editor, cx| {
+                assert!(editor.focus_handle(cx).is_focused(cx));
+            });
+        }
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<HitCounts>().global.get("go to line: toggle"),
+                Some(&50),
+                "Every dispatch should be recorded once"
+            );
+            assert_eq!(
+                cx.global::<LastConfirmedCommand>().name,
+                "go to line: toggle"
+            );
+            assert_eq!(
+                cx.global::<QueryHistory>().get(0),
+                Some("go to line: Toggle")
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_go_to_action_definition(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.update(|cx| release_channel::init("0.0.0", cx));
+
+        let mut language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        );
+        let mut fake_servers = language
+            .set_fake_lsp_adapter(Arc::<FakeLspAdapter>::default())
+            .await;
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/dir",
+                json!({
+                    "main.rs": "",
+                    "actions.rs": "pub struct Backspace;\n",
+                }),
+            )
+            .await;
+        let project = Project::test(app_state.fs.clone(), ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(Arc::new(language)));
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/main.rs", cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(
+            |params: lsp::WorkspaceSymbolParams, _| async move {
+                let symbols = if params.query == "Backspace" {
+                    #[allow(deprecated)]
+                    let symbol = lsp::SymbolInformation {
+                        name: "Backspace".to_string(),
+                        kind: lsp::SymbolKind::STRUCT,
+                        tags: None,
+                        deprecated: None,
+                        container_name: None,
+                        location: lsp::Location::new(
+                            lsp::Url::from_file_path("/dir/actions.rs").unwrap(),
+                            lsp::Range::new(lsp::Position::new(0, 11), lsp::Position::new(0, 20)),
+                        ),
+                    };
+                    vec![symbol]
+                } else {
+                    Vec::new()
+                };
+                Ok(Some(lsp::WorkspaceSymbolResponse::Flat(symbols)))
+            },
+        );
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("alt-enter");
+        cx.run_until_parked();
+
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(project_path.path.as_ref(), Path::new("actions.rs"));
+        });
+
+        // Actions without a matching project symbol are reported with a toast.
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("go to line: Toggle");
+        cx.simulate_keystrokes("alt-enter");
+        cx.run_until_parked();
+
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notification::<MessageNotification>(GO_TO_ACTION_DEFINITION_TOAST_ID)
+                .is_some());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_dump_ranking_comparison(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let item_count = |workspace: &View<Workspace>, cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                workspace.active_pane().read(cx).items_len()
+            })
+        };
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("toggle soft wrap");
+        cx.dispatch_action(DumpRankingComparison);
+        cx.run_until_parked();
+        assert_eq!(
+            item_count(&workspace, cx),
+            1,
+            "Rankings should only be compared in Zed Dev"
+        );
+
+        cx.update(|cx| release_channel::init("0.0.0", cx));
+        cx.dispatch_action(DumpRankingComparison);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            let comparison = workspace
+                .active_item_as::<Editor>(cx)
+                .unwrap()
+                .read(cx)
+                .text(cx);
+            let lines = comparison.lines().collect::<Vec<_>>();
+            assert_eq!(
+                lines[..4],
+                [
+                    "# Ranking comparison for \"toggle soft wrap\"",
+                    "",
+                    "| # | default | fuzzy_only | no_abbreviations | no_synonyms | no_usage |",
+                    "|---|---|---|---|---|---|",
+                ]
+            );
+            assert_eq!(
+                lines[4],
+                "| 1 | editor: toggle soft wrap | editor: toggle soft wrap | editor: toggle soft wrap | editor: toggle soft wrap | editor: toggle soft wrap |"
+            );
+            assert!(lines.len() <= 4 + COMPARED_MATCHES);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_explain_command(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+        let prompts = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let prompts = prompts.clone();
+            cx.set_global(CommandPaletteAssistant(Rc::new(move |prompt, _, _| {
+                prompts.borrow_mut().push(prompt);
+                Task::ready(Ok(()))
+            })));
+        });
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("toggle soft wrap");
+        cx.simulate_keystrokes("cmd-i");
+        cx.run_until_parked();
+        assert!(
+            prompts.borrow().is_empty(),
+            "Commands should not be explained without the assistant feature flag"
+        );
+
+        cx.update(|cx| cx.update_flags(false, vec!["assistant".to_string()]));
+        cx.simulate_keystrokes("cmd-i");
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
+        {
+            let prompts = prompts.borrow();
+            assert_eq!(prompts.len(), 1);
+            assert!(
+                prompts[0].contains("`editor::ToggleSoftWrap`"),
+                "The prompt should name the raw action: {}",
+                prompts[0]
+            );
+            assert!(prompts[0].contains("editor: toggle soft wrap"));
+            assert!(prompts[0].contains("`soft_wrap`"));
+        }
+
+        // Without a provider configured, the assistant fails to ask, which gets reported with a toast.
+        cx.update(|cx| {
+            cx.set_global(CommandPaletteAssistant(Rc::new(|_, _, _| {
+                Task::ready(Err(anyhow!("no assistant provider is configured")))
+            })));
+        });
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("cmd-i");
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notification::<MessageNotification>(explain_command::EXPLAIN_COMMAND_TOAST_ID)
+                .is_some());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_open_command_settings(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                &*paths::CONFIG_DIR,
+                json!({ "settings.json": "// Zed settings\n{\n  \"buffer_font_size\": 16\n}\n" }),
+            )
+            .await;
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("cmd-,");
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(
+                workspace.active_modal::<CommandPalette>(cx).is_some(),
+                "Commands without a setting should leave the palette as it is"
+            );
+        });
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        open_palette(&workspace, cx);
+        cx.simulate_input("toggle soft wrap");
+        cx.simulate_keystrokes("cmd-,");
+        cx.run_until_parked();
+        let settings_editor = workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        settings_editor.update(cx, |editor, cx| {
+            assert_eq!(
+                editor.text(cx),
+                "// Zed settings\n{\n  // \"soft_wrap\": \"none\",\n  \"buffer_font_size\": 16\n}\n",
+                "A missing setting should be added commented out, with its default value"
+            );
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(2, 5),
+                "The cursor should be placed at the setting's key"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_open_command_keymap(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let keymap = "[\n  {\n    \"bindings\": {\n      \"cmd-k b\": \"editor::Backspace\"\n    }\n  }\n]\n";
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(&*paths::CONFIG_DIR, json!({ "keymap.json": keymap }))
+            .await;
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.simulate_keystrokes("cmd-n");
+
+        let open_keymap = |query: &str, cx: &mut VisualTestContext| {
+            open_palette(&workspace, cx);
+            cx.simulate_input(query);
+            cx.simulate_keystrokes("cmd-shift-enter");
+            cx.run_until_parked();
+            workspace.update(cx, |workspace, cx| {
+                assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+                let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+                assert!(editor.focus_handle(cx).is_focused(cx));
+                editor
+            })
+        };
+
+        let keymap_editor = open_keymap("editor: backspace", cx);
+        keymap_editor.update(cx, |editor, cx| {
+            assert_eq!(
+                editor.text(cx),
+                keymap,
+                "The bound action's keymap should be left as it is"
+            );
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(3, 6),
+                "The cursor should be placed at the user's binding of the action"
+            );
+        });
+
+        let keymap_editor = open_keymap("editor: delete line", cx);
+        keymap_editor.update(cx, |editor, cx| {
+            assert_eq!(
+                editor.text(cx),
+                "[\n  {\n    \"bindings\": {\n      \"cmd-k b\": \"editor::Backspace\"\n    }\n  },\n  \
+                 {\n    \"bindings\": {\n      \"\": \"editor::DeleteLine\"\n    }\n  }\n]\n",
+                "A binding of the unbound action should be added at the end"
+            );
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(8, 7),
+                "The cursor should be placed inside the binding's keystrokes"
+            );
+        });
+    }
+
     fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let app_state = AppState::test(cx);
@@ -526,7 +6877,12 @@ mod tests {
             menu::init();
             go_to_line::init(cx);
             workspace::init(app_state.clone(), cx);
+            picker::init(cx);
             init(cx);
+            // The key-value store is shared between the tests, start with no usage recorded.
+            cx.set_global(HitCounts::default());
+            cx.set_global(RecentCommands::default());
+            cx.set_global(PinnedCommands::default());
             Project::init_settings(cx);
             KeymapFile::parse(
                 r#"[
@@ -534,8 +6890,24 @@ mod tests {
                         "bindings": {
                             "cmd-n": "workspace::NewFile",
                             "enter": "menu::Confirm",
+                            "cmd-enter": "menu::SecondaryConfirm",
                             "cmd-shift-p": "command_palette::Toggle"
                         }
+                    },
+                    {
+                        "context": "CommandPalette",
+                        "bindings": {
+                            "escape": "menu::Cancel",
+                            "alt-enter": "command_palette::GoToActionDefinition",
+                            "cmd-shift-backspace": "command_palette::ForgetRecent",
+                            "cmd-,": "command_palette::OpenCommandSettings",
+                            "cmd-shift-enter": "command_palette::OpenCommandKeymap",
+                            "cmd-i": "command_palette::ExplainCommand",
+                            "cmd-alt-p": "command_palette::TogglePinned",
+                            "cmd-alt-backspace": "command_palette::ForgetHitCount",
+                            "alt-up": "command_palette::PreviousQuery",
+                            "alt-down": "command_palette::NextQuery"
+                        }
                     }
                 ]"#,
             )
@@ -0,0 +1,119 @@
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CommandPaletteSettings {
+    pub usage_scope: UsageScope,
+    pub abbreviations: bool,
+    pub synonyms: HashMap<String, String>,
+    pub show_last_command: bool,
+    pub open_on_pending_prefix_ms: Option<u64>,
+    pub auto_confirm_exact_match: bool,
+    pub ranking_profile: Option<String>,
+    pub project_commands: HashMap<String, ProjectCommand>,
+    pub match_descriptions: bool,
+    pub aliases: HashMap<String, String>,
+    pub show_unavailable_commands: bool,
+}
+
+/// What a command defined in the project settings runs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectCommand {
+    /// The runnable with the label given, as listed in the runnables modal.
+    Runnable(String),
+    /// A one-shot runnable of the command line given, run in the worktree.
+    Command(String),
+}
+
+/// Where the command palette records which commands get confirmed, to rank them higher later.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageScope {
+    /// Share the usage between all workspaces.
+    #[default]
+    Global,
+    /// Keep the usage separate for every workspace.
+    Workspace,
+    /// Record the usage both for the workspace and globally, ranking by the workspace's usage first,
+    /// and by the usage in all the workspaces then, e.g. for the commands not used in a new workspace yet.
+    WorkspaceFirst,
+    /// Do not record the usage, always ordering the commands alphabetically.
+    Off,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct CommandPaletteSettingsContent {
+    /// Where to record the commands confirmed in the palette, ranking them higher later.
+    ///
+    /// Default: global
+    pub usage_scope: Option<UsageScope>,
+    /// Whether the short queries should also match the initials of the commands' words,
+    /// e.g. "gtd" for "editor: go to definition".
+    ///
+    /// Default: true
+    pub abbreviations: Option<bool>,
+    /// Terms to match the commands by, besides their names, mapped to the actions of the commands:
+    /// e.g. `"beautify": "editor::Format"`. Extends the built-in terms of the other editors.
+    ///
+    /// Default: {}
+    pub synonyms: Option<HashMap<String, String>>,
+    /// Whether to briefly show the last command confirmed in the palette in the status bar.
+    ///
+    /// Default: false
+    pub show_last_command: Option<bool>,
+    /// After how many milliseconds of a multi-keystroke binding's prefix pending, open the palette
+    /// listing the commands bound after it. The palette is not opened when unset.
+    ///
+    /// Default: null
+    pub open_on_pending_prefix_ms: Option<u64>,
+    /// Whether the palette, opened with a query by the `command_palette::Toggle` action,
+    /// should run the command right away when it's the only one the query names exactly.
+    ///
+    /// Default: false
+    pub auto_confirm_exact_match: Option<bool>,
+    /// The name of the profile to rank the matches with, for comparing the ranking tweaks in Zed Dev.
+    /// Ignored in the other release channels.
+    ///
+    /// Default: null
+    pub ranking_profile: Option<String>,
+    /// Commands to show in the palette, badged "project", mapped to the runnables they run:
+    /// e.g. `"deploy staging": { "runnable": "deploy" }` or `"lint": { "command": "npm run lint" }`.
+    /// Meant for the project settings, the runnables get run once the user trusts the worktree.
+    ///
+    /// Default: {}
+    pub project_commands: Option<HashMap<String, ProjectCommand>>,
+    /// Whether the queries should also match the descriptions shown under the commands' names,
+    /// listing those matches below the ones of the names.
+    ///
+    /// Default: true
+    pub match_descriptions: Option<bool>,
+    /// Shorthands to type for the commands, mapped to the actions of the commands: e.g. `"fmt": "editor::Format"`,
+    /// or to the commands' names, e.g. for the project commands named in other scripts than the keyboard's one.
+    /// The aliases of the actions and the commands the palette does not list are ignored.
+    ///
+    /// Default: {}
+    pub aliases: Option<HashMap<String, String>>,
+    /// Whether to also list the commands unavailable where the palette got opened from, e.g. the editor's ones
+    /// with a panel focused, greyed out below the available ones. Confirming them explains why they don't run.
+    ///
+    /// Default: false
+    pub show_unavailable_commands: Option<bool>,
+}
+
+impl Settings for CommandPaletteSettings {
+    const KEY: Option<&'static str> = Some("command_palette");
+
+    type FileContent = CommandPaletteSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
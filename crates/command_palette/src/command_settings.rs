@@ -0,0 +1,121 @@
+//! Settings the commands correspond to, e.g. `soft_wrap` for "editor: toggle soft wrap", to change their defaults from the palette.
+
+use anyhow::Context;
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{AppContext, Global, ViewContext};
+use util::paths;
+use workspace::{create_and_open_local_file, Workspace};
+
+/// Settings of the common toggles, by the names of the actions toggling them.
+const BUILTIN_COMMAND_SETTINGS: &[(&str, &str)] = &[
+    ("editor::ToggleSoftWrap", "soft_wrap"),
+    ("editor::ToggleInlayHints", "inlay_hints.enabled"),
+    ("editor::Format", "formatter"),
+    ("workspace::ToggleVimMode", "vim_mode"),
+    ("copilot::Suggest", "show_copilot_suggestions"),
+    ("theme_selector::Toggle", "theme"),
+    ("zed::IncreaseBufferFontSize", "buffer_font_size"),
+    ("zed::DecreaseBufferFontSize", "buffer_font_size"),
+    ("zed::ResetBufferFontSize", "buffer_font_size"),
+    ("project_panel::ToggleFocus", "project_panel"),
+    ("terminal_panel::ToggleFocus", "terminal"),
+    ("assistant::ToggleFocus", "assistant"),
+];
+
+/// Settings the actions correspond to, as dot-separated paths of keys, by the actions' names.
+#[derive(Default)]
+pub(crate) struct CommandSettings(HashMap<&'static str, &'static str>);
+
+impl Global for CommandSettings {}
+
+impl CommandSettings {
+    pub(crate) fn init(cx: &mut AppContext) {
+        let command_settings = cx.default_global::<Self>();
+        for &(action_name, setting_key) in BUILTIN_COMMAND_SETTINGS {
+            command_settings.0.entry(action_name).or_insert(setting_key);
+        }
+    }
+
+    pub(crate) fn get(&self, action_name: &str) -> Option<&'static str> {
+        self.0.get(action_name).copied()
+    }
+}
+
+/// Associates the action with the setting it corresponds to, offering to open the settings file at it from the palette.
+/// The setting is a dot-separated path of keys, e.g. `inlay_hints.enabled`.
+pub fn register_command_setting(
+    action_name: &'static str,
+    setting_key: &'static str,
+    cx: &mut AppContext,
+) {
+    cx.default_global::<CommandSettings>()
+        .0
+        .insert(action_name, setting_key);
+}
+
+/// Opens the user settings file with the cursor at the setting given,
+/// adding the setting with its default value commented out, if it's not there.
+pub(crate) fn open_setting(
+    setting_key: &'static str,
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let open_settings = create_and_open_local_file(&paths::SETTINGS, cx, || {
+        settings::initial_user_settings_content().as_ref().into()
+    });
+    cx.spawn(|_, mut cx| async move {
+        let editor = open_settings
+            .await?
+            .downcast::<Editor>()
+            .context("settings file is not opened in an editor")?;
+        editor.update(&mut cx, |editor, cx| {
+            let key_path = setting_key.split('.').collect::<Vec<_>>();
+            let text = editor.text(cx);
+            let position = match settings::json_key_range(&text, &key_path) {
+                Some(key_range) => key_range.start,
+                None => {
+                    let offset = top_level_object_start(&text);
+                    let template = setting_template(&key_path);
+                    editor.edit([(offset..offset, template.clone())], cx);
+                    offset + template.find('"').unwrap_or(0)
+                }
+            };
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([position..position])
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The offset right after the opening brace of the settings, skipping the comments before it.
+fn top_level_object_start(text: &str) -> usize {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if !line.trim_start().starts_with("//") {
+            if let Some(brace_ix) = line.find('{') {
+                return offset + brace_ix + 1;
+            }
+        }
+        offset += line.len();
+    }
+    text.len()
+}
+
+/// A commented out line with the setting and its default value, e.g. `// "inlay_hints": {"enabled":false},`.
+fn setting_template(key_path: &[&str]) -> String {
+    let default_settings =
+        settings::parse_json_with_comments::<serde_json::Value>(&settings::default_settings())
+            .unwrap_or_default();
+    let mut value = key_path
+        .iter()
+        .try_fold(&default_settings, |value, key| value.get(key))
+        .cloned()
+        .unwrap_or_default();
+    for key in key_path.iter().skip(1).rev() {
+        value = serde_json::json!({ key.to_string(): value });
+    }
+    let first_key = key_path.first().copied().unwrap_or_default();
+    format!("\n  // \"{first_key}\": {value},")
+}
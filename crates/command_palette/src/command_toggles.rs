@@ -0,0 +1,94 @@
+//! States of the toggle-style commands, e.g. whether the soft wrap is on for "editor: toggle soft wrap", shown in the palette.
+
+use collections::HashMap;
+use editor::{Editor, SoftWrap};
+use gpui::{AppContext, Global, View, WindowContext};
+use ui::prelude::*;
+use workspace::Workspace;
+
+/// Tells whether the command's toggle is on in the workspace given, `None` if it does not apply, e.g. with no editor active.
+/// Probed for every rendered row of the palette, so it should only read the state, not compute it.
+pub type ToggleProbe = fn(&Workspace, &WindowContext) -> Option<bool>;
+
+/// Probes of the common toggles, by the names of the actions toggling them.
+const BUILTIN_TOGGLE_PROBES: &[(&str, ToggleProbe)] = &[
+    ("editor::ToggleSoftWrap", soft_wrap_probe),
+    ("editor::ToggleInlayHints", inlay_hints_probe),
+    ("workspace::ToggleLeftDock", left_dock_probe),
+    ("workspace::ToggleRightDock", right_dock_probe),
+    ("workspace::ToggleBottomDock", bottom_dock_probe),
+    ("workspace::ToggleZoom", zoom_probe),
+];
+
+/// Probes of the toggles' states, by the names of the actions toggling them.
+#[derive(Default)]
+pub(crate) struct ToggleProbes(HashMap<&'static str, ToggleProbe>);
+
+impl Global for ToggleProbes {}
+
+impl ToggleProbes {
+    pub(crate) fn init(cx: &mut AppContext) {
+        let toggle_probes = cx.default_global::<Self>();
+        for &(action_name, probe) in BUILTIN_TOGGLE_PROBES {
+            toggle_probes.0.entry(action_name).or_insert(probe);
+        }
+    }
+
+    pub(crate) fn get(&self, action_name: &str) -> Option<ToggleProbe> {
+        self.0.get(action_name).copied()
+    }
+}
+
+/// Associates the toggle-style action with the probe of its state, shown next to the command in the palette.
+pub fn register_toggle_probe(action_name: &'static str, probe: ToggleProbe, cx: &mut AppContext) {
+    cx.default_global::<ToggleProbes>()
+        .0
+        .insert(action_name, probe);
+}
+
+/// An "on" or "off" pill with the toggle's state.
+pub(crate) fn render_toggle_state(on: bool, cx: &WindowContext) -> impl IntoElement {
+    let colors = cx.theme().colors();
+    h_flex()
+        .debug_selector(|| format!("TOGGLE_STATE-{}", if on { "on" } else { "off" }))
+        .px_1p5()
+        .rounded_full()
+        .border_1()
+        .border_color(colors.border)
+        .when(on, |pill| pill.bg(colors.element_selected))
+        .child(
+            Label::new(if on { "on" } else { "off" })
+                .size(LabelSize::XSmall)
+                .color(if on { Color::Accent } else { Color::Muted }),
+        )
+}
+
+fn active_editor(workspace: &Workspace, cx: &WindowContext) -> Option<View<Editor>> {
+    workspace.active_item_as::<Editor>(cx)
+}
+
+fn soft_wrap_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    let editor = active_editor(workspace, cx)?;
+    let soft_wrap = editor.read(cx).soft_wrap_mode(cx);
+    Some(!matches!(soft_wrap, SoftWrap::None))
+}
+
+fn inlay_hints_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    Some(active_editor(workspace, cx)?.read(cx).inlay_hints_enabled())
+}
+
+fn left_dock_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    Some(workspace.left_dock().read(cx).is_open())
+}
+
+fn right_dock_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    Some(workspace.right_dock().read(cx).is_open())
+}
+
+fn bottom_dock_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    Some(workspace.bottom_dock().read(cx).is_open())
+}
+
+fn zoom_probe(workspace: &Workspace, cx: &WindowContext) -> Option<bool> {
+    Some(workspace.active_pane().read(cx).is_zoomed())
+}
@@ -0,0 +1,124 @@
+//! Named variants of the actions taking arguments, listed in the palette as commands of their own,
+//! e.g. "pane: close active item (skip saving)" next to "pane: close active item", run with the default arguments.
+
+use collections::{HashMap, HashSet};
+use gpui::{Action, AppContext, Global};
+use workspace::{
+    pane::{CloseActiveItem, CloseAllItems, SaveIntent},
+    CloseAllItemsAndPanes,
+};
+
+use crate::{humanize_action_name, synonyms::normalize};
+
+/// Variants of the workspace's actions, which cannot register them on their own, the palette depending on the workspace.
+fn builtin_command_variants() -> Vec<(&'static str, Box<dyn Action>)> {
+    vec![
+        (
+            "save",
+            Box::new(CloseActiveItem {
+                save_intent: Some(SaveIntent::SaveAll),
+            }),
+        ),
+        (
+            "skip saving",
+            Box::new(CloseActiveItem {
+                save_intent: Some(SaveIntent::Skip),
+            }),
+        ),
+        (
+            "save all",
+            Box::new(CloseAllItems {
+                save_intent: Some(SaveIntent::SaveAll),
+            }),
+        ),
+        (
+            "skip saving",
+            Box::new(CloseAllItems {
+                save_intent: Some(SaveIntent::Skip),
+            }),
+        ),
+        (
+            "skip saving",
+            Box::new(CloseAllItemsAndPanes {
+                save_intent: Some(SaveIntent::Skip),
+            }),
+        ),
+    ]
+}
+
+/// Variants of the actions, by the names of the actions, in the order of their registration.
+#[derive(Default)]
+pub(crate) struct CommandVariants(HashMap<String, Vec<(String, Box<dyn Action>)>>);
+
+impl Global for CommandVariants {}
+
+impl CommandVariants {
+    pub(crate) fn init(cx: &mut AppContext) {
+        let command_variants = cx.default_global::<Self>();
+        for (label, action) in builtin_command_variants() {
+            command_variants.insert(label.to_string(), action);
+        }
+    }
+
+    /// The labels of the action's variants, along with the variants' copies to run.
+    pub(crate) fn get(&self, action_name: &str) -> Vec<(String, Box<dyn Action>)> {
+        self.0
+            .get(action_name)
+            .into_iter()
+            .flatten()
+            .map(|(label, action)| (label.clone(), action.boxed_clone()))
+            .collect()
+    }
+
+    /// The variant named as given, see [`variant_name`], regardless of the case, along with its copy to run.
+    pub(crate) fn find(&self, name: &str) -> Option<(String, Box<dyn Action>)> {
+        let name = normalize(name);
+        self.0
+            .iter()
+            .flat_map(|(action_name, variants)| {
+                let command_name = humanize_action_name(action_name);
+                variants
+                    .iter()
+                    .map(move |(label, action)| (variant_name(&command_name, label), action))
+            })
+            .find(|(variant_name, _)| normalize(variant_name) == name)
+            .map(|(variant_name, action)| (variant_name, action.boxed_clone()))
+    }
+
+    /// Adds the variant, replacing the one of the action with the same label.
+    fn insert(&mut self, label: String, action: Box<dyn Action>) {
+        let variants = self.0.entry(action.name().to_string()).or_default();
+        variants.retain(|(variant_label, _)| *variant_label != label);
+        variants.push((label, action));
+    }
+}
+
+/// Lists the variants of the action taking arguments in the palette, as commands of their own named after the action,
+/// followed by the variant's label in parentheses, e.g. `("visual line", Box::new(SwitchMode(Mode::VisualLine)))`
+/// for "vim: switch mode (visual line)". The usage of every variant gets recorded by its own name.
+/// The variants are only listed along with the action's command, when the action is available.
+pub fn register_command_variants(
+    variants: impl IntoIterator<Item = (impl Into<String>, Box<dyn Action>)>,
+    cx: &mut AppContext,
+) {
+    let command_variants = cx.default_global::<CommandVariants>();
+    for (label, action) in variants {
+        command_variants.insert(label.into(), action);
+    }
+}
+
+/// The name of the variant's command.
+pub(crate) fn variant_name(command_name: &str, label: &str) -> String {
+    format!("{command_name} ({label})")
+}
+
+/// Whether the usage key is the name of a variant of one of the commands given, see [`variant_name`].
+/// The variants themselves may be registered later than the usage gets loaded, by the crates listing them.
+pub(crate) fn is_variant_usage_key(usage_key: &str, command_names: &HashSet<String>) -> bool {
+    usage_key
+        .strip_suffix(')')
+        .and_then(|usage_key| usage_key.split_once(" ("))
+        .map_or(false, |(command_name, _)| {
+            command_names.contains(command_name)
+        })
+}
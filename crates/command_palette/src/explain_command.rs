@@ -0,0 +1,87 @@
+//! Asking the assistant what a command does, from the palette.
+
+use std::rc::Rc;
+
+use anyhow::Result;
+use gpui::{Global, Task, ViewContext};
+use workspace::{Toast, Workspace};
+
+pub(crate) const EXPLAIN_COMMAND_TOAST_ID: usize = 0x3e91a0c7;
+
+/// The assistant's entry point, asking it the prompt given and showing the answer in its panel.
+/// Set by the assistant, so that the palette does not depend on it; fails if no provider is configured.
+pub struct CommandPaletteAssistant(
+    pub Rc<dyn Fn(String, &mut Workspace, &mut ViewContext<Workspace>) -> Task<Result<()>>>,
+);
+
+impl Global for CommandPaletteAssistant {}
+
+/// What the palette knows about the command to explain.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CommandDetails {
+    /// Name of the command's action, e.g. `editor::ToggleSoftWrap`.
+    pub action_name: String,
+    /// Name of the command, as shown in the palette, e.g. `editor: toggle soft wrap`.
+    pub command_name: String,
+    /// The keystrokes the command is bound to in the context the palette got opened from.
+    pub key_binding: Option<String>,
+    /// The setting the command corresponds to, if any.
+    pub setting_key: Option<&'static str>,
+}
+
+/// The question to ask the assistant about the command.
+pub(crate) fn explain_prompt(details: &CommandDetails) -> String {
+    let mut prompt = format!(
+        "Explain what the `{}` action does in the Zed editor. \
+         It is listed in the command palette as \"{}\"",
+        details.action_name, details.command_name
+    );
+    match &details.key_binding {
+        Some(key_binding) => prompt.push_str(&format!(" and bound to `{key_binding}`.")),
+        None => prompt.push_str(" and not bound to any keys."),
+    }
+    if let Some(setting_key) = details.setting_key {
+        prompt.push_str(&format!(
+            " Its default can be changed with the `{setting_key}` setting."
+        ));
+    }
+    prompt.push_str(" Describe when it is useful, briefly.");
+    prompt
+}
+
+/// Asks the assistant to explain the command, telling the user with a toast if there's no assistant to ask.
+pub(crate) fn explain_command(
+    details: CommandDetails,
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(ask) = cx
+        .try_global::<CommandPaletteAssistant>()
+        .map(|assistant| assistant.0.clone())
+    else {
+        workspace.show_toast(
+            Toast::new(
+                EXPLAIN_COMMAND_TOAST_ID,
+                "Explaining commands needs the assistant",
+            ),
+            cx,
+        );
+        return;
+    };
+    let answer = ask(explain_prompt(&details), workspace, cx);
+    cx.spawn(|workspace, mut cx| async move {
+        if let Err(error) = answer.await {
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        EXPLAIN_COMMAND_TOAST_ID,
+                        format!("Cannot explain '{}': {error:#}", details.command_name),
+                    ),
+                    cx,
+                )
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
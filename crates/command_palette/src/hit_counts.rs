@@ -0,0 +1,467 @@
+use std::time::{Duration, SystemTime};
+
+use collections::{HashMap, HashSet};
+use db::{kvp::KEY_VALUE_STORE, versioned::read_persisted};
+use fuzzy::StringMatch;
+use gpui::{AppContext, Global};
+use util::ResultExt;
+use workspace::WorkspaceId;
+
+use crate::{
+    command_variants::is_variant_usage_key,
+    humanize_action_name,
+    intercepted_commands::is_intercepted_usage_key,
+    persistence::{parse_hit_counts, serialize_hit_counts, PersistedHitCounts},
+    project_commands::is_project_usage_key,
+    published_actions::{letter_spaced_commands, renamed_commands},
+    Command, UsageScope,
+};
+
+const HIT_COUNTS_KEY: &str = "command_palette_hit_counts";
+/// How long it takes for the hit count of a command not used since to halve.
+const HIT_COUNT_HALF_LIFE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// The most the commands' usage raises the scores of their matches by, relative to the scores.
+const MAX_USAGE_BOOST: f64 = 0.1;
+/// The hit count, at which the commands' usage raises their matches' scores the most.
+const SATURATING_HIT_COUNT: usize = 200;
+/// How many times the workspace's hit counts get added to the global ones, which include them already,
+/// ranking by the usage in [`UsageScope::WorkspaceFirst`].
+const WORKSPACE_HIT_COUNT_WEIGHT: usize = 2;
+
+/// Hit count for each command in the palette, persisted between the restarts.
+/// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
+/// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
+///
+/// The counts are kept as recorded, along with the time the commands got used last, and decay when read:
+/// the commands not used for a while stop dominating the ranking.
+#[derive(Default, Debug, PartialEq)]
+pub(crate) struct HitCounts {
+    pub global: HashMap<String, usize>,
+    pub by_workspace: HashMap<WorkspaceId, HashMap<String, usize>>,
+    /// When the commands got used last, in the same scopes as their hit counts.
+    last_used: HashMap<String, SystemTime>,
+    last_used_by_workspace: HashMap<WorkspaceId, HashMap<String, SystemTime>>,
+}
+
+impl Global for HitCounts {}
+
+impl HitCounts {
+    /// Loads the persisted hit counts, dropping the ones of the commands no action stands for anymore.
+    /// The project commands' ones are kept, with their projects' settings not loaded yet,
+    /// and so are the intercepted commands' ones, with the interceptors returning them only for the queries,
+    /// and the actions' variants' ones, with the variants registered later.
+    /// The counts of the renamed actions' commands carry over to their new names, and so do the ones of the commands
+    /// with acronyms, recorded by the names with the acronyms' letters spaced out.
+    pub(crate) fn load(cx: &AppContext) -> Self {
+        let Some(persisted) = read_persisted(
+            HIT_COUNTS_KEY,
+            |serialized| parse_hit_counts(serialized, SystemTime::now()),
+            cx,
+        ) else {
+            return Self::default();
+        };
+        Self::restore(persisted, cx)
+    }
+
+    /// The hit counts, as loaded after a restart.
+    #[cfg(test)]
+    pub(crate) fn reloaded(&self, cx: &AppContext) -> Self {
+        let serialized = serialize_hit_counts(&self.to_persisted()).unwrap();
+        Self::restore(
+            parse_hit_counts(&serialized, SystemTime::now()).unwrap(),
+            cx,
+        )
+    }
+
+    /// The persisted hit counts of the actions registered, see [`Self::load`].
+    fn restore(persisted: PersistedHitCounts, cx: &AppContext) -> Self {
+        let command_names = cx
+            .all_action_names()
+            .iter()
+            .map(|action_name| humanize_action_name(action_name))
+            .collect::<HashSet<_>>();
+        let mut renamed = renamed_commands();
+        renamed.extend(letter_spaced_commands(
+            cx.all_action_names().iter().map(AsRef::as_ref),
+        ));
+        Self::from_persisted(persisted, &command_names, &renamed)
+    }
+
+    /// The persisted hit counts, pruned and carried over to the renamed commands as [`Self::load`] says.
+    fn from_persisted(
+        persisted: PersistedHitCounts,
+        command_names: &HashSet<String>,
+        renamed_commands: &HashMap<String, String>,
+    ) -> Self {
+        let mut hit_counts = Self {
+            global: persisted.global.into_iter().collect(),
+            by_workspace: persisted
+                .by_workspace
+                .into_iter()
+                .map(|(workspace_id, hit_counts)| (workspace_id, hit_counts.into_iter().collect()))
+                .collect(),
+            last_used: persisted.last_used.into_iter().collect(),
+            last_used_by_workspace: persisted
+                .last_used_by_workspace
+                .into_iter()
+                .map(|(workspace_id, last_used)| (workspace_id, last_used.into_iter().collect()))
+                .collect(),
+        };
+        let migrate = |hit_counts: &mut HashMap<String, usize>,
+                       last_used: &mut HashMap<String, SystemTime>| {
+            for (old_name, new_name) in renamed_commands {
+                if let Some(hit_count) = hit_counts.remove(old_name) {
+                    *hit_counts.entry(new_name.clone()).or_default() += hit_count;
+                }
+                if let Some(old_last_used) = last_used.remove(old_name) {
+                    let new_last_used = last_used.entry(new_name.clone()).or_insert(old_last_used);
+                    *new_last_used = old_last_used.max(*new_last_used);
+                }
+            }
+            hit_counts.retain(|command_name, _| {
+                command_names.contains(command_name)
+                    || is_project_usage_key(command_name)
+                    || is_intercepted_usage_key(command_name)
+                    || is_variant_usage_key(command_name, command_names)
+            });
+            last_used.retain(|command_name, _| hit_counts.contains_key(command_name));
+        };
+        migrate(&mut hit_counts.global, &mut hit_counts.last_used);
+        for (workspace_id, workspace_hit_counts) in &mut hit_counts.by_workspace {
+            let last_used = hit_counts
+                .last_used_by_workspace
+                .entry(*workspace_id)
+                .or_default();
+            migrate(workspace_hit_counts, last_used);
+        }
+        hit_counts
+            .by_workspace
+            .retain(|_, workspace_hit_counts| !workspace_hit_counts.is_empty());
+        let by_workspace = &hit_counts.by_workspace;
+        hit_counts
+            .last_used_by_workspace
+            .retain(|workspace_id, _| by_workspace.contains_key(workspace_id));
+        hit_counts
+    }
+
+    fn to_persisted(&self) -> PersistedHitCounts {
+        PersistedHitCounts {
+            global: self.global.clone().into_iter().collect(),
+            by_workspace: self
+                .by_workspace
+                .iter()
+                .map(|(&workspace_id, hit_counts)| {
+                    (workspace_id, hit_counts.clone().into_iter().collect())
+                })
+                .collect(),
+            last_used: self.last_used.clone().into_iter().collect(),
+            last_used_by_workspace: self
+                .last_used_by_workspace
+                .iter()
+                .map(|(&workspace_id, last_used)| {
+                    (workspace_id, last_used.clone().into_iter().collect())
+                })
+                .collect(),
+        }
+    }
+
+    /// Hit counts to rank the commands by, decayed by the time since the commands got used last, as of the time given.
+    /// The commands, whose counts decayed below a half, are left out. `None` if the usage is not tracked.
+    pub(crate) fn for_scope(
+        &self,
+        scope: UsageScope,
+        workspace_id: WorkspaceId,
+        now: SystemTime,
+    ) -> Option<HashMap<String, usize>> {
+        let empty = HashMap::default();
+        let workspace_last_used = self
+            .last_used_by_workspace
+            .get(&workspace_id)
+            .unwrap_or(&empty);
+        match scope {
+            UsageScope::Global => Some(decayed_hit_counts(&self.global, &self.last_used, now)),
+            UsageScope::Workspace => Some(decayed_hit_counts(
+                self.by_workspace.get(&workspace_id)?,
+                workspace_last_used,
+                now,
+            )),
+            UsageScope::WorkspaceFirst => {
+                let mut hit_counts = decayed_hit_counts(&self.global, &self.last_used, now);
+                if let Some(workspace_hit_counts) = self.by_workspace.get(&workspace_id) {
+                    for (command_name, hit_count) in
+                        decayed_hit_counts(workspace_hit_counts, workspace_last_used, now)
+                    {
+                        *hit_counts.entry(command_name).or_default() +=
+                            hit_count * WORKSPACE_HIT_COUNT_WEIGHT;
+                    }
+                }
+                Some(hit_counts)
+            }
+            UsageScope::Off => None,
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        scope: UsageScope,
+        workspace_id: WorkspaceId,
+        command_name: String,
+        cx: &AppContext,
+    ) {
+        let now = SystemTime::now();
+        let record = |hit_counts: &mut HashMap<String, usize>,
+                      last_used: &mut HashMap<String, SystemTime>| {
+            last_used.insert(command_name.clone(), now);
+            *hit_counts.entry(command_name.clone()).or_default() += 1;
+        };
+        if matches!(scope, UsageScope::Global | UsageScope::WorkspaceFirst) {
+            record(&mut self.global, &mut self.last_used);
+        }
+        if matches!(scope, UsageScope::Workspace | UsageScope::WorkspaceFirst) {
+            record(
+                self.by_workspace.entry(workspace_id).or_default(),
+                self.last_used_by_workspace.entry(workspace_id).or_default(),
+            );
+        }
+        if scope != UsageScope::Off {
+            self.serialize(cx);
+        }
+    }
+
+    /// Forgets the usage of the command, in all scopes.
+    pub(crate) fn forget(&mut self, command_name: &str, cx: &AppContext) {
+        let mut forgotten = self.global.remove(command_name).is_some();
+        self.last_used.remove(command_name);
+        for hit_counts in self.by_workspace.values_mut() {
+            forgotten |= hit_counts.remove(command_name).is_some();
+        }
+        for last_used in self.last_used_by_workspace.values_mut() {
+            last_used.remove(command_name);
+        }
+        if forgotten {
+            self.serialize(cx);
+        }
+    }
+
+    /// Forgets the usage of all commands, in all scopes.
+    pub(crate) fn reset(&mut self, cx: &AppContext) {
+        *self = Self::default();
+        self.serialize(cx);
+    }
+
+    fn serialize(&self, cx: &AppContext) {
+        let Some(serialized) = serialize_hit_counts(&self.to_persisted()).log_err() else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(HIT_COUNTS_KEY.into(), serialized)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+/// The hit counts decayed as of the time given, leaving out the ones decayed below a half.
+fn decayed_hit_counts(
+    hit_counts: &HashMap<String, usize>,
+    last_used: &HashMap<String, SystemTime>,
+    now: SystemTime,
+) -> HashMap<String, usize> {
+    hit_counts
+        .iter()
+        .filter_map(|(command_name, &hit_count)| {
+            let hit_count = match last_used.get(command_name) {
+                Some(&last_used) => decayed_hit_count(hit_count, last_used, now),
+                None => hit_count,
+            };
+            (hit_count > 0).then(|| (command_name.clone(), hit_count))
+        })
+        .collect()
+}
+
+/// The hit count, halved for every [`HIT_COUNT_HALF_LIFE`] since the command got used last, rounded.
+fn decayed_hit_count(hit_count: usize, last_used: SystemTime, now: SystemTime) -> usize {
+    let Ok(unused_for) = now.duration_since(last_used) else {
+        return hit_count;
+    };
+    let half_lives = unused_for.as_secs_f64() / HIT_COUNT_HALF_LIFE.as_secs_f64();
+    (hit_count as f64 * 0.5f64.powf(half_lives)).round() as usize
+}
+
+/// Raises the scores of the matches by the hit counts of their commands, for the frequently used commands
+/// to go above the unused ones matched about as well, and reorders the matches by the raised scores.
+///
+/// The boost grows logarithmically with the hit count, up to [`MAX_USAGE_BOOST`] of the score at [`SATURATING_HIT_COUNT`]:
+/// a match scored higher by more than that goes first regardless of the usage.
+pub(crate) fn boost_by_usage(
+    mut matches: Vec<StringMatch>,
+    commands: &[&Command],
+    hit_counts: &HashMap<String, usize>,
+) -> Vec<StringMatch> {
+    if hit_counts.is_empty() {
+        return matches;
+    }
+    for string_match in &mut matches {
+        let usage_key = commands[string_match.candidate_id].usage_key();
+        if let Some(&hit_count) = hit_counts.get(&usage_key) {
+            string_match.score *= 1.0 + usage_boost(hit_count);
+        }
+    }
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+/// The share of the match's score its command's hit count adds.
+fn usage_boost(hit_count: usize) -> f64 {
+    let hit_count = hit_count.min(SATURATING_HIT_COUNT) as f64;
+    MAX_USAGE_BOOST * hit_count.ln_1p() / (SATURATING_HIT_COUNT as f64).ln_1p()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_hit_counts() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let command_names = ["editor: backspace", "workspace: new file"]
+            .into_iter()
+            .map(String::from)
+            .collect::<HashSet<_>>();
+        let persisted = parse_hit_counts(
+            r#"{
+                "version": "1",
+                "global": {
+                    "editor: backspace": 3,
+                    "removed: action": 7,
+                    "editor: delete left": 2,
+                    "[project] /repo: deploy staging": 4
+                },
+                "by_workspace": {
+                    "1": { "workspace: new file": 2 },
+                    "2": { "removed: action": 1 }
+                }
+            }"#,
+            now,
+        )
+        .unwrap();
+        let hit_counts = HitCounts::from_persisted(
+            persisted,
+            &command_names,
+            &HashMap::from_iter([(
+                "editor: delete left".to_string(),
+                "editor: backspace".to_string(),
+            )]),
+        );
+        assert_eq!(
+            hit_counts,
+            HitCounts {
+                global: HashMap::from_iter([
+                    ("editor: backspace".to_string(), 5),
+                    ("[project] /repo: deploy staging".to_string(), 4)
+                ]),
+                by_workspace: HashMap::from_iter([(
+                    1,
+                    HashMap::from_iter([("workspace: new file".to_string(), 2)])
+                )]),
+                last_used: HashMap::from_iter([
+                    ("editor: backspace".to_string(), now),
+                    ("[project] /repo: deploy staging".to_string(), now)
+                ]),
+                last_used_by_workspace: HashMap::from_iter([(
+                    1,
+                    HashMap::from_iter([("workspace: new file".to_string(), now)])
+                )]),
+            },
+            "The hit counts of the commands gone should be pruned, the renamed ones carried over, the project ones kept, \
+            all of them starting to decay on load"
+        );
+
+        let mut hit_counts = hit_counts;
+        hit_counts
+            .last_used
+            .insert("editor: backspace".to_string(), UNIX_EPOCH);
+        let serialized = serialize_hit_counts(&hit_counts.to_persisted()).unwrap();
+        assert_eq!(
+            HitCounts::from_persisted(
+                parse_hit_counts(&serialized, now).unwrap(),
+                &command_names,
+                &HashMap::default(),
+            ),
+            hit_counts,
+            "The times of the last use should survive the restarts"
+        );
+        assert!(
+            parse_hit_counts(r#"{ "version": "999" }"#, now).is_err(),
+            "Unknown formats should not be loaded"
+        );
+    }
+
+    #[test]
+    fn test_hit_count_decay() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(decayed_hit_count(100, now, now), 100);
+        assert_eq!(decayed_hit_count(100, now - HIT_COUNT_HALF_LIFE, now), 50);
+        assert_eq!(
+            decayed_hit_count(100, now - HIT_COUNT_HALF_LIFE * 2, now),
+            25
+        );
+        assert_eq!(
+            decayed_hit_count(100, now + HIT_COUNT_HALF_LIFE, now),
+            100,
+            "The counts used after the time given should not decay"
+        );
+
+        let mut hit_counts = HitCounts::default();
+        for (command_name, hit_count, last_used) in [
+            ("editor: backspace", 200, now - HIT_COUNT_HALF_LIFE * 6),
+            ("editor: format", 5, now),
+            ("editor: fold", 1, now - HIT_COUNT_HALF_LIFE * 2),
+        ] {
+            hit_counts
+                .global
+                .insert(command_name.to_string(), hit_count);
+            hit_counts
+                .last_used
+                .insert(command_name.to_string(), last_used);
+        }
+        assert_eq!(
+            hit_counts.for_scope(UsageScope::Global, 1, now),
+            Some(HashMap::from_iter([
+                ("editor: backspace".to_string(), 3),
+                ("editor: format".to_string(), 5),
+            ])),
+            "Heavily used commands should stop dominating once unused for long, the barely used ones drop out"
+        );
+        assert_eq!(hit_counts.for_scope(UsageScope::Workspace, 1, now), None);
+        assert_eq!(hit_counts.for_scope(UsageScope::Off, 1, now), None);
+        assert_eq!(
+            hit_counts.for_scope(UsageScope::WorkspaceFirst, 1, now),
+            hit_counts.for_scope(UsageScope::Global, 1, now),
+            "The workspace with no usage should fall back to the global one"
+        );
+
+        hit_counts
+            .by_workspace
+            .insert(1, HashMap::from_iter([("editor: format".to_string(), 2)]));
+        hit_counts
+            .last_used_by_workspace
+            .insert(1, HashMap::from_iter([("editor: format".to_string(), now)]));
+        assert_eq!(
+            hit_counts.for_scope(UsageScope::WorkspaceFirst, 1, now),
+            Some(HashMap::from_iter([
+                ("editor: backspace".to_string(), 3),
+                (
+                    "editor: format".to_string(),
+                    5 + 2 * WORKSPACE_HIT_COUNT_WEIGHT
+                ),
+            ])),
+            "The workspace's usage should weigh more than the global one"
+        );
+    }
+}
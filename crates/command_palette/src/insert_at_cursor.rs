@@ -0,0 +1,65 @@
+//! Inserting the selected command's keybinding or action name at the cursor of the editor the palette got opened from,
+//! e.g. when writing docs or keymaps.
+
+use editor::Editor;
+use gpui::{FocusHandle, FocusableView, Keystroke, ViewContext};
+use serde::Deserialize;
+use workspace::{Toast, Workspace};
+
+pub(crate) const INSERT_AT_CURSOR_TOAST_ID: usize = 0x7a3b91c4;
+
+/// How the keybinding gets written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeybindingFormat {
+    /// In the current platform's notation, as the palette shows it, e.g. `⌘⇧P` on macOS.
+    #[default]
+    Platform,
+    /// As written in the keymap, e.g. `cmd-shift-p`.
+    Keymap,
+}
+
+impl KeybindingFormat {
+    pub(crate) fn format(self, key_binding: &gpui::KeyBinding) -> String {
+        match self {
+            Self::Platform => ui::KeyBinding::new(key_binding.clone()).text(),
+            Self::Keymap => key_binding
+                .keystrokes()
+                .iter()
+                .map(Keystroke::unparse)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Inserts the text at the cursor of the editor with the focus handle given,
+/// telling why nothing got inserted with a toast if the text is missing or the focus was not in an editor.
+pub(crate) fn insert_text(
+    workspace: &mut Workspace,
+    focus_handle: &FocusHandle,
+    text: Result<String, String>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let text = match text {
+        Ok(text) => text,
+        Err(message) => {
+            workspace.show_toast(Toast::new(INSERT_AT_CURSOR_TOAST_ID, message), cx);
+            return;
+        }
+    };
+    let editor = workspace
+        .items_of_type::<Editor>(cx)
+        .find(|editor| &editor.focus_handle(cx) == focus_handle);
+    let Some(editor) = editor else {
+        workspace.show_toast(
+            Toast::new(
+                INSERT_AT_CURSOR_TOAST_ID,
+                "Open the command palette from an editor to insert into it",
+            ),
+            cx,
+        );
+        return;
+    };
+    editor.update(cx, |editor, cx| editor.insert(&text, cx));
+}
@@ -0,0 +1,36 @@
+//! Usage of the commands the interceptors return, e.g. vim's `:w`, recorded by the ranking keys they supply,
+//! to order the intercepted commands by how often they get confirmed.
+
+use std::cmp::Reverse;
+
+use collections::HashMap;
+
+use crate::CommandInterceptResult;
+
+/// What the usage of the intercepted commands gets recorded by starts with, unlike any humanized action name.
+const USAGE_KEY_PREFIX: &str = "[intercepted] ";
+
+/// What the usage of the intercepted command with the ranking key given gets recorded by.
+pub(crate) fn usage_key(ranking_key: &str) -> String {
+    format!("{USAGE_KEY_PREFIX}{ranking_key}")
+}
+
+/// Whether the usage recorded by the key given is an intercepted command's one.
+pub(crate) fn is_intercepted_usage_key(usage_key: &str) -> bool {
+    usage_key.starts_with(USAGE_KEY_PREFIX)
+}
+
+/// Orders the intercept results by the hit counts of their ranking keys, keeping the interceptors' order
+/// for the equally used ones and the ones with no ranking key.
+pub(crate) fn rank_intercept_results(
+    results: &mut [CommandInterceptResult],
+    hit_counts: &HashMap<String, usize>,
+) {
+    results.sort_by_key(|result| {
+        let hit_count = result
+            .ranking_key
+            .as_ref()
+            .and_then(|ranking_key| hit_counts.get(&usage_key(ranking_key)));
+        Reverse(hit_count.copied().unwrap_or(0))
+    });
+}
@@ -0,0 +1,40 @@
+//! Queries starting with `key:`, e.g. `key: cmd-k cmd-s`, listing the commands bound to the keystrokes typed after it,
+//! to find out what a binding does.
+
+use gpui::{Action, FocusHandle, KeyBinding, Keystroke, WindowContext};
+
+const KEY_QUERY_PREFIX: &str = "key:";
+
+/// The keystrokes typed after `key:`, parsed as in the keymap, or why they could not be.
+/// `None` for the queries not starting with `key:`.
+pub(crate) fn parse_key_query(query: &str) -> Option<anyhow::Result<Vec<Keystroke>>> {
+    let keystrokes = query.trim_start().strip_prefix(KEY_QUERY_PREFIX)?;
+    Some(
+        keystrokes
+            .split_whitespace()
+            .map(Keystroke::parse)
+            .collect(),
+    )
+}
+
+/// Whether the keystrokes start with the prefix ones, compared the way the keymap matches them.
+pub(crate) fn starts_with_keystrokes(keystrokes: &[Keystroke], prefix: &[Keystroke]) -> bool {
+    keystrokes.len() >= prefix.len()
+        && keystrokes.iter().zip(prefix).all(|(keystroke, prefix)| {
+            keystroke.key == prefix.key && keystroke.modifiers == prefix.modifiers
+        })
+}
+
+/// The action's binding, in the context of the focus handle given, that starts with the keystrokes typed,
+/// the last one if there are several, as the keymap prefers it.
+pub(crate) fn binding_starting_with(
+    action: &dyn Action,
+    keystrokes: &[Keystroke],
+    focus_handle: &FocusHandle,
+    cx: &mut WindowContext,
+) -> Option<KeyBinding> {
+    cx.bindings_for_action_in(action, focus_handle)
+        .into_iter()
+        .rev()
+        .find(|binding| starts_with_keystrokes(binding.keystrokes(), keystrokes))
+}
@@ -0,0 +1,111 @@
+//! A status bar item, briefly showing the last command confirmed in the palette.
+
+use std::time::Duration;
+
+use gpui::{Render, Subscription, Task, View, ViewContext, WeakView};
+use settings::Settings;
+use ui::{prelude::*, Button, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+use crate::{CommandExecuted, CommandPalette, CommandPaletteSettings};
+
+/// How long the command is shown at its full color.
+pub(crate) const SHOWN_FOR: Duration = Duration::from_secs(3);
+/// How long every step of the command fading out takes.
+pub(crate) const FADE_STEP: Duration = Duration::from_millis(250);
+
+/// Shows the command last confirmed in the workspace's palette, when `command_palette.show_last_command` is on.
+pub struct LastCommandIndicator {
+    workspace: WeakView<Workspace>,
+    pub(crate) last_command: Option<String>,
+    color: Color,
+    _fade_task: Task<()>,
+    _palette_subscription: Option<Subscription>,
+    _observe_palettes: Subscription,
+}
+
+impl LastCommandIndicator {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let indicator = cx.view().downgrade();
+        let _observe_palettes = cx.observe_new_views(move |palette: &mut CommandPalette, cx| {
+            let palette_view = cx.view().clone();
+            indicator
+                .update(cx, |indicator, cx| {
+                    if indicator.workspace == palette.workspace {
+                        indicator._palette_subscription =
+                            Some(cx.subscribe(&palette_view, Self::command_executed));
+                    }
+                })
+                .ok();
+        });
+        Self {
+            workspace: workspace.weak_handle(),
+            last_command: None,
+            color: Color::Default,
+            _fade_task: Task::ready(()),
+            _palette_subscription: None,
+            _observe_palettes,
+        }
+    }
+
+    fn command_executed(
+        &mut self,
+        _: View<CommandPalette>,
+        event: &CommandExecuted,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !CommandPaletteSettings::get_global(cx).show_last_command {
+            return;
+        }
+        self.last_command = Some(event.command_name.clone());
+        self.color = Color::Default;
+        self._fade_task = cx.spawn(|indicator, mut cx| async move {
+            cx.background_executor().timer(SHOWN_FOR).await;
+            for color in [Color::Muted, Color::Disabled] {
+                indicator
+                    .update(&mut cx, |indicator, cx| {
+                        indicator.color = color;
+                        cx.notify();
+                    })
+                    .ok();
+                cx.background_executor().timer(FADE_STEP).await;
+            }
+            indicator
+                .update(&mut cx, |indicator, cx| {
+                    indicator.last_command = None;
+                    cx.notify();
+                })
+                .ok();
+        });
+        cx.notify();
+    }
+
+    fn reopen_palette(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(command_name) = self.last_command.clone() else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                CommandPalette::toggle_with(workspace, Some(command_name), None, None, None, cx)
+            })
+            .ok();
+    }
+}
+
+impl Render for LastCommandIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.last_command.clone(), |el, command_name| {
+            el.debug_selector(|| "last-command".to_string()).child(
+                Button::new("last-command", command_name)
+                    .label_size(LabelSize::Small)
+                    .color(self.color)
+                    .tooltip(|cx| Tooltip::text("Open in the Command Palette", cx))
+                    .on_click(cx.listener(|indicator, _, cx| indicator.reopen_palette(cx))),
+            )
+        })
+    }
+}
+
+impl StatusItemView for LastCommandIndicator {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
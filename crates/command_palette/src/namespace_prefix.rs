@@ -0,0 +1,87 @@
+//! Queries starting with an action namespace, e.g. `editor: togcom`, listing only the namespace's commands
+//! and matching the rest of the query against the rest of their names.
+
+use collections::HashMap;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::BackgroundExecutor;
+
+use crate::{humanize_action_name, namespace_and_name, Command};
+
+/// The namespace the query starts with, followed by a colon, as in the actions' names (`go_to_line:`)
+/// or in the commands' ones (`go to line:`), and the rest of the query after it.
+/// Queries with an unknown namespace are matched as a whole, and so are the ones naming an action, e.g. `editor::Backspace`.
+pub(crate) fn split_namespace_prefix<'a, 'b>(
+    query: &'a str,
+    commands: &[&'b Command],
+) -> Option<(&'b str, &'a str)> {
+    let (prefix, rest) = query.trim_start().split_once(':')?;
+    let prefix = prefix.trim_end();
+    if prefix.is_empty() || rest.starts_with(':') {
+        return None;
+    }
+    let namespace = commands
+        .iter()
+        .map(|&command| namespace_and_name(command.action.name()).0)
+        .find(|namespace| {
+            !namespace.is_empty()
+                && (namespace.eq_ignore_ascii_case(prefix)
+                    || humanize_action_name(namespace).eq_ignore_ascii_case(prefix))
+        })?;
+    Some((namespace, rest.trim_start()))
+}
+
+/// Matches the query against the names of the namespace's commands without the namespace,
+/// keeping the order of the commands given for the equally scored matches, all of them for an empty query.
+/// The matches' positions are in the commands' whole names, as those get rendered. Up to `max_matches` get fuzzy matched.
+pub(crate) async fn match_in_namespace(
+    namespace: &str,
+    query: &str,
+    commands: &[&Command],
+    max_matches: usize,
+    executor: BackgroundExecutor,
+) -> Vec<StringMatch> {
+    let prefix = format!("{}: ", humanize_action_name(namespace));
+    let mut prefix_lens = HashMap::default();
+    let candidates = commands
+        .iter()
+        .enumerate()
+        .filter(|(_, command)| namespace_and_name(command.action.name()).0 == namespace)
+        .map(|(ix, command)| {
+            let name = command.name.strip_prefix(&prefix).unwrap_or(&command.name);
+            prefix_lens.insert(ix, command.name.len() - name.len());
+            StringMatchCandidate::new(ix, name.to_string())
+        })
+        .collect::<Vec<_>>();
+    let matches = if query.is_empty() {
+        candidates
+            .into_iter()
+            .map(|candidate| StringMatch {
+                candidate_id: candidate.id,
+                string: candidate.string,
+                positions: Vec::new(),
+                score: 0.0,
+            })
+            .collect()
+    } else {
+        fuzzy::match_strings(
+            &candidates,
+            query,
+            true,
+            max_matches,
+            &Default::default(),
+            executor,
+        )
+        .await
+    };
+    matches
+        .into_iter()
+        .map(|mut string_match| {
+            let prefix_len = prefix_lens[&string_match.candidate_id];
+            for position in &mut string_match.positions {
+                *position += prefix_len;
+            }
+            string_match.string = commands[string_match.candidate_id].name.clone();
+            string_match
+        })
+        .collect()
+}
@@ -0,0 +1,174 @@
+//! Formats of the state the palette persists in the key-value store between the restarts, e.g. the hit counts.
+//!
+//! Every format is versioned: a change of it adds a version, along with the migration from the previous one,
+//! for the state persisted by the older Zed versions to carry over. The JSON of every version is snapshotted
+//! under `test_data/persistence`, for the changes of a format without a new version to fail the tests.
+//! The helpers tagging and reading the versions are shared with the other crates, see [`db::versioned`].
+
+use std::{collections::BTreeMap, time::SystemTime};
+
+use anyhow::{anyhow, Result};
+use db::versioned::{from_value, parse_versioned, to_versioned_json};
+use serde::{Deserialize, Serialize};
+use workspace::WorkspaceId;
+
+/// The hit counts as persisted by the first releases of the palette: the global ones, with no version tag.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct HitCountsV0(pub BTreeMap<String, usize>);
+
+/// The hit counts, in every usage scope.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HitCountsV1 {
+    pub global: BTreeMap<String, usize>,
+    pub by_workspace: BTreeMap<WorkspaceId, BTreeMap<String, usize>>,
+}
+
+/// The hit counts, in every usage scope, along with the times the commands got used last, for the counts to decay.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HitCountsV2 {
+    pub global: BTreeMap<String, usize>,
+    pub by_workspace: BTreeMap<WorkspaceId, BTreeMap<String, usize>>,
+    pub last_used: BTreeMap<String, SystemTime>,
+    pub last_used_by_workspace: BTreeMap<WorkspaceId, BTreeMap<String, SystemTime>>,
+}
+
+/// The latest format of the hit counts, the one they get saved in.
+pub(crate) type PersistedHitCounts = HitCountsV2;
+const HIT_COUNTS_VERSION: &str = "2";
+
+/// The global counts got recorded in the global scope.
+fn migrate_hit_counts_v0(v0: HitCountsV0) -> HitCountsV1 {
+    HitCountsV1 {
+        global: v0.0,
+        by_workspace: BTreeMap::new(),
+    }
+}
+
+/// The counts with no time of the last use start decaying at the time given.
+fn migrate_hit_counts_v1(v1: HitCountsV1, now: SystemTime) -> HitCountsV2 {
+    HitCountsV2 {
+        last_used: v1.global.keys().map(|name| (name.clone(), now)).collect(),
+        last_used_by_workspace: v1
+            .by_workspace
+            .iter()
+            .map(|(&workspace_id, hit_counts)| {
+                let last_used = hit_counts.keys().map(|name| (name.clone(), now));
+                (workspace_id, last_used.collect())
+            })
+            .collect(),
+        global: v1.global,
+        by_workspace: v1.by_workspace,
+    }
+}
+
+/// Parses the hit counts persisted in any of their formats, migrated to the latest one as of the time given.
+pub(crate) fn parse_hit_counts(serialized: &str, now: SystemTime) -> Result<PersistedHitCounts> {
+    let (version, value) = parse_versioned(serialized)?;
+    let v1 = match version.as_deref() {
+        None => migrate_hit_counts_v0(from_value(value, "0")?),
+        Some("1") => from_value(value, "1")?,
+        Some("2") => return from_value(value, "2"),
+        Some(version) => return Err(anyhow!("unknown version {version:?}")),
+    };
+    Ok(migrate_hit_counts_v1(v1, now))
+}
+
+pub(crate) fn serialize_hit_counts(hit_counts: &PersistedHitCounts) -> Result<String> {
+    to_versioned_json(HIT_COUNTS_VERSION, hit_counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use db::versioned::assert_snapshot;
+
+    use super::*;
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_data/persistence")
+            .join(name)
+    }
+
+    fn time(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn sample_hit_counts_v1() -> HitCountsV1 {
+        HitCountsV1 {
+            global: BTreeMap::from_iter([
+                ("editor: backspace".to_string(), 3),
+                ("workspace: new file".to_string(), 1),
+            ]),
+            by_workspace: BTreeMap::from_iter([(
+                219,
+                BTreeMap::from_iter([("editor: format".to_string(), 2)]),
+            )]),
+        }
+    }
+
+    fn sample_hit_counts_v2() -> HitCountsV2 {
+        migrate_hit_counts_v1(sample_hit_counts_v1(), time(1_700_000_000))
+    }
+
+    #[test]
+    fn test_hit_counts_snapshots() {
+        assert_snapshot(
+            &snapshot_path("hit_counts_v1.json"),
+            "1",
+            &sample_hit_counts_v1(),
+        );
+        assert_snapshot(
+            &snapshot_path("hit_counts_v2.json"),
+            "2",
+            &sample_hit_counts_v2(),
+        );
+
+        let latest = sample_hit_counts_v2();
+        let serialized = serialize_hit_counts(&latest).unwrap();
+        assert_eq!(parse_hit_counts(&serialized, time(0)).unwrap(), latest);
+    }
+
+    #[test]
+    fn test_hit_counts_migrations() {
+        let now = time(1_700_000_000);
+        let v0 = std::fs::read_to_string(snapshot_path("hit_counts_v0.json")).unwrap();
+        assert_eq!(
+            parse_hit_counts(&v0, now).unwrap(),
+            HitCountsV2 {
+                global: BTreeMap::from_iter([
+                    ("editor: backspace".to_string(), 3),
+                    ("workspace: new file".to_string(), 1),
+                ]),
+                last_used: BTreeMap::from_iter([
+                    ("editor: backspace".to_string(), now),
+                    ("workspace: new file".to_string(), now),
+                ]),
+                ..HitCountsV2::default()
+            }
+        );
+        for name in ["hit_counts_v1.json", "hit_counts_v2.json"] {
+            let serialized = std::fs::read_to_string(snapshot_path(name)).unwrap();
+            assert_eq!(
+                parse_hit_counts(&serialized, now).unwrap(),
+                sample_hit_counts_v2(),
+                "{name} should migrate to the latest format"
+            );
+        }
+
+        for unreadable in [
+            "[1, 2]",
+            r#"{"version": "3", "global": {}}"#,
+            r#"{"version": 2}"#,
+            r#"{"version": "2", "global": []}"#,
+            "{\"version\": \"2\", \"glob",
+        ] {
+            assert!(
+                parse_hit_counts(unreadable, now).is_err(),
+                "{unreadable} should not be read"
+            );
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Commands pinned by the user, listed on top of the palette when the query is empty,
+//! and ranked above the slightly better matches otherwise.
+
+use db::kvp::KEY_VALUE_STORE;
+use fuzzy::StringMatch;
+use gpui::{AppContext, Global};
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+const PINNED_COMMANDS_KEY: &str = "command_palette_pinned_commands";
+/// What the scores of the pinned commands' matches get raised by.
+const PINNED_SCORE_BONUS: f64 = 0.1;
+
+/// The pinned commands, by their usage keys, in the order they got pinned in.
+/// Persisted between the restarts, as the recent commands. Shared between all workspaces.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct PinnedCommands {
+    pinned: Vec<String>,
+}
+
+impl Global for PinnedCommands {}
+
+impl PinnedCommands {
+    pub(crate) fn load() -> Self {
+        KEY_VALUE_STORE
+            .read_kvp(PINNED_COMMANDS_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|pinned| serde_json::from_str(&pinned).log_err())
+            .unwrap_or_default()
+    }
+
+    /// The usage keys of the pinned commands, including the ones no longer available.
+    pub(crate) fn pinned(&self) -> &[String] {
+        &self.pinned
+    }
+
+    pub(crate) fn is_pinned(&self, usage_key: &str) -> bool {
+        self.pinned.iter().any(|pinned| pinned == usage_key)
+    }
+
+    /// Pins the command, after the ones pinned before, or unpins it if it's pinned.
+    pub(crate) fn toggle(&mut self, usage_key: String, cx: &AppContext) {
+        if self.is_pinned(&usage_key) {
+            self.pinned.retain(|pinned| pinned != &usage_key);
+        } else {
+            self.pinned.push(usage_key);
+        }
+        self.serialize(cx);
+    }
+
+    fn serialize(&self, cx: &AppContext) {
+        let Some(serialized) = serde_json::to_string(self).log_err() else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(PINNED_COMMANDS_KEY.into(), serialized)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+/// Raises the scores of the pinned commands' matches, reordering the matches by the scores then.
+/// The order of the equally scored matches is kept.
+pub(crate) fn boost_pinned(matches: &mut [StringMatch], is_pinned: impl Fn(usize) -> bool) {
+    let mut boosted = false;
+    for string_match in matches.iter_mut() {
+        if is_pinned(string_match.candidate_id) {
+            string_match.score += PINNED_SCORE_BONUS;
+            boosted = true;
+        }
+    }
+    if boosted {
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+}
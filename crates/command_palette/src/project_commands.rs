@@ -0,0 +1,70 @@
+//! Commands the projects define in their settings, under `command_palette.project_commands`,
+//! running the projects' runnables from the palette.
+
+use std::path::Path;
+
+use gpui::{Action, AppContext};
+use project::Project;
+use settings::Settings;
+use zed_actions::{ProjectRunnable, ScheduleRunnable};
+
+use crate::{abbreviations::initials, Command, CommandPaletteSettings, ProjectCommand};
+
+/// What the usage of the project commands gets recorded by starts with, unlike any humanized action name.
+const USAGE_KEY_PREFIX: &str = "[project] ";
+
+/// The commands defined in the settings of the project's local worktrees, in the order of their names.
+/// A name defined in several worktrees runs the command of the first one.
+pub(crate) fn project_commands(project: &Project, cx: &AppContext) -> Vec<Command> {
+    let mut commands = Vec::<Command>::new();
+    for worktree in project.directory_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            continue;
+        }
+        let root = worktree.abs_path();
+        let settings =
+            CommandPaletteSettings::get(Some((worktree.id().to_usize(), Path::new(""))), cx);
+        let mut project_commands = settings.project_commands.iter().collect::<Vec<_>>();
+        project_commands.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, project_command) in project_commands {
+            if commands.iter().any(|command| &command.name == name) {
+                continue;
+            }
+            let (runnable, description) = match project_command {
+                ProjectCommand::Runnable(label) => (
+                    ProjectRunnable::Label(label.clone()),
+                    format!("Run the runnable '{label}'"),
+                ),
+                ProjectCommand::Command(command) => (
+                    ProjectRunnable::Command(command.clone()),
+                    format!("Run `{command}`"),
+                ),
+            };
+            commands.push(Command {
+                name: name.clone(),
+                action: ScheduleRunnable {
+                    runnable,
+                    worktree_root: Some(root.to_path_buf()),
+                }
+                .boxed_clone(),
+                initials: initials(name).into(),
+                description: Some(description.into()),
+                ranking_key: None,
+                project_root: Some(root.clone()),
+                available: true,
+            });
+        }
+    }
+    commands
+}
+
+/// What the usage of the project command with the name given, defined in the worktree given, gets recorded by.
+pub(crate) fn usage_key(project_root: &Path, name: &str) -> String {
+    format!("{USAGE_KEY_PREFIX}{}: {name}", project_root.display())
+}
+
+/// Whether the usage recorded by the key given is a project command's one.
+pub(crate) fn is_project_usage_key(usage_key: &str) -> bool {
+    usage_key.starts_with(USAGE_KEY_PREFIX)
+}
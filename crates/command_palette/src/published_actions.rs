@@ -0,0 +1,226 @@
+//! The names of the actions the palette has shown to users, who refer to them in their aliases, hidden lists,
+//! keybindings and hit-count history. Renaming a published action breaks all of those: the old name has to stay
+//! resolvable through a deprecation alias. The snapshot of the published names gets checked against the available
+//! actions at test time, to catch the renames missing their alias.
+
+use collections::{HashMap, HashSet};
+
+use crate::{humanize_action_name, namespace_and_name};
+
+/// Actions renamed after getting published, as `(old name, new name)`: the old names resolve to the new ones.
+pub(crate) const DEPRECATED_ACTION_ALIASES: &[(&str, &str)] = &[];
+
+/// Environment variable to set when running the palette tests, to regenerate the snapshot of the published names.
+pub(crate) const UPDATE_PUBLISHED_ACTIONS_VAR: &str = "UPDATE_PUBLISHED_ACTIONS";
+
+/// Whether the action given is only registered by the tests, e.g. `ranking_test::Wait`, so never published.
+pub(crate) fn is_test_action(action_name: &str) -> bool {
+    let (namespace, _) = namespace_and_name(action_name);
+    namespace == "test" || namespace.ends_with("_test")
+}
+
+/// The action the deprecated name given stands for now, if it's one.
+pub(crate) fn resolve_deprecated_alias(action_name: &str) -> Option<&'static str> {
+    DEPRECATED_ACTION_ALIASES
+        .iter()
+        .find(|(old_name, _)| *old_name == action_name)
+        .map(|(_, new_name)| *new_name)
+}
+
+/// The commands of the renamed actions, by the commands of their deprecated names, e.g. to carry their hit counts over.
+pub(crate) fn renamed_commands() -> HashMap<String, String> {
+    DEPRECATED_ACTION_ALIASES
+        .iter()
+        .map(|(old_name, new_name)| {
+            (
+                humanize_action_name(old_name),
+                humanize_action_name(new_name),
+            )
+        })
+        .collect()
+}
+
+/// The renamed actions' names by the commands of their deprecated names, for the palette to find the renamed commands
+/// by the names users know them by, e.g. "buffer search: deploy".
+pub(crate) fn previous_command_names() -> HashMap<String, String> {
+    DEPRECATED_ACTION_ALIASES
+        .iter()
+        .map(|(old_name, new_name)| (humanize_action_name(old_name), new_name.to_string()))
+        .collect()
+}
+
+/// The commands of the actions with acronyms in their names, by the names they had while the acronyms' letters
+/// got humanized one by one, e.g. "zed: open u r l", to carry their hit counts over.
+pub(crate) fn letter_spaced_commands<'a>(
+    action_names: impl IntoIterator<Item = &'a str>,
+) -> HashMap<String, String> {
+    action_names
+        .into_iter()
+        .filter_map(|action_name| {
+            let mut spaced_name = String::with_capacity(action_name.len() * 2);
+            let mut previous_char = None;
+            for char in action_name.chars() {
+                if char.is_uppercase() && previous_char.map_or(false, char::is_uppercase) {
+                    spaced_name.push('_');
+                }
+                spaced_name.push(char);
+                previous_char = Some(char);
+            }
+            let old_command_name = humanize_action_name(&spaced_name);
+            let command_name = humanize_action_name(action_name);
+            (old_command_name != command_name).then_some((old_command_name, command_name))
+        })
+        .collect()
+}
+
+/// The published names from the snapshot given, one per line, skipping the comments.
+pub(crate) fn parse_snapshot(snapshot: &str) -> Vec<&str> {
+    snapshot
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// The snapshot of the available action names given, sorted the way the palette lists them.
+pub(crate) fn generate_snapshot<'a>(action_names: impl IntoIterator<Item = &'a str>) -> String {
+    let mut action_names = action_names.into_iter().collect::<Vec<_>>();
+    action_names.sort_by(|a, b| namespace_and_name(a).cmp(&namespace_and_name(b)));
+    action_names.dedup();
+    let mut snapshot = format!(
+        "# Names of the actions registered in the command palette's tests, checked by `test_published_action_names`.\n\
+         # Generated with `{UPDATE_PUBLISHED_ACTIONS_VAR}=1 cargo test -p command_palette test_published_action_names`: do not edit by hand.\n"
+    );
+    for name in action_names {
+        snapshot.push_str(name);
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+/// Problems with the published names, given the ones available now and the deprecation aliases:
+/// the published names gone without an alias, telling the alias entry to add, and the aliases to no available action.
+pub(crate) fn check_published_names(
+    published: &[&str],
+    available: &HashSet<&str>,
+    aliases: &[(&str, &str)],
+) -> Vec<String> {
+    let published_set = published.iter().copied().collect::<HashSet<_>>();
+    let mut new_names = available
+        .iter()
+        .copied()
+        .filter(|name| !published_set.contains(name))
+        .collect::<Vec<_>>();
+    new_names.sort_by(|a, b| namespace_and_name(a).cmp(&namespace_and_name(b)));
+
+    let mut problems = Vec::new();
+    for &old_name in published {
+        if available.contains(old_name) {
+            continue;
+        }
+        if aliases.iter().any(|(alias, _)| *alias == old_name) {
+            continue;
+        }
+        let (namespace, name) = namespace_and_name(old_name);
+        let candidates = new_names
+            .iter()
+            .copied()
+            .filter(|new_name| {
+                let (new_namespace, new_name) = namespace_and_name(new_name);
+                new_namespace == namespace || new_name == name
+            })
+            .collect::<Vec<_>>();
+        let new_name = match candidates.as_slice() {
+            [new_name] => *new_name,
+            _ => "<new action name>",
+        };
+        let mut problem = format!(
+            "Published action `{old_name}` is not available anymore. If it got renamed, add \
+             `(\"{old_name}\", \"{new_name}\"),` to `DEPRECATED_ACTION_ALIASES` in \
+             crates/command_palette/src/published_actions.rs."
+        );
+        if candidates.len() > 1 {
+            problem.push_str(&format!(" Candidates: {}.", candidates.join(", ")));
+        }
+        problem.push_str(&format!(
+            " If it got removed for good, regenerate the snapshot with `{UPDATE_PUBLISHED_ACTIONS_VAR}=1`."
+        ));
+        problems.push(problem);
+    }
+    for (old_name, new_name) in aliases {
+        if !available.contains(new_name) {
+            problems.push(format!(
+                "The deprecated action `{old_name}` is aliased to `{new_name}`, which is not available."
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_spaced_commands() {
+        assert_eq!(
+            letter_spaced_commands(["zed::OpenURL", "editor::Backspace", "zed::ToggleUIFontSize"]),
+            HashMap::from_iter([
+                ("zed: open u r l".to_string(), "zed: open url".to_string()),
+                (
+                    "zed: toggle u i font size".to_string(),
+                    "zed: toggle ui font size".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_check_published_names() {
+        let published = parse_snapshot(&generate_snapshot([
+            "editor::Backspace",
+            "editor::SelectAll",
+            "workspace::NewFile",
+            "go_to_line::Toggle",
+        ]));
+        assert_eq!(
+            published,
+            [
+                "editor::Backspace",
+                "editor::SelectAll",
+                "go_to_line::Toggle",
+                "workspace::NewFile"
+            ]
+        );
+
+        let available = HashSet::from_iter([
+            "editor::Backspace",
+            "editor::SelectAllMatches",
+            "go_to_line::Toggle",
+            "workspace::NewFile",
+            "workspace::NewWindow",
+        ]);
+        let problems = check_published_names(&published, &available, &[]);
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].contains(r#"`("editor::SelectAll", "editor::SelectAllMatches"),`"#),
+            "The problem should tell the alias entry to add: {}",
+            problems[0]
+        );
+
+        assert!(check_published_names(
+            &published,
+            &available,
+            &[("editor::SelectAll", "editor::SelectAllMatches")]
+        )
+        .is_empty());
+        assert_eq!(
+            check_published_names(
+                &published,
+                &available,
+                &[("editor::SelectAll", "editor::SelectEverything")]
+            ),
+            ["The deprecated action `editor::SelectAll` is aliased to `editor::SelectEverything`, which is not available."]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+use gpui::Global;
+use release_channel::parse_zed_link;
+
+/// How many of the queries, the commands got confirmed with, the palette goes back through.
+pub(crate) const MAX_QUERY_HISTORY: usize = 20;
+
+/// Queries the commands got confirmed with in the palette during the session, to refine them when opening it again.
+#[derive(Default)]
+pub(crate) struct QueryHistory {
+    /// Most recent first.
+    queries: VecDeque<String>,
+}
+
+impl Global for QueryHistory {}
+
+impl QueryHistory {
+    /// Records the query, moving it to the top if it's there already. The empty queries are not recorded,
+    /// nor are the `zed://` links, which may carry tokens, e.g. of the channel invites.
+    pub(crate) fn record(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() || parse_zed_link(query).is_some() {
+            return;
+        }
+        self.queries.retain(|recorded| recorded != query);
+        self.queries.push_front(query.to_string());
+        self.queries.truncate(MAX_QUERY_HISTORY);
+    }
+
+    /// The query recorded the number of queries given before the last one.
+    pub(crate) fn get(&self, ix: usize) -> Option<&str> {
+        self.queries.get(ix).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_history() {
+        let mut history = QueryHistory::default();
+        for ix in 0..MAX_QUERY_HISTORY + 2 {
+            history.record(&format!("query {ix}"));
+        }
+        history.record("query 5 ");
+        history.record("");
+        history.record("zed://channel/1?token=secret");
+
+        assert_eq!(history.get(0), Some("query 5"));
+        assert_eq!(
+            history.get(1),
+            Some(&*format!("query {}", MAX_QUERY_HISTORY + 1))
+        );
+        assert_eq!(history.get(MAX_QUERY_HISTORY - 1), Some("query 3"));
+        assert_eq!(history.get(MAX_QUERY_HISTORY), None);
+        assert_eq!(
+            history
+                .queries
+                .iter()
+                .filter(|query| *query == "query 5")
+                .count(),
+            1,
+            "Reusing a query should move it to the top, not record it twice"
+        );
+    }
+}
@@ -0,0 +1,735 @@
+//! The palette's ranking pipeline: the fuzzy matches of every query alternative, reordered by the scorers of a profile.
+//!
+//! Zed Dev can rank with the profile named by `command_palette.ranking_profile`, and compare the rankings
+//! of all profiles for the current query with `command_palette::DumpRankingComparison`.
+
+use std::{cmp::Reverse, time::SystemTime};
+
+use collections::{HashMap, HashSet};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{AppContext, BackgroundExecutor};
+use release_channel::ReleaseChannel;
+use settings::Settings;
+use ui::SharedString;
+use workspace::WorkspaceId;
+
+use crate::{
+    abbreviations::rank_abbreviations,
+    alternatives::merge_alternative_matches,
+    hit_counts::boost_by_usage,
+    humanize_action_name, humanized_positions,
+    intercepted_commands::is_intercepted_usage_key,
+    pinned_commands::PinnedCommands,
+    published_actions::previous_command_names,
+    recent_commands::RecentCommands,
+    synonyms::rank_synonyms,
+    terms::{match_terms, merge_term_matches},
+    Command, CommandPaletteSettings, HitCounts,
+};
+
+/// What the scores of the commands' descriptions matched get multiplied by, for those to go below the names matched equally well.
+const DESCRIPTION_MATCH_WEIGHT: f64 = 0.5;
+
+/// How many of the top matches of every profile the comparison lists.
+pub(crate) const COMPARED_MATCHES: usize = 20;
+
+/// What the scorers rank the matches of a query alternative by.
+pub(crate) struct RankingInput<'a> {
+    pub query: &'a str,
+    /// The commands, ordered by their usage, with the matches' candidate ids indexing into them.
+    pub commands: &'a [&'a Command],
+    /// Hit counts of the commands in the usage scope, by their usage keys.
+    pub hit_counts: &'a HashMap<String, usize>,
+    /// Whether `command_palette.abbreviations` is on.
+    pub abbreviations: bool,
+    pub user_synonyms: &'a HashMap<String, String>,
+}
+
+/// A step of the ranking, reordering the matches of a query alternative, possibly adding the commands matched otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Scorer {
+    /// Raises the scores of the frequently used commands, by a bounded bonus, above the unused ones matched about as well.
+    Usage,
+    /// Puts the commands abbreviated by a short query above the weak fuzzy matches, unless the abbreviations are off.
+    Abbreviations,
+    /// Puts the commands named by the query in the other editors' or the user's terms first.
+    Synonyms,
+}
+
+impl Scorer {
+    /// Ranks the matches, recording the synonyms matched for the commands by their candidate ids.
+    pub(crate) fn score(
+        self,
+        input: &RankingInput,
+        matches: Vec<StringMatch>,
+        matched_synonyms: &mut HashMap<usize, SharedString>,
+    ) -> Vec<StringMatch> {
+        match self {
+            Self::Usage => boost_by_usage(matches, input.commands, input.hit_counts),
+            Self::Abbreviations if input.abbreviations => {
+                rank_abbreviations(input.query, input.commands, matches)
+            }
+            Self::Abbreviations => matches,
+            Self::Synonyms => {
+                let (matches, synonyms) =
+                    rank_synonyms(input.query, input.commands, input.user_synonyms, matches);
+                matched_synonyms.extend(synonyms);
+                matches
+            }
+        }
+    }
+}
+
+/// A named list of the scorers to rank the fuzzy matches with, applied in order.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RankingProfile {
+    pub name: &'static str,
+    pub scorers: &'static [Scorer],
+}
+
+/// The profiles to choose from, the default one first.
+pub(crate) const RANKING_PROFILES: &[RankingProfile] = &[
+    RankingProfile {
+        name: "default",
+        scorers: &[Scorer::Usage, Scorer::Abbreviations, Scorer::Synonyms],
+    },
+    RankingProfile {
+        name: "fuzzy_only",
+        scorers: &[],
+    },
+    RankingProfile {
+        name: "no_abbreviations",
+        scorers: &[Scorer::Usage, Scorer::Synonyms],
+    },
+    RankingProfile {
+        name: "no_synonyms",
+        scorers: &[Scorer::Usage, Scorer::Abbreviations],
+    },
+    RankingProfile {
+        name: "no_usage",
+        scorers: &[Scorer::Abbreviations, Scorer::Synonyms],
+    },
+];
+
+impl RankingProfile {
+    /// The profile the palette ranks with: the one named by `command_palette.ranking_profile` in Zed Dev,
+    /// the default one otherwise, or if there's no profile with that name.
+    pub(crate) fn active(cx: &AppContext) -> &'static Self {
+        let default = &RANKING_PROFILES[0];
+        if ReleaseChannel::try_global(cx) != Some(ReleaseChannel::Dev) {
+            return default;
+        }
+        CommandPaletteSettings::get_global(cx)
+            .ranking_profile
+            .as_deref()
+            .and_then(|name| RANKING_PROFILES.iter().find(|profile| profile.name == name))
+            .unwrap_or(default)
+    }
+
+    /// Ranks the fuzzy matches of the query alternative with the profile's scorers.
+    pub(crate) fn rank(
+        &self,
+        input: &RankingInput,
+        fuzzy_matches: Vec<StringMatch>,
+        matched_synonyms: &mut HashMap<usize, SharedString>,
+    ) -> Vec<StringMatch> {
+        self.scorers.iter().fold(fuzzy_matches, |matches, scorer| {
+            scorer.score(input, matches, matched_synonyms)
+        })
+    }
+}
+
+/// Matches the commands, by their names, their actions' names and their descriptions, against every alternative of the query,
+/// ranking them with the profile given, and merges the matches. Returns them along with the synonyms and the aliases matched,
+/// by the candidate ids. Every alternative fuzzy matches up to `max_matches` commands by each of those.
+pub(crate) async fn match_alternatives(
+    alternatives: &[String],
+    candidates: &[StringMatchCandidate],
+    action_name_candidates: &[StringMatchCandidate],
+    description_candidates: &[StringMatchCandidate],
+    alias_candidates: &[StringMatchCandidate],
+    commands: &[&Command],
+    snapshot: &RankingSnapshot,
+    profile: &RankingProfile,
+    max_matches: usize,
+    executor: BackgroundExecutor,
+) -> (
+    Vec<StringMatch>,
+    HashMap<usize, SharedString>,
+    HashMap<usize, SharedString>,
+) {
+    let mut alternative_matches = Vec::with_capacity(alternatives.len());
+    let mut matched_synonyms = HashMap::default();
+    let mut matched_aliases = HashMap::default();
+    for alternative in alternatives {
+        let matches = if alternative.is_empty() {
+            candidates
+                .iter()
+                .map(|candidate| StringMatch {
+                    candidate_id: candidate.id,
+                    string: candidate.string.clone(),
+                    positions: Vec::new(),
+                    score: 0.0,
+                })
+                .collect()
+        } else {
+            let fuzzy_matches = fuzzy::match_strings(
+                candidates,
+                alternative,
+                true,
+                max_matches,
+                &Default::default(),
+                executor.clone(),
+            )
+            .await;
+            let action_name_matches = fuzzy::match_strings(
+                action_name_candidates,
+                alternative,
+                true,
+                max_matches,
+                &Default::default(),
+                executor.clone(),
+            )
+            .await;
+            let description_matches = fuzzy::match_strings(
+                description_candidates,
+                alternative,
+                true,
+                max_matches,
+                &Default::default(),
+                executor.clone(),
+            )
+            .await;
+            let alias_matches = fuzzy::match_strings(
+                alias_candidates,
+                alternative,
+                true,
+                max_matches,
+                &Default::default(),
+                executor.clone(),
+            )
+            .await;
+            let term_matches =
+                match_terms(candidates, alternative, max_matches, executor.clone()).await;
+            let fuzzy_matches = merge_term_matches(fuzzy_matches, term_matches);
+            let fuzzy_matches =
+                merge_action_name_matches(fuzzy_matches, action_name_matches, commands);
+            let fuzzy_matches =
+                merge_alias_matches(fuzzy_matches, alias_matches, commands, &mut matched_aliases);
+            let fuzzy_matches =
+                merge_description_matches(fuzzy_matches, description_matches, commands);
+            let input = RankingInput {
+                query: alternative,
+                commands,
+                hit_counts: &snapshot.hit_counts,
+                abbreviations: snapshot.abbreviations,
+                user_synonyms: &snapshot.user_synonyms,
+            };
+            profile.rank(&input, fuzzy_matches, &mut matched_synonyms)
+        };
+        alternative_matches.push(matches);
+    }
+    (
+        merge_alternative_matches(alternative_matches),
+        matched_synonyms,
+        matched_aliases,
+    )
+}
+
+/// Merges the fuzzy matches of the actions' names into the ones of the commands' names, keeping the better match of each command.
+/// The matches of the actions' names are highlighted in the commands' names, as those get rendered, when they are the humanized action names.
+fn merge_action_name_matches(
+    mut matches: Vec<StringMatch>,
+    action_name_matches: Vec<StringMatch>,
+    commands: &[&Command],
+) -> Vec<StringMatch> {
+    if action_name_matches.is_empty() {
+        return matches;
+    }
+    let mut match_ixs = matches
+        .iter()
+        .enumerate()
+        .map(|(ix, string_match)| (string_match.candidate_id, ix))
+        .collect::<HashMap<_, _>>();
+    for action_name_match in action_name_matches {
+        let match_ix = match_ixs.get(&action_name_match.candidate_id).copied();
+        if match_ix.map_or(false, |ix| matches[ix].score >= action_name_match.score) {
+            continue;
+        }
+        let command = commands[action_name_match.candidate_id];
+        let action_name = command.action.name();
+        let positions = if humanize_action_name(action_name) == command.name {
+            humanized_positions(action_name, &action_name_match.positions)
+        } else {
+            Vec::new()
+        };
+        let string_match = StringMatch {
+            candidate_id: action_name_match.candidate_id,
+            score: action_name_match.score,
+            positions,
+            string: command.name.clone(),
+        };
+        match match_ix {
+            Some(ix) => matches[ix] = string_match,
+            None => {
+                match_ixs.insert(string_match.candidate_id, matches.len());
+                matches.push(string_match);
+            }
+        }
+    }
+    // The same order as the fuzzy matches come in.
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+/// Merges the fuzzy matches of the aliases into the ones of the commands' names, keeping the better match of each command,
+/// with nothing highlighted in the names of the commands matched better by an alias. Records those aliases by the candidate ids.
+fn merge_alias_matches(
+    mut matches: Vec<StringMatch>,
+    alias_matches: Vec<StringMatch>,
+    commands: &[&Command],
+    matched_aliases: &mut HashMap<usize, SharedString>,
+) -> Vec<StringMatch> {
+    if alias_matches.is_empty() {
+        return matches;
+    }
+    let mut match_ixs = matches
+        .iter()
+        .enumerate()
+        .map(|(ix, string_match)| (string_match.candidate_id, ix))
+        .collect::<HashMap<_, _>>();
+    for alias_match in alias_matches {
+        let match_ix = match_ixs.get(&alias_match.candidate_id).copied();
+        if match_ix.map_or(false, |ix| matches[ix].score >= alias_match.score) {
+            continue;
+        }
+        matched_aliases.insert(alias_match.candidate_id, alias_match.string.into());
+        let string_match = StringMatch {
+            candidate_id: alias_match.candidate_id,
+            score: alias_match.score,
+            positions: Vec::new(),
+            string: commands[alias_match.candidate_id].name.clone(),
+        };
+        match match_ix {
+            Some(ix) => matches[ix] = string_match,
+            None => {
+                match_ixs.insert(string_match.candidate_id, matches.len());
+                matches.push(string_match);
+            }
+        }
+    }
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+/// Adds the commands matched only by their descriptions to the fuzzy matches, with lowered scores and nothing highlighted in their names.
+fn merge_description_matches(
+    mut matches: Vec<StringMatch>,
+    description_matches: Vec<StringMatch>,
+    commands: &[&Command],
+) -> Vec<StringMatch> {
+    if description_matches.is_empty() {
+        return matches;
+    }
+    let matched_ids = matches
+        .iter()
+        .map(|string_match| string_match.candidate_id)
+        .collect::<HashSet<_>>();
+    matches.extend(
+        description_matches
+            .into_iter()
+            .filter(|description_match| !matched_ids.contains(&description_match.candidate_id))
+            .map(|description_match| StringMatch {
+                candidate_id: description_match.candidate_id,
+                score: description_match.score * DESCRIPTION_MATCH_WEIGHT,
+                positions: Vec::new(),
+                string: commands[description_match.candidate_id].name.clone(),
+            }),
+    );
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+/// What the palette ranks its commands by besides the query, read from the globals once per palette session
+/// and again only when they change: the matches of every keystroke get ranked off the main thread, with no global reads.
+#[derive(Debug)]
+pub(crate) struct RankingSnapshot {
+    /// Names of the palette's commands, in the order of their candidate ids.
+    pub command_names: Vec<String>,
+    /// Hit counts of the palette's commands in the usage scope, except for the never promoted ones.
+    pub hit_counts: HashMap<String, usize>,
+    /// Hit counts of the intercepted commands with ranking keys in the usage scope, by their usage keys.
+    pub intercepted_hit_counts: HashMap<String, usize>,
+    /// The recent commands of the usage scope, most recent first.
+    pub recent: Vec<String>,
+    /// The pinned commands, in the order they got pinned in.
+    pub pinned: Vec<String>,
+    /// Whether `command_palette.abbreviations` is on.
+    pub abbreviations: bool,
+    /// Whether `command_palette.match_descriptions` is on.
+    pub match_descriptions: bool,
+    pub user_synonyms: HashMap<String, String>,
+    /// `command_palette.aliases`, the actions' or the commands' names by the aliases.
+    pub aliases: HashMap<String, String>,
+    /// The renamed actions' names by the commands of their deprecated names, matched as the aliases are.
+    pub previous_names: HashMap<String, String>,
+    pub profile: &'static RankingProfile,
+    /// Whether Zed Dev is running, opening the `zed://` links typed in.
+    pub dev_channel: bool,
+}
+
+impl RankingSnapshot {
+    /// Reads the ranking inputs for the commands given, copying only the hit counts of those.
+    pub(crate) fn read(commands: &[Command], workspace_id: WorkspaceId, cx: &AppContext) -> Self {
+        let settings = CommandPaletteSettings::get_global(cx);
+        let usage_scope = settings.usage_scope;
+        let recent_commands = cx.global::<RecentCommands>();
+        let hit_counts =
+            cx.global::<HitCounts>()
+                .for_scope(usage_scope, workspace_id, SystemTime::now());
+        Self {
+            command_names: commands.iter().map(Command::usage_key).collect(),
+            hit_counts: commands
+                .iter()
+                .map(Command::usage_key)
+                .filter(|usage_key| !recent_commands.is_never_promoted(usage_key))
+                .filter_map(|usage_key| {
+                    let hit_count = *hit_counts.as_ref()?.get(&usage_key)?;
+                    Some((usage_key, hit_count))
+                })
+                .collect(),
+            intercepted_hit_counts: hit_counts
+                .into_iter()
+                .flatten()
+                .filter(|(usage_key, _)| is_intercepted_usage_key(usage_key))
+                .collect(),
+            recent: recent_commands.recent(usage_scope, workspace_id).to_vec(),
+            pinned: cx.global::<PinnedCommands>().pinned().to_vec(),
+            abbreviations: settings.abbreviations,
+            match_descriptions: settings.match_descriptions,
+            user_synonyms: settings.synonyms.clone(),
+            aliases: settings.aliases.clone(),
+            previous_names: previous_command_names(),
+            profile: RankingProfile::active(cx),
+            dev_channel: ReleaseChannel::try_global(cx) == Some(ReleaseChannel::Dev),
+        }
+    }
+
+    /// Indices of all the commands, ranked by their usage: the pinned ones first, then the recent ones,
+    /// then the rest by their hit counts. Returns them along with the numbers of the pinned and the recent commands.
+    pub(crate) fn usage_order(&self) -> (Vec<usize>, usize, usize) {
+        let names = &self.command_names;
+        let mut order = (0..names.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&ix| {
+            let name = &names[ix];
+            let pinned_ix = self
+                .pinned
+                .iter()
+                .position(|pinned_name| pinned_name == name);
+            let recent_ix = self
+                .recent
+                .iter()
+                .position(|recent_name| recent_name == name);
+            let hit_count = self.hit_counts.get(name);
+            (
+                pinned_ix.is_none(),
+                pinned_ix,
+                recent_ix.is_none(),
+                recent_ix,
+                Reverse(hit_count),
+                name,
+            )
+        });
+        let pinned_count = order
+            .iter()
+            .take_while(|&&ix| self.pinned.contains(&names[ix]))
+            .count();
+        let recent_count = order[pinned_count..]
+            .iter()
+            .take_while(|&&ix| self.recent.contains(&names[ix]))
+            .count();
+        (order, pinned_count, recent_count)
+    }
+}
+
+/// A Markdown table with the top command names ranked by every profile, side by side.
+pub(crate) fn comparison_table(query: &str, rankings: &[(&str, Vec<String>)]) -> String {
+    let mut table = format!("# Ranking comparison for {query:?}\n\n| # |");
+    for (profile_name, _) in rankings {
+        table.push_str(&format!(" {profile_name} |"));
+    }
+    table.push_str("\n|---|");
+    table.push_str(&"---|".repeat(rankings.len()));
+    table.push('\n');
+    let rows = rankings
+        .iter()
+        .map(|(_, names)| names.len())
+        .max()
+        .unwrap_or(0);
+    for row in 0..rows {
+        table.push_str(&format!("| {} |", row + 1));
+        for (_, names) in rankings {
+            let name = names.get(row).map_or("", String::as_str);
+            table.push_str(&format!(" {name} |"));
+        }
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{actions, Action, NoAction};
+
+    use crate::abbreviations::initials;
+
+    use super::*;
+
+    actions!(ranking_test, [Wait]);
+
+    fn command(name: &str) -> Command {
+        Command {
+            name: name.to_string(),
+            action: NoAction.boxed_clone(),
+            initials: initials(name).into(),
+            description: None,
+            ranking_key: None,
+            project_root: None,
+            available: true,
+        }
+    }
+
+    fn string_match(candidate_id: usize, commands: &[&Command]) -> StringMatch {
+        StringMatch {
+            candidate_id,
+            score: 0.01,
+            positions: vec![0],
+            string: commands[candidate_id].name.clone(),
+        }
+    }
+
+    fn names(matches: &[StringMatch]) -> Vec<&str> {
+        matches
+            .iter()
+            .map(|string_match| string_match.string.as_str())
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_non_latin_command_names(cx: &mut gpui::TestAppContext) {
+        let commands = [
+            "проект: собрать",
+            "テスト: 実行する",
+            "İstanbul: haritayı aç",
+        ]
+        .map(command);
+        let commands = commands.iter().collect::<Vec<_>>();
+        let candidates = crate::command_candidates(&commands);
+        for (query, name, highlighted) in [
+            ("собрать", "проект: собрать", "собрать"),
+            ("СОБР", "проект: собрать", "собр"),
+            ("実行", "テスト: 実行する", "実行"),
+            ("istanbul", "İstanbul: haritayı aç", "İstanbul"),
+            ("aç", "İstanbul: haritayı aç", "aç"),
+        ] {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                query,
+                true,
+                100,
+                &Default::default(),
+                cx.executor(),
+            )
+            .await;
+            assert_eq!(names(&matches[..1]), [name], "{query:?} should match");
+            let string_match = &matches[0];
+            assert_eq!(
+                string_match
+                    .ranges()
+                    .map(|range| &string_match.string[range])
+                    .collect::<String>(),
+                highlighted,
+                "The characters of {query:?} should be highlighted"
+            );
+        }
+    }
+
+    #[test]
+    fn test_abbreviations_scorer() {
+        let commands = ["editor: format", "editor: go to definition"].map(command);
+        let commands = commands.iter().collect::<Vec<_>>();
+        let user_synonyms = HashMap::default();
+        let mut input = RankingInput {
+            query: "gtd",
+            commands: &commands,
+            hit_counts: &HashMap::default(),
+            abbreviations: true,
+            user_synonyms: &user_synonyms,
+        };
+        let weak_fuzzy_matches = vec![string_match(0, &commands)];
+        let mut matched_synonyms = HashMap::default();
+
+        let ranked =
+            Scorer::Abbreviations.score(&input, weak_fuzzy_matches.clone(), &mut matched_synonyms);
+        assert_eq!(
+            names(&ranked),
+            ["editor: go to definition", "editor: format"]
+        );
+        input.abbreviations = false;
+        let ranked = Scorer::Abbreviations.score(&input, weak_fuzzy_matches, &mut matched_synonyms);
+        assert_eq!(
+            names(&ranked),
+            ["editor: format"],
+            "The scorer should do nothing with the abbreviations off"
+        );
+        assert!(matched_synonyms.is_empty());
+    }
+
+    #[test]
+    fn test_synonyms_scorer() {
+        let wait = Command {
+            action: Wait.boxed_clone(),
+            ..command("ranking test: wait")
+        };
+        let commands = [command("editor: format"), wait];
+        let commands = commands.iter().collect::<Vec<_>>();
+        let user_synonyms = HashMap::from_iter([("idle".to_string(), Wait.name().to_string())]);
+        let input = RankingInput {
+            query: "Idle",
+            commands: &commands,
+            hit_counts: &HashMap::default(),
+            abbreviations: true,
+            user_synonyms: &user_synonyms,
+        };
+        let mut matched_synonyms = HashMap::default();
+
+        let ranked = Scorer::Synonyms.score(
+            &input,
+            vec![string_match(0, &commands)],
+            &mut matched_synonyms,
+        );
+        assert_eq!(names(&ranked), ["ranking test: wait", "editor: format"]);
+        assert_eq!(
+            matched_synonyms,
+            HashMap::from_iter([(1, SharedString::from("idle"))])
+        );
+    }
+
+    #[test]
+    fn test_usage_scorer() {
+        let commands = ["editor: format", "editor: fold"].map(command);
+        let commands = commands.iter().collect::<Vec<_>>();
+        let scored = |scores: [f64; 2]| {
+            scores
+                .into_iter()
+                .enumerate()
+                .map(|(candidate_id, score)| StringMatch {
+                    score,
+                    ..string_match(candidate_id, &commands)
+                })
+                .collect::<Vec<_>>()
+        };
+        let hit_counts = HashMap::from_iter([("editor: format".to_string(), 200)]);
+        let input = RankingInput {
+            query: "fo",
+            commands: &commands,
+            hit_counts: &hit_counts,
+            abbreviations: true,
+            user_synonyms: &HashMap::default(),
+        };
+        let mut matched_synonyms = HashMap::default();
+
+        let ranked = Scorer::Usage.score(&input, scored([0.5, 0.5]), &mut matched_synonyms);
+        assert_eq!(
+            names(&ranked),
+            ["editor: format", "editor: fold"],
+            "A frequently used command should go above an equally scored unused one"
+        );
+        let ranked = Scorer::Usage.score(&input, scored([0.5, 0.52]), &mut matched_synonyms);
+        assert_eq!(names(&ranked), ["editor: format", "editor: fold"]);
+        let ranked = Scorer::Usage.score(&input, scored([0.3, 0.9]), &mut matched_synonyms);
+        assert_eq!(
+            names(&ranked),
+            ["editor: fold", "editor: format"],
+            "A much better match should win over a popular but barely matching command"
+        );
+
+        let hit_counts = HashMap::from_iter([("editor: format".to_string(), 100_000)]);
+        let input = RankingInput {
+            hit_counts: &hit_counts,
+            ..input
+        };
+        let ranked = Scorer::Usage.score(&input, scored([0.5, 0.6]), &mut matched_synonyms);
+        assert_eq!(
+            names(&ranked),
+            ["editor: fold", "editor: format"],
+            "The boost should be capped, however many hits the command has"
+        );
+    }
+
+    #[test]
+    fn test_ranking_profiles() {
+        for (ix, profile) in RANKING_PROFILES.iter().enumerate() {
+            assert!(
+                RANKING_PROFILES[ix + 1..]
+                    .iter()
+                    .all(|other| other.name != profile.name),
+                "Profile names should be unique"
+            );
+        }
+
+        let commands = ["editor: format", "editor: go to definition"].map(command);
+        let commands = commands.iter().collect::<Vec<_>>();
+        let user_synonyms = HashMap::default();
+        let input = RankingInput {
+            query: "gtd",
+            commands: &commands,
+            hit_counts: &HashMap::default(),
+            abbreviations: true,
+            user_synonyms: &user_synonyms,
+        };
+        let ranked_by = |name: &str| {
+            let profile = RANKING_PROFILES
+                .iter()
+                .find(|profile| profile.name == name)
+                .unwrap();
+            let ranked = profile.rank(
+                &input,
+                vec![string_match(0, &commands)],
+                &mut HashMap::default(),
+            );
+            names(&ranked)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked_by("default"),
+            ["editor: go to definition", "editor: format"]
+        );
+        assert_eq!(ranked_by("fuzzy_only"), ["editor: format"]);
+        assert_eq!(ranked_by("no_abbreviations"), ["editor: format"]);
+    }
+
+    #[test]
+    fn test_comparison_table() {
+        let table = comparison_table(
+            "fmt",
+            &[
+                ("default", vec!["editor: format".to_string()]),
+                (
+                    "fuzzy_only",
+                    vec!["editor: format".to_string(), "editor: fold".to_string()],
+                ),
+            ],
+        );
+        assert_eq!(
+            table,
+            "# Ranking comparison for \"fmt\"\n\n\
+             | # | default | fuzzy_only |\n\
+             |---|---|---|\n\
+             | 1 | editor: format | editor: format |\n\
+             | 2 |  | editor: fold |\n"
+        );
+    }
+}
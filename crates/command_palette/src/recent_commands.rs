@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{AppContext, Global};
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+use workspace::WorkspaceId;
+
+use crate::UsageScope;
+
+const RECENT_COMMANDS_KEY: &str = "command_palette_recent_commands";
+/// How many of the recently confirmed commands are shown at the top of the palette.
+pub(crate) const MAX_RECENT_COMMANDS: usize = 10;
+
+/// Commands recently confirmed in the palette, shown on top of it when the query is empty.
+/// Persisted between the restarts, as the hit counts.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct RecentCommands {
+    /// Most recent first.
+    global: Vec<String>,
+    by_workspace: HashMap<WorkspaceId, Vec<String>>,
+    /// Commands that are neither shown in the recents, nor ranked higher for their hit counts.
+    never_promoted: BTreeSet<String>,
+}
+
+impl Global for RecentCommands {}
+
+impl RecentCommands {
+    pub(crate) fn load() -> Self {
+        KEY_VALUE_STORE
+            .read_kvp(RECENT_COMMANDS_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|recent| serde_json::from_str(&recent).log_err())
+            .unwrap_or_default()
+    }
+
+    /// The recent commands of the scope, the global ones for a workspace with none, if the scope says so.
+    pub(crate) fn recent(&self, scope: UsageScope, workspace_id: WorkspaceId) -> &[String] {
+        let workspace_recent = self
+            .by_workspace
+            .get(&workspace_id)
+            .map_or(&[][..], Vec::as_slice);
+        match scope {
+            UsageScope::Global => &self.global,
+            UsageScope::Workspace => workspace_recent,
+            UsageScope::WorkspaceFirst if workspace_recent.is_empty() => &self.global,
+            UsageScope::WorkspaceFirst => workspace_recent,
+            UsageScope::Off => &[],
+        }
+    }
+
+    pub(crate) fn is_never_promoted(&self, command_name: &str) -> bool {
+        self.never_promoted.contains(command_name)
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        scope: UsageScope,
+        workspace_id: WorkspaceId,
+        command_name: String,
+        cx: &AppContext,
+    ) {
+        if self.is_never_promoted(&command_name) {
+            return;
+        }
+        let recents = self.recents_mut(scope, workspace_id);
+        if recents.is_empty() {
+            return;
+        }
+        for recent in recents {
+            recent.retain(|recent_name| recent_name != &command_name);
+            recent.insert(0, command_name.clone());
+            recent.truncate(MAX_RECENT_COMMANDS);
+        }
+        self.serialize(cx);
+    }
+
+    /// Removes the command from the recents, keeping its hit count.
+    pub(crate) fn forget(
+        &mut self,
+        scope: UsageScope,
+        workspace_id: WorkspaceId,
+        command_name: &str,
+        cx: &AppContext,
+    ) {
+        let recents = self.recents_mut(scope, workspace_id);
+        if recents.is_empty() {
+            return;
+        }
+        for recent in recents {
+            recent.retain(|recent_name| recent_name != command_name);
+        }
+        self.serialize(cx);
+    }
+
+    pub(crate) fn never_promote(&mut self, command_name: String, cx: &AppContext) {
+        self.global
+            .retain(|recent_name| recent_name != &command_name);
+        for recent in self.by_workspace.values_mut() {
+            recent.retain(|recent_name| recent_name != &command_name);
+        }
+        self.never_promoted.insert(command_name);
+        self.serialize(cx);
+    }
+
+    /// The lists of the recent commands the scope records the commands in.
+    fn recents_mut(
+        &mut self,
+        scope: UsageScope,
+        workspace_id: WorkspaceId,
+    ) -> Vec<&mut Vec<String>> {
+        match scope {
+            UsageScope::Global => vec![&mut self.global],
+            UsageScope::Workspace => vec![self.by_workspace.entry(workspace_id).or_default()],
+            UsageScope::WorkspaceFirst => vec![
+                self.by_workspace.entry(workspace_id).or_default(),
+                &mut self.global,
+            ],
+            UsageScope::Off => Vec::new(),
+        }
+    }
+
+    fn serialize(&self, cx: &AppContext) {
+        let Some(serialized) = serde_json::to_string(self).log_err() else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(RECENT_COMMANDS_KEY.into(), serialized)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    #[gpui::test]
+    fn test_recent_commands_limit(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut recent_commands = RecentCommands::default();
+            for ix in 0..MAX_RECENT_COMMANDS + 2 {
+                let command_name = format!("test: command {ix}");
+                recent_commands.record(UsageScope::Global, 0, command_name, cx);
+            }
+            recent_commands.record(UsageScope::Global, 0, "test: command 5".to_string(), cx);
+
+            let recent = recent_commands.recent(UsageScope::Global, 0);
+            assert_eq!(recent.len(), MAX_RECENT_COMMANDS);
+            assert_eq!(recent[0], "test: command 5");
+            assert_eq!(recent[1], "test: command 11");
+            assert_eq!(
+                recent
+                    .iter()
+                    .filter(|name| *name == "test: command 5")
+                    .count(),
+                1,
+                "Rerunning a recent command should move it to the top, not list it twice"
+            );
+            assert!(!recent.contains(&"test: command 0".to_string()));
+        });
+    }
+}
@@ -0,0 +1,252 @@
+//! Matching the query's words as case-insensitive substrings of the commands' names' words, tolerating a typo per word,
+//! for the queries the fuzzy matching lists nothing for: a single mistyped or transposed letter, as in `backpsace`,
+//! leaves the query no subsequence of the command's name.
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+
+/// How many characters the query needs for its substrings to be matched: the shorter ones match too many commands.
+pub(crate) const MIN_SUBSTRING_QUERY_LEN: usize = 3;
+
+/// How many characters a word of the query needs for a typo in it to be tolerated:
+/// with one of the shorter ones mistyped, too little of the word is left to tell what it was meant to be.
+const MIN_TYPO_WORD_LEN: usize = 4;
+
+/// The candidates every word of the query matches a word of, regardless of the case, either as a substring,
+/// or as the word's start with one character mistyped, missing, extra, or swapped with the next one.
+/// The matched characters are highlighted. The candidates matched with the fewest typos go first,
+/// then the ones matched the earliest, then in the candidates' order. All of them are scored zero, below any fuzzy match.
+pub(crate) fn match_substrings(
+    candidates: &[StringMatchCandidate],
+    query: &str,
+) -> Vec<StringMatch> {
+    let query_words = query
+        .split_whitespace()
+        .map(|word| word.to_lowercase().chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if query_words.iter().map(Vec::len).sum::<usize>() < MIN_SUBSTRING_QUERY_LEN {
+        return Vec::new();
+    }
+    let mut matches = candidates
+        .iter()
+        .filter_map(|candidate| {
+            // Lowercasing may change the characters' count and lengths, e.g. of 'İ', so every lowercased character
+            // keeps the offset of the one it comes from, for the positions to stay on the name's character boundaries.
+            let lowercase = candidate
+                .string
+                .char_indices()
+                .flat_map(|(ix, char)| char.to_lowercase().map(move |char| (ix, char)))
+                .collect::<Vec<_>>();
+            // Split at the separators of the commands' names only: the combining marks, e.g. of the lowercased 'İ', are parts of the words.
+            let words = lowercase
+                .split(|(_, char)| char.is_whitespace() || char.is_ascii_punctuation())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>();
+            let mut typos = 0;
+            let mut positions = Vec::new();
+            for query_word in &query_words {
+                let (word_typos, matched) = words
+                    .iter()
+                    .filter_map(|word| match_word(query_word, word))
+                    .min_by_key(|(word_typos, _)| *word_typos)?;
+                typos += word_typos;
+                positions.extend(matched.iter().map(|(ix, _)| *ix));
+            }
+            positions.sort_unstable();
+            positions.dedup();
+            Some((
+                typos,
+                StringMatch {
+                    candidate_id: candidate.id,
+                    score: 0.0,
+                    positions,
+                    string: candidate.string.clone(),
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(typos, string_match)| (*typos, string_match.positions[0]));
+    matches
+        .into_iter()
+        .map(|(_, string_match)| string_match)
+        .collect()
+}
+
+/// Matches the query's word in the candidate's word, returning the number of typos and the word's characters matched.
+fn match_word<'a>(
+    query_word: &[char],
+    word: &'a [(usize, char)],
+) -> Option<(usize, &'a [(usize, char)])> {
+    let len = query_word.len();
+    if let Some(start) = word
+        .windows(len)
+        .position(|window| window.iter().map(|(_, char)| char).eq(query_word.iter()))
+    {
+        return Some((0, &word[start..start + len]));
+    }
+    if len < MIN_TYPO_WORD_LEN {
+        return None;
+    }
+    // A missing character makes the query's word shorter than the start it stands for, an extra one longer.
+    [len, len - 1, len + 1]
+        .into_iter()
+        .filter(|&prefix_len| prefix_len <= word.len())
+        .map(|prefix_len| &word[..prefix_len])
+        .find(|prefix| {
+            let prefix = prefix.iter().map(|(_, char)| *char).collect::<Vec<_>>();
+            edit_distance(query_word, &prefix) <= 1
+        })
+        .map(|prefix| (1, prefix))
+}
+
+/// The number of characters to insert, delete, replace, or swap with their neighbors, to turn one string into the other.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(names: &[&str]) -> Vec<StringMatchCandidate> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatchCandidate::new(id, name.to_string()))
+            .collect()
+    }
+
+    fn matched(matches: &[StringMatch]) -> Vec<(usize, String)> {
+        matches
+            .iter()
+            .map(|string_match| {
+                let name = &string_match.string;
+                assert!(
+                    string_match
+                        .positions
+                        .iter()
+                        .all(|&position| name.is_char_boundary(position)),
+                    "The positions in {name:?} should be on the characters' boundaries"
+                );
+                (
+                    string_match.candidate_id,
+                    string_match
+                        .ranges()
+                        .map(|range| &name[range])
+                        .collect::<String>(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_match_substrings() {
+        let candidates = candidates(&[
+            "editor: format",
+            "editor: format selections",
+            "zed: reformat",
+        ]);
+
+        assert_eq!(
+            matched(&match_substrings(&candidates, "FORMAT ")),
+            [
+                (2, "format".to_string()),
+                (0, "format".to_string()),
+                (1, "format".to_string()),
+            ],
+            "The earliest substrings should go first, keeping the candidates' order otherwise"
+        );
+        assert!(match_substrings(&candidates, "fo").is_empty());
+        assert!(match_substrings(&candidates, "tamrof").is_empty());
+        assert_eq!(
+            matched(&match_substrings(&candidates, "sel form")),
+            [(1, "formatsel".to_string())],
+            "Every word of the query should match, in any order"
+        );
+        assert!(match_substrings(&candidates, "format zed editor").is_empty());
+    }
+
+    #[test]
+    fn test_match_substrings_with_typos() {
+        let candidates = candidates(&[
+            "editor: backspace",
+            "editor: delete",
+            "editor: format",
+            "zed: reformat",
+        ]);
+        for (query, expected) in [
+            // Swapped letters.
+            ("backpsace", vec![(0, "backspace")]),
+            ("fromat", vec![(2, "format")]),
+            // A mistyped letter.
+            ("deleye", vec![(1, "delete")]),
+            // A missing letter.
+            ("bakspace", vec![(0, "backspace")]),
+            // An extra letter.
+            ("editor backsspace", vec![(0, "editorbackspace")]),
+        ] {
+            let expected = expected
+                .into_iter()
+                .map(|(id, substring)| (id, substring.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                matched(&match_substrings(&candidates, query)),
+                expected,
+                "Unexpected matches of {query:?}"
+            );
+        }
+
+        assert_eq!(
+            matched(&match_substrings(
+                &self::candidates(&["lsp: restart", "editor: reset"]),
+                "reset"
+            )),
+            [(1, "reset".to_string()), (0, "rest".to_string())],
+            "The matches without typos should go first"
+        );
+        assert!(
+            match_substrings(&candidates, "bcakpsace").is_empty(),
+            "Only one typo per word should be tolerated"
+        );
+        assert!(
+            match_substrings(&candidates, "zde").is_empty(),
+            "No typos should be tolerated in the short words"
+        );
+    }
+
+    #[test]
+    fn test_match_substrings_in_other_scripts() {
+        let candidates = candidates(&["İstanbul: ÖFFNEN", "проект: СОБРАТЬ", "テスト: 実行する"]);
+        for (query, candidate_id, substring) in [
+            ("i\u{307}stanbul", 0, "İstanbul"),
+            ("öffnen", 0, "ÖFFNEN"),
+            ("собрать", 1, "СОБРАТЬ"),
+            ("сорбать", 1, "СОБРАТЬ"),
+            ("実行する", 2, "実行する"),
+        ] {
+            assert_eq!(
+                matched(&match_substrings(&candidates, query)),
+                [(candidate_id, substring.to_string())],
+                "Unexpected matches of {query:?}"
+            );
+        }
+    }
+}
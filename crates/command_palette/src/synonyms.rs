@@ -0,0 +1,156 @@
+//! Terms the other editors use for the commands named differently in Zed: "word wrap" for "editor: toggle soft wrap".
+
+use collections::{HashMap, HashSet};
+use fuzzy::StringMatch;
+use ui::SharedString;
+
+use crate::Command;
+
+/// Shorter queries are too ambiguous to be taken for the beginning of a synonym.
+const MIN_SYNONYM_PREFIX_LEN: usize = 3;
+
+/// Menu terms of VS Code, Sublime Text and JetBrains IDEs, mapped to the names of the actions they stand for.
+const BUILTIN_SYNONYMS: &[(&str, &str)] = &[
+    ("preferences", "zed::OpenSettings"),
+    ("options", "zed::OpenSettings"),
+    ("keyboard shortcuts", "zed::OpenKeymap"),
+    ("key bindings", "zed::OpenKeymap"),
+    ("exit", "zed::Quit"),
+    ("word wrap", "editor::ToggleSoftWrap"),
+    ("toggle word wrap", "editor::ToggleSoftWrap"),
+    ("line comment", "editor::ToggleComments"),
+    ("toggle line comment", "editor::ToggleComments"),
+    ("comment with line comment", "editor::ToggleComments"),
+    ("format document", "editor::Format"),
+    ("reformat code", "editor::Format"),
+    ("rename symbol", "editor::Rename"),
+    ("find usages", "editor::FindAllReferences"),
+    ("copy line down", "editor::DuplicateLine"),
+    ("duplicate line or selection", "editor::DuplicateLine"),
+    ("find in files", "workspace::NewSearch"),
+    ("quick open", "file_finder::Toggle"),
+    ("go to anything", "file_finder::Toggle"),
+    ("go to file", "file_finder::Toggle"),
+    ("go to symbol", "outline::Toggle"),
+    ("file structure", "outline::Toggle"),
+    ("go to symbol in workspace", "project_symbols::Toggle"),
+    ("color theme", "theme_selector::Toggle"),
+    ("close editor", "pane::CloseActiveItem"),
+    ("close tab", "pane::CloseActiveItem"),
+    ("reopen closed editor", "pane::ReopenClosedItem"),
+    ("reopen closed tab", "pane::ReopenClosedItem"),
+    ("integrated terminal", "terminal_panel::ToggleFocus"),
+    ("explorer", "project_panel::ToggleFocus"),
+    ("side bar", "workspace::ToggleLeftDock"),
+    ("sidebar", "workspace::ToggleLeftDock"),
+    ("maximize editor", "workspace::ToggleZoom"),
+];
+
+/// Puts the commands, whose synonym is the query or starts with it, above the other matches:
+/// first the ones with the whole synonym typed, then the rest in the order of the synonyms.
+///
+/// The user synonyms given take precedence over the built-in ones. Returns the synonyms matched
+/// for the commands whose names don't contain them, by the commands' candidate ids.
+pub(crate) fn rank_synonyms(
+    query: &str,
+    commands: &[&Command],
+    user_synonyms: &HashMap<String, String>,
+    matches: Vec<StringMatch>,
+) -> (Vec<StringMatch>, HashMap<usize, SharedString>) {
+    let query = normalize(query);
+    if query.is_empty() {
+        return (matches, HashMap::default());
+    }
+
+    let mut user_synonyms = user_synonyms
+        .iter()
+        .map(|(term, action_name)| (normalize(term), action_name.as_str()))
+        .collect::<Vec<_>>();
+    user_synonyms.sort();
+    let user_terms = user_synonyms
+        .iter()
+        .map(|(term, _)| term.clone())
+        .collect::<HashSet<_>>();
+    let synonyms = user_synonyms.iter().cloned().chain(
+        BUILTIN_SYNONYMS
+            .iter()
+            .filter(|(term, _)| !user_terms.contains(*term))
+            .map(|(term, action_name)| (term.to_string(), *action_name)),
+    );
+
+    let mut whole_terms = Vec::new();
+    let mut prefix_terms = Vec::new();
+    for (term, action_name) in synonyms {
+        if term == query {
+            whole_terms.push((term, action_name));
+        } else if query.chars().count() >= MIN_SYNONYM_PREFIX_LEN && term.starts_with(&query) {
+            prefix_terms.push((term, action_name));
+        }
+    }
+    if whole_terms.is_empty() && prefix_terms.is_empty() {
+        return (matches, HashMap::default());
+    }
+
+    let mut matches = matches;
+    let mut synonym_matches = Vec::new();
+    let mut matched_synonyms = HashMap::default();
+    for (term, action_name) in whole_terms.into_iter().chain(prefix_terms) {
+        let Some(candidate_id) = commands
+            .iter()
+            .position(|command| command.action.name() == action_name)
+        else {
+            continue;
+        };
+        if synonym_matches
+            .iter()
+            .any(|synonym_match: &StringMatch| synonym_match.candidate_id == candidate_id)
+        {
+            continue;
+        }
+        let command = &commands[candidate_id];
+        let synonym_match = match matches
+            .iter()
+            .position(|string_match| string_match.candidate_id == candidate_id)
+        {
+            Some(ix) => matches.remove(ix),
+            None => StringMatch {
+                candidate_id,
+                score: 1.0,
+                positions: Vec::new(),
+                string: command.name.clone(),
+            },
+        };
+        if !command.name.to_lowercase().contains(&term) {
+            matched_synonyms.insert(candidate_id, SharedString::from(term));
+        }
+        synonym_matches.push(synonym_match);
+    }
+
+    synonym_matches.extend(matches);
+    (synonym_matches, matched_synonyms)
+}
+
+/// The name of the action the term given stands for, the user synonyms taking precedence over the built-in ones.
+pub(crate) fn resolve_synonym(
+    term: &str,
+    user_synonyms: &HashMap<String, String>,
+) -> Option<String> {
+    let term = normalize(term);
+    user_synonyms
+        .iter()
+        .find(|(user_term, _)| normalize(user_term) == term)
+        .map(|(_, action_name)| action_name.clone())
+        .or_else(|| {
+            BUILTIN_SYNONYMS
+                .iter()
+                .find(|(builtin_term, _)| *builtin_term == term)
+                .map(|(_, action_name)| action_name.to_string())
+        })
+}
+
+pub(crate) fn normalize(term: &str) -> String {
+    term.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,153 @@
+//! Terms of the palette's query, its words matched independently of each other: `editor comments toggle`
+//! matches "editor: toggle comments", which the fuzzy matching of the whole query, in its words' order, misses.
+
+use collections::{HashMap, HashSet};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::BackgroundExecutor;
+
+/// How much lower the commands matched by the terms score than they would by their average, for the commands
+/// matched by the whole query, with its words in order, to rank above the ones matched by the terms only.
+const TERM_MATCH_PENALTY: f64 = 0.8;
+
+/// Matches every term of the query against the candidates on its own, listing the candidates all the terms match,
+/// scored with the average of the terms' scores, lowered by [`TERM_MATCH_PENALTY`], with all the terms' positions highlighted.
+/// Nothing gets matched for the queries of a single term. Every term matches up to `max_matches` candidates.
+pub(crate) async fn match_terms(
+    candidates: &[StringMatchCandidate],
+    query: &str,
+    max_matches: usize,
+    executor: BackgroundExecutor,
+) -> Vec<StringMatch> {
+    let terms = query.split_whitespace().collect::<Vec<_>>();
+    if terms.len() < 2 {
+        return Vec::new();
+    }
+    let mut combined = HashMap::<usize, StringMatch>::default();
+    for (term_ix, term) in terms.iter().enumerate() {
+        let term_matches = fuzzy::match_strings(
+            candidates,
+            term,
+            true,
+            max_matches,
+            &Default::default(),
+            executor.clone(),
+        )
+        .await;
+        if term_ix == 0 {
+            combined.extend(
+                term_matches
+                    .into_iter()
+                    .map(|term_match| (term_match.candidate_id, term_match)),
+            );
+            continue;
+        }
+        let mut matched = HashMap::<usize, StringMatch>::default();
+        for term_match in term_matches {
+            if let Some(mut string_match) = combined.remove(&term_match.candidate_id) {
+                string_match.score += term_match.score;
+                string_match.positions.extend(term_match.positions);
+                matched.insert(term_match.candidate_id, string_match);
+            }
+        }
+        combined = matched;
+        if combined.is_empty() {
+            break;
+        }
+    }
+    let mut matches = combined
+        .into_values()
+        .map(|mut string_match| {
+            string_match.score = string_match.score / terms.len() as f64 * TERM_MATCH_PENALTY;
+            string_match.positions.sort_unstable();
+            string_match.positions.dedup();
+            string_match
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+/// Adds the matches of the terms, for the candidates the whole query did not match, ordering them all by the score.
+/// The matches of the whole query are left as they are.
+pub(crate) fn merge_term_matches(
+    mut matches: Vec<StringMatch>,
+    term_matches: Vec<StringMatch>,
+) -> Vec<StringMatch> {
+    if term_matches.is_empty() {
+        return matches;
+    }
+    let matched = matches
+        .iter()
+        .map(|string_match| string_match.candidate_id)
+        .collect::<HashSet<_>>();
+    matches.extend(
+        term_matches
+            .into_iter()
+            .filter(|term_match| !matched.contains(&term_match.candidate_id)),
+    );
+    // The same order as the fuzzy matches come in.
+    matches.sort_by(|a, b| b.cmp(a));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_match_terms(cx: &mut TestAppContext) {
+        let candidates = ["editor: toggle comments", "editor: toggle soft wrap"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatchCandidate::new(id, name.to_string()))
+            .collect::<Vec<_>>();
+
+        let matches =
+            match_terms(&candidates, "editor  comments toggle", 10000, cx.executor()).await;
+        assert_eq!(
+            matches
+                .iter()
+                .map(|string_match| (string_match.candidate_id, string_match.positions.clone()))
+                .collect::<Vec<_>>(),
+            [(0, (0..6).chain(8..14).chain(15..23).collect::<Vec<_>>())],
+            "All the terms should match, in any order, with all of their positions highlighted"
+        );
+
+        assert!(match_terms(&candidates, "toggle", 10000, cx.executor())
+            .await
+            .is_empty());
+        assert!(
+            match_terms(&candidates, "toggle comments wrap", 10000, cx.executor())
+                .await
+                .is_empty(),
+            "The candidates not matched by some term should not be listed"
+        );
+
+        let whole_query_matches = fuzzy::match_strings(
+            &candidates,
+            "toggle comments",
+            true,
+            10000,
+            &Default::default(),
+            cx.executor(),
+        )
+        .await;
+        let merged = merge_term_matches(
+            whole_query_matches.clone(),
+            match_terms(&candidates, "toggle comments", 10000, cx.executor()).await,
+        );
+        let ids_and_positions = |matches: &[StringMatch]| {
+            matches
+                .iter()
+                .map(|string_match| (string_match.candidate_id, string_match.positions.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids_and_positions(&merged),
+            ids_and_positions(&whole_query_matches),
+            "The matches of the whole query should be kept as they are"
+        );
+    }
+}
@@ -3,7 +3,11 @@ use anyhow::{anyhow, Context as _, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use collections::{HashMap, HashSet};
-use futures::{channel::oneshot, future::Shared, Future, FutureExt, TryFutureExt};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::Shared,
+    Future, FutureExt, TryFutureExt,
+};
 use gpui::{
     actions, AppContext, AsyncAppContext, Context, Entity, EntityId, EventEmitter, Global, Model,
     ModelContext, Task, WeakModel,
@@ -21,10 +25,12 @@ use settings::SettingsStore;
 use smol::{fs, io::BufReader, stream::StreamExt};
 use std::{
     any::TypeId,
+    cell::RefCell,
     ffi::OsString,
     mem,
     ops::Range,
     path::{Path, PathBuf},
+    rc::{Rc, Weak},
     sync::Arc,
 };
 use util::{
@@ -40,9 +46,125 @@ use util::{
 pub struct CommandPaletteFilter {
     pub hidden_namespaces: HashSet<&'static str>,
     pub hidden_action_types: HashSet<TypeId>,
+    /// The commands hidden for as long as the [`FilterHandle`]s hiding them live.
+    scoped: Rc<RefCell<ScopedFilters>>,
+    /// Tells that a handle got dropped, for the filter's observers to get notified.
+    released_tx: Option<mpsc::UnboundedSender<()>>,
 }
 
 impl Global for CommandPaletteFilter {}
+
+#[derive(Default)]
+struct ScopedFilters {
+    next_handle_id: usize,
+    hidden: HashMap<usize, HiddenCommands>,
+}
+
+enum HiddenCommands {
+    Namespace(&'static str),
+    ActionTypes(Vec<TypeId>),
+}
+
+impl CommandPaletteFilter {
+    /// Hides the namespace's commands from the palette until the returned handle gets dropped.
+    /// Unlike [`Self::hidden_namespaces`], the namespace stays hidden while any handle hiding it lives.
+    pub fn hide_namespace(cx: &mut AppContext, namespace: &'static str) -> FilterHandle {
+        Self::hide(HiddenCommands::Namespace(namespace), cx)
+    }
+
+    /// Hides the commands of the action types given from the palette until the returned handle gets dropped.
+    pub fn hide_action_types(
+        cx: &mut AppContext,
+        action_types: impl IntoIterator<Item = TypeId>,
+    ) -> FilterHandle {
+        Self::hide(
+            HiddenCommands::ActionTypes(action_types.into_iter().collect()),
+            cx,
+        )
+    }
+
+    /// Whether the namespace's commands are hidden, statically or by a handle.
+    pub fn is_namespace_hidden(&self, namespace: &str) -> bool {
+        self.hidden_namespaces.contains(namespace)
+            || self
+                .scoped
+                .borrow()
+                .hidden
+                .values()
+                .any(|hidden| match hidden {
+                    HiddenCommands::Namespace(hidden_namespace) => *hidden_namespace == namespace,
+                    HiddenCommands::ActionTypes(_) => false,
+                })
+    }
+
+    /// Whether the commands of the action type are hidden, statically or by a handle.
+    pub fn is_action_type_hidden(&self, action_type: TypeId) -> bool {
+        self.hidden_action_types.contains(&action_type)
+            || self
+                .scoped
+                .borrow()
+                .hidden
+                .values()
+                .any(|hidden| match hidden {
+                    HiddenCommands::Namespace(_) => false,
+                    HiddenCommands::ActionTypes(action_types) => {
+                        action_types.contains(&action_type)
+                    }
+                })
+    }
+
+    fn hide(hidden: HiddenCommands, cx: &mut AppContext) -> FilterHandle {
+        let released_tx = Self::released_tx(cx);
+        let filter = cx.default_global::<Self>();
+        let mut scoped = filter.scoped.borrow_mut();
+        let id = scoped.next_handle_id;
+        scoped.next_handle_id += 1;
+        scoped.hidden.insert(id, hidden);
+        FilterHandle {
+            id,
+            scoped: Rc::downgrade(&filter.scoped),
+            released_tx,
+        }
+    }
+
+    /// The handles can't notify the filter's observers once dropped, with no context at hand: they tell this task to.
+    fn released_tx(cx: &mut AppContext) -> mpsc::UnboundedSender<()> {
+        if let Some(released_tx) = cx.default_global::<Self>().released_tx.clone() {
+            return released_tx;
+        }
+        let (released_tx, mut released_rx) = mpsc::unbounded();
+        cx.spawn(|cx| async move {
+            while released_rx.next().await.is_some() {
+                cx.update(|cx| {
+                    if cx.has_global::<Self>() {
+                        cx.update_global::<Self, _>(|_, _| {});
+                    }
+                })
+                .ok();
+            }
+        })
+        .detach();
+        cx.default_global::<Self>().released_tx = Some(released_tx.clone());
+        released_tx
+    }
+}
+
+/// Keeps commands hidden from the palette, see [`CommandPaletteFilter::hide_namespace`]. Dropping it shows them again.
+#[must_use]
+pub struct FilterHandle {
+    id: usize,
+    scoped: Weak<RefCell<ScopedFilters>>,
+    released_tx: mpsc::UnboundedSender<()>,
+}
+
+impl Drop for FilterHandle {
+    fn drop(&mut self) {
+        if let Some(scoped) = self.scoped.upgrade() {
+            scoped.borrow_mut().hidden.remove(&self.id);
+        }
+        self.released_tx.unbounded_send(()).ok();
+    }
+}
 actions!(
     copilot,
     [
@@ -1,5 +1,6 @@
 pub mod kvp;
 pub mod query;
+pub mod versioned;
 
 // Re-export
 pub use anyhow;
@@ -0,0 +1,115 @@
+//! Versioned formats of the values persisted in the key-value store, e.g. the command palette's hit counts.
+//!
+//! Every format is tagged with its version: a change of it adds a version, along with the migration from the previous one,
+//! for the values persisted by the older Zed versions to carry over. The crates persisting the values snapshot the JSON
+//! of every version, see [`assert_snapshot`], for the changes of a format without a new version to fail their tests.
+//! The values no version reads get moved aside rather than dropped, see [`read_persisted`].
+
+use anyhow::{bail, Context as _, Result};
+use gpui::AppContext;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use util::ResultExt;
+
+use crate::kvp::KEY_VALUE_STORE;
+
+/// What the keys of the unreadable values get suffixed with, once the values get moved aside.
+pub const QUARANTINE_SUFFIX: &str = ".unreadable";
+
+/// Environment variable to set when running the tests, to regenerate the snapshots of the formats.
+#[cfg(any(test, feature = "test-support"))]
+pub const UPDATE_PERSISTENCE_SNAPSHOTS_VAR: &str = "UPDATE_PERSISTENCE_SNAPSHOTS";
+
+/// Reads the value persisted under the key given, parsed by the function given. An unreadable value gets copied aside,
+/// under the key suffixed with [`QUARANTINE_SUFFIX`], to be looked into later: the state starts over,
+/// and the next save overwrites the value.
+pub fn read_persisted<T>(
+    key: &str,
+    parse: impl FnOnce(&str) -> Result<T>,
+    cx: &AppContext,
+) -> Option<T> {
+    let serialized = KEY_VALUE_STORE.read_kvp(key).log_err().flatten()?;
+    match parse(&serialized) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            log::error!("Moving aside the unreadable value of {key:?}: {error:#}");
+            let quarantine_key = format!("{key}{QUARANTINE_SUFFIX}");
+            cx.background_executor()
+                .spawn(async move { KEY_VALUE_STORE.write_kvp(quarantine_key, serialized).await })
+                .detach_and_log_err(cx);
+            None
+        }
+    }
+}
+
+/// The value, tagged with the version of its format.
+#[derive(Serialize)]
+pub struct Versioned<'a, T> {
+    pub version: &'a str,
+    #[serde(flatten)]
+    pub value: &'a T,
+}
+
+/// Serializes the value, tagged with the version of its format.
+pub fn to_versioned_json<T: Serialize>(version: &str, value: &T) -> Result<String> {
+    Ok(serde_json::to_string(&Versioned { version, value })?)
+}
+
+/// The version the serialized value is tagged with, `None` for the values persisted before the formats got versioned.
+pub fn parse_versioned(serialized: &str) -> Result<(Option<String>, Value)> {
+    let value = serde_json::from_str::<Value>(serialized)?;
+    let version = match value.get("version") {
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(version) => bail!("malformed version {version}"),
+        None => None,
+    };
+    Ok((version, value))
+}
+
+/// Deserializes the value of the version given, as returned by [`parse_versioned`].
+pub fn from_value<T: DeserializeOwned>(value: Value, version: &str) -> Result<T> {
+    serde_json::from_value(value).with_context(|| format!("malformed version {version:?}"))
+}
+
+/// Panics unless the value, serialized with the version given, matches the snapshot at the path given.
+/// With [`UPDATE_PERSISTENCE_SNAPSHOTS_VAR`] set, writes the snapshot instead.
+#[cfg(any(test, feature = "test-support"))]
+#[track_caller]
+pub fn assert_snapshot<T: Serialize>(path: &std::path::Path, version: &str, value: &T) {
+    let serialized = serde_json::to_string_pretty(&Versioned { version, value }).unwrap() + "\n";
+    if std::env::var(UPDATE_PERSISTENCE_SNAPSHOTS_VAR).is_ok() {
+        std::fs::write(path, serialized).unwrap();
+        return;
+    }
+    let snapshot = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        serialized,
+        snapshot,
+        "The format of {} changed: add a version, along with a migration, \
+         or regenerate the snapshots with `{UPDATE_PERSISTENCE_SNAPSHOTS_VAR}=1`",
+        path.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_json() {
+        let serialized = to_versioned_json("2", &serde_json::json!({ "count": 3 })).unwrap();
+        assert_eq!(serialized, r#"{"version":"2","count":3}"#);
+        let (version, value) = parse_versioned(&serialized).unwrap();
+        assert_eq!(version.as_deref(), Some("2"));
+        assert_eq!(value["count"], 3);
+
+        let (version, _) = parse_versioned("[1, 2]").unwrap();
+        assert_eq!(
+            version, None,
+            "The unversioned values should be read as such"
+        );
+        assert!(parse_versioned(r#"{"version": 2}"#).is_err());
+        assert!(parse_versioned("{\"version\": \"2\"").is_err());
+        assert!(from_value::<Vec<u32>>(serde_json::json!({}), "1").is_err());
+    }
+}
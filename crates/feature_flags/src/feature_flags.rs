@@ -18,6 +18,13 @@ pub trait FeatureFlag {
     const NAME: &'static str;
 }
 
+/// The assistant's integrations outside of its panel, e.g. explaining commands from the command palette.
+pub struct AssistantFeatureFlag;
+
+impl FeatureFlag for AssistantFeatureFlag {
+    const NAME: &'static str = "assistant";
+}
+
 pub trait FeatureFlagViewExt<V: 'static> {
     fn observe_flag<T: FeatureFlag, F>(&mut self, callback: F) -> Subscription
     where
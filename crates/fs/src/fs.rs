@@ -103,6 +103,8 @@ pub struct Metadata {
     pub mtime: SystemTime,
     pub is_symlink: bool,
     pub is_dir: bool,
+    /// Whether the file can be executed by anyone, always `false` for directories.
+    pub is_executable: bool,
 }
 
 pub struct RealFs;
@@ -249,11 +251,20 @@ impl Fs for RealFs {
         #[cfg(windows)]
         let inode = 0;
 
+        let is_dir = metadata.file_type().is_dir();
+        #[cfg(unix)]
+        let is_executable = !is_dir && metadata.mode() & 0o111 != 0;
+
+        // todo!(windows)
+        #[cfg(windows)]
+        let is_executable = false;
+
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
             is_symlink,
-            is_dir: metadata.file_type().is_dir(),
+            is_dir,
+            is_executable,
         }))
     }
 
@@ -424,6 +435,7 @@ enum FakeFsEntry {
         inode: u64,
         mtime: SystemTime,
         content: String,
+        is_executable: bool,
     },
     Dir {
         inode: u64,
@@ -603,6 +615,7 @@ impl FakeFs {
             inode,
             mtime,
             content,
+            is_executable: false,
         }));
         state.write_path(path, move |entry| {
             match entry {
@@ -619,6 +632,18 @@ impl FakeFs {
         Ok(())
     }
 
+    pub fn set_executable(&self, path: impl AsRef<Path>, executable: bool) {
+        let mut state = self.state.lock();
+        let path = path.as_ref();
+        let entry = state.read_path(path).unwrap();
+        if let FakeFsEntry::File { is_executable, .. } = &mut *entry.lock() {
+            *is_executable = executable;
+        } else {
+            panic!("not a file: {path:?}");
+        }
+        state.emit_event(&[path]);
+    }
+
     pub fn pause_events(&self) {
         self.state.lock().events_paused = true;
     }
@@ -904,6 +929,7 @@ impl Fs for FakeFs {
             inode,
             mtime,
             content: String::new(),
+            is_executable: false,
         }));
         state.write_path(path, |entry| {
             match entry {
@@ -995,6 +1021,7 @@ impl Fs for FakeFs {
                     inode,
                     mtime,
                     content: String::new(),
+                    is_executable: false,
                 })))
                 .clone(),
             )),
@@ -1144,17 +1171,24 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    is_executable,
+                    ..
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: false,
                     is_symlink,
+                    is_executable: *is_executable,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: true,
                     is_symlink,
+                    is_executable: false,
                 },
                 FakeFsEntry::Symlink { .. } => unreachable!(),
             }))
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    iter,
     sync::atomic::{self, AtomicBool},
 };
 
@@ -10,7 +11,9 @@ const ADDITIONAL_DISTANCE_PENALTY: f64 = 0.05;
 const MIN_DISTANCE_PENALTY: f64 = 0.2;
 
 pub struct Matcher<'a> {
-    query: &'a [char],
+    /// The query's characters, each repeated as many times as it has characters in lowercase,
+    /// for the query's characters to be at the same indices as the lowercase ones.
+    query: Vec<char>,
     lowercase_query: &'a [char],
     query_char_bag: CharBag,
     smart_case: bool,
@@ -40,13 +43,21 @@ impl<'a> Matcher<'a> {
         smart_case: bool,
         max_results: usize,
     ) -> Self {
+        let mut aligned_query = query
+            .iter()
+            .flat_map(|&c| iter::repeat(c).take(c.to_lowercase().count()))
+            .collect::<Vec<_>>();
+        // The query lowercased as a whole may differ, e.g. with a final sigma: compare with the lowercase characters then.
+        if aligned_query.len() != lowercase_query.len() {
+            aligned_query = lowercase_query.to_vec();
+        }
         Self {
-            query,
+            query: aligned_query,
             lowercase_query,
             query_char_bag,
             min_score: 0.0,
             last_positions: vec![0; lowercase_query.len()],
-            match_positions: vec![0; query.len()],
+            match_positions: Vec::with_capacity(lowercase_query.len()),
             score_matrix: Vec::new(),
             best_position_matrix: Vec::new(),
             smart_case,
@@ -68,6 +79,9 @@ impl<'a> Matcher<'a> {
     {
         let mut candidate_chars = Vec::new();
         let mut lowercase_candidate_chars = Vec::new();
+        // The indices in the candidate of the characters the lowercase ones come from: some characters have more than one
+        // in lowercase, e.g. 'İ', shifting the lowercase characters after them.
+        let mut lowercase_char_ixs = Vec::new();
 
         for candidate in candidates {
             if !candidate.has_chars(self.query_char_bag) {
@@ -80,16 +94,20 @@ impl<'a> Matcher<'a> {
 
             candidate_chars.clear();
             lowercase_candidate_chars.clear();
-            for c in candidate.to_string().chars() {
+            lowercase_char_ixs.clear();
+            for (ix, c) in candidate.to_string().chars().enumerate() {
                 candidate_chars.push(c);
-                lowercase_candidate_chars.append(&mut c.to_lowercase().collect::<Vec<_>>());
+                for lowercase_c in c.to_lowercase() {
+                    lowercase_candidate_chars.push(lowercase_c);
+                    lowercase_char_ixs.push(ix);
+                }
             }
 
             if !self.find_last_positions(lowercase_prefix, &lowercase_candidate_chars) {
                 continue;
             }
 
-            let matrix_len = self.query.len() * (prefix.len() + candidate_chars.len());
+            let matrix_len = self.query.len() * (prefix.len() + lowercase_candidate_chars.len());
             self.score_matrix.clear();
             self.score_matrix.resize(matrix_len, None);
             self.best_position_matrix.clear();
@@ -98,6 +116,7 @@ impl<'a> Matcher<'a> {
             let score = self.score_match(
                 &candidate_chars,
                 &lowercase_candidate_chars,
+                &lowercase_char_ixs,
                 prefix,
                 lowercase_prefix,
             );
@@ -140,16 +159,20 @@ impl<'a> Matcher<'a> {
         true
     }
 
+    /// Scores the path, matched by its lowercase characters, `path_cased`, with `char_ixs` being the indices in the path
+    /// of the characters they come from. The positions of the match are the byte offsets of the path's characters.
     fn score_match(
         &mut self,
         path: &[char],
         path_cased: &[char],
+        char_ixs: &[usize],
         prefix: &[char],
         lowercase_prefix: &[char],
     ) -> f64 {
         let score = self.recursive_score_match(
             path,
             path_cased,
+            char_ixs,
             prefix,
             lowercase_prefix,
             0,
@@ -161,12 +184,19 @@ impl<'a> Matcher<'a> {
             return 0.0;
         }
 
-        let path_len = prefix.len() + path.len();
+        let path_len = prefix.len() + path_cased.len();
         let mut cur_start = 0;
         let mut byte_ix = 0;
         let mut char_ix = 0;
+        self.match_positions.clear();
         for i in 0..self.query.len() {
-            let match_char_ix = self.best_position_matrix[i * path_len + cur_start];
+            let match_ix = self.best_position_matrix[i * path_len + cur_start];
+            cur_start = match_ix + 1;
+            let match_char_ix = if match_ix < prefix.len() {
+                match_ix
+            } else {
+                prefix.len() + char_ixs[match_ix - prefix.len()]
+            };
             while char_ix < match_char_ix {
                 let ch = prefix
                     .get(char_ix)
@@ -175,8 +205,10 @@ impl<'a> Matcher<'a> {
                 byte_ix += ch.len_utf8();
                 char_ix += 1;
             }
-            cur_start = match_char_ix + 1;
-            self.match_positions[i] = byte_ix;
+            // The lowercase characters of the same character are highlighted once, with it.
+            if self.match_positions.last() != Some(&byte_ix) {
+                self.match_positions.push(byte_ix);
+            }
         }
 
         score
@@ -187,6 +219,7 @@ impl<'a> Matcher<'a> {
         &mut self,
         path: &[char],
         path_cased: &[char],
+        char_ixs: &[usize],
         prefix: &[char],
         lowercase_prefix: &[char],
         query_idx: usize,
@@ -197,7 +230,7 @@ impl<'a> Matcher<'a> {
             return 1.0;
         }
 
-        let path_len = prefix.len() + path.len();
+        let path_len = prefix.len() + path_cased.len();
 
         if let Some(memoized) = self.score_matrix[query_idx * path_len + path_idx] {
             return memoized;
@@ -226,7 +259,7 @@ impl<'a> Matcher<'a> {
                 let curr = if j < prefix.len() {
                     prefix[j]
                 } else {
-                    path[j - prefix.len()]
+                    path[char_ixs[j - prefix.len()]]
                 };
 
                 let mut char_score = 1.0;
@@ -234,7 +267,7 @@ impl<'a> Matcher<'a> {
                     let last = if j - 1 < prefix.len() {
                         prefix[j - 1]
                     } else {
-                        path[j - 1 - prefix.len()]
+                        path[char_ixs[j - 1 - prefix.len()]]
                     };
 
                     if last == '/' {
@@ -266,7 +299,7 @@ impl<'a> Matcher<'a> {
 
                 // Scale the score based on how deep within the path we found the match.
                 if query_idx == 0 {
-                    multiplier /= ((prefix.len() + path.len()) - last_slash) as f64;
+                    multiplier /= (path_len - last_slash) as f64;
                 }
 
                 let mut next_score = 1.0;
@@ -285,6 +318,7 @@ impl<'a> Matcher<'a> {
                 let new_score = self.recursive_score_match(
                     path,
                     path_cased,
+                    char_ixs,
                     prefix,
                     lowercase_prefix,
                     query_idx + 1,
@@ -393,12 +427,24 @@ mod tests {
             vec![("\u{0130}", vec![0])]
         );
 
-        // Path is the lower-case version of the query
+        // Path is the lower-case version of the query, both of its characters are matched.
         let paths = vec!["i\u{307}"];
         let query = "\u{0130}";
         assert_eq!(
             match_single_path_query(query, false, &paths),
-            vec![("i\u{307}", vec![0])]
+            vec![("i\u{307}", vec![0, 1])]
+        );
+
+        // The characters after the one with more chars in lower-case are highlighted, rather than the ones before them,
+        // up to the end of the path.
+        let paths = vec!["\u{0130}stanbul"];
+        assert_eq!(
+            match_single_path_query("bul", false, &paths),
+            vec![("\u{0130}stanbul", vec![6, 7, 8])]
+        );
+        assert_eq!(
+            match_single_path_query("\u{0130}sl", false, &["\u{0130}stanbul"]),
+            vec![("\u{0130}stanbul", vec![0, 2, 8])]
         );
     }
 
@@ -79,10 +79,12 @@ impl dyn Action {
     }
 }
 
-type ActionBuilder = fn(json: serde_json::Value) -> anyhow::Result<Box<dyn Action>>;
+/// Builds an action out of its JSON data, as registered for the action's name.
+pub type ActionBuilder = fn(json: serde_json::Value) -> anyhow::Result<Box<dyn Action>>;
 
 pub(crate) struct ActionRegistry {
     builders_by_name: HashMap<SharedString, ActionBuilder>,
+    type_ids_by_name: HashMap<SharedString, TypeId>,
     names_by_type_id: HashMap<TypeId, SharedString>,
     all_names: Vec<SharedString>, // So we can return a static slice.
 }
@@ -91,6 +93,7 @@ impl Default for ActionRegistry {
     fn default() -> Self {
         let mut this = ActionRegistry {
             builders_by_name: Default::default(),
+            type_ids_by_name: Default::default(),
             names_by_type_id: Default::default(),
             all_names: Default::default(),
         };
@@ -142,6 +145,7 @@ impl ActionRegistry {
     fn insert_action(&mut self, action: ActionData) {
         let name: SharedString = action.name.into();
         self.builders_by_name.insert(name.clone(), action.build);
+        self.type_ids_by_name.insert(name.clone(), action.type_id);
         self.names_by_type_id.insert(action.type_id, name.clone());
         self.all_names.push(name);
     }
@@ -174,6 +178,14 @@ impl ActionRegistry {
     pub fn all_action_names(&self) -> &[SharedString] {
         self.all_names.as_slice()
     }
+
+    /// The type of the action registered with the given name, along with its builder.
+    pub fn action_builder(&self, name: &str) -> Option<(TypeId, ActionBuilder)> {
+        Some((
+            *self.type_ids_by_name.get(name)?,
+            *self.builders_by_name.get(name)?,
+        ))
+    }
 }
 
 /// Defines unit structs that can be used as actions.
@@ -30,12 +30,13 @@ use util::{
 
 use crate::WindowAppearance;
 use crate::{
-    current_platform, image_cache::ImageCache, init_app_menus, Action, ActionRegistry, Any,
-    AnyView, AnyWindowHandle, AppMetadata, AssetSource, BackgroundExecutor, ClipboardItem, Context,
-    DispatchPhase, Entity, EventEmitter, ForegroundExecutor, Global, KeyBinding, Keymap, Keystroke,
-    LayoutId, Menu, PathPromptOptions, Pixels, Platform, PlatformDisplay, Point, Render,
-    SharedString, SubscriberSet, Subscription, SvgRenderer, Task, TextStyle, TextStyleRefinement,
-    TextSystem, View, ViewContext, Window, WindowContext, WindowHandle, WindowId,
+    current_platform, image_cache::ImageCache, init_app_menus, Action, ActionBuilder,
+    ActionRegistry, Any, AnyView, AnyWindowHandle, AppMetadata, AssetSource, BackgroundExecutor,
+    ClipboardItem, Context, DispatchPhase, Entity, EventEmitter, ForegroundExecutor, Global,
+    KeyBinding, Keymap, Keystroke, LayoutId, Menu, PathPromptOptions, Pixels, Platform,
+    PlatformDisplay, Point, Render, SharedString, SubscriberSet, Subscription, SvgRenderer, Task,
+    TextStyle, TextStyleRefinement, TextSystem, View, ViewContext, Window, WindowContext,
+    WindowHandle, WindowId,
 };
 
 mod async_context;
@@ -241,6 +242,14 @@ pub struct AppContext {
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
+    pub(crate) pending_keystrokes_handler: Option<PendingKeystrokesHandler>,
+}
+
+/// Builds the action to dispatch instead of replaying the keystrokes of a multi-stroke binding, pending for too long.
+#[derive(Clone)]
+pub(crate) struct PendingKeystrokesHandler {
+    pub(crate) delay: Duration,
+    pub(crate) build_action: Rc<dyn Fn(&[Keystroke]) -> Box<dyn Action>>,
 }
 
 impl AppContext {
@@ -299,6 +308,7 @@ impl AppContext {
                 quit_observers: SubscriberSet::new(),
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
+                pending_keystrokes_handler: None,
             }),
         });
 
@@ -1012,6 +1022,25 @@ impl AppContext {
         inner(&mut self.keystroke_observers, Box::new(f))
     }
 
+    /// Register a callback to build the action, dispatched in place of the keystrokes of a multi-stroke binding,
+    /// when they stay pending for the delay given. Delays longer than the pending keystrokes' timeout have no effect.
+    /// Replaces the callback registered previously, if any.
+    pub fn on_pending_keystrokes(
+        &mut self,
+        delay: Duration,
+        build_action: impl Fn(&[Keystroke]) -> Box<dyn Action> + 'static,
+    ) {
+        self.pending_keystrokes_handler = Some(PendingKeystrokesHandler {
+            delay,
+            build_action: Rc::new(build_action),
+        });
+    }
+
+    /// Unregister the callback set with [`Self::on_pending_keystrokes`], replaying the pending keystrokes on timeout again.
+    pub fn clear_pending_keystrokes_handler(&mut self) {
+        self.pending_keystrokes_handler = None;
+    }
+
     pub(crate) fn push_text_style(&mut self, text_style: TextStyleRefinement) {
         self.text_style_stack.push(text_style);
     }
@@ -1078,6 +1107,12 @@ impl AppContext {
         self.actions.all_action_names()
     }
 
+    /// Get the type and the builder of the action registered with the given name,
+    /// to build it from some arbitrary data without the app at hand.
+    pub fn action_builder(&self, name: &str) -> Option<(TypeId, ActionBuilder)> {
+        self.actions.action_builder(name)
+    }
+
     /// Register a callback to be invoked when the application is about to quit.
     /// It is not possible to cancel the quit event at this point.
     pub fn on_app_quit<Fut>(
@@ -1195,6 +1230,11 @@ impl AppContext {
     pub fn has_active_drag(&self) -> bool {
         self.active_drag.is_some()
     }
+
+    /// The value being dragged, if there's one of the given type.
+    pub fn active_drag_value<T: 'static>(&self) -> Option<&T> {
+        self.active_drag.as_ref()?.value.downcast_ref()
+    }
 }
 
 impl Context for AppContext {
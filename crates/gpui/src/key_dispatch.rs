@@ -288,12 +288,16 @@ impl DispatchTree {
     }
 
     pub fn is_action_available(&self, action: &dyn Action, target: DispatchNodeId) -> bool {
+        self.is_action_type_available(action.as_any().type_id(), target)
+    }
+
+    pub fn is_action_type_available(&self, action_type: TypeId, target: DispatchNodeId) -> bool {
         for node_id in self.dispatch_path(target) {
             let node = &self.nodes[node_id.0];
             if node
                 .action_listeners
                 .iter()
-                .any(|listener| listener.action_type == action.as_any().type_id())
+                .any(|listener| listener.action_type == action_type)
             {
                 return true;
             }
@@ -322,6 +326,17 @@ impl DispatchTree {
             .collect()
     }
 
+    pub fn global_bindings_for_action(&self, action: &dyn Action) -> Vec<KeyBinding> {
+        let keymap = self.keymap.borrow();
+        keymap
+            .bindings_for_action(action)
+            .filter(|binding| {
+                binding.context_predicate.is_none() && keymap.binding_enabled(binding, &[])
+            })
+            .cloned()
+            .collect()
+    }
+
     // dispatch_key pushes the next keystroke into any key binding matchers.
     // any matching bindings are returned in the order that they should be dispatched:
     // * First by length of binding (so if you have a binding for "b" and "ab", the "ab" binding fires first)
@@ -110,6 +110,27 @@ impl Keystroke {
     }
 }
 
+impl Keystroke {
+    /// Produces the keymap representation of the keystroke, e.g. `cmd-shift-k`, parsed back by [`Keystroke::parse`].
+    /// The ime key is omitted.
+    pub fn unparse(&self) -> String {
+        let mut unparsed = String::new();
+        for (pressed, modifier) in [
+            (self.modifiers.control, "ctrl-"),
+            (self.modifiers.alt, "alt-"),
+            (self.modifiers.shift, "shift-"),
+            (self.modifiers.command, "cmd-"),
+            (self.modifiers.function, "fn-"),
+        ] {
+            if pressed {
+                unparsed.push_str(modifier);
+            }
+        }
+        unparsed.push_str(&self.key);
+        unparsed
+    }
+}
+
 impl std::fmt::Display for Keystroke {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.modifiers.control {
@@ -38,6 +38,8 @@ mod element_cx;
 pub use element_cx::*;
 
 const ACTIVE_DRAG_Z_INDEX: u16 = 1;
+/// How long the keystrokes of a multi-stroke binding stay pending, before they get replayed.
+const PENDING_KEYSTROKES_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// A global stacking order, which is created by stacking successive z-index values.
 /// Each z-index will always be interpreted in the context of its parent z-index.
@@ -849,6 +851,20 @@ impl<'a> WindowContext<'a> {
             .is_action_available(action, target)
     }
 
+    /// Determine whether the actions of the given type are available along the dispatch path to the given focus handle,
+    /// as if it were focused, the ones that cannot be built without data included.
+    pub fn is_action_type_available_in(
+        &self,
+        action_type: TypeId,
+        focus_handle: &FocusHandle,
+    ) -> bool {
+        let dispatch_tree = &self.window.rendered_frame.dispatch_tree;
+        let target = dispatch_tree
+            .focusable_node_id(focus_handle.id)
+            .unwrap_or_else(|| dispatch_tree.root_node_id());
+        dispatch_tree.is_action_type_available(action_type, target)
+    }
+
     /// The position of the mouse relative to the window.
     pub fn mouse_position(&self) -> Point<Pixels> {
         self.window.mouse_position
@@ -1289,14 +1305,28 @@ impl<'a> WindowContext<'a> {
                     currently_pending.bindings.push(binding);
                 }
 
+                let handler = self
+                    .app
+                    .pending_keystrokes_handler
+                    .clone()
+                    .filter(|handler| handler.delay < PENDING_KEYSTROKES_TIMEOUT);
                 currently_pending.timer = Some(self.spawn(|mut cx| async move {
-                    cx.background_executor.timer(Duration::from_secs(1)).await;
+                    let delay = handler
+                        .as_ref()
+                        .map_or(PENDING_KEYSTROKES_TIMEOUT, |handler| handler.delay);
+                    cx.background_executor.timer(delay).await;
                     cx.update(move |cx| {
                         cx.clear_pending_keystrokes();
                         let Some(currently_pending) = cx.window.pending_input.take() else {
                             return;
                         };
-                        cx.replay_pending_input(currently_pending)
+                        match handler {
+                            Some(handler) if cx.window.focus == currently_pending.focus => {
+                                let action = (handler.build_action)(&currently_pending.keystrokes);
+                                cx.dispatch_action(action);
+                            }
+                            _ => cx.replay_pending_input(currently_pending),
+                        }
                     })
                     .log_err();
                 }));
@@ -1552,6 +1582,15 @@ impl<'a> WindowContext<'a> {
             .available_actions(node_id)
     }
 
+    /// Returns all actions available for the given focus handle, as if it were focused.
+    pub fn available_actions_in(&self, focus_handle: &FocusHandle) -> Vec<Box<dyn Action>> {
+        let dispatch_tree = &self.window.rendered_frame.dispatch_tree;
+        let node_id = dispatch_tree
+            .focusable_node_id(focus_handle.id)
+            .unwrap_or_else(|| dispatch_tree.root_node_id());
+        dispatch_tree.available_actions(node_id)
+    }
+
     /// Returns key bindings that invoke the given action on the currently focused element.
     pub fn bindings_for_action(&self, action: &dyn Action) -> Vec<KeyBinding> {
         self.window
@@ -1582,6 +1621,14 @@ impl<'a> WindowContext<'a> {
         dispatch_tree.bindings_for_action(action, &context_stack)
     }
 
+    /// Returns the bindings of the given action with no context, which invoke it wherever the focus is in the window.
+    pub fn global_bindings_for_action(&self, action: &dyn Action) -> Vec<KeyBinding> {
+        self.window
+            .rendered_frame
+            .dispatch_tree
+            .global_bindings_for_action(action)
+    }
+
     /// Returns a generic event listener that invokes the given listener with the view and context associated with the given view handle.
     pub fn listener_for<V: Render, E>(
         &self,
@@ -1,13 +1,44 @@
 use editor::Editor;
 use gpui::{
-    div, list, prelude::*, uniform_list, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, Length, ListState, MouseButton, MouseDownEvent, Render, Task,
-    UniformListScrollHandle, View, ViewContext, WindowContext,
+    canvas, div, list, prelude::*, uniform_list, AnyElement, AppContext, Bounds, DefiniteLength,
+    DismissEvent, EventEmitter, FocusHandle, FocusableView, Length, ListState, MouseButton,
+    MouseDownEvent, Pixels, Rems, Render, Task, UniformListScrollHandle, View, ViewContext,
+    WindowContext,
 };
-use std::sync::Arc;
+use settings::Settings;
+use std::{ops::Range, sync::Arc};
 use ui::{prelude::*, v_flex, Color, Divider, Label, ListItem, ListItemSpacing};
 use workspace::ModalView;
 
+mod picker_settings;
+
+pub use picker_settings::{PickerSettings, PickerSettingsContent};
+
+pub fn init(cx: &mut AppContext) {
+    PickerSettings::register(cx);
+}
+
+/// The narrowest a modal with a picker gets on small windows.
+pub const MIN_MODAL_WIDTH: Rems = Rems(20.);
+/// Room, left around a modal with a picker so it does not touch the window edges.
+const MODAL_HORIZONTAL_MARGIN: Rems = Rems(2.);
+/// Room, taken by the modal layer offset, the query editor and the bottom margin of the modal pickers,
+/// that their list of matches does not get.
+const MODAL_VERTICAL_MARGIN: Rems = Rems(9.);
+/// The shortest a modal picker's list of matches gets on small windows.
+const MIN_MODAL_LIST_HEIGHT: Rems = Rems(6.);
+const DEFAULT_MAX_LIST_HEIGHT: Rems = Rems(18.);
+
+/// Width of a modal with a picker: the `preferred` one if it fits into the window,
+/// otherwise the window's width without margins, but no narrower than [`MIN_MODAL_WIDTH`].
+pub fn modal_width(preferred: Rems, cx: &WindowContext) -> Pixels {
+    let rem_size = cx.rem_size();
+    let available = cx.viewport_size().width - MODAL_HORIZONTAL_MARGIN * rem_size * 2.;
+    (preferred * rem_size)
+        .min(available)
+        .max(MIN_MODAL_WIDTH * rem_size)
+}
+
 enum ElementContainer {
     List(ListState),
     UniformList(UniformListScrollHandle),
@@ -21,6 +52,10 @@ pub struct Picker<D: PickerDelegate> {
     confirm_on_update: Option<bool>,
     width: Option<Length>,
     max_height: Option<Length>,
+    /// Matches, rendered by the uniform list during the last layout.
+    visible_range: Range<usize>,
+    /// Bounds of the list of matches during the last layout.
+    list_bounds: Option<Bounds<Pixels>>,
 
     /// Whether the `Picker` is rendered as a self-contained modal.
     ///
@@ -38,10 +73,27 @@ pub trait PickerDelegate: Sized + 'static {
     fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>);
 
     fn placeholder_text(&self) -> Arc<str>;
+    /// The text shown in place of the matches, when there are none.
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No matches".into()
+    }
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()>;
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>);
+    /// The match to confirm as soon as the matches get updated, without the user confirming it,
+    /// e.g. the only one named by the query exactly. Taken once per update.
+    fn take_auto_confirm_index(&mut self) -> Option<usize> {
+        None
+    }
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>);
+    /// Called with the range of the matches laid out by the uniform list, once their rows got rendered,
+    /// e.g. to load the data too costly to wait for before the first paint.
+    fn matches_rendered(
+        &mut self,
+        _visible_range: Range<usize>,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) {
+    }
 
     fn render_match(
         &self,
@@ -95,6 +147,8 @@ impl<D: PickerDelegate> Picker<D> {
             confirm_on_update: None,
             width: None,
             max_height: None,
+            visible_range: 0..0,
+            list_bounds: None,
             is_modal: true,
         };
         this.update_matches("".to_string(), cx);
@@ -182,6 +236,67 @@ impl<D: PickerDelegate> Picker<D> {
         }
     }
 
+    fn select_page_up(&mut self, _: &menu::SelectPageUp, cx: &mut ViewContext<Self>) {
+        let count = self.delegate.match_count();
+        if count > 0 {
+            let ix = self
+                .delegate
+                .selected_index()
+                .saturating_sub(self.page_size());
+            self.delegate.set_selected_index(ix, cx);
+            self.scroll_to_item_index(ix);
+            cx.notify();
+        }
+    }
+
+    fn select_page_down(&mut self, _: &menu::SelectPageDown, cx: &mut ViewContext<Self>) {
+        let count = self.delegate.match_count();
+        if count > 0 {
+            let ix = (self.delegate.selected_index() + self.page_size()).min(count - 1);
+            self.delegate.set_selected_index(ix, cx);
+            self.scroll_to_item_index(ix);
+            cx.notify();
+        }
+    }
+
+    /// How many matches a page-wise selection movement skips: all visible ones,
+    /// except the last, likely cut off, one that stays visible on the next page.
+    fn page_size(&self) -> usize {
+        let visible_count = match &self.element_container {
+            ElementContainer::UniformList(_) => self.visible_range.len(),
+            ElementContainer::List(state) => {
+                let Some(list_bounds) = self.list_bounds else {
+                    return 1;
+                };
+                (state.logical_scroll_top().item_ix..self.delegate.match_count())
+                    .take_while(|&ix| {
+                        state
+                            .bounds_for_item(ix)
+                            .map_or(false, |bounds| bounds.top() < list_bounds.bottom())
+                    })
+                    .count()
+            }
+        };
+        visible_count.saturating_sub(1).max(1)
+    }
+
+    /// The maximum height of the list of matches, clamped to the window for the modal pickers.
+    fn max_list_height(&self, cx: &WindowContext) -> Length {
+        let max_height = self.max_height.unwrap_or(DEFAULT_MAX_LIST_HEIGHT.into());
+        if !self.is_modal {
+            return max_height;
+        }
+        let rem_size = cx.rem_size();
+        let available = (cx.viewport_size().height - MODAL_VERTICAL_MARGIN * rem_size)
+            .max(MIN_MODAL_LIST_HEIGHT * rem_size);
+        match max_height {
+            Length::Definite(DefiniteLength::Absolute(max_height)) => {
+                max_height.to_pixels(rem_size).min(available).into()
+            }
+            _ => available.into(),
+        }
+    }
+
     pub fn cycle_selection(&mut self, cx: &mut ViewContext<Self>) {
         let count = self.delegate.match_count();
         let index = self.delegate.selected_index();
@@ -262,7 +377,15 @@ impl<D: PickerDelegate> Picker<D> {
         let index = self.delegate.selected_index();
         self.scroll_to_item_index(index);
         self.pending_update_matches = None;
-        if let Some(secondary) = self.confirm_on_update.take() {
+        let auto_confirm_ix = self.delegate.take_auto_confirm_index();
+        if let Some(ix) = auto_confirm_ix {
+            self.delegate.set_selected_index(ix, cx);
+        }
+        if let Some(secondary) = self
+            .confirm_on_update
+            .take()
+            .or(auto_confirm_ix.map(|_| false))
+        {
             self.delegate.confirm(secondary, cx);
         }
         cx.notify();
@@ -277,6 +400,12 @@ impl<D: PickerDelegate> Picker<D> {
             .update(cx, |editor, cx| editor.set_text(query, cx));
     }
 
+    /// Selects the whole query, for the typing to replace it.
+    pub fn select_query(&self, cx: &mut ViewContext<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.select_all(&Default::default(), cx));
+    }
+
     fn scroll_to_item_index(&mut self, ix: usize) {
         match &mut self.element_container {
             ElementContainer::List(state) => state.scroll_to_reveal_item(ix),
@@ -289,7 +418,10 @@ impl<D: PickerDelegate> Picker<D> {
             .on_mouse_down(
                 MouseButton::Left,
                 cx.listener(move |this, event: &MouseDownEvent, cx| {
-                    this.handle_click(ix, event.modifiers.command, cx)
+                    // Buttons within the match handle their own clicks.
+                    if !cx.default_prevented() {
+                        this.handle_click(ix, event.modifiers.command, cx)
+                    }
                 }),
             )
             .children(
@@ -314,9 +446,13 @@ impl<D: PickerDelegate> Picker<D> {
                 "candidates",
                 self.delegate.match_count(),
                 move |picker, visible_range, cx| {
-                    visible_range
+                    picker.visible_range = visible_range.clone();
+                    let elements = visible_range
+                        .clone()
                         .map(|ix| picker.render_element(cx, ix))
-                        .collect()
+                        .collect();
+                    picker.delegate.matches_rendered(visible_range, cx);
+                    elements
                 },
             )
             .py_2()
@@ -356,6 +492,8 @@ impl<D: PickerDelegate> Render for Picker<D> {
             .on_action(cx.listener(Self::select_prev))
             .on_action(cx.listener(Self::select_first))
             .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_page_up))
+            .on_action(cx.listener(Self::select_page_down))
             .on_action(cx.listener(Self::cancel))
             .on_action(cx.listener(Self::confirm))
             .on_action(cx.listener(Self::secondary_confirm))
@@ -364,24 +502,212 @@ impl<D: PickerDelegate> Render for Picker<D> {
             .when(self.delegate.match_count() > 0, |el| {
                 el.child(
                     v_flex()
+                        .debug_selector(|| "PICKER-matches".into())
+                        .relative()
                         .flex_grow()
-                        .max_h(self.max_height.unwrap_or(rems(18.).into()))
+                        .max_h(self.max_list_height(cx))
                         .overflow_hidden()
                         .children(self.delegate.render_header(cx))
-                        .child(self.render_element_container(cx)),
+                        .child(self.render_element_container(cx))
+                        .child(
+                            canvas({
+                                let picker = cx.view().clone();
+                                move |bounds, cx| {
+                                    picker.update(cx, |picker, _| {
+                                        picker.list_bounds = Some(*bounds);
+                                    })
+                                }
+                            })
+                            .absolute()
+                            .size_full(),
+                        ),
                 )
             })
             .when(self.delegate.match_count() == 0, |el| {
                 el.child(
-                    v_flex().flex_grow().py_2().child(
-                        ListItem::new("empty_state")
-                            .inset(true)
-                            .spacing(ListItemSpacing::Sparse)
-                            .disabled(true)
-                            .child(Label::new("No matches").color(Color::Muted)),
-                    ),
+                    v_flex()
+                        .debug_selector(|| "PICKER-empty-state".into())
+                        .flex_grow()
+                        .py_2()
+                        .child(
+                            ListItem::new("empty_state")
+                                .inset(true)
+                                .spacing(ListItemSpacing::Sparse)
+                                .disabled(true)
+                                .child(
+                                    Label::new(self.delegate.no_matches_text(cx))
+                                        .color(Color::Muted),
+                                ),
+                        ),
                 )
             })
             .children(self.delegate.render_footer(cx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::{px, size, Div, KeyBinding, TestAppContext, VisualTestContext};
+    use settings::SettingsStore;
+
+    use super::*;
+
+    struct TestDelegate {
+        match_count: usize,
+        selected_index: usize,
+    }
+
+    impl PickerDelegate for TestDelegate {
+        type ListItem = Div;
+
+        fn match_count(&self) -> usize {
+            self.match_count
+        }
+
+        fn selected_index(&self) -> usize {
+            self.selected_index
+        }
+
+        fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+            self.selected_index = ix;
+        }
+
+        fn placeholder_text(&self) -> Arc<str> {
+            "Select...".into()
+        }
+
+        fn update_matches(&mut self, _: String, _: &mut ViewContext<Picker<Self>>) -> Task<()> {
+            Task::ready(())
+        }
+
+        fn confirm(&mut self, _: bool, _: &mut ViewContext<Picker<Self>>) {}
+
+        fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+        fn render_match(
+            &self,
+            ix: usize,
+            _: bool,
+            _: &mut ViewContext<Picker<Self>>,
+        ) -> Option<Self::ListItem> {
+            Some(div().h(px(20.)).child(format!("Match {ix}")))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_list_height_on_small_windows(cx: &mut TestAppContext) {
+        let (_, cx) = open_picker(100, cx);
+
+        cx.simulate_resize(size(px(800.), px(600.)));
+        cx.update(|cx| cx.refresh());
+        assert_eq!(
+            cx.debug_bounds("PICKER-matches").unwrap().size.height,
+            px(288.),
+            "The list should take the default max height when the window is tall enough"
+        );
+
+        cx.simulate_resize(size(px(800.), px(300.)));
+        cx.update(|cx| cx.refresh());
+        assert_eq!(
+            cx.debug_bounds("PICKER-matches").unwrap().size.height,
+            px(300. - 144.),
+            "The list should fit into the window, leaving room for the query editor and margins"
+        );
+
+        cx.simulate_resize(size(px(800.), px(200.)));
+        cx.update(|cx| cx.refresh());
+        assert_eq!(
+            cx.debug_bounds("PICKER-matches").unwrap().size.height,
+            px(96.),
+            "The list should not get shorter than its minimum height"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_page_navigation(cx: &mut TestAppContext) {
+        let (picker, cx) = open_picker(100, cx);
+        cx.simulate_resize(size(px(800.), px(300.)));
+        cx.update(|cx| cx.refresh());
+
+        let page_size = picker.update(cx, |picker, _| picker.page_size());
+        assert!(
+            (2..10).contains(&page_size),
+            "Unexpected page size {page_size} for a 156px list with 20px matches"
+        );
+
+        cx.simulate_keystrokes("pagedown");
+        assert_eq!(selected_index(&picker, cx), page_size);
+
+        let next_page_size = picker.update(cx, |picker, _| picker.page_size());
+        cx.simulate_keystrokes("pagedown");
+        assert_eq!(selected_index(&picker, cx), page_size + next_page_size);
+
+        cx.simulate_keystrokes("pageup pageup");
+        assert_eq!(
+            selected_index(&picker, cx),
+            0,
+            "Page up should stop at the first match"
+        );
+
+        cx.simulate_keystrokes("ctrl-end");
+        assert_eq!(selected_index(&picker, cx), 99);
+        cx.simulate_keystrokes("pagedown");
+        assert_eq!(
+            selected_index(&picker, cx),
+            99,
+            "Page down should stop at the last match"
+        );
+
+        cx.simulate_keystrokes("pageup");
+        let page_size = picker.update(cx, |picker, _| picker.page_size());
+        assert_eq!(selected_index(&picker, cx), 99 - page_size);
+
+        cx.simulate_keystrokes("ctrl-home");
+        assert_eq!(selected_index(&picker, cx), 0);
+    }
+
+    #[gpui::test]
+    async fn test_page_navigation_with_few_matches(cx: &mut TestAppContext) {
+        let (picker, cx) = open_picker(3, cx);
+        cx.update(|cx| cx.refresh());
+
+        cx.simulate_keystrokes("pagedown");
+        assert_eq!(selected_index(&picker, cx), 2);
+        cx.simulate_keystrokes("pageup");
+        assert_eq!(selected_index(&picker, cx), 0);
+    }
+
+    fn selected_index(picker: &View<Picker<TestDelegate>>, cx: &mut VisualTestContext) -> usize {
+        picker.update(cx, |picker, _| picker.delegate.selected_index())
+    }
+
+    fn open_picker(
+        match_count: usize,
+        cx: &mut TestAppContext,
+    ) -> (View<Picker<TestDelegate>>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            cx.bind_keys([
+                KeyBinding::new("pageup", menu::SelectPageUp, None),
+                KeyBinding::new("pagedown", menu::SelectPageDown, None),
+                KeyBinding::new("ctrl-home", menu::SelectFirst, None),
+                KeyBinding::new("ctrl-end", menu::SelectLast, None),
+            ]);
+        });
+        let (picker, cx) = cx.add_window_view(|cx| {
+            Picker::uniform_list(
+                TestDelegate {
+                    match_count,
+                    selected_index: 0,
+                },
+                cx,
+            )
+        });
+        picker.update(cx, |picker, cx| picker.focus(cx));
+        (picker, cx)
+    }
+}
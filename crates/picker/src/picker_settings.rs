@@ -0,0 +1,56 @@
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{HighlightedLabel, ListItem};
+
+/// Settings of the pickers' rows, stored under the `picker` key.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PickerSettings {
+    /// Whether the selected row gets outlined and an opaque background, and the matched characters get bold.
+    pub high_contrast_selection: bool,
+    /// Whether the matched characters get underlined.
+    pub underline_matches: bool,
+}
+
+impl PickerSettings {
+    /// Applies the selection emphasis to the row of a match.
+    pub fn style_match_item(&self, item: ListItem) -> ListItem {
+        item.emphasize_selection(self.high_contrast_selection)
+    }
+
+    /// Applies the emphasis of the matched characters to the label of a match.
+    pub fn style_match_label(&self, label: HighlightedLabel) -> HighlightedLabel {
+        label
+            .bold_highlights(self.high_contrast_selection)
+            .underline_highlights(self.underline_matches)
+    }
+}
+
+/// Contents of the `picker` settings key.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct PickerSettingsContent {
+    /// Whether to outline the selected row and give it an opaque background, and to render the matched
+    /// characters bold, for the selection to stand out with the low contrast themes.
+    ///
+    /// Default: false
+    pub high_contrast_selection: Option<bool>,
+    /// Whether to underline the matched characters, for them to stand out regardless of the theme's colors.
+    ///
+    /// Default: false
+    pub underline_matches: Option<bool>,
+}
+
+impl Settings for PickerSettings {
+    const KEY: Option<&'static str> = Some("picker");
+
+    type FileContent = PickerSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
@@ -5,6 +5,7 @@ pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
 mod runnable_inventory;
+mod scripts_source;
 pub mod search;
 pub mod terminals;
 pub mod worktree;
@@ -93,11 +94,75 @@ use util::{
 pub use fs::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
-pub use runnable_inventory::Inventory;
+pub use runnable_inventory::{
+    Inventory, InventoryEvent, RunAttempt, RunRecord, RunStatus, ScheduledRunnable, SpawnPriority,
+    MAX_RUN_HISTORY_LEN,
+};
+pub use scripts_source::ScriptsSource;
 pub use worktree::*;
 
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
 
+/// Names the worktrees given (as their ids, root names and absolute paths) by their root names, followed by
+/// as many of their parent directories as it takes to tell apart the ones with the same root name:
+/// `zed (work)` and `zed (forks)`.
+pub fn worktree_display_names<'a>(
+    worktrees: impl IntoIterator<Item = (WorktreeId, &'a str, Arc<Path>)>,
+) -> HashMap<WorktreeId, String> {
+    let mut by_root_name = BTreeMap::<&str, Vec<(WorktreeId, Arc<Path>)>>::new();
+    for (id, root_name, abs_path) in worktrees {
+        by_root_name
+            .entry(root_name)
+            .or_default()
+            .push((id, abs_path));
+    }
+
+    let mut names = HashMap::default();
+    for (root_name, worktrees) in by_root_name {
+        if let [(id, _)] = worktrees.as_slice() {
+            names.insert(*id, root_name.to_string());
+            continue;
+        }
+        let max_depth = worktrees
+            .iter()
+            .map(|(_, abs_path)| abs_path.components().count().saturating_sub(1))
+            .max()
+            .unwrap_or(0);
+        let parents_suffix = |abs_path: &Path, depth: usize| {
+            let parents = abs_path
+                .parent()
+                .map(|parent| parent.components().collect::<Vec<_>>())
+                .unwrap_or_default();
+            let suffix_start = parents.len().saturating_sub(depth);
+            parents[suffix_start..]
+                .iter()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .collect::<PathBuf>()
+        };
+        let mut depth = 1;
+        while depth < max_depth {
+            let suffixes = worktrees
+                .iter()
+                .map(|(_, abs_path)| parents_suffix(abs_path, depth))
+                .collect::<HashSet<_>>();
+            if suffixes.len() == worktrees.len() {
+                break;
+            }
+            depth += 1;
+        }
+        for (id, abs_path) in &worktrees {
+            let suffix = parents_suffix(abs_path, depth);
+            let name = if suffix.as_os_str().is_empty() {
+                root_name.to_string()
+            } else {
+                format!("{root_name} ({})", suffix.display())
+            };
+            names.insert(*id, name);
+        }
+    }
+    names
+}
+
 pub trait Item {
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId>;
     fn project_path(&self, cx: &AppContext) -> Option<ProjectPath>;
@@ -551,6 +616,7 @@ impl SearchMatchCandidate {
 impl Project {
     pub fn init_settings(cx: &mut AppContext) {
         ProjectSettings::register(cx);
+        runnable::init(cx);
     }
 
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
@@ -1103,6 +1169,28 @@ impl Project {
             .map(|tree| tree.read(cx).root_name())
     }
 
+    /// Visible worktrees, rooted at a directory: the ones to pick among when a directory is needed.
+    pub fn directory_worktrees<'a>(
+        &'a self,
+        cx: &'a AppContext,
+    ) -> impl 'a + DoubleEndedIterator<Item = Model<Worktree>> {
+        self.visible_worktrees(cx).filter(|worktree| {
+            worktree
+                .read(cx)
+                .root_entry()
+                .map_or(false, |entry| entry.is_dir())
+        })
+    }
+
+    /// Names to present the visible worktrees with, as the project panel does.
+    /// See [`worktree_display_names`].
+    pub fn worktree_display_names(&self, cx: &AppContext) -> HashMap<WorktreeId, String> {
+        worktree_display_names(self.visible_worktrees(cx).map(|worktree| {
+            let worktree = worktree.read(cx);
+            (worktree.id(), worktree.root_name(), worktree.abs_path())
+        }))
+    }
+
     pub fn worktree_for_id(&self, id: WorktreeId, cx: &AppContext) -> Option<Model<Worktree>> {
         self.worktrees()
             .find(|worktree| worktree.read(cx).id() == id)
@@ -6727,6 +6815,7 @@ impl Project {
                 false
             }
         });
+        self.update_runnable_worktrees(cx);
         self.metadata_changed(cx);
     }
 
@@ -6739,6 +6828,9 @@ impl Project {
                     this.update_local_worktree_language_servers(&worktree, changes, cx);
                     this.update_local_worktree_settings(&worktree, changes, cx);
                     this.update_prettier_settings(&worktree, changes, cx);
+                    this.runnables.update(cx, |inventory, cx| {
+                        inventory.worktree_entries_changed(changes, cx)
+                    });
                     cx.emit(Event::WorktreeUpdatedEntries(
                         worktree.read(cx).id(),
                         changes.clone(),
@@ -6774,10 +6866,20 @@ impl Project {
         })
         .detach();
 
+        self.update_runnable_worktrees(cx);
         cx.emit(Event::WorktreeAdded);
         self.metadata_changed(cx);
     }
 
+    fn update_runnable_worktrees(&mut self, cx: &mut ModelContext<Self>) {
+        let worktrees = self
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.downgrade())
+            .collect();
+        self.runnables
+            .update(cx, |inventory, cx| inventory.set_worktrees(worktrees, cx));
+    }
+
     fn update_local_worktree_buffers(
         &mut self,
         worktree_handle: &Model<Worktree>,
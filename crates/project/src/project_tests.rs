@@ -4419,6 +4419,69 @@ async fn search(
         .collect())
 }
 
+#[gpui::test]
+async fn test_worktree_display_names(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/",
+        json!({
+            "work": {
+                "zed": { "a.rs": "" },
+                "notes": { "todo.md": "" },
+                "readme.md": "",
+            },
+            "forks": {
+                "zed": { "b.rs": "" },
+            },
+            "archive": {
+                "work": {
+                    "notes": { "done.md": "" },
+                },
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(
+        fs,
+        [
+            "/work/zed".as_ref(),
+            "/forks/zed".as_ref(),
+            "/work/notes".as_ref(),
+            "/archive/work/notes".as_ref(),
+            "/work/readme.md".as_ref(),
+        ],
+        cx,
+    )
+    .await;
+
+    project.update(cx, |project, cx| {
+        let names = project.worktree_display_names(cx);
+        let name = |worktree: Model<Worktree>| names[&worktree.read(cx).id()].clone();
+        assert_eq!(
+            project.visible_worktrees(cx).map(name).collect::<Vec<_>>(),
+            [
+                "zed (work)",
+                "zed (forks)",
+                "notes (work)",
+                "notes (archive/work)",
+                "readme.md",
+            ],
+            "Duplicate root names should get as many parent directories as it takes to tell them apart"
+        );
+        assert_eq!(
+            project.directory_worktrees(cx).map(name).collect::<Vec<_>>(),
+            [
+                "zed (work)",
+                "zed (forks)",
+                "notes (work)",
+                "notes (archive/work)",
+            ],
+            "Single-file worktrees should not be offered as directories"
+        );
+    });
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
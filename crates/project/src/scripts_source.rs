@@ -0,0 +1,317 @@
+//! A source of runnables, made of the executable files found in the script directories of the project's worktrees.
+
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription, Task};
+use runnable::{
+    Runnable, RunnableId, RunnablesSettings, Source, SpawnInTerminal, StructuredOutput,
+};
+use settings::{Settings, SettingsStore};
+
+use crate::{Event, Project};
+
+/// How deep into the script directories the executables are looked for: 1 means no subdirectories.
+const MAX_SCRIPT_DEPTH: usize = 2;
+
+/// The source of runnables, running the executables from the `runnables.script_dirs` of every local worktree.
+pub struct ScriptsSource {
+    runnables: Vec<Arc<ScriptRunnable>>,
+    project: Model<Project>,
+    script_dirs: Vec<String>,
+    _refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// An executable file from a script directory, spawned with no arguments from its worktree root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScriptRunnable {
+    id: RunnableId,
+    /// Path of the script, relative to its worktree root.
+    name: String,
+    abs_path: PathBuf,
+    worktree_root: PathBuf,
+}
+
+impl Runnable for ScriptRunnable {
+    fn id(&self) -> &RunnableId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.worktree_root)
+    }
+
+    fn project_root(&self) -> Option<&Path> {
+        Some(&self.worktree_root)
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal> {
+        Ok(SpawnInTerminal {
+            id: self.id.clone(),
+            label: self.name.clone(),
+            command: self.abs_path.to_string_lossy().into_owned(),
+            args: Vec::new(),
+            cwd,
+            env: HashMap::default(),
+            clean_env: false,
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: true,
+            structured_output: StructuredOutput::None,
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
+            retry: None,
+            tty: true,
+        })
+    }
+}
+
+impl ScriptsSource {
+    /// Initializes the source, rescanning the script directories whenever they or the settings change.
+    pub fn new(project: Model<Project>, cx: &mut AppContext) -> Model<Box<dyn Source>> {
+        cx.new_model(|cx| {
+            let _subscriptions = vec![
+                cx.subscribe(&project, |source: &mut Box<dyn Source>, _, event, cx| {
+                    if let Some(scripts_source) = source.as_any().downcast_mut::<Self>() {
+                        let needs_refresh = match event {
+                            Event::WorktreeAdded | Event::WorktreeRemoved(_) => true,
+                            Event::WorktreeUpdatedEntries(_, changes) => changes
+                                .iter()
+                                .any(|(path, _, _)| scripts_source.is_in_script_dir(path)),
+                            _ => false,
+                        };
+                        if needs_refresh {
+                            scripts_source.refresh(cx);
+                        }
+                    }
+                }),
+                cx.observe_global::<SettingsStore>(|source: &mut Box<dyn Source>, cx| {
+                    if let Some(scripts_source) = source.as_any().downcast_mut::<Self>() {
+                        let script_dirs = RunnablesSettings::get_global(cx).script_dirs.clone();
+                        if scripts_source.script_dirs != script_dirs {
+                            scripts_source.script_dirs = script_dirs;
+                            scripts_source.refresh(cx);
+                        }
+                    }
+                }),
+            ];
+            let mut scripts_source = Self {
+                runnables: Vec::new(),
+                project,
+                script_dirs: RunnablesSettings::get_global(cx).script_dirs.clone(),
+                _refresh_task: Task::ready(()),
+                _subscriptions,
+            };
+            scripts_source.refresh(cx);
+            Box::new(scripts_source)
+        })
+    }
+
+    fn is_in_script_dir(&self, path: &Path) -> bool {
+        self.script_dirs
+            .iter()
+            .any(|script_dir| path.starts_with(script_dir))
+    }
+
+    fn refresh(&mut self, cx: &mut ModelContext<Box<dyn Source>>) {
+        let project = self.project.read(cx);
+        let fs = project.fs().clone();
+        let mut candidates = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let Some(worktree) = worktree.as_local() else {
+                continue;
+            };
+            let worktree_root = worktree.abs_path().to_path_buf();
+            for script_dir in &self.script_dirs {
+                let script_dir = Path::new(script_dir);
+                for entry in worktree.descendent_entries(false, false, script_dir) {
+                    let depth = entry
+                        .path
+                        .strip_prefix(script_dir)
+                        .map_or(usize::MAX, |path| path.components().count());
+                    if depth <= MAX_SCRIPT_DEPTH {
+                        candidates.push(ScriptRunnable {
+                            id: RunnableId::new(format!(
+                                "script:{}",
+                                worktree_root.join(&entry.path).display()
+                            )),
+                            name: entry.path.to_string_lossy().into_owned(),
+                            abs_path: worktree_root.join(&entry.path),
+                            worktree_root: worktree_root.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        self._refresh_task = cx.spawn(|source, mut cx| async move {
+            let is_executable = join_all(candidates.iter().map(|candidate| {
+                let fs = fs.clone();
+                async move {
+                    fs.metadata(&candidate.abs_path)
+                        .await
+                        .ok()
+                        .flatten()
+                        .map_or(false, |metadata| metadata.is_executable)
+                }
+            }))
+            .await;
+            let runnables = candidates
+                .into_iter()
+                .zip(is_executable)
+                .filter(|(_, is_executable)| *is_executable)
+                .map(|(candidate, _)| Arc::new(candidate))
+                .collect::<Vec<_>>();
+            source
+                .update(&mut cx, |source, cx| {
+                    if let Some(scripts_source) = source.as_any().downcast_mut::<Self>() {
+                        if scripts_source.runnables != runnables {
+                            scripts_source.runnables = runnables;
+                            cx.notify();
+                        }
+                    }
+                })
+                .ok();
+        });
+    }
+}
+
+impl Source for ScriptsSource {
+    fn name(&self) -> &str {
+        "scripts"
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn runnables_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn Source>>,
+    ) -> Vec<Arc<dyn Runnable>> {
+        self.runnables
+            .iter()
+            .map(|runnable| runnable.clone() as Arc<dyn Runnable>)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init("0.0.0", cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            runnable::init(cx);
+        });
+    }
+
+    fn runnable_names(source: &Model<Box<dyn Source>>, cx: &mut TestAppContext) -> Vec<String> {
+        source.update(cx, |source, cx| {
+            source
+                .runnables_for_path(None, cx)
+                .iter()
+                .map(|runnable| runnable.name().to_string())
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_scripts_source(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".gitignore": "scripts/generated.sh",
+                "scripts": {
+                    "build.sh": "#!/bin/sh",
+                    "README.md": "Scripts to run",
+                    "generated.sh": "#!/bin/sh",
+                    "ci": {
+                        "lint.sh": "#!/bin/sh",
+                        "nested": {
+                            "deep.sh": "#!/bin/sh",
+                        },
+                    },
+                },
+                "src": {
+                    "main.sh": "#!/bin/sh",
+                },
+            }),
+        )
+        .await;
+        for path in [
+            "/root/scripts/build.sh",
+            "/root/scripts/generated.sh",
+            "/root/scripts/ci/lint.sh",
+            "/root/scripts/ci/nested/deep.sh",
+            "/root/src/main.sh",
+        ] {
+            fs.set_executable(path, true);
+        }
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let source = cx.update(|cx| ScriptsSource::new(project.clone(), cx));
+        cx.run_until_parked();
+        assert_eq!(
+            runnable_names(&source, cx),
+            ["scripts/build.sh", "scripts/ci/lint.sh"],
+            "Only the executable, not ignored files up to the depth limit should be runnables"
+        );
+        let spawn_in_terminal = source.update(cx, |source, cx| {
+            let runnable = source.runnables_for_path(None, cx)[0].clone();
+            runnable
+                .exec(runnable.cwd().map(Path::to_path_buf))
+                .unwrap()
+        });
+        assert_eq!(spawn_in_terminal.command, "/root/scripts/build.sh");
+        assert_eq!(spawn_in_terminal.cwd, Some(PathBuf::from("/root")));
+
+        fs.insert_file("/root/scripts/deploy.sh", "#!/bin/sh".to_string())
+            .await;
+        fs.set_executable("/root/scripts/deploy.sh", true);
+        fs.insert_file("/root/scripts/notes.txt", "TODO".to_string())
+            .await;
+        cx.run_until_parked();
+        assert_eq!(
+            runnable_names(&source, cx),
+            [
+                "scripts/build.sh",
+                "scripts/ci/lint.sh",
+                "scripts/deploy.sh"
+            ]
+        );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<RunnablesSettings>(cx, |settings| {
+                    settings.script_dirs = Some(vec!["src".to_string()]);
+                });
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(runnable_names(&source, cx), ["src/main.sh"]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Project;
 use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use runnable::StructuredOutput;
 use settings::Settings;
 use smol::channel::bounded;
 use std::path::{Path, PathBuf};
@@ -32,35 +33,63 @@ impl Project {
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
         let mut env = settings.env.clone();
-        let (spawn_runnable, shell) = if let Some(spawn_runnable) = spawn_runnable {
-            env.extend(spawn_runnable.env);
-            (
-                Some(RunableState {
-                    id: spawn_runnable.id,
-                    label: spawn_runnable.label,
-                    completed: false,
-                    completion_rx,
-                }),
-                Shell::WithArguments {
+        let builder = if let Some(spawn_runnable) = spawn_runnable {
+            let clean_env = spawn_runnable.clean_env;
+            if clean_env {
+                env = terminal::clean_runnable_env(&spawn_runnable.env);
+            } else {
+                env.extend(spawn_runnable.env);
+            }
+            let runnable = RunableState {
+                id: spawn_runnable.id,
+                label: spawn_runnable.label,
+                completed: false,
+                completion_rx,
+                structured_output: spawn_runnable.structured_output,
+            };
+            // The structured output gets parsed from the process's stdout, which a PTY would mix with stderr.
+            if spawn_runnable.tty && spawn_runnable.structured_output == StructuredOutput::None {
+                let shell = Shell::WithArguments {
                     program: spawn_runnable.command,
                     args: spawn_runnable.args,
-                },
-            )
+                };
+                TerminalBuilder::new(
+                    working_directory.clone(),
+                    Some(runnable),
+                    shell,
+                    env,
+                    clean_env,
+                    Some(settings.blinking.clone()),
+                    settings.alternate_scroll,
+                    window,
+                    completion_tx,
+                )
+            } else {
+                TerminalBuilder::new_piped(
+                    working_directory.clone(),
+                    runnable,
+                    spawn_runnable.command,
+                    spawn_runnable.args,
+                    env,
+                    clean_env,
+                    completion_tx,
+                )
+            }
         } else {
-            (None, settings.shell.clone())
+            TerminalBuilder::new(
+                working_directory.clone(),
+                None,
+                settings.shell.clone(),
+                env,
+                false,
+                Some(settings.blinking.clone()),
+                settings.alternate_scroll,
+                window,
+                completion_tx,
+            )
         };
 
-        let terminal = TerminalBuilder::new(
-            working_directory.clone(),
-            spawn_runnable,
-            shell,
-            env,
-            Some(settings.blinking.clone()),
-            settings.alternate_scroll,
-            window,
-            completion_tx,
-        )
-        .map(|builder| {
+        let terminal = builder.map(|builder| {
             let terminal_handle = cx.new_model(|cx| builder.subscribe(cx));
 
             self.terminals
@@ -23,7 +23,7 @@ use project::{
 };
 use project_panel_settings::{ProjectPanelDockPosition, ProjectPanelSettings};
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, ffi::OsStr, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Ordering, ops::Range, path::Path, sync::Arc};
 use theme::ThemeSettings;
 use ui::{prelude::*, v_flex, ContextMenu, Icon, KeyBinding, Label, ListItem};
 use unicase::UniCase;
@@ -113,6 +113,7 @@ actions!(
         CopyRelativePath,
         RevealInFinder,
         OpenInTerminal,
+        RunTaskHere,
         Cut,
         Paste,
         Delete,
@@ -439,6 +440,7 @@ impl ProjectPanel {
                         .action("Reveal in Finder", Box::new(RevealInFinder))
                         .when(is_dir, |menu| {
                             menu.action("Open in Terminal", Box::new(OpenInTerminal))
+                                .action("Run Task Here…", Box::new(RunTaskHere))
                                 .action("Search Inside", Box::new(NewSearchInDirectory))
                         })
                         .separator()
@@ -987,6 +989,22 @@ impl ProjectPanel {
         }
     }
 
+    /// Opens the runnables modal with the selected directory as the working directory of the runnables spawned from it.
+    fn run_task_here(&mut self, _: &RunTaskHere, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if !entry.is_dir() {
+                return;
+            }
+            let path = worktree.abs_path().join(&entry.path);
+            cx.dispatch_action(
+                zed_actions::Spawn {
+                    cwd_override: Some(path),
+                }
+                .boxed_clone(),
+            )
+        }
+    }
+
     pub fn new_search_in_directory(
         &mut self,
         _: &NewSearchInDirectory,
@@ -1245,6 +1263,7 @@ impl ProjectPanel {
         mut callback: impl FnMut(ProjectEntryId, EntryDetails, &mut ViewContext<ProjectPanel>),
     ) {
         let mut ix = 0;
+        let worktree_names = self.project.read(cx).worktree_display_names(cx);
         for (worktree_id, visible_worktree_entries) in &self.visible_entries {
             if ix >= range.end {
                 return;
@@ -1266,7 +1285,9 @@ impl ProjectPanel {
             };
             if let Some(worktree) = self.project.read(cx).worktree_for_id(*worktree_id, cx) {
                 let snapshot = worktree.read(cx).snapshot();
-                let root_name = OsStr::new(snapshot.root_name());
+                let root_name = worktree_names
+                    .get(worktree_id)
+                    .map_or(snapshot.root_name(), String::as_str);
                 let expanded_entry_ids = self
                     .expanded_dir_ids
                     .get(&snapshot.id())
@@ -1295,12 +1316,10 @@ impl ProjectPanel {
                     };
 
                     let mut details = EntryDetails {
-                        filename: entry
-                            .path
-                            .file_name()
-                            .unwrap_or(root_name)
-                            .to_string_lossy()
-                            .to_string(),
+                        filename: entry.path.file_name().map_or_else(
+                            || root_name.to_string(),
+                            |file_name| file_name.to_string_lossy().to_string(),
+                        ),
                         icon,
                         path: entry.path.clone(),
                         depth: entry.path.components().count(),
@@ -1515,6 +1534,7 @@ impl Render for ProjectPanel {
                 .when(project.is_local(), |el| {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_in_terminal))
+                        .on_action(cx.listener(Self::run_task_here))
                 })
                 .on_mouse_down(
                     MouseButton::Right,
@@ -20,19 +20,28 @@ actions!(project_symbols, [Toggle]);
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
-            workspace.register_action(|workspace, _: &Toggle, cx| {
-                let project = workspace.project().clone();
-                let handle = cx.view().downgrade();
-                workspace.toggle_modal(cx, move |cx| {
-                    let delegate = ProjectSymbolsDelegate::new(handle, project);
-                    Picker::uniform_list(delegate, cx).width(rems(34.))
-                })
-            });
+            workspace
+                .register_action(|workspace, _: &Toggle, cx| toggle_with_query(workspace, "", cx));
         },
     )
     .detach();
 }
 
+/// Toggles the project symbols modal, searching for the query given.
+pub fn toggle_with_query(workspace: &mut Workspace, query: &str, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    let handle = cx.view().downgrade();
+    let query = query.to_string();
+    workspace.toggle_modal(cx, move |cx| {
+        let delegate = ProjectSymbolsDelegate::new(handle, project);
+        let picker = Picker::uniform_list(delegate, cx).width(rems(34.));
+        if !query.is_empty() {
+            picker.set_query(query, cx);
+        }
+        picker
+    })
+}
+
 pub type ProjectSymbols = View<Picker<ProjectSymbolsDelegate>>;
 
 pub struct ProjectSymbolsDelegate {
@@ -0,0 +1,111 @@
+//! Minimal environments of the runnables defined with `clean_env`, for their builds not to depend on
+//! the variables inherited from Zed, the user's shell or the terminal settings.
+
+use collections::HashMap;
+
+/// Variables the runnables with a clean environment inherit, besides their explicit env.
+#[cfg(not(windows))]
+pub const CLEAN_ENV_ALLOWLIST: &[&str] = &["PATH", "HOME", "TERM"];
+
+/// Variables the runnables with a clean environment inherit, besides their explicit env.
+/// Windows programs fail to start without the system directories' variables, matched case-insensitively there.
+#[cfg(windows)]
+pub const CLEAN_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "TERM",
+    "SystemRoot",
+    "SystemDrive",
+    "windir",
+    "ComSpec",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+];
+
+/// The environment of a runnable with `clean_env` set: the allowlisted variables of the inherited ones,
+/// overridden by the runnable's explicit env.
+pub fn clean_env(
+    inherited: impl IntoIterator<Item = (String, String)>,
+    explicit: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut env = inherited
+        .into_iter()
+        .filter(|(key, _)| is_allowlisted(key))
+        .collect::<HashMap<_, _>>();
+    env.extend(
+        explicit
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+    env
+}
+
+fn is_allowlisted(key: &str) -> bool {
+    CLEAN_ENV_ALLOWLIST.iter().any(|allowed| {
+        if cfg!(windows) {
+            allowed.eq_ignore_ascii_case(key)
+        } else {
+            *allowed == key
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_env() {
+        let inherited = vars(&[
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/zed"),
+            ("TERM", "alacritty"),
+            ("RUSTFLAGS", "-C target-cpu=native"),
+            ("ZED_TERM", "true"),
+            ("LANG", "en_US.UTF-8"),
+        ]);
+        let explicit = HashMap::from_iter(vars(&[
+            ("PATH", "/opt/toolchain/bin"),
+            ("CARGO_INCREMENTAL", "0"),
+        ]));
+
+        let mut env = clean_env(inherited, &explicit)
+            .into_iter()
+            .collect::<Vec<_>>();
+        env.sort();
+        assert_eq!(
+            env,
+            vars(&[
+                ("CARGO_INCREMENTAL", "0"),
+                ("HOME", "/home/zed"),
+                ("PATH", "/opt/toolchain/bin"),
+                ("TERM", "alacritty"),
+            ]),
+            "Only the allowlisted variables should be inherited, the explicit ones taking precedence"
+        );
+        assert!(clean_env(Vec::new(), &HashMap::default()).is_empty());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_clean_env_on_windows() {
+        let inherited = vars(&[
+            ("Path", r"C:\Windows\system32"),
+            ("SYSTEMROOT", r"C:\Windows"),
+            ("USERNAME", "zed"),
+        ]);
+        let mut keys = clean_env(inherited, &HashMap::default())
+            .into_keys()
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["Path", "SYSTEMROOT"]);
+    }
+}
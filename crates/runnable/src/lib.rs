@@ -1,22 +1,53 @@
 //! Baseline interface of Runnables in Zed: all runnables in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod clean_env;
+mod oneshot_runnable;
+mod redaction;
+mod runnable_variables;
+mod runnables_settings;
 mod static_runnable;
 pub mod static_source;
+mod structured_output;
 
+pub use clean_env::{clean_env, CLEAN_ENV_ALLOWLIST};
+pub use oneshot_runnable::OneshotRunnable;
+pub use redaction::{is_secret_env_key, redact_env, redact_text, REDACTED};
+pub use runnable_variables::{
+    register_variable_provider, resolve_variables, RunnableContext, VariableProvider,
+    ZED_VARIABLE_PREFIX,
+};
+pub use runnables_settings::{RunnablesOnClose, RunnablesSettings, RunnablesSettingsContent};
 pub use static_runnable::StaticRunnable;
+pub use structured_output::{StructuredOutput, TestFailure, TestLocation, TestResults};
 
 use collections::HashMap;
-use gpui::ModelContext;
+use gpui::{AppContext, ModelContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
 use std::any::Any;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Registers the settings shared by all runnables.
+pub fn init(cx: &mut AppContext) {
+    RunnablesSettings::register(cx);
+}
+
 /// Runnable identifier, unique within the application.
 /// Based on it, runnable reruns and terminal tabs are managed.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct RunnableId(String);
 
+impl RunnableId {
+    /// Creates a new runnable identifier out of the string given.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
 /// Contains all information needed by Zed to spawn a new terminal tab for the given runnable.
 #[derive(Debug, Clone)]
 pub struct SpawnInTerminal {
@@ -32,10 +63,104 @@ pub struct SpawnInTerminal {
     pub cwd: Option<PathBuf>,
     /// Env overrides for the command, will be appended to the terminal's environment from the settings.
     pub env: HashMap<String, String>,
+    /// Whether to spawn the command with only the [`CLEAN_ENV_ALLOWLIST`] variables inherited, besides `env`,
+    /// ignoring the terminal's environment from the settings.
+    pub clean_env: bool,
     /// Whether to use a new terminal tab or reuse the existing one to spawn the process.
     pub use_new_terminal: bool,
     /// Whether to allow multiple instances of the same runnable to be run, or rather wait for the existing ones to finish.
     pub allow_concurrent_runs: bool,
+    /// Whether to show the terminal with the runnable, once it's spawned.
+    pub reveal: bool,
+    /// Whether the command's output goes to a PTY, or to plain pipes, shown in the terminal all the same.
+    pub tty: bool,
+    /// The format of the structured output to parse the test results from, once the runnable finishes.
+    pub structured_output: StructuredOutput,
+    /// Actions to dispatch to the workspace, once the command exits successfully.
+    pub on_success: Vec<ChainedAction>,
+    /// Actions to dispatch to the workspace, once the command exits with a failure.
+    pub on_failure: Vec<ChainedAction>,
+    /// How to run the command again, once it exits with a failure.
+    pub retry: Option<RetryPolicy>,
+}
+
+/// An action dispatched once a runnable's process exits, e.g. to reload the buffers regenerated by the command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ChainedAction {
+    /// Name of the action, as registered in Zed, e.g. `"workspace::ReloadActiveItem"`.
+    pub name: String,
+    /// Arguments of the action, for the actions taking any.
+    #[serde(default)]
+    pub args: Option<serde_json::Value>,
+}
+
+/// How a runnable gets run again automatically once its run fails, e.g. for the flaky tests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RetryPolicy {
+    /// How many times the runnable gets run at most, the first run included.
+    pub attempts: usize,
+    /// How many seconds to wait before running the runnable again.
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// Exit codes of the runs to retry, all the failed runs are retried when empty.
+    #[serde(default)]
+    pub only_on_exit_codes: Vec<i32>,
+}
+
+impl RetryPolicy {
+    /// Whether the attempt with the number given, counting from 1, should be followed by another one,
+    /// after failing with the exit code given.
+    pub fn should_retry(&self, attempt: usize, exit_code: i32) -> bool {
+        attempt < self.attempts
+            && (self.only_on_exit_codes.is_empty() || self.only_on_exit_codes.contains(&exit_code))
+    }
+}
+
+/// Adjustments of a runnable, made by the user right before scheduling it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnableOverrides {
+    /// Current working directory to use instead of the runnable's one.
+    pub cwd: Option<PathBuf>,
+    /// Arguments, appended to the runnable's ones.
+    pub extra_args: Vec<String>,
+    /// Whether to use a new terminal tab or reuse the existing one, instead of the runnable's choice.
+    pub use_new_terminal: Option<bool>,
+    /// Whether to show the terminal with the runnable, instead of the runnable's choice.
+    pub reveal: Option<bool>,
+}
+
+impl RunnableOverrides {
+    /// Applies the overrides (except the cwd, which has to be passed into [`Runnable::exec`]) to the spawn payload given.
+    pub fn apply(&self, spawn_in_terminal: &mut SpawnInTerminal) {
+        spawn_in_terminal
+            .args
+            .extend(self.extra_args.iter().cloned());
+        if let Some(use_new_terminal) = self.use_new_terminal {
+            spawn_in_terminal.use_new_terminal = use_new_terminal;
+        }
+        if let Some(reveal) = self.reveal {
+            spawn_in_terminal.reveal = reveal;
+        }
+    }
+}
+
+/// A requirement for the runnable to be offered for scheduling, checked against the project it's listed in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// A file or a directory with the path given, relative to the worktree root, exists in any of the project's worktrees.
+    FileExists(PathBuf),
+    /// The environment variable with the name given is set for Zed.
+    EnvSet(String),
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileExists(path) => write!(f, "file_exists: {}", path.display()),
+            Self::EnvSet(name) => write!(f, "env_set: {name}"),
+        }
+    }
 }
 
 /// Represents a short lived recipe of a runnable, whose main purpose
@@ -49,7 +174,47 @@ pub trait Runnable {
     fn cwd(&self) -> Option<&Path>;
     /// Sets up everything needed to spawn the runnable in the given directory (`cwd`).
     /// If a runnable is intended to be spawned in the terminal, it should return the corresponding struct filled with the data necessary.
-    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal>;
+    /// Fails with the reason the runnable cannot be spawned, e.g. a missing variable or an unsupported platform, shown to the user.
+    fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal>;
+    /// The condition to satisfy for the runnable to be offered, if any.
+    fn enabled_if(&self) -> Option<&Condition> {
+        None
+    }
+    /// Root of the worktree, whose files define the runnable, if it comes from the project rather than from the user.
+    /// Such runnables are not spawned until the user trusts their worktree.
+    fn project_root(&self) -> Option<&Path> {
+        None
+    }
+    /// A free-form note about the runnable from its definition, shown with it in the UI.
+    fn note(&self) -> Option<&str> {
+        None
+    }
+    /// Index of the runnable's definition in the runnables config file, if it's defined there,
+    /// for the UI to edit the definition in place.
+    fn definition_ix(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// A collapsed group of runnables, e.g. all targets of a package, with the runnables materialized one by one on demand.
+/// Sources with lots of runnables list their groups instead of every runnable.
+///
+/// The runnables of a group are offered regardless of their [`Runnable::enabled_if`] conditions.
+pub trait RunnableGroup {
+    /// Human readable name of the group to display in the UI, unique within its source.
+    fn name(&self) -> &str;
+    /// Names of the group's runnables, in the order of their indices: listing them should be cheaper than materializing the runnables.
+    fn runnable_names(&self) -> Vec<String>;
+    /// Materializes the group's runnable with the index given.
+    fn runnable(&self, ix: usize) -> Option<Arc<dyn Runnable>>;
+    /// Materializes the group's runnable with the id given, if it's in the group.
+    ///
+    /// By default, the group's runnables get materialized one after another until the one with the id is found.
+    fn find_runnable(&self, id: &RunnableId) -> Option<Arc<dyn Runnable>> {
+        (0..self.runnable_names().len())
+            .filter_map(|ix| self.runnable(ix))
+            .find(|runnable| runnable.id() == id)
+    }
 }
 
 /// [`Source`] produces runnables that can be scheduled.
@@ -57,6 +222,8 @@ pub trait Runnable {
 /// Implementations of this trait could be e.g. [`StaticSource`] that parses runnables from a .json files and provides process templates to be spawned;
 /// another one could be a language server providing lenses with tests or build server listing all targets for a given project.
 pub trait Source: Any {
+    /// Human readable name of the source, used to tell where a runnable comes from.
+    fn name(&self) -> &str;
     /// A way to erase the type of the source, processing and storing them generically.
     fn as_any(&mut self) -> &mut dyn Any;
     /// Collects all runnables available for scheduling, for the path given.
@@ -65,4 +232,12 @@ pub trait Source: Any {
         path: Option<&Path>,
         cx: &mut ModelContext<Box<dyn Source>>,
     ) -> Vec<Arc<dyn Runnable>>;
+    /// Collects the groups of runnables available for scheduling, for the path given, besides the ones listed by [`Self::runnables_for_path`].
+    fn runnable_groups_for_path(
+        &mut self,
+        _path: Option<&Path>,
+        _cx: &mut ModelContext<Box<dyn Source>>,
+    ) -> Vec<Arc<dyn RunnableGroup>> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,207 @@
+//! Runnables spawned from a command line typed in by the user, or from a script dropped onto the terminal panel,
+//! not defined in any of the sources.
+
+use std::path::{Path, PathBuf};
+
+use collections::HashMap;
+
+use crate::{Runnable, RunnableId, SpawnInTerminal, StructuredOutput};
+
+const ONESHOT_ID_PREFIX: &str = "oneshot_";
+/// Extensions of the scripts run with their interpreters, when not executable themselves.
+const SCRIPT_INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("py", "python3"),
+    ("rb", "ruby"),
+    ("pl", "perl"),
+];
+
+/// A command line to run once, in the shell.
+/// Reruns of the same command line share the id, so that their runs are counted together.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OneshotRunnable {
+    id: RunnableId,
+    command_line: String,
+    cwd: Option<PathBuf>,
+    project_root: Option<PathBuf>,
+}
+
+impl OneshotRunnable {
+    /// Creates a runnable out of the command line given, trimmed.
+    pub fn new(command_line: &str) -> Self {
+        let command_line = command_line.trim().to_string();
+        Self {
+            id: RunnableId(format!(
+                "{ONESHOT_ID_PREFIX}{:016x}",
+                hash_command_line(&command_line)
+            )),
+            command_line,
+            cwd: None,
+            project_root: None,
+        }
+    }
+
+    /// Creates a runnable running the script in its directory: directly, if it's executable, or with the interpreter
+    /// of its extension otherwise. `None` if the file is neither executable nor of a known script extension.
+    pub fn for_script(path: &Path, is_executable: bool) -> Option<Self> {
+        // The full path keeps the ids of the same-named scripts of different directories apart.
+        let script = quote_for_shell(path.to_str()?);
+        let command_line = if is_executable {
+            script
+        } else {
+            let (_, interpreter) = SCRIPT_INTERPRETERS
+                .iter()
+                .find(|(extension, _)| Self::has_extension(path, extension))?;
+            format!("{interpreter} {script}")
+        };
+        Some(Self {
+            cwd: path.parent().map(Path::to_path_buf),
+            ..Self::new(&command_line)
+        })
+    }
+
+    /// Whether the file is run with an interpreter by its extension, regardless of whether it's executable.
+    pub fn is_script(path: &Path) -> bool {
+        SCRIPT_INTERPRETERS
+            .iter()
+            .any(|(extension, _)| Self::has_extension(path, extension))
+    }
+
+    fn has_extension(path: &Path, extension: &str) -> bool {
+        path.extension().and_then(|ext| ext.to_str()) == Some(extension)
+    }
+
+    /// Marks the runnable as coming from the worktree given, for it not to be spawned until the user trusts the worktree.
+    pub fn with_project_root(mut self, project_root: PathBuf) -> Self {
+        self.project_root = Some(project_root);
+        self
+    }
+
+    /// The command line the runnable runs.
+    pub fn command_line(&self) -> &str {
+        &self.command_line
+    }
+
+    /// Whether the id given belongs to a one-shot runnable.
+    pub fn is_oneshot(id: &RunnableId) -> bool {
+        id.0.starts_with(ONESHOT_ID_PREFIX)
+    }
+
+    /// The hash of the one-shot runnable's command line, out of its id, stable between the releases.
+    pub fn command_hash(id: &RunnableId) -> Option<u64> {
+        let hash = id.0.strip_prefix(ONESHOT_ID_PREFIX)?;
+        u64::from_str_radix(hash, 16).ok()
+    }
+}
+
+/// Single-quotes the text for `sh`, unless it's made of the characters safe to pass as they are.
+fn quote_for_shell(text: &str) -> String {
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./+=:@".contains(c))
+    {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn hash_command_line(command_line: &str) -> u64 {
+    // FNV-1a: unlike the std hashers, it is guaranteed not to change.
+    command_line
+        .trim()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+impl Runnable for OneshotRunnable {
+    fn id(&self) -> &RunnableId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.command_line
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    fn project_root(&self) -> Option<&Path> {
+        self.project_root.as_deref()
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal> {
+        Ok(SpawnInTerminal {
+            id: self.id.clone(),
+            label: self.command_line.clone(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), self.command_line.clone()],
+            cwd,
+            env: HashMap::default(),
+            clean_env: false,
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: true,
+            tty: true,
+            structured_output: StructuredOutput::None,
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
+            retry: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oneshot_ids() {
+        let runnable = OneshotRunnable::new(" cargo test -p editor ");
+        assert_eq!(runnable.command_line(), "cargo test -p editor");
+        assert_eq!(
+            runnable.id(),
+            OneshotRunnable::new("cargo test -p editor").id()
+        );
+        assert_ne!(runnable.id(), OneshotRunnable::new("cargo test").id());
+        assert!(OneshotRunnable::is_oneshot(runnable.id()));
+        assert!(!OneshotRunnable::is_oneshot(&RunnableId::new(
+            "static_test_0"
+        )));
+        assert_eq!(
+            OneshotRunnable::command_hash(OneshotRunnable::new("cargo test").id()),
+            Some(0xaafd_9dd9_f42b_63a5),
+            "The hashes get persisted, so they should never change"
+        );
+    }
+
+    #[test]
+    fn test_script_runnables() {
+        let deploy = OneshotRunnable::for_script(Path::new("/root/scripts/deploy"), true).unwrap();
+        assert_eq!(deploy.command_line(), "/root/scripts/deploy");
+        assert_eq!(deploy.cwd(), Some(Path::new("/root/scripts")));
+        assert_eq!(deploy.project_root(), None);
+        let deploy = deploy.with_project_root(PathBuf::from("/root"));
+        assert_eq!(deploy.project_root(), Some(Path::new("/root")));
+
+        let setup = OneshotRunnable::for_script(Path::new("/root/set up.py"), false).unwrap();
+        assert_eq!(setup.command_line(), "python3 '/root/set up.py'");
+        assert_eq!(setup.cwd(), Some(Path::new("/root")));
+        assert_eq!(
+            OneshotRunnable::for_script(Path::new("/root/it's.sh"), true)
+                .unwrap()
+                .command_line(),
+            r"'/root/it'\''s.sh'"
+        );
+
+        assert!(OneshotRunnable::for_script(Path::new("/root/README.md"), false).is_none());
+        assert!(!OneshotRunnable::is_script(Path::new("/root/README.md")));
+        assert!(OneshotRunnable::is_script(Path::new("/root/build.sh")));
+    }
+}
@@ -0,0 +1,335 @@
+//! Variables (`$NAME` or `${NAME}`), substituted into the runnables right before they get spawned.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _};
+use collections::HashMap;
+use futures::future::BoxFuture;
+use gpui::{AppContext, Global, Task};
+use regex::{Captures, Regex};
+
+use crate::SpawnInTerminal;
+
+/// Prefix of the variables, resolved by Zed itself.
+pub const ZED_VARIABLE_PREFIX: &str = "ZED_";
+
+const VARIABLE_PATTERN: &str = r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))";
+
+/// Circumstances a runnable is scheduled in, used to resolve its variables.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunnableContext {
+    /// Root of the worktree the runnable is scheduled in: `$ZED_WORKTREE_ROOT`.
+    pub worktree_root: Option<PathBuf>,
+    /// Absolute path of the file, active when the runnable got scheduled: `$ZED_FILE`.
+    pub active_file: Option<PathBuf>,
+    /// 1-based row of the newest cursor in the active file: `$ZED_ROW`.
+    pub row: Option<u32>,
+    /// 1-based column of the newest cursor in the active file: `$ZED_COLUMN`.
+    pub column: Option<u32>,
+}
+
+/// Contributes runnable variables beyond the built-in `$ZED_*` ones, e.g. `$CARGO_PACKAGE`.
+pub trait VariableProvider: Send + Sync {
+    /// Names of the variables (without the `$`) this provider is able to resolve.
+    fn variable_names(&self) -> &[&'static str];
+    /// Resolves one of the [`Self::variable_names`] for the runnable scheduled in the context given.
+    fn resolve(
+        &self,
+        name: &str,
+        context: &RunnableContext,
+    ) -> BoxFuture<'static, anyhow::Result<String>>;
+}
+
+#[derive(Default)]
+struct VariableProviders(Vec<Arc<dyn VariableProvider>>);
+
+impl Global for VariableProviders {}
+
+/// Registers a provider, consulted for the variables that are not built into Zed.
+pub fn register_variable_provider(provider: Arc<dyn VariableProvider>, cx: &mut AppContext) {
+    cx.default_global::<VariableProviders>().0.push(provider);
+}
+
+/// Substitutes the variables in the command, args, env values and cwd of the runnable.
+///
+/// Every variable is resolved once per call, regardless of the number of its occurrences.
+/// Variables that are neither built-in nor known to any provider are left as is, for the shell to expand them,
+/// unless they have the [`ZED_VARIABLE_PREFIX`]: those, as well as any failed resolution, abort the whole resolution.
+pub fn resolve_variables(
+    spawn_in_terminal: SpawnInTerminal,
+    context: RunnableContext,
+    cx: &AppContext,
+) -> Task<anyhow::Result<SpawnInTerminal>> {
+    let providers = cx
+        .try_global::<VariableProviders>()
+        .map(|providers| providers.0.clone())
+        .unwrap_or_default();
+    cx.background_executor().spawn(async move {
+        let variable_regex = Regex::new(VARIABLE_PATTERN)?;
+        let mut spawn_in_terminal = spawn_in_terminal;
+        let cwd = spawn_in_terminal
+            .cwd
+            .as_ref()
+            .map(|cwd| cwd.to_string_lossy().into_owned());
+
+        let mut names = BTreeSet::new();
+        for text in std::iter::once(&spawn_in_terminal.command)
+            .chain(&spawn_in_terminal.args)
+            .chain(spawn_in_terminal.env.values())
+            .chain(&cwd)
+        {
+            names.extend(
+                variable_regex
+                    .captures_iter(text)
+                    .map(|captures| variable_name(&captures).to_string()),
+            );
+        }
+
+        let mut resolved = HashMap::default();
+        for name in names {
+            let value = match builtin_variable(&name, &context) {
+                Some(value) => value,
+                None => match providers
+                    .iter()
+                    .find(|provider| provider.variable_names().contains(&name.as_str()))
+                {
+                    Some(provider) => provider.resolve(&name, &context).await,
+                    None if name.starts_with(ZED_VARIABLE_PREFIX) => {
+                        Err(anyhow!("unknown variable"))
+                    }
+                    None => continue,
+                },
+            };
+            let value = value.with_context(|| format!("Failed to resolve ${name}"))?;
+            resolved.insert(name, value);
+        }
+
+        let substitute = |text: &str| {
+            variable_regex
+                .replace_all(text, |captures: &Captures| {
+                    resolved
+                        .get(variable_name(captures))
+                        .cloned()
+                        .unwrap_or_else(|| captures[0].to_string())
+                })
+                .into_owned()
+        };
+        spawn_in_terminal.command = substitute(&spawn_in_terminal.command);
+        for arg in &mut spawn_in_terminal.args {
+            *arg = substitute(arg);
+        }
+        for value in spawn_in_terminal.env.values_mut() {
+            *value = substitute(value);
+        }
+        spawn_in_terminal.cwd = cwd.map(|cwd| PathBuf::from(substitute(&cwd)));
+        Ok(spawn_in_terminal)
+    })
+}
+
+fn variable_name<'a>(captures: &Captures<'a>) -> &'a str {
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map_or("", |name| name.as_str())
+}
+
+fn builtin_variable(name: &str, context: &RunnableContext) -> Option<anyhow::Result<String>> {
+    let path_to_string = |path: &Path| path.to_string_lossy().into_owned();
+    let value = match name.strip_prefix(ZED_VARIABLE_PREFIX)? {
+        "WORKTREE_ROOT" => context
+            .worktree_root
+            .as_deref()
+            .map(path_to_string)
+            .context("no worktree is open"),
+        "FILE" => context
+            .active_file
+            .as_deref()
+            .map(path_to_string)
+            .context("no file is active"),
+        "ROW" => context
+            .row
+            .map(|row| row.to_string())
+            .context("no cursor in the active file"),
+        "COLUMN" => context
+            .column
+            .map(|column| column.to_string())
+            .context("no cursor in the active file"),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::FutureExt;
+    use gpui::TestAppContext;
+
+    use crate::{RunnableId, StructuredOutput};
+
+    use super::*;
+
+    struct TestProvider {
+        resolutions: AtomicUsize,
+        result: Result<&'static str, &'static str>,
+    }
+
+    impl VariableProvider for TestProvider {
+        fn variable_names(&self) -> &[&'static str] {
+            &["TEST_PACKAGE"]
+        }
+
+        fn resolve(
+            &self,
+            _: &str,
+            _: &RunnableContext,
+        ) -> BoxFuture<'static, anyhow::Result<String>> {
+            self.resolutions.fetch_add(1, Ordering::SeqCst);
+            let result = self
+                .result
+                .map(str::to_string)
+                .map_err(|message| anyhow!(message));
+            async move { result }.boxed()
+        }
+    }
+
+    fn spawn_in_terminal(command: &str, args: &[&str]) -> SpawnInTerminal {
+        SpawnInTerminal {
+            id: RunnableId::new("test"),
+            label: "test".to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: Some(PathBuf::from("$ZED_WORKTREE_ROOT/crates")),
+            env: HashMap::from_iter([("FILE".to_string(), "${ZED_FILE}".to_string())]),
+            clean_env: false,
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: true,
+            structured_output: StructuredOutput::None,
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
+            retry: None,
+            tty: true,
+        }
+    }
+
+    fn context() -> RunnableContext {
+        RunnableContext {
+            worktree_root: Some(PathBuf::from("/root")),
+            active_file: Some(PathBuf::from("/root/src/main.rs")),
+            row: Some(3),
+            column: Some(7),
+        }
+    }
+
+    fn register_provider(
+        result: Result<&'static str, &'static str>,
+        cx: &mut TestAppContext,
+    ) -> Arc<TestProvider> {
+        let provider = Arc::new(TestProvider {
+            resolutions: AtomicUsize::new(0),
+            result,
+        });
+        cx.update(|cx| register_variable_provider(provider.clone(), cx));
+        provider
+    }
+
+    #[gpui::test]
+    async fn test_builtin_variables(cx: &mut TestAppContext) {
+        let resolved = cx
+            .update(|cx| {
+                resolve_variables(
+                    spawn_in_terminal("cargo", &["$ZED_FILE:$ZED_ROW:${ZED_COLUMN}", "$HOME"]),
+                    context(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(resolved.args, ["/root/src/main.rs:3:7", "$HOME"]);
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/root/crates")));
+        assert_eq!(resolved.env["FILE"], "/root/src/main.rs");
+
+        let error = cx
+            .update(|cx| {
+                resolve_variables(
+                    spawn_in_terminal("cargo", &["$ZED_FILE"]),
+                    RunnableContext {
+                        active_file: None,
+                        ..context()
+                    },
+                    cx,
+                )
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to resolve $ZED_FILE: no file is active"
+        );
+
+        let error = cx
+            .update(|cx| resolve_variables(spawn_in_terminal("$ZED_UNKNOWN", &[]), context(), cx))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to resolve $ZED_UNKNOWN: unknown variable"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_provided_variables(cx: &mut TestAppContext) {
+        let spawn = spawn_in_terminal("cargo", &["test", "-p", "$TEST_PACKAGE", "${TEST_PACKAGE}"]);
+
+        let unresolved = cx
+            .update(|cx| resolve_variables(spawn.clone(), context(), cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            unresolved.args,
+            ["test", "-p", "$TEST_PACKAGE", "${TEST_PACKAGE}"]
+        );
+
+        let provider = register_provider(Ok("zed"), cx);
+        let resolved = cx
+            .update(|cx| resolve_variables(spawn.clone(), context(), cx))
+            .await
+            .unwrap();
+        assert_eq!(resolved.args, ["test", "-p", "zed", "zed"]);
+        assert_eq!(
+            provider.resolutions.load(Ordering::SeqCst),
+            1,
+            "Variables should be resolved once per schedule"
+        );
+
+        cx.update(|cx| resolve_variables(spawn, context(), cx))
+            .await
+            .unwrap();
+        assert_eq!(provider.resolutions.load(Ordering::SeqCst), 2);
+    }
+
+    #[gpui::test]
+    async fn test_provider_failure(cx: &mut TestAppContext) {
+        register_provider(Err("no Cargo.toml found"), cx);
+        let error = cx
+            .update(|cx| {
+                resolve_variables(
+                    spawn_in_terminal("cargo", &["-p", "$TEST_PACKAGE"]),
+                    context(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to resolve $TEST_PACKAGE: no Cargo.toml found"
+        );
+    }
+}
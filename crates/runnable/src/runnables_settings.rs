@@ -0,0 +1,109 @@
+//! Settings of the runnables, shared by all runnable sources.
+
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+
+use crate::RunnableId;
+
+/// Settings for the runnables, stored under the `runnables` key.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RunnablesSettings {
+    /// What to do with the runnables still running when their workspace closes.
+    pub on_close: RunnablesOnClose,
+    /// Directories, relative to the worktree roots, whose executable files are offered as runnables.
+    pub script_dirs: Vec<String>,
+    /// Substrings of the env variable names, whose values are masked wherever the runnables get logged, shown or persisted.
+    pub secret_env_patterns: Vec<String>,
+    /// How many runs of the same one-shot command within a week get it offered to be saved as a runnable, 0 to never offer.
+    pub suggest_saving_oneshot_after: usize,
+    /// Labels to show the runnables with instead of their own names, by the runnables' ids.
+    pub label_overrides: HashMap<RunnableId, String>,
+    /// Notes about the runnables, by the runnables' ids, taking precedence over the notes of their definitions.
+    pub notes: HashMap<RunnableId, String>,
+    /// How many runnables can be starting at once, the rest queued until the ones starting get spawned.
+    pub max_concurrent_spawns: usize,
+}
+
+impl RunnablesSettings {
+    /// The label the user renamed the runnable to, if any.
+    pub fn label_override(&self, id: &RunnableId) -> Option<&str> {
+        self.label_overrides.get(id).map(String::as_str)
+    }
+
+    /// The note the user wrote about the runnable, if any.
+    pub fn note(&self, id: &RunnableId) -> Option<&str> {
+        self.notes.get(id).map(String::as_str)
+    }
+}
+
+/// What to do with the runnables still running when their workspace closes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunnablesOnClose {
+    /// Ask whether to kill the runnables or to leave them running.
+    #[default]
+    Ask,
+    /// Kill the runnables, waiting for their processes to terminate.
+    Kill,
+    /// Leave the runnables running, offering to clean them up on the next start.
+    Detach,
+}
+
+/// Contents of the `runnables` settings key.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct RunnablesSettingsContent {
+    /// What to do with the runnables still running when their workspace closes.
+    ///
+    /// Default: ask
+    pub on_close: Option<RunnablesOnClose>,
+    /// Directories, relative to the worktree roots, whose executable files are offered as runnables.
+    ///
+    /// Default: ["scripts"]
+    pub script_dirs: Option<Vec<String>>,
+    /// Substrings of the env variable names, matched case-insensitively, whose values are masked
+    /// wherever the runnables get logged, shown or persisted.
+    ///
+    /// Default: ["TOKEN", "SECRET", "PASSWORD", "API_KEY"]
+    pub secret_env_patterns: Option<Vec<String>>,
+    /// How many runs of the same one-shot command within a week get it offered to be saved as a runnable.
+    /// Set to 0 to never offer that.
+    ///
+    /// Default: 3
+    pub suggest_saving_oneshot_after: Option<usize>,
+    /// Labels to show the runnables with instead of their own names, by the runnables' ids,
+    /// e.g. to give a provider's runnable a shorter name without touching its source.
+    /// Set with the rename action of the runnables modal, into the project settings.
+    ///
+    /// Default: {}
+    pub label_overrides: Option<HashMap<RunnableId, String>>,
+    /// Free-form notes about the runnables, by the runnables' ids, shown with them in the runnables modal,
+    /// for the runnables not defined in the runnables file, e.g. the providers' and the one-shot ones.
+    /// Set with the edit note action of the runnables modal, into the project settings.
+    ///
+    /// Default: {}
+    pub notes: Option<HashMap<RunnableId, String>>,
+    /// How many runnables can be starting (resolving their variables and spawning their processes) at once.
+    /// The rest wait in a queue, the runnables picked by the user ahead of the reruns, and those ahead of
+    /// the runnables started automatically, e.g. by the actions chained to other runs.
+    /// Values below 1 are treated as 1.
+    ///
+    /// Default: 4
+    pub max_concurrent_spawns: Option<usize>,
+}
+
+impl Settings for RunnablesSettings {
+    const KEY: Option<&'static str> = Some("runnables");
+
+    type FileContent = RunnablesSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
@@ -2,12 +2,14 @@
 
 use std::path::{Path, PathBuf};
 
-use crate::{static_source::Definition, Runnable, RunnableId, SpawnInTerminal};
+use crate::{static_source::Definition, Condition, Runnable, RunnableId, SpawnInTerminal};
 
 /// A single config file entry with the deserialized runnable definition.
 #[derive(Clone, Debug, PartialEq)]
 pub struct StaticRunnable {
     id: RunnableId,
+    /// Index of the definition in the config file's runnables.
+    definition_ix: usize,
     definition: Definition,
 }
 
@@ -15,22 +17,30 @@ impl StaticRunnable {
     pub(super) fn new(id: usize, runnable: Definition) -> Self {
         Self {
             id: RunnableId(format!("static_{}_{}", runnable.label, id)),
+            definition_ix: id,
             definition: runnable,
         }
     }
 }
 
 impl Runnable for StaticRunnable {
-    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
-        Some(SpawnInTerminal {
+    fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal> {
+        Ok(SpawnInTerminal {
             id: self.id.clone(),
             cwd,
             use_new_terminal: self.definition.use_new_terminal,
             allow_concurrent_runs: self.definition.allow_concurrent_runs,
+            reveal: true,
             label: self.definition.label.clone(),
             command: self.definition.command.clone(),
             args: self.definition.args.clone(),
             env: self.definition.env.clone(),
+            clean_env: self.definition.clean_env,
+            structured_output: self.definition.structured_output,
+            tty: self.definition.tty,
+            on_success: self.definition.on_success.clone(),
+            on_failure: self.definition.on_failure.clone(),
+            retry: self.definition.retry.clone(),
         })
     }
 
@@ -45,4 +55,16 @@ impl Runnable for StaticRunnable {
     fn cwd(&self) -> Option<&Path> {
         self.definition.cwd.as_deref()
     }
+
+    fn enabled_if(&self) -> Option<&Condition> {
+        self.definition.enabled_if.as_ref()
+    }
+
+    fn note(&self) -> Option<&str> {
+        self.definition.note.as_deref()
+    }
+
+    fn definition_ix(&self) -> Option<usize> {
+        Some(self.definition_ix)
+    }
 }
@@ -5,6 +5,7 @@ use std::{
     sync::Arc,
 };
 
+use anyhow::Context as _;
 use collections::HashMap;
 use futures::StreamExt;
 use gpui::{AppContext, Context, Model, ModelContext, Subscription};
@@ -12,7 +13,9 @@ use schemars::{gen::SchemaSettings, JsonSchema};
 use serde::{Deserialize, Serialize};
 use util::ResultExt;
 
-use crate::{Runnable, Source, StaticRunnable};
+use crate::{
+    ChainedAction, Condition, RetryPolicy, Runnable, Source, StaticRunnable, StructuredOutput,
+};
 use futures::channel::mpsc::UnboundedReceiver;
 
 /// The source of runnables defined in a runnables config file.
@@ -35,6 +38,10 @@ pub(crate) struct Definition {
     /// Env overrides for the command, will be appended to the terminal's environment from the settings.
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Whether to spawn the command with only PATH, HOME and TERM (plus the system variables on Windows) inherited,
+    /// besides `env`, rather than the whole environment of Zed and the terminal settings.
+    #[serde(default)]
+    pub clean_env: bool,
     /// Current working directory to spawn the command into, defaults to current project root.
     #[serde(default)]
     pub cwd: Option<PathBuf>,
@@ -44,6 +51,52 @@ pub(crate) struct Definition {
     /// Whether to allow multiple instances of the same runnable to be run, or rather wait for the existing ones to finish.
     #[serde(default)]
     pub allow_concurrent_runs: bool,
+    /// The condition to satisfy for the runnable to be offered, e.g. `{ "file_exists": "docker-compose.yml" }`.
+    #[serde(default)]
+    pub enabled_if: Option<Condition>,
+    /// The format of the structured output the command prints, to report the test results from, e.g. `"cargo_json"`.
+    /// The output gets read from the command's stdout, so the command gets plain pipes for it, whatever `tty` says.
+    #[serde(default)]
+    pub structured_output: StructuredOutput,
+    /// Whether to give the command a PTY, or plain pipes for its output, e.g. for the scripts choking on the control sequences.
+    #[serde(default = "default_tty")]
+    pub tty: bool,
+    /// Actions to dispatch once the command exits successfully, e.g. `[{ "name": "workspace::ReloadActiveItem" }]`.
+    #[serde(default)]
+    pub on_success: Vec<ChainedAction>,
+    /// Actions to dispatch once the command exits with a failure.
+    #[serde(default)]
+    pub on_failure: Vec<ChainedAction>,
+    /// How to run the command again once it fails, e.g. `{ "attempts": 3, "delay_secs": 5, "only_on_exit_codes": [1] }`.
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// A free-form note about the runnable, e.g. what it needs to be running, shown with it in the runnables modal.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl Definition {
+    /// Drops the chained actions, which are not registered or reject their arguments, logging them.
+    fn validate_chained_actions(&mut self, cx: &AppContext) {
+        let label = &self.label;
+        for chained_actions in [&mut self.on_success, &mut self.on_failure] {
+            chained_actions.retain(|chained_action| {
+                cx.build_action(&chained_action.name, chained_action.args.clone())
+                    .with_context(|| {
+                        format!(
+                            "runnable '{label}' chains an invalid action '{}'",
+                            chained_action.name
+                        )
+                    })
+                    .log_err()
+                    .is_some()
+            });
+        }
+    }
+}
+
+fn default_tty() -> bool {
+    true
 }
 
 /// A group of Runnables defined in a JSON file.
@@ -123,7 +176,10 @@ impl StaticSource {
                             .clone()
                             .into_iter()
                             .enumerate()
-                            .map(|(id, definition)| StaticRunnable::new(id, definition))
+                            .map(|(id, mut definition)| {
+                                definition.validate_chained_actions(cx);
+                                StaticRunnable::new(id, definition)
+                            })
                             .collect();
                         cx.notify();
                     }
@@ -151,6 +207,10 @@ impl Source for StaticSource {
             .collect()
     }
 
+    fn name(&self) -> &str {
+        "static"
+    }
+
     fn as_any(&mut self) -> &mut dyn std::any::Any {
         self
     }
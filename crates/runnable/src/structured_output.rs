@@ -0,0 +1,182 @@
+//! Per-test results, parsed out of the structured output the test runners print besides the human readable one.
+
+use std::{fmt, path::PathBuf};
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The format of the structured output, printed by the runnable's process, to parse the test results from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutput {
+    /// The output is not parsed, only the exit status of the run is known.
+    #[default]
+    None,
+    /// libtest's JSON events, printed by `cargo test -- -Z unstable-options --format json`.
+    CargoJson,
+}
+
+/// Results of the tests, reported in the structured output of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestResults {
+    /// The number of tests passed.
+    pub passed: usize,
+    /// The number of tests failed.
+    pub failed: usize,
+    /// The number of tests skipped.
+    pub ignored: usize,
+    /// The tests failed, in the order they were reported.
+    pub failures: Vec<TestFailure>,
+}
+
+/// A failed test, along with the place it failed at, when the runner reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    /// Name of the test, as printed by the runner.
+    pub name: String,
+    /// The place the test failed at.
+    pub location: Option<TestLocation>,
+}
+
+/// A position in a source file, with the path relative to the run's cwd or absolute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestLocation {
+    /// Path of the source file.
+    pub path: PathBuf,
+    /// 1-based line of the position.
+    pub row: u32,
+    /// 1-based column of the position.
+    pub column: u32,
+}
+
+impl fmt::Display for TestResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} passed, {} failed", self.passed, self.failed)?;
+        if self.ignored > 0 {
+            write!(f, ", {} ignored", self.ignored)?;
+        }
+        Ok(())
+    }
+}
+
+impl StructuredOutput {
+    /// Parses the test results out of the run's output, skipping the lines not in the format.
+    /// Returns `None` for no structured output, or if no tests got reported.
+    pub fn parse(&self, output: &str) -> Option<TestResults> {
+        match self {
+            Self::None => None,
+            Self::CargoJson => parse_cargo_json(output),
+        }
+    }
+}
+
+/// A libtest JSON event, with the fields irrelevant for the results omitted.
+#[derive(Deserialize)]
+struct CargoTestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    stdout: Option<String>,
+}
+
+fn parse_cargo_json(output: &str) -> Option<TestResults> {
+    // Rust 1.73 moved the panic message after the location: `panicked at src/lib.rs:1:2:\nmessage`,
+    // while the older versions print `panicked at 'message', src/lib.rs:1:2`.
+    let panic_location = Regex::new(r"panicked at (?:(?s:'.*'), )?(\S+?):(\d+):(\d+)").unwrap();
+    let mut results = TestResults::default();
+    let mut reported_any = false;
+    for line in output.lines() {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            continue;
+        }
+        let Ok(event) = serde_json::from_str::<CargoTestEvent>(line) else {
+            continue;
+        };
+        if event.kind != "test" {
+            continue;
+        }
+        match event.event.as_str() {
+            "ok" => results.passed += 1,
+            "ignored" => results.ignored += 1,
+            "failed" | "timeout" => {
+                results.failed += 1;
+                let location = event.stdout.as_deref().and_then(|stdout| {
+                    let captures = panic_location.captures(stdout)?;
+                    Some(TestLocation {
+                        path: PathBuf::from(&captures[1]),
+                        row: captures[2].parse().ok()?,
+                        column: captures[3].parse().ok()?,
+                    })
+                });
+                results.failures.push(TestFailure {
+                    name: event.name.unwrap_or_default(),
+                    location,
+                });
+            }
+            _ => continue,
+        }
+        reported_any = true;
+    }
+    reported_any.then_some(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_json() {
+        let results = StructuredOutput::CargoJson
+            .parse(include_str!("../test_data/cargo_test.json"))
+            .unwrap();
+        assert_eq!((results.passed, results.failed, results.ignored), (4, 2, 1));
+        assert_eq!(results.to_string(), "4 passed, 2 failed, 1 ignored");
+        assert_eq!(
+            results.failures,
+            [
+                TestFailure {
+                    name: "tests::test_parse_empty".to_string(),
+                    location: Some(TestLocation {
+                        path: PathBuf::from("crates/parser/src/lib.rs"),
+                        row: 42,
+                        column: 9,
+                    }),
+                },
+                TestFailure {
+                    name: "tests::test_checked_sub".to_string(),
+                    location: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cargo_json_legacy_panic_format() {
+        let results = StructuredOutput::CargoJson
+            .parse(include_str!("../test_data/cargo_test_legacy.json"))
+            .unwrap();
+        assert_eq!((results.passed, results.failed, results.ignored), (1, 1, 0));
+        assert_eq!(results.to_string(), "1 passed, 1 failed");
+        assert_eq!(
+            results.failures[0].location,
+            Some(TestLocation {
+                path: PathBuf::from("src/main.rs"),
+                row: 17,
+                column: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unstructured_output() {
+        let output = "running 1 test\ntest tests::it_works ... ok\n\ntest result: ok. 1 passed";
+        assert_eq!(StructuredOutput::CargoJson.parse(output), None);
+        assert_eq!(
+            StructuredOutput::None.parse(include_str!("../test_data/cargo_test.json")),
+            None
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Actions chained to the runnables' exits, e.g. reloading the buffers once a code generator succeeds.
+//!
+//! An action may schedule another run, chaining its actions in turn: each chain is cut after [`MAX_CHAIN_DEPTH`] runs,
+//! so that an action rerunning its own runnable does not loop forever.
+
+use collections::HashMap;
+use gpui::{AppContext, Global, ViewContext};
+use project::{RunRecord, RunStatus};
+use runnable::RunnableId;
+use util::ResultExt;
+use workspace::{Toast, Workspace};
+
+/// How many runs, scheduled by the chained actions one after another, a chain may have.
+pub(crate) const MAX_CHAIN_DEPTH: usize = 8;
+const CHAIN_CUT_TOAST_ID: usize = 0xc4a1_2ed0;
+const CHAIN_SKIPPED_TOAST_ID: usize = 0xc4a1_2ed1;
+
+/// Depths of the chains the runs belong to, with the runs started outside of any chain not tracked.
+#[derive(Default)]
+pub(crate) struct ActionChains {
+    /// Depth of the runs to get scheduled by the actions being dispatched, if any.
+    dispatching_depth: Option<usize>,
+    run_depths: HashMap<RunnableId, usize>,
+}
+
+impl Global for ActionChains {}
+
+impl ActionChains {
+    /// Depth of the chain a run scheduled now would belong to, if it gets scheduled by a chained action.
+    pub(crate) fn dispatching_depth(cx: &AppContext) -> Option<usize> {
+        cx.try_global::<Self>()
+            .and_then(|chains| chains.dispatching_depth)
+    }
+
+    /// Records the depth of the chain the runnable's latest run belongs to.
+    pub(crate) fn run_started(id: RunnableId, depth: usize, cx: &mut AppContext) {
+        cx.default_global::<Self>().run_depths.insert(id, depth);
+    }
+}
+
+/// Dispatches the actions chained to the outcome of the finished run, to the focused element of the workspace's window.
+/// Runs finishing without the exit status known, e.g. killed by a signal, dispatch nothing, telling the user so.
+pub(crate) fn dispatch_chained_actions(
+    workspace: &mut Workspace,
+    run: &RunRecord,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let chained_actions = match run.status {
+        RunStatus::Succeeded => &run.on_success,
+        RunStatus::Failed(_) => &run.on_failure,
+        RunStatus::Unknown => {
+            if !run.on_success.is_empty() || !run.on_failure.is_empty() {
+                log::warn!(
+                    "skipping the actions chained to '{}': its exit status is unknown",
+                    run.label
+                );
+                workspace.show_toast(
+                    Toast::new(
+                        CHAIN_SKIPPED_TOAST_ID,
+                        format!(
+                            "Skipped the actions chained to '{}': its exit status is unknown",
+                            run.label
+                        ),
+                    ),
+                    cx,
+                );
+            }
+            return;
+        }
+        RunStatus::Running | RunStatus::Cancelled => return,
+    };
+    let depth = cx
+        .default_global::<ActionChains>()
+        .run_depths
+        .remove(&run.id)
+        .unwrap_or(0);
+    if chained_actions.is_empty() {
+        return;
+    }
+    if depth >= MAX_CHAIN_DEPTH {
+        workspace.show_toast(
+            Toast::new(
+                CHAIN_CUT_TOAST_ID,
+                format!(
+                    "Stopped dispatching the actions chained to '{}' after {MAX_CHAIN_DEPTH} runs in a row",
+                    run.label
+                ),
+            ),
+            cx,
+        );
+        return;
+    }
+    let actions = chained_actions
+        .iter()
+        .filter_map(|chained_action| {
+            cx.build_action(&chained_action.name, chained_action.args.clone())
+                .log_err()
+        })
+        .collect::<Vec<_>>();
+    // The actions get dispatched on the next effect cycle: the runs they schedule meanwhile continue this chain.
+    cx.default_global::<ActionChains>().dispatching_depth = Some(depth + 1);
+    for action in actions {
+        cx.dispatch_action(action);
+    }
+    cx.window_context().defer(|cx| {
+        cx.default_global::<ActionChains>().dispatching_depth = None;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        path::{Path, PathBuf},
+        rc::Rc,
+    };
+
+    use editor::Editor;
+    use futures::StreamExt as _;
+    use gpui::{actions, TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project, SpawnPriority};
+    use runnable::{ChainedAction, Runnable, RunnableOverrides, SpawnInTerminal, StructuredOutput};
+    use workspace::{notifications::simple_message_notification::MessageNotification, AppState};
+
+    use super::*;
+    use crate::retries::tests::{finished_runs, load_terminal_panel};
+
+    actions!(
+        chained_actions_test,
+        [RecordSuccess, RecordFailure, RunAgain]
+    );
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init(app_state.clone(), cx);
+            Project::init_settings(cx);
+            terminal_view::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    fn chained(name: &str) -> ChainedAction {
+        ChainedAction {
+            name: name.to_string(),
+            args: None,
+        }
+    }
+
+    /// A runnable, whose success schedules it again.
+    struct LoopingRunnable {
+        id: RunnableId,
+    }
+
+    impl Runnable for LoopingRunnable {
+        fn id(&self) -> &RunnableId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "regenerate"
+        }
+
+        fn cwd(&self) -> Option<&Path> {
+            None
+        }
+
+        fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal> {
+            Ok(SpawnInTerminal {
+                id: self.id.clone(),
+                label: "regenerate".to_string(),
+                command: "make".to_string(),
+                args: Vec::new(),
+                cwd,
+                env: HashMap::default(),
+                clean_env: false,
+                use_new_terminal: false,
+                allow_concurrent_runs: false,
+                reveal: true,
+                tty: true,
+                structured_output: StructuredOutput::None,
+                on_success: vec![chained("chained_actions_test::RunAgain")],
+                on_failure: Vec::new(),
+                retry: None,
+            })
+        }
+    }
+
+    /// A runnable, running the shell script given in a PTY, with the actions recording its outcome chained.
+    struct ScriptRunnable {
+        id: RunnableId,
+        script: &'static str,
+    }
+
+    impl Runnable for ScriptRunnable {
+        fn id(&self) -> &RunnableId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "script"
+        }
+
+        fn cwd(&self) -> Option<&Path> {
+            None
+        }
+
+        fn exec(&self, cwd: Option<PathBuf>) -> anyhow::Result<SpawnInTerminal> {
+            Ok(SpawnInTerminal {
+                id: self.id.clone(),
+                label: "script".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), self.script.to_string()],
+                cwd,
+                env: HashMap::default(),
+                clean_env: false,
+                use_new_terminal: false,
+                allow_concurrent_runs: false,
+                reveal: true,
+                tty: true,
+                structured_output: StructuredOutput::None,
+                on_success: vec![chained("chained_actions_test::RecordSuccess")],
+                on_failure: vec![chained("chained_actions_test::RecordFailure")],
+                retry: None,
+            })
+        }
+    }
+
+    async fn focused_workspace(
+        cx: &mut TestAppContext,
+    ) -> (gpui::View<Workspace>, &mut VisualTestContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let editor = cx.new_view(|cx| Editor::single_line(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item(Box::new(editor.clone()), cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+        (workspace, cx)
+    }
+
+    #[gpui::test]
+    async fn test_actions_chained_to_exit_status(cx: &mut TestAppContext) {
+        let (workspace, cx) = focused_workspace(cx).await;
+        let dispatched = Rc::new(RefCell::new(Vec::new()));
+        workspace.update(cx, |workspace, _| {
+            let dispatched_on_success = dispatched.clone();
+            let dispatched_on_failure = dispatched.clone();
+            workspace
+                .register_action(move |_, _: &RecordSuccess, _| {
+                    dispatched_on_success.borrow_mut().push("success")
+                })
+                .register_action(move |_, _: &RecordFailure, _| {
+                    dispatched_on_failure.borrow_mut().push("failure")
+                });
+        });
+        let inventory = workspace.update(cx, |workspace, cx| {
+            workspace.project().read(cx).runnable_inventory().clone()
+        });
+        let id = RunnableId::new("codegen");
+        let mut spawn_in_terminal = LoopingRunnable { id: id.clone() }.exec(None).unwrap();
+        spawn_in_terminal.on_success = vec![chained("chained_actions_test::RecordSuccess")];
+        spawn_in_terminal.on_failure = vec![chained("chained_actions_test::RecordFailure")];
+        let run_with_exit_status = |exit_status, cx: &mut VisualTestContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory.runnable_started(&spawn_in_terminal, cx);
+                inventory.runnable_finished(id.clone(), exit_status, None, cx);
+            });
+            cx.run_until_parked();
+            dispatched.borrow().clone()
+        };
+
+        assert_eq!(run_with_exit_status(Some(0), cx), vec!["success"]);
+        assert_eq!(
+            run_with_exit_status(Some(2), cx),
+            vec!["success", "failure"]
+        );
+        assert_eq!(
+            run_with_exit_status(None, cx),
+            vec!["success", "failure"],
+            "Runs with an unknown exit status should dispatch nothing"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_actions_chained_to_pty_runs(cx: &mut TestAppContext) {
+        let (workspace, cx) = focused_workspace(cx).await;
+        load_terminal_panel(&workspace, cx).await;
+        let dispatched = Rc::new(RefCell::new(Vec::new()));
+        workspace.update(cx, |workspace, _| {
+            let dispatched_on_success = dispatched.clone();
+            let dispatched_on_failure = dispatched.clone();
+            workspace
+                .register_action(move |_, _: &RecordSuccess, _| {
+                    dispatched_on_success.borrow_mut().push("success")
+                })
+                .register_action(move |_, _: &RecordFailure, _| {
+                    dispatched_on_failure.borrow_mut().push("failure")
+                });
+        });
+        let inventory = workspace.update(cx, |workspace, cx| {
+            workspace.project().read(cx).runnable_inventory().clone()
+        });
+        let mut finished_runs = finished_runs(&inventory, cx);
+        let schedule_script = |script, cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                let runnable = ScriptRunnable {
+                    id: RunnableId::new("script"),
+                    script,
+                };
+                crate::schedule_runnable(
+                    workspace,
+                    &runnable,
+                    RunnableOverrides::default(),
+                    SpawnPriority::Interactive,
+                    cx,
+                );
+            });
+        };
+
+        schedule_script("exit 0", cx);
+        let run = finished_runs.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(run.status, RunStatus::Succeeded);
+        assert_eq!(*dispatched.borrow(), vec!["success"]);
+        schedule_script("exit 2", cx);
+        let run = finished_runs.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(run.status, RunStatus::Failed(2));
+        assert_eq!(*dispatched.borrow(), vec!["success", "failure"]);
+
+        schedule_script("kill -9 $$", cx);
+        let run = finished_runs.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(run.status, RunStatus::Unknown);
+        assert_eq!(
+            *dispatched.borrow(),
+            vec!["success", "failure"],
+            "Runs killed by a signal have no exit status to chain the actions to"
+        );
+        workspace.update(cx, |workspace, _| {
+            assert!(
+                workspace
+                    .notification::<MessageNotification>(CHAIN_SKIPPED_TOAST_ID)
+                    .is_some(),
+                "The user should be told about the chained actions skipped"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_chain_depth_limit(cx: &mut TestAppContext) {
+        let (workspace, cx) = focused_workspace(cx).await;
+        let id = RunnableId::new("regenerate");
+        let reruns = Rc::new(RefCell::new(0));
+        workspace.update(cx, |workspace, cx| {
+            let reruns = reruns.clone();
+            let looping_id = id.clone();
+            workspace.register_action(move |workspace, _: &RunAgain, cx| {
+                *reruns.borrow_mut() += 1;
+                let runnable = LoopingRunnable {
+                    id: looping_id.clone(),
+                };
+                crate::schedule_runnable(
+                    workspace,
+                    &runnable,
+                    RunnableOverrides::default(),
+                    SpawnPriority::Automatic,
+                    cx,
+                );
+            });
+            let runnable = LoopingRunnable { id: id.clone() };
+            crate::schedule_runnable(
+                workspace,
+                &runnable,
+                RunnableOverrides::default(),
+                SpawnPriority::Automatic,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+
+        let inventory = workspace.update(cx, |workspace, cx| {
+            workspace.project().read(cx).runnable_inventory().clone()
+        });
+        let running = |cx: &mut VisualTestContext| {
+            inventory.read_with(cx, |inventory, _| {
+                inventory
+                    .run_history()
+                    .iter()
+                    .filter(|run| run.status == RunStatus::Running)
+                    .count()
+            })
+        };
+        for _ in 0..MAX_CHAIN_DEPTH * 2 {
+            if running(cx) == 0 {
+                break;
+            }
+            inventory.update(cx, |inventory, cx| {
+                inventory.runnable_finished(id.clone(), Some(0), None, cx)
+            });
+            cx.run_until_parked();
+        }
+
+        assert_eq!(
+            *reruns.borrow(),
+            MAX_CHAIN_DEPTH,
+            "The chain should be cut after the maximum depth"
+        );
+        assert_eq!(running(cx), 0);
+        assert_eq!(
+            inventory.read_with(cx, |inventory, _| inventory.run_history().len()),
+            MAX_CHAIN_DEPTH + 1
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use gpui::{
+    div, FocusHandle, InteractiveElement, IntoElement, ParentElement, Render, ViewContext,
+    WindowContext,
+};
+use ui::{prelude::*, v_flex, Label, ListItem, ListItemSpacing, Selectable};
+
+/// Asks for the working directory of a runnable among the project's worktree roots, with the focused root picked.
+pub(crate) struct CwdPrompt {
+    roots: Vec<PathBuf>,
+    focus_handles: Vec<FocusHandle>,
+}
+
+impl CwdPrompt {
+    pub(crate) fn new(roots: Vec<PathBuf>, cx: &mut ViewContext<Self>) -> Self {
+        let focus_handles = roots.iter().map(|_| cx.focus_handle()).collect();
+        Self {
+            roots,
+            focus_handles,
+        }
+    }
+
+    /// The roots to pick from, in the tab order.
+    pub(crate) fn fields(&self) -> Vec<FocusHandle> {
+        self.focus_handles.clone()
+    }
+
+    /// The root focused, or the first one if the focus is elsewhere.
+    pub(crate) fn selected_root(&self, cx: &WindowContext) -> Option<PathBuf> {
+        self.roots
+            .iter()
+            .zip(&self.focus_handles)
+            .find(|(_, focus_handle)| focus_handle.is_focused(cx))
+            .or_else(|| self.roots.iter().zip(&self.focus_handles).next())
+            .map(|(root, _)| root.clone())
+    }
+}
+
+impl Render for CwdPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().children(self.roots.iter().zip(&self.focus_handles).enumerate().map(
+            |(ix, (root, focus_handle))| {
+                let focus_handle_to_click = focus_handle.clone();
+                div().track_focus(focus_handle).child(
+                    ListItem::new(("runnable-cwd", ix))
+                        .spacing(ListItemSpacing::Sparse)
+                        .selected(focus_handle.is_focused(cx))
+                        .on_click(move |_, cx| cx.focus(&focus_handle_to_click))
+                        .child(Label::new(root.to_string_lossy().to_string())),
+                )
+            },
+        ))
+    }
+}
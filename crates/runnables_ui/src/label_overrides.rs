@@ -0,0 +1,91 @@
+//! Labels the user renamed the runnables to, stored in the project settings by the runnables' ids,
+//! so that they survive the sources reloading their runnables.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use gpui::{AppContext, ViewContext};
+use project::Project;
+use runnable::{Runnable, RunnablesSettings, RunnablesSettingsContent};
+use settings::{Settings, SettingsStore};
+use util::paths::LOCAL_SETTINGS_RELATIVE_PATH;
+use workspace::Workspace;
+
+use crate::local_worktree_roots;
+
+/// The label the runnable was renamed to, in the settings of any of the project's local worktrees or in the user ones.
+pub(crate) fn label_override(
+    project: &Project,
+    runnable: &dyn Runnable,
+    cx: &AppContext,
+) -> Option<String> {
+    project
+        .directory_worktrees(cx)
+        .filter(|worktree| worktree.read(cx).is_local())
+        .map(|worktree| Some(worktree.read(cx).id().to_usize()))
+        .chain([None])
+        .find_map(|worktree_id| {
+            RunnablesSettings::get(worktree_id.map(|id| (id, Path::new(""))), cx)
+                .label_override(runnable.id())
+                .map(str::to_string)
+        })
+}
+
+/// Renames the runnable in the settings of its worktree, or of the first local worktree of the project for the user's runnables.
+/// Projects with no local worktrees keep the label in the user settings.
+pub(crate) fn rename_runnable(
+    workspace: &Workspace,
+    runnable: &dyn Runnable,
+    label: String,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let id = runnable.id().clone();
+    update_runnable_settings(
+        workspace,
+        runnable,
+        move |settings| {
+            settings
+                .label_overrides
+                .get_or_insert_with(Default::default)
+                .insert(id, label);
+        },
+        cx,
+    );
+}
+
+/// Updates the runnables settings of the runnable's worktree, or of the first local worktree of the project for the user's runnables,
+/// or the user settings for the projects with no local worktrees.
+pub(crate) fn update_runnable_settings(
+    workspace: &Workspace,
+    runnable: &dyn Runnable,
+    update: impl 'static + Send + FnOnce(&mut RunnablesSettingsContent),
+    cx: &mut ViewContext<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let Some(root) = runnable
+        .project_root()
+        .map(Path::to_path_buf)
+        .or_else(|| local_worktree_roots(workspace, cx).into_iter().next())
+    else {
+        settings::update_settings_file::<RunnablesSettings>(fs, cx, update);
+        return;
+    };
+    let settings_path = root.join(&*LOCAL_SETTINGS_RELATIVE_PATH);
+    cx.spawn(|_, mut cx| async move {
+        let old_text = if fs.is_file(&settings_path).await {
+            fs.load(&settings_path).await?
+        } else {
+            "{}".to_string()
+        };
+        let new_text = cx.read_global(|store: &SettingsStore, _| {
+            store.new_text_for_update::<RunnablesSettings>(old_text, update)
+        })?;
+        if let Some(settings_dir) = settings_path.parent() {
+            fs.create_dir(settings_dir).await?;
+        }
+        fs.atomic_write(settings_path.clone(), new_text)
+            .await
+            .with_context(|| format!("Failed to write settings to file {settings_path:?}"))
+    })
+    .detach_and_log_err(cx);
+}
@@ -1,75 +1,283 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use gpui::{AppContext, ViewContext, WindowContext};
+use collections::HashMap;
+
+use chained_actions::ActionChains;
+use editor::Editor;
+use gpui::{AppContext, ClipboardItem, ViewContext, WindowContext};
 use modal::RunnablesModal;
-use runnable::Runnable;
+use oneshot_suggestions::OneshotSuggestions;
+use project::{InventoryEvent, ScheduledRunnable, SpawnPriority};
+use runnable::{Runnable, RunnableContext, RunnableOverrides};
+use text::Point;
 use util::ResultExt;
-use workspace::Workspace;
+use workspace::{Toast, Workspace};
+use worktree_trust::TrustedWorktrees;
+use zed_actions::Spawn;
 
+mod chained_actions;
+mod cwd_prompt;
+mod label_overrides;
 mod modal;
+mod oneshot_suggestions;
+mod options;
+mod persistence;
+mod project_commands;
+mod prompt_stack;
+mod retries;
+mod run_history;
+mod run_script;
+mod runnable_notes;
+mod test_results;
+mod text_prompt;
+mod worktree_trust;
 
 pub fn init(cx: &mut AppContext) {
+    TrustedWorktrees::init(cx);
+    OneshotSuggestions::init(cx);
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            let inventory = workspace.project().read(cx).runnable_inventory().clone();
+            run_history::persist_run_history(workspace.database_id(), inventory.clone(), cx);
+            cx.subscribe(&inventory, |workspace, _, event, cx| match event {
+                InventoryEvent::RunFinished(run) => {
+                    test_results::show_test_results(workspace, run, cx);
+                    // The actions chained to the run's outcome wait for its last attempt.
+                    if retries::retry_failed_run(workspace, run, cx) {
+                        return;
+                    }
+                    chained_actions::dispatch_chained_actions(workspace, run, cx);
+                    oneshot_suggestions::suggest_saving_oneshot(workspace, run, cx);
+                }
+                InventoryEvent::SpawnFailed { label, error, .. } => workspace.show_toast(
+                    Toast::new(
+                        RUNNABLE_ABORTED_TOAST_ID,
+                        format!("Runnable '{label}' aborted. {error}"),
+                    ),
+                    cx,
+                ),
+                InventoryEvent::FailuresChanged
+                | InventoryEvent::RunHistoryChanged
+                | InventoryEvent::SpawnQueueChanged => {}
+            })
+            .detach();
             workspace
-                .register_action(|workspace, _: &modal::Spawn, cx| {
-                    let inventory = workspace.project().read(cx).runnable_inventory().clone();
+                .register_action(|workspace, action: &Spawn, cx| {
+                    let project = workspace.project().clone();
                     let workspace_handle = workspace.weak_handle();
+                    let cwd_override = action.cwd_override.clone();
                     workspace.toggle_modal(cx, |cx| {
-                        RunnablesModal::new(inventory, workspace_handle, cx)
+                        RunnablesModal::new(project, workspace_handle, cwd_override, cx)
                     })
                 })
                 .register_action(move |workspace, _: &modal::Rerun, cx| {
-                    if let Some(runnable) = workspace.project().update(cx, |project, cx| {
-                        project
-                            .runnable_inventory()
-                            .update(cx, |inventory, cx| inventory.last_scheduled_runnable(cx))
-                    }) {
-                        schedule_runnable(workspace, runnable.as_ref(), cx)
+                    if let Some((runnable, overrides)) =
+                        workspace.project().update(cx, |project, cx| {
+                            project
+                                .runnable_inventory()
+                                .update(cx, |inventory, cx| inventory.last_scheduled_runnable(cx))
+                        })
+                    {
+                        schedule_runnable(
+                            workspace,
+                            runnable.as_ref(),
+                            overrides,
+                            SpawnPriority::Rerun,
+                            cx,
+                        )
                     };
+                })
+                .register_action(run_script::run_script)
+                .register_action(project_commands::schedule_project_runnable)
+                .register_action(|workspace, _: &modal::CopySnapshotAsJson, cx| {
+                    let inventory = workspace.project().read(cx).runnable_inventory().clone();
+                    let snapshot = inventory.update(cx, |inventory, cx| inventory.snapshot(cx));
+                    if let Some(json) = serde_json::to_string_pretty(&snapshot).log_err() {
+                        cx.write_to_clipboard(ClipboardItem::new(json));
+                    }
                 });
         },
     )
     .detach();
 }
 
+const RUNNABLE_ABORTED_TOAST_ID: usize = 0x52f1c0d4;
+
+/// Resolves the runnable's variables and spawns it, recording it as the last scheduled one.
+/// Runnables renamed by the user get spawned with the label they were renamed to.
+/// Runnables from the project's files get spawned only once the user trusts their worktree, asked for on the first run.
+/// Runnables scheduled by the actions chained to another run's exit continue that run's chain.
+/// Runnables failing to get spawned are reported with [`InventoryEvent::SpawnFailed`], shown as a toast.
+/// Runnables wait in the inventory's spawn queue for a slot to resolve their variables and spawn in, by their priority:
+/// the ones scheduled by chained actions are always [`SpawnPriority::Automatic`].
+///
+/// Nothing gets recorded until the spawn event is emitted: if the workspace is dropped mid-resolution,
+/// the scheduling is abandoned silently.
 fn schedule_runnable(
     workspace: &Workspace,
     runnable: &dyn Runnable,
+    overrides: RunnableOverrides,
+    priority: SpawnPriority,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
-    let cwd = match runnable.cwd() {
-        Some(cwd) => Some(cwd.to_path_buf()),
-        None => runnable_cwd(workspace, cx).log_err().flatten(),
+    let worktree_root = runnable_cwd(workspace, cx).log_err().flatten();
+    let cwd = overrides
+        .cwd
+        .clone()
+        .or_else(|| runnable.cwd().map(Path::to_path_buf))
+        .or_else(|| worktree_root.clone());
+    let inventory = workspace.project().read(cx).runnable_inventory().clone();
+    let label_override =
+        label_overrides::label_override(workspace.project().read(cx), runnable, cx);
+    let mut spawn_in_terminal = match runnable.exec(cwd) {
+        Ok(spawn_in_terminal) => spawn_in_terminal,
+        Err(error) => {
+            inventory.update(cx, |inventory, cx| {
+                inventory.runnable_spawn_failed(
+                    runnable.id().clone(),
+                    label_override.as_deref().unwrap_or(runnable.name()),
+                    &error,
+                    &HashMap::default(),
+                    cx,
+                )
+            });
+            return;
+        }
     };
-    let spawn_in_terminal = runnable.exec(cwd);
-    if let Some(spawn_in_terminal) = spawn_in_terminal {
-        workspace.project().update(cx, |project, cx| {
-            project.runnable_inventory().update(cx, |inventory, _| {
-                inventory.last_scheduled_runnable = Some(runnable.id().clone());
-            })
-        });
-        cx.emit(workspace::Event::SpawnRunnable(spawn_in_terminal));
+    overrides.apply(&mut spawn_in_terminal);
+    if let Some(label) = label_override {
+        spawn_in_terminal.label = label;
     }
+    let scheduled_runnable = ScheduledRunnable {
+        id: runnable.id().clone(),
+        overrides,
+    };
+    let label = spawn_in_terminal.label.clone();
+    let env = spawn_in_terminal.env.clone();
+    let context = runnable_context(workspace, worktree_root, cx);
+    let chain_depth = ActionChains::dispatching_depth(cx);
+    let priority = if chain_depth.is_some() {
+        SpawnPriority::Automatic
+    } else {
+        priority
+    };
+    let ask_for_trust = worktree_trust::untrusted_root(runnable, cx).map(|root| {
+        let trusted = worktree_trust::ask_for_trust(&root, cx);
+        (root, trusted)
+    });
+    cx.spawn(|workspace, mut cx| async move {
+        if let Some((root, trusted)) = ask_for_trust {
+            if !trusted.await {
+                return;
+            }
+            if cx.update(|cx| TrustedWorktrees::trust(root, cx)).is_err() {
+                return;
+            }
+        }
+        let Ok(Some(started)) = inventory.update(&mut cx, |inventory, cx| {
+            inventory.queue_spawn(scheduled_runnable.id.clone(), priority, cx)
+        }) else {
+            return;
+        };
+        if started.await.is_err() {
+            return;
+        }
+        if let Ok(resolve_variables) = workspace.update(&mut cx, |_, cx| {
+            runnable::resolve_variables(spawn_in_terminal, context, cx)
+        }) {
+            let spawn_in_terminal = resolve_variables.await;
+            workspace
+                .update(&mut cx, |workspace, cx| match spawn_in_terminal {
+                    Ok(spawn_in_terminal) => {
+                        if let Some(chain_depth) = chain_depth {
+                            ActionChains::run_started(
+                                spawn_in_terminal.id.clone(),
+                                chain_depth,
+                                cx,
+                            );
+                        }
+                        workspace.project().update(cx, |project, cx| {
+                            project.runnable_inventory().update(cx, |inventory, cx| {
+                                inventory.last_scheduled_runnable = Some(scheduled_runnable);
+                                inventory.runnable_started(&spawn_in_terminal, cx)
+                            })
+                        });
+                        cx.emit(workspace::Event::SpawnRunnable(spawn_in_terminal));
+                    }
+                    Err(error) => inventory.update(cx, |inventory, cx| {
+                        inventory.runnable_spawn_failed(
+                            scheduled_runnable.id,
+                            &label,
+                            &error,
+                            &env,
+                            cx,
+                        )
+                    }),
+                })
+                .ok();
+        }
+        inventory
+            .update(&mut cx, |inventory, cx| inventory.spawn_started(cx))
+            .ok();
+    })
+    .detach();
+}
+
+fn runnable_context(
+    workspace: &Workspace,
+    worktree_root: Option<PathBuf>,
+    cx: &WindowContext,
+) -> RunnableContext {
+    let mut context = RunnableContext {
+        worktree_root,
+        ..RunnableContext::default()
+    };
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return context;
+    };
+    let editor = editor.read(cx);
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return context;
+    };
+    context.active_file = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx));
+    if context.active_file.is_some() {
+        let cursor = editor.selections.newest::<Point>(cx).head();
+        context.row = Some(cursor.row + 1);
+        context.column = Some(cursor.column + 1);
+    }
+    context
+}
+
+/// Absolute paths of the project's local directory worktrees, to pick the runnable's cwd among.
+fn local_worktree_roots(workspace: &Workspace, cx: &AppContext) -> Vec<PathBuf> {
+    workspace
+        .project()
+        .read(cx)
+        .directory_worktrees(cx)
+        .filter(|worktree| worktree.read(cx).is_local())
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+        .collect()
 }
 
 fn runnable_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Option<PathBuf>> {
     let project = workspace.project().read(cx);
     let available_worktrees = project
-        .worktrees()
-        .filter(|worktree| {
-            let worktree = worktree.read(cx);
-            worktree.is_visible()
-                && worktree.is_local()
-                && worktree.root_entry().map_or(false, |e| e.is_dir())
-        })
+        .directory_worktrees(cx)
+        .filter(|worktree| worktree.read(cx).is_local())
         .collect::<Vec<_>>();
     let cwd = match available_worktrees.len() {
         0 => None,
         1 => Some(available_worktrees[0].read(cx).abs_path()),
         _ => {
             let cwd_for_active_entry = project.active_entry().and_then(|entry_id| {
-                available_worktrees.into_iter().find_map(|worktree| {
+                available_worktrees.iter().find_map(|worktree| {
                     let worktree = worktree.read(cx);
                     if worktree.contains_entry(entry_id) {
                         Some(worktree.abs_path())
@@ -78,10 +286,18 @@ fn runnable_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result
                     }
                 })
             });
-            anyhow::ensure!(
-                cwd_for_active_entry.is_some(),
-                "Cannot determine runnable cwd for multiple worktrees"
-            );
+            if cwd_for_active_entry.is_none() {
+                let display_names = project.worktree_display_names(cx);
+                let worktree_names = available_worktrees
+                    .iter()
+                    .filter_map(|worktree| display_names.get(&worktree.read(cx).id()))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                anyhow::bail!(
+                    "Cannot determine runnable cwd for multiple worktrees: {}",
+                    worktree_names.join(", ")
+                );
+            }
             cwd_for_active_entry
         }
     };
@@ -0,0 +1,339 @@
+//! Offering to save the one-shot commands, run over and over, as runnables: once per session and command,
+//! unless the user asked not to be offered the command again.
+
+use std::time::Duration;
+
+use collections::HashSet;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{AppContext, DismissEvent, EventEmitter, Global, Render, WeakView};
+use project::RunRecord;
+use runnable::{OneshotRunnable, RunnablesSettings};
+use settings::Settings;
+use ui::prelude::*;
+use util::{paths, ResultExt};
+use workspace::Workspace;
+
+const DECLINED_ONESHOTS_KEY: &str = "runnables_declined_oneshot_commands";
+/// The period the runs of a one-shot command are counted in.
+const SUGGESTION_PERIOD: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+pub(crate) const SAVE_ONESHOT_NOTIFICATION_ID: usize = 0x0e5a_7ed1;
+
+/// One-shot commands, by their hashes, that should not be offered to be saved as runnables.
+#[derive(Default)]
+pub(crate) struct OneshotSuggestions {
+    /// Commands the user asked not to be offered again, persisted between the restarts.
+    declined: HashSet<u64>,
+    /// Commands offered in the current session.
+    offered: HashSet<u64>,
+}
+
+impl Global for OneshotSuggestions {}
+
+impl OneshotSuggestions {
+    /// Loads the commands declined in the previous sessions.
+    pub(crate) fn init(cx: &mut AppContext) {
+        let declined = KEY_VALUE_STORE
+            .read_kvp(DECLINED_ONESHOTS_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|declined| serde_json::from_str(&declined).log_err())
+            .unwrap_or_default();
+        cx.set_global(Self {
+            declined,
+            offered: HashSet::default(),
+        });
+    }
+
+    fn decline(command_hash: u64, cx: &mut AppContext) {
+        let suggestions = cx.default_global::<Self>();
+        if !suggestions.declined.insert(command_hash) {
+            return;
+        }
+        let Some(serialized) = serde_json::to_string(&suggestions.declined).log_err() else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(DECLINED_ONESHOTS_KEY.to_string(), serialized)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+/// Offers to save the one-shot command of the finished run as a runnable, if it's been run
+/// `runnables.suggest_saving_oneshot_after` times within the last week.
+pub(crate) fn suggest_saving_oneshot(
+    workspace: &mut Workspace,
+    run: &RunRecord,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(command_hash) = OneshotRunnable::command_hash(&run.id) else {
+        return;
+    };
+    let threshold = RunnablesSettings::get_global(cx).suggest_saving_oneshot_after;
+    if threshold == 0 {
+        return;
+    }
+    let suggestions = cx.default_global::<OneshotSuggestions>();
+    if suggestions.declined.contains(&command_hash) || suggestions.offered.contains(&command_hash) {
+        return;
+    }
+    let inventory = workspace.project().read(cx).runnable_inventory().read(cx);
+    let since = inventory.now() - SUGGESTION_PERIOD;
+    let runs = inventory.runs_since(&run.id, since);
+    if runs < threshold {
+        return;
+    }
+
+    cx.default_global::<OneshotSuggestions>()
+        .offered
+        .insert(command_hash);
+    // The label is the command line, with its secrets redacted: these are better not saved to a file anyway.
+    let command_line = SharedString::from(run.label.clone());
+    let workspace_handle = workspace.weak_handle();
+    workspace.dismiss_notification::<SaveOneshotNotification>(SAVE_ONESHOT_NOTIFICATION_ID, cx);
+    workspace.show_notification(SAVE_ONESHOT_NOTIFICATION_ID, cx, |cx| {
+        cx.new_view(|_| SaveOneshotNotification {
+            command_line,
+            command_hash,
+            runs,
+            workspace: workspace_handle,
+        })
+    });
+}
+
+/// Offers to save the one-shot command run over and over as a runnable.
+pub(crate) struct SaveOneshotNotification {
+    pub(crate) command_line: SharedString,
+    command_hash: u64,
+    runs: usize,
+    workspace: WeakView<Workspace>,
+}
+
+impl EventEmitter<DismissEvent> for SaveOneshotNotification {}
+
+impl SaveOneshotNotification {
+    /// Opens the runnables file with the command's definition added, for the user to name it and save the file.
+    pub(crate) fn save(&mut self, cx: &mut ViewContext<Self>) {
+        let command_line = self.command_line.clone();
+        let Some(open_task) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(paths::RUNNABLES.clone(), true, cx)
+            })
+            .log_err()
+        else {
+            return;
+        };
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    insert_oneshot_definition(editor, &command_line, cx)
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        cx.emit(DismissEvent);
+    }
+
+    /// Stops offering the command to be saved, in this session and the next ones.
+    pub(crate) fn decline(&mut self, cx: &mut ViewContext<Self>) {
+        OneshotSuggestions::decline(self.command_hash, cx);
+        cx.emit(DismissEvent);
+    }
+}
+
+/// Inserts the definition running the command line in the shell, as the one-shot runnable did, first into the file's runnables,
+/// or at the end of the file if there are none, selecting its label to be renamed.
+fn insert_oneshot_definition(
+    editor: &mut Editor,
+    command_line: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    let definition = serde_json::json!({
+        "label": command_line,
+        "command": "sh",
+        "args": ["-c", command_line],
+    });
+    let Some(definition) = serde_json::to_string_pretty(&definition).log_err() else {
+        return;
+    };
+    let text = editor.buffer().read(cx).snapshot(cx).text();
+    let (offset, new_text) = match text
+        .find("\"runnables\"")
+        .and_then(|key| Some(key + text[key..].find('[')? + 1))
+    {
+        Some(array_start) => (array_start, format!("\n{definition},")),
+        None => (text.len(), format!("\n{definition}\n")),
+    };
+    const LABEL_KEY: &str = "\"label\": ";
+    let label_start = offset + new_text.find(LABEL_KEY).unwrap_or(0) + LABEL_KEY.len();
+    let label_end = label_start + serde_json::Value::from(command_line).to_string().len();
+    editor.edit([(offset..offset, new_text)], cx);
+    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+        selections.select_ranges([label_start..label_end])
+    });
+}
+
+impl Render for SaveOneshotNotification {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .elevation_3(cx)
+            .p_4()
+            .gap_1()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(div().max_w_80().child(Label::new(format!(
+                        "'{}' ran {} times this week. Save it as a runnable?",
+                        self.command_line, self.runs
+                    ))))
+                    .child(
+                        div()
+                            .id("cancel")
+                            .child(Icon::new(IconName::Close))
+                            .cursor_pointer()
+                            .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("save", "Save as Runnable")
+                            .style(ButtonStyle::Filled)
+                            .on_click(cx.listener(|notification, _, cx| notification.save(cx))),
+                    )
+                    .child(
+                        Button::new("decline", "Don't Ask Again for This Command")
+                            .on_click(cx.listener(|notification, _, cx| notification.decline(cx))),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, View, VisualTestContext};
+    use project::{FakeFs, Inventory, Project};
+    use runnable::Runnable;
+    use workspace::AppState;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init(app_state.clone(), cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+
+    fn run_oneshot(
+        command_line: &str,
+        inventory: &gpui::Model<Inventory>,
+        cx: &mut VisualTestContext,
+    ) {
+        let runnable = OneshotRunnable::new(command_line);
+        inventory.update(cx, |inventory, cx| {
+            inventory.runnable_started(&runnable.exec(None).unwrap(), cx);
+            inventory.runnable_finished(runnable.id().clone(), Some(0), None, cx);
+        });
+        cx.run_until_parked();
+    }
+
+    fn offered_command(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Option<SharedString> {
+        workspace.update(cx, |workspace, cx| {
+            let notification =
+                workspace.notification::<SaveOneshotNotification>(SAVE_ONESHOT_NOTIFICATION_ID)?;
+            Some(notification.read(cx).command_line.clone())
+        })
+    }
+
+    fn dismiss(workspace: &View<Workspace>, cx: &mut VisualTestContext) {
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .dismiss_notification::<SaveOneshotNotification>(SAVE_ONESHOT_NOTIFICATION_ID, cx)
+        });
+    }
+
+    #[gpui::test]
+    async fn test_suggest_saving_repeated_oneshots(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = Project::test(FakeFs::new(cx.executor()), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let inventory = project.read_with(cx, |project, _| project.runnable_inventory().clone());
+
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        run_oneshot("cargo build", &inventory, cx);
+        assert_eq!(offered_command(&workspace, cx), None);
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        assert_eq!(
+            offered_command(&workspace, cx).as_deref(),
+            Some("cargo test -p editor"),
+            "The third run of the command should offer to save it"
+        );
+        dismiss(&workspace, cx);
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        assert_eq!(
+            offered_command(&workspace, cx),
+            None,
+            "The command should be offered once per session"
+        );
+
+        run_oneshot("cargo build", &inventory, cx);
+        run_oneshot("cargo build", &inventory, cx);
+        let notification = workspace.update(cx, |workspace, _| {
+            workspace
+                .notification::<SaveOneshotNotification>(SAVE_ONESHOT_NOTIFICATION_ID)
+                .unwrap()
+        });
+        notification.update(cx, |notification, cx| notification.decline(cx));
+        cx.run_until_parked();
+        assert_eq!(offered_command(&workspace, cx), None);
+
+        // A new session offers the commands not declined again.
+        cx.update(|cx| OneshotSuggestions::init(cx));
+        run_oneshot("cargo build", &inventory, cx);
+        assert_eq!(
+            offered_command(&workspace, cx),
+            None,
+            "The declined command should not be offered in the next sessions"
+        );
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        assert_eq!(
+            offered_command(&workspace, cx).as_deref(),
+            Some("cargo test -p editor")
+        );
+
+        cx.update(|cx| {
+            cx.update_global(|store: &mut settings::SettingsStore, cx| {
+                store.update_user_settings::<RunnablesSettings>(cx, |settings| {
+                    settings.suggest_saving_oneshot_after = Some(0);
+                });
+            });
+        });
+        dismiss(&workspace, cx);
+        cx.update(|cx| OneshotSuggestions::init(cx));
+        run_oneshot("cargo test -p editor", &inventory, cx);
+        assert_eq!(
+            offered_command(&workspace, cx),
+            None,
+            "Nothing should be offered with the suggestions turned off"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::{path::PathBuf, sync::Arc};
+
+use editor::Editor;
+use gpui::{
+    div, AppContext, FocusHandle, FocusableView, IntoElement, ParentElement, Render, Styled, View,
+    ViewContext, VisualContext,
+};
+use runnable::{Runnable, RunnableOverrides};
+use ui::{prelude::*, v_flex, CheckboxWithLabel, Label, Selection};
+
+/// A sheet to adjust the runnable before scheduling it, prefilled from the runnable definition.
+pub(crate) struct RunnableOptions {
+    cwd_editor: View<Editor>,
+    args_editor: View<Editor>,
+    default_cwd: Option<PathBuf>,
+    use_new_terminal: bool,
+    reveal: bool,
+    /// Why the runnable cannot be spawned, if it cannot, shown above the options.
+    exec_error: Option<SharedString>,
+    /// The variables the runnable gets spawned with, if it gets only these, with a clean environment.
+    clean_env_keys: Option<SharedString>,
+    /// The note about the runnable, shown above the options.
+    note: Option<SharedString>,
+}
+
+impl RunnableOptions {
+    /// Prefills the working directory with the override given, or the runnable's own cwd, or the default one, in that order.
+    pub(crate) fn new(
+        runnable: Arc<dyn Runnable>,
+        cwd_override: Option<PathBuf>,
+        default_cwd: Option<PathBuf>,
+        note: Option<SharedString>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let default_cwd = cwd_override
+            .or_else(|| runnable.cwd().map(|cwd| cwd.to_path_buf()))
+            .or(default_cwd);
+        let (spawn_in_terminal, exec_error) = match runnable.exec(default_cwd.clone()) {
+            Ok(spawn_in_terminal) => (Some(spawn_in_terminal), None),
+            Err(error) => (None, Some(format!("{error:#}").into())),
+        };
+        let cwd_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Working directory", cx);
+            if let Some(cwd) = &default_cwd {
+                editor.set_text(cwd.to_string_lossy(), cx);
+            }
+            editor
+        });
+        let clean_env_keys = spawn_in_terminal
+            .as_ref()
+            .filter(|spawn| spawn.clean_env)
+            .map(|spawn| {
+                let mut keys = terminal::clean_runnable_env(&spawn.env)
+                    .into_keys()
+                    .collect::<Vec<_>>();
+                keys.sort();
+                SharedString::from(keys.join(", "))
+            });
+        let args_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Extra arguments", cx);
+            editor
+        });
+        Self {
+            cwd_editor,
+            args_editor,
+            default_cwd,
+            use_new_terminal: spawn_in_terminal
+                .as_ref()
+                .map_or(false, |spawn| spawn.use_new_terminal),
+            reveal: spawn_in_terminal.map_or(true, |spawn| spawn.reveal),
+            exec_error,
+            clean_env_keys,
+            note,
+        }
+    }
+
+    /// The sheet's editors, in the tab order.
+    pub(crate) fn fields(&self, cx: &AppContext) -> Vec<FocusHandle> {
+        vec![
+            self.args_editor.focus_handle(cx),
+            self.cwd_editor.focus_handle(cx),
+        ]
+    }
+
+    pub(crate) fn overrides(&self, cx: &AppContext) -> RunnableOverrides {
+        let cwd = self.cwd_editor.read(cx).text(cx);
+        let cwd = cwd.trim();
+        RunnableOverrides {
+            cwd: if cwd.is_empty() {
+                self.default_cwd.clone()
+            } else {
+                Some(PathBuf::from(cwd))
+            },
+            extra_args: self
+                .args_editor
+                .read(cx)
+                .text(cx)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            use_new_terminal: Some(self.use_new_terminal),
+            reveal: Some(self.reveal),
+        }
+    }
+}
+
+fn selection(selected: bool) -> Selection {
+    if selected {
+        Selection::Selected
+    } else {
+        Selection::Unselected
+    }
+}
+
+impl Render for RunnableOptions {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .children(self.note.clone().map(|note| {
+                div()
+                    .debug_selector(|| "RUNNABLE-OPTIONS-note".into())
+                    .child(Label::new(note).size(LabelSize::Small).color(Color::Muted))
+            }))
+            .children(
+                self.exec_error
+                    .clone()
+                    .map(|exec_error| Label::new(exec_error).color(Color::Error)),
+            )
+            .children(self.clean_env_keys.clone().map(|keys| {
+                Label::new(format!("Clean environment: {keys}"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
+            .child(Label::new("Extra arguments").color(Color::Muted))
+            .child(div().px_1().py_0p5().child(self.args_editor.clone()))
+            .child(Label::new("Working directory").color(Color::Muted))
+            .child(div().px_1().py_0p5().child(self.cwd_editor.clone()))
+            .child(CheckboxWithLabel::new(
+                "runnable-options-new-terminal",
+                Label::new("Spawn in a new terminal"),
+                selection(self.use_new_terminal),
+                cx.listener(|options, selection: &Selection, cx| {
+                    options.use_new_terminal = *selection == Selection::Selected;
+                    cx.notify();
+                }),
+            ))
+            .child(CheckboxWithLabel::new(
+                "runnable-options-reveal",
+                Label::new("Reveal the terminal"),
+                selection(self.reveal),
+                cx.listener(|options, selection: &Selection, cx| {
+                    options.reveal = *selection == Selection::Selected;
+                    cx.notify();
+                }),
+            ))
+    }
+}
@@ -0,0 +1,136 @@
+//! Formats of the state the runnables persist in the key-value store between the restarts, e.g. the run history.
+//!
+//! Every format is versioned: a change of it adds a version, along with the migration from the previous one,
+//! for the state persisted by the older Zed versions to carry over. The JSON of every version is snapshotted
+//! under `test_data/persistence`, for the changes of a format without a new version to fail the tests.
+//! The helpers tagging and reading the versions are shared with the other crates, see [`db::versioned`].
+
+use anyhow::{anyhow, Result};
+use db::versioned::{from_value, parse_versioned, to_versioned_json};
+use project::RunRecord;
+use serde::{Deserialize, Serialize};
+
+/// The run history as persisted before its format got versioned: the bare list of the runs.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct RunHistoryV0(pub Vec<RunRecord>);
+
+/// The runs of a workspace, oldest first.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RunHistoryV1 {
+    pub runs: Vec<RunRecord>,
+}
+
+/// The latest format of the run history, the one it gets saved in.
+pub(crate) type PersistedRunHistory = RunHistoryV1;
+const RUN_HISTORY_VERSION: &str = "1";
+
+/// The list of the runs got wrapped, for the format to be tagged with its version.
+fn migrate_run_history_v0(v0: RunHistoryV0) -> RunHistoryV1 {
+    RunHistoryV1 { runs: v0.0 }
+}
+
+/// Parses the run history persisted in any of its formats, migrated to the latest one.
+pub(crate) fn parse_run_history(serialized: &str) -> Result<PersistedRunHistory> {
+    let (version, value) = parse_versioned(serialized)?;
+    match version.as_deref() {
+        None => Ok(migrate_run_history_v0(from_value(value, "0")?)),
+        Some("1") => from_value(value, "1"),
+        Some(version) => Err(anyhow!("unknown version {version:?}")),
+    }
+}
+
+pub(crate) fn serialize_run_history(run_history: &PersistedRunHistory) -> Result<String> {
+    to_versioned_json(RUN_HISTORY_VERSION, run_history)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    use db::versioned::assert_snapshot;
+    use project::{RunAttempt, RunStatus};
+    use runnable::RunnableId;
+
+    use super::*;
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_data/persistence")
+            .join(name)
+    }
+
+    fn time(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn run(id: &str, status: RunStatus, started_at: u64) -> RunRecord {
+        RunRecord {
+            id: RunnableId::new(id),
+            label: format!("cargo {id}"),
+            command: "cargo".to_string(),
+            args: vec![id.to_string()],
+            cwd: Some("/dir".into()),
+            env: BTreeMap::from_iter([("RUST_LOG".to_string(), "info".to_string())]),
+            status,
+            started_at: time(started_at),
+            finished_at: Some(time(started_at + 30)),
+            test_results: None,
+            previous_session: false,
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
+            retry: None,
+            failed_attempts: Vec::new(),
+        }
+    }
+
+    fn sample_runs() -> Vec<RunRecord> {
+        vec![
+            run("check", RunStatus::Succeeded, 1_700_000_000),
+            run("test", RunStatus::Failed(101), 1_700_000_100),
+        ]
+    }
+
+    #[test]
+    fn test_run_history_snapshots() {
+        let mut runs = sample_runs();
+        runs[1].failed_attempts.push(RunAttempt {
+            status: RunStatus::Failed(101),
+            started_at: time(1_700_000_050),
+            finished_at: Some(time(1_700_000_080)),
+        });
+        let latest = RunHistoryV1 { runs };
+        assert_snapshot(&snapshot_path("run_history_v1.json"), "1", &latest);
+
+        let serialized = serialize_run_history(&latest).unwrap();
+        assert_eq!(parse_run_history(&serialized).unwrap(), latest);
+    }
+
+    #[test]
+    fn test_run_history_migrations() {
+        let v0 = std::fs::read_to_string(snapshot_path("run_history_v0.json")).unwrap();
+        assert_eq!(
+            parse_run_history(&v0).unwrap(),
+            RunHistoryV1 {
+                runs: sample_runs()
+            },
+            "The runs of the unversioned history should carry over"
+        );
+
+        for unreadable in [
+            r#"{"runs": []}"#,
+            r#"{"version": "2", "runs": []}"#,
+            r#"{"version": "1", "runs": [{"id": "check"}]}"#,
+            "[{\"id\": \"che",
+        ] {
+            assert!(
+                parse_run_history(unreadable).is_err(),
+                "{unreadable} should not be read"
+            );
+        }
+    }
+}
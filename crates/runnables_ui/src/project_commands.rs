@@ -0,0 +1,67 @@
+//! Scheduling the project's runnables with [`ScheduleRunnable`], e.g. from the commands the projects define for the palette.
+
+use gpui::ViewContext;
+use project::SpawnPriority;
+use runnable::{OneshotRunnable, RunnableOverrides};
+use workspace::{Toast, Workspace};
+use zed_actions::{ProjectRunnable, ScheduleRunnable};
+
+use crate::{label_overrides::label_override, schedule_runnable};
+
+const UNKNOWN_RUNNABLE_TOAST_ID: usize = 0x9d2e_61b5;
+
+/// Schedules the runnable labeled as given, by its own label or the one the user renamed it to,
+/// or the one-shot runnable of the command given, run in its worktree once the user trusts it.
+pub(crate) fn schedule_project_runnable(
+    workspace: &mut Workspace,
+    action: &ScheduleRunnable,
+    cx: &mut ViewContext<Workspace>,
+) {
+    match &action.runnable {
+        ProjectRunnable::Label(label) => {
+            let project = workspace.project().clone();
+            let inventory = project.read(cx).runnable_inventory().clone();
+            let runnables =
+                inventory.update(cx, |inventory, cx| inventory.list_runnables(None, cx));
+            let runnable = runnables.into_iter().find(|runnable| {
+                runnable.name() == label
+                    || label_override(project.read(cx), runnable.as_ref(), cx).as_ref()
+                        == Some(label)
+            });
+            let Some(runnable) = runnable else {
+                workspace.show_toast(
+                    Toast::new(
+                        UNKNOWN_RUNNABLE_TOAST_ID,
+                        format!("No runnable labeled '{label}' in the project"),
+                    ),
+                    cx,
+                );
+                return;
+            };
+            schedule_runnable(
+                workspace,
+                runnable.as_ref(),
+                RunnableOverrides::default(),
+                SpawnPriority::Interactive,
+                cx,
+            );
+        }
+        ProjectRunnable::Command(command) => {
+            let mut runnable = OneshotRunnable::new(command);
+            if let Some(worktree_root) = &action.worktree_root {
+                runnable = runnable.with_project_root(worktree_root.clone());
+            }
+            let overrides = RunnableOverrides {
+                cwd: action.worktree_root.clone(),
+                ..RunnableOverrides::default()
+            };
+            schedule_runnable(
+                workspace,
+                &runnable,
+                overrides,
+                SpawnPriority::Interactive,
+                cx,
+            );
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! The interactive steps of a runnable's scheduling, shown one at a time with the same keyboard behavior for all of them:
+//! enter confirms the step, escape goes one step back, tab and shift-tab cycle the step's fields.
+
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+
+use gpui::{
+    actions, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, InteractiveElement,
+    IntoElement, ParentElement, Render, Styled, View, ViewContext, VisualContext, WindowContext,
+};
+use runnable::{Runnable, RunnableOverrides};
+use ui::{prelude::*, v_flex, Label};
+
+use crate::{cwd_prompt::CwdPrompt, options::RunnableOptions};
+
+actions!(runnables, [FocusNextField, FocusPrevField]);
+
+/// A step to ask the user for, before the runnable gets scheduled.
+#[derive(Clone)]
+pub(crate) enum PromptKind {
+    /// Pick the working directory among the worktree roots given, when it cannot be told from the active entry.
+    Cwd(Vec<PathBuf>),
+    /// Adjust the runnable with the options sheet, showing the note about the runnable, if any, above the options.
+    Options { note: Option<SharedString> },
+}
+
+impl PromptKind {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Cwd(_) => "Working directory",
+            Self::Options { .. } => "Options",
+        }
+    }
+}
+
+enum PromptView {
+    Cwd(View<CwdPrompt>),
+    Options(View<RunnableOptions>),
+}
+
+impl PromptView {
+    fn fields(&self, cx: &AppContext) -> Vec<FocusHandle> {
+        match self {
+            Self::Cwd(prompt) => prompt.read(cx).fields(),
+            Self::Options(options) => options.read(cx).fields(cx),
+        }
+    }
+
+    fn apply(&self, overrides: &mut RunnableOverrides, cx: &WindowContext) {
+        match self {
+            Self::Cwd(prompt) => overrides.cwd = prompt.read(cx).selected_root(cx),
+            Self::Options(options) => *overrides = options.read(cx).overrides(cx),
+        }
+    }
+
+    fn to_any(&self) -> AnyView {
+        match self {
+            Self::Cwd(prompt) => prompt.clone().into(),
+            Self::Options(options) => options.clone().into(),
+        }
+    }
+}
+
+struct ShownPrompt {
+    kind: PromptKind,
+    view: PromptView,
+    /// Overrides confirmed by the steps before this one.
+    overrides: RunnableOverrides,
+}
+
+pub(crate) enum PromptStackEvent {
+    /// All steps got confirmed, with the overrides to schedule the runnable with.
+    Confirmed(RunnableOverrides),
+    /// The first step got cancelled.
+    Cancelled,
+}
+
+/// The steps of a runnable's scheduling, with the confirmed ones kept underneath the current one to go back to.
+pub(crate) struct PromptStack {
+    runnable: Arc<dyn Runnable>,
+    default_cwd: Option<PathBuf>,
+    shown: Vec<ShownPrompt>,
+    pending: VecDeque<PromptKind>,
+    focus_handle: FocusHandle,
+}
+
+impl PromptStack {
+    /// Shows the first of the prompts, with the overrides given as the ones confirmed before it.
+    pub(crate) fn new(
+        runnable: Arc<dyn Runnable>,
+        default_cwd: Option<PathBuf>,
+        overrides: RunnableOverrides,
+        prompts: Vec<PromptKind>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut stack = Self {
+            runnable,
+            default_cwd,
+            shown: Vec::new(),
+            pending: prompts.into(),
+            focus_handle: cx.focus_handle(),
+        };
+        stack.show_next(overrides, cx);
+        stack
+    }
+
+    /// Shows the next pending step, returning `false` if there's none left.
+    fn show_next(&mut self, overrides: RunnableOverrides, cx: &mut ViewContext<Self>) -> bool {
+        let Some(kind) = self.pending.pop_front() else {
+            return false;
+        };
+        let view = match &kind {
+            PromptKind::Cwd(roots) => {
+                PromptView::Cwd(cx.new_view(|cx| CwdPrompt::new(roots.clone(), cx)))
+            }
+            PromptKind::Options { note } => {
+                let cwd_override = overrides.cwd.clone();
+                let default_cwd = self.default_cwd.clone();
+                let runnable = self.runnable.clone();
+                let note = note.clone();
+                PromptView::Options(cx.new_view(|cx| {
+                    RunnableOptions::new(runnable, cwd_override, default_cwd, note, cx)
+                }))
+            }
+        };
+        self.shown.push(ShownPrompt {
+            kind,
+            view,
+            overrides,
+        });
+        self.focus_field(0, cx);
+        true
+    }
+
+    /// Accessible name of the current step, telling the runnable it's for.
+    pub(crate) fn label(&self) -> SharedString {
+        let title = self.shown.last().map_or("", |prompt| prompt.kind.title());
+        format!("{title} for {}", self.runnable.name()).into()
+    }
+
+    /// Index of the current step's field focused, if any.
+    pub(crate) fn focused_field(&self, cx: &WindowContext) -> Option<usize> {
+        self.shown
+            .last()?
+            .view
+            .fields(cx)
+            .iter()
+            .position(|field| field.contains_focused(cx))
+    }
+
+    fn focus_field(&self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(prompt) = self.shown.last() else {
+            return;
+        };
+        if let Some(field) = prompt.view.fields(cx).get(ix) {
+            cx.focus(field);
+        }
+        cx.notify();
+    }
+
+    fn cycle_fields(&self, forward: bool, cx: &mut ViewContext<Self>) {
+        let Some(prompt) = self.shown.last() else {
+            return;
+        };
+        let field_count = prompt.view.fields(cx).len();
+        if field_count == 0 {
+            return;
+        }
+        let next_ix = match self.focused_field(cx) {
+            Some(ix) if forward => (ix + 1) % field_count,
+            Some(ix) => (ix + field_count - 1) % field_count,
+            None => 0,
+        };
+        self.focus_field(next_ix, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some(prompt) = self.shown.last() else {
+            return;
+        };
+        let mut overrides = prompt.overrides.clone();
+        prompt.view.apply(&mut overrides, cx);
+        if !self.show_next(overrides.clone(), cx) {
+            cx.emit(PromptStackEvent::Confirmed(overrides));
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        let Some(prompt) = self.shown.pop() else {
+            return;
+        };
+        self.pending.push_front(prompt.kind);
+        if self.shown.is_empty() {
+            cx.emit(PromptStackEvent::Cancelled);
+        } else {
+            self.focus_field(0, cx);
+        }
+    }
+
+    fn focus_next_field(&mut self, _: &FocusNextField, cx: &mut ViewContext<Self>) {
+        self.cycle_fields(true, cx);
+    }
+
+    fn focus_prev_field(&mut self, _: &FocusPrevField, cx: &mut ViewContext<Self>) {
+        self.cycle_fields(false, cx);
+    }
+}
+
+impl EventEmitter<PromptStackEvent> for PromptStack {}
+
+impl FocusableView for PromptStack {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.shown
+            .last()
+            .and_then(|prompt| prompt.view.fields(cx).into_iter().next())
+            .unwrap_or_else(|| self.focus_handle.clone())
+    }
+}
+
+impl Render for PromptStack {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RunnablePrompt")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::focus_next_field))
+            .on_action(cx.listener(Self::focus_prev_field))
+            .elevation_3(cx)
+            .p_4()
+            .gap_2()
+            .child(Label::new(self.label()))
+            .children(self.shown.last().map(|prompt| prompt.view.to_any()))
+    }
+}
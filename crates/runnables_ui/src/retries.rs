@@ -0,0 +1,334 @@
+//! Automatic retries of the failed runs, by the retry policies of their runnables, e.g. for the flaky tests.
+//!
+//! Every retry gets recorded as the next attempt of the failed run, and cancelling the runnables aborts the pending retries.
+
+use std::time::Duration;
+
+use gpui::ViewContext;
+use project::{RunRecord, RunStatus, SpawnPriority};
+use runnable::RunnableOverrides;
+use workspace::Workspace;
+
+use crate::schedule_runnable;
+
+/// Schedules the failed run's runnable to run again after the delay of its retry policy, with the overrides it last ran with,
+/// if the policy retries the run's exit code and has attempts left. Returns whether the retry got scheduled.
+pub(crate) fn retry_failed_run(
+    workspace: &mut Workspace,
+    run: &RunRecord,
+    cx: &mut ViewContext<Workspace>,
+) -> bool {
+    let (RunStatus::Failed(exit_code), Some(retry)) = (run.status, &run.retry) else {
+        return false;
+    };
+    if !retry.should_retry(run.attempt(), exit_code) {
+        return false;
+    }
+    let id = run.id.clone();
+    let delay = Duration::from_secs(retry.delay_secs);
+    let inventory = workspace.project().read(cx).runnable_inventory().clone();
+    inventory.update(cx, |inventory, _| inventory.retry_scheduled(id.clone()));
+    cx.spawn(|workspace, mut cx| async move {
+        cx.background_executor().timer(delay).await;
+        workspace.update(&mut cx, |workspace, cx| {
+            let runnable = inventory.update(cx, |inventory, cx| {
+                if !inventory.is_retry_pending(&id) {
+                    return None;
+                }
+                let runnable = inventory.find_runnable(&id, cx);
+                if runnable.is_none() {
+                    inventory.runnable_cancelled(&id, cx);
+                }
+                runnable
+            });
+            let Some(runnable) = runnable else {
+                return;
+            };
+            let overrides = inventory
+                .read(cx)
+                .last_scheduled_runnable
+                .as_ref()
+                .filter(|scheduled| scheduled.id == id)
+                .map(|scheduled| scheduled.overrides.clone())
+                .unwrap_or_default();
+            schedule_runnable(
+                workspace,
+                runnable.as_ref(),
+                overrides,
+                SpawnPriority::Automatic,
+                cx,
+            );
+        })
+    })
+    .detach_and_log_err(cx);
+    true
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use futures::{channel::mpsc, StreamExt as _};
+    use gpui::{Model, TestAppContext, View, VisualTestContext};
+    use project::{FakeFs, Inventory, InventoryEvent, Project};
+    use runnable::{static_source::StaticSource, RunnableId};
+    use serde_json::json;
+    use terminal_view::terminal_panel::TerminalPanel;
+    use workspace::AppState;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init(app_state.clone(), cx);
+            Project::init_settings(cx);
+            terminal_view::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    /// Loads the terminal panel into the workspace, for the runnables scheduled to get spawned in a PTY for real.
+    pub(crate) async fn load_terminal_panel(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) {
+        let async_cx = cx.update(|cx| cx.to_async());
+        let terminal_panel = TerminalPanel::load(workspace.downgrade(), async_cx)
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| workspace.add_panel(terminal_panel, cx));
+        cx.executor().allow_parking();
+    }
+
+    /// The runs of the inventory's runnables, as they finish.
+    pub(crate) fn finished_runs(
+        inventory: &Model<Inventory>,
+        cx: &mut VisualTestContext,
+    ) -> mpsc::UnboundedReceiver<RunRecord> {
+        let (finished_tx, finished_rx) = mpsc::unbounded();
+        cx.update(|cx| {
+            cx.subscribe(inventory, move |_, event, _| {
+                if let InventoryEvent::RunFinished(run) = event {
+                    finished_tx.unbounded_send(run.clone()).ok();
+                }
+            })
+            .detach();
+        });
+        finished_rx
+    }
+
+    #[gpui::test]
+    async fn test_retrying_failed_runs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = Project::test(FakeFs::new(cx.executor()), [], cx).await;
+        let (runnables_tx, runnables_rx) = mpsc::unbounded();
+        cx.update(|cx| {
+            let source = StaticSource::new(runnables_rx, cx);
+            project.update(cx, |project, cx| {
+                project
+                    .runnable_inventory()
+                    .update(cx, |inventory, cx| inventory.add_source(source, cx))
+            });
+        });
+        runnables_tx
+            .unbounded_send(
+                json!({
+                    "version": "1",
+                    "runnables": [{
+                        "label": "integration tests",
+                        "command": "cargo",
+                        "args": ["test"],
+                        "retry": { "attempts": 3, "delay_secs": 5, "only_on_exit_codes": [1] }
+                    }]
+                })
+                .to_string(),
+            )
+            .unwrap();
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let spawns = Rc::new(RefCell::new(0));
+        cx.update(|cx| {
+            let spawns = spawns.clone();
+            cx.subscribe(&workspace, move |_, event, _| {
+                if let workspace::Event::SpawnRunnable(_) = event {
+                    *spawns.borrow_mut() += 1;
+                }
+            })
+            .detach();
+        });
+        cx.run_until_parked();
+        let inventory = project.read_with(cx, |project, _| project.runnable_inventory().clone());
+        let id = RunnableId::new("static_integration tests_0");
+        let schedule = |cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                let runnable = inventory
+                    .update(cx, |inventory, cx| inventory.find_runnable(&id, cx))
+                    .unwrap();
+                schedule_runnable(
+                    workspace,
+                    runnable.as_ref(),
+                    RunnableOverrides::default(),
+                    SpawnPriority::Interactive,
+                    cx,
+                );
+            });
+            cx.run_until_parked();
+        };
+        let finish = |exit_code, cx: &mut VisualTestContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory.runnable_finished(id.clone(), Some(exit_code), None, cx)
+            });
+            cx.run_until_parked();
+        };
+        let runs = |cx: &mut VisualTestContext| {
+            inventory.read_with(cx, |inventory, _| inventory.run_history().to_vec())
+        };
+
+        // The process fails twice, then succeeds.
+        schedule(cx);
+        finish(1, cx);
+        assert_eq!(*spawns.borrow(), 1, "Retries should wait for their delay");
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+        assert_eq!(*spawns.borrow(), 2);
+        assert_eq!(runs(cx)[0].retry_annotation().as_deref(), Some("retry 2/3"));
+        finish(1, cx);
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+        finish(0, cx);
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+        assert_eq!(*spawns.borrow(), 3);
+        let history = runs(cx);
+        assert_eq!(
+            history.len(),
+            1,
+            "All attempts should be recorded as one run"
+        );
+        assert_eq!(history[0].status, RunStatus::Succeeded);
+        assert_eq!(
+            history[0]
+                .failed_attempts
+                .iter()
+                .map(|attempt| attempt.status)
+                .collect::<Vec<_>>(),
+            [RunStatus::Failed(1), RunStatus::Failed(1)]
+        );
+
+        // Exit codes not retried and exhausted attempts stop the retries.
+        schedule(cx);
+        finish(2, cx);
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+        assert_eq!(*spawns.borrow(), 4);
+        schedule(cx);
+        for _ in 0..3 {
+            finish(1, cx);
+            cx.executor().advance_clock(Duration::from_secs(5));
+            cx.run_until_parked();
+        }
+        assert_eq!(*spawns.borrow(), 7);
+        let last_run = runs(cx).pop().unwrap();
+        assert_eq!(last_run.status, RunStatus::Failed(1));
+        assert_eq!(last_run.attempt(), 3);
+
+        // Cancelling the runnables aborts the pending retry.
+        schedule(cx);
+        finish(1, cx);
+        inventory.update(cx, |inventory, cx| inventory.cancel_queued_spawns(cx));
+        cx.executor().advance_clock(Duration::from_secs(5));
+        cx.run_until_parked();
+        assert_eq!(*spawns.borrow(), 8);
+        assert_eq!(runs(cx).len(), 4);
+    }
+
+    #[gpui::test]
+    async fn test_retrying_failed_pty_runs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let attempts_dir = tempfile::tempdir().unwrap();
+        let attempts_path = attempts_dir.path().join("attempts");
+        let project = Project::test(FakeFs::new(cx.executor()), [], cx).await;
+        let (runnables_tx, runnables_rx) = mpsc::unbounded();
+        cx.update(|cx| {
+            let source = StaticSource::new(runnables_rx, cx);
+            project.update(cx, |project, cx| {
+                project
+                    .runnable_inventory()
+                    .update(cx, |inventory, cx| inventory.add_source(source, cx))
+            });
+        });
+        // A runnable in a PTY, as they are by default, failing twice before it succeeds.
+        runnables_tx
+            .unbounded_send(
+                json!({
+                    "version": "1",
+                    "runnables": [{
+                        "label": "flaky tests",
+                        "command": "sh",
+                        "args": [
+                            "-c",
+                            "attempt=$(( $(cat \"$1\" 2>/dev/null || echo 0) + 1 )); echo $attempt > \"$1\"; [ $attempt -ge 3 ]",
+                            "sh",
+                            attempts_path,
+                        ],
+                        "retry": { "attempts": 3, "delay_secs": 5, "only_on_exit_codes": [1] }
+                    }]
+                })
+                .to_string(),
+            )
+            .unwrap();
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        load_terminal_panel(&workspace, cx).await;
+        cx.run_until_parked();
+        let inventory = project.read_with(cx, |project, _| project.runnable_inventory().clone());
+        let mut finished_runs = finished_runs(&inventory, cx);
+        workspace.update(cx, |workspace, cx| {
+            let runnable = inventory
+                .update(cx, |inventory, cx| {
+                    inventory.find_runnable(&RunnableId::new("static_flaky tests_0"), cx)
+                })
+                .unwrap();
+            schedule_runnable(
+                workspace,
+                runnable.as_ref(),
+                RunnableOverrides::default(),
+                SpawnPriority::Interactive,
+                cx,
+            );
+        });
+
+        for attempt in 1..3 {
+            let run = finished_runs.next().await.unwrap();
+            assert_eq!(
+                run.status,
+                RunStatus::Failed(1),
+                "Attempt {attempt} should fail with the exit code of its process"
+            );
+            cx.run_until_parked();
+            cx.executor().advance_clock(Duration::from_secs(5));
+            cx.run_until_parked();
+        }
+        let run = finished_runs.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(run.status, RunStatus::Succeeded);
+        assert_eq!(run.attempt(), 3);
+        assert_eq!(std::fs::read_to_string(&attempts_path).unwrap().trim(), "3");
+        let history = inventory.read_with(cx, |inventory, _| inventory.run_history().to_vec());
+        assert_eq!(
+            history.len(),
+            1,
+            "All attempts should be recorded as one run"
+        );
+        assert_eq!(
+            history[0]
+                .failed_attempts
+                .iter()
+                .map(|attempt| attempt.status)
+                .collect::<Vec<_>>(),
+            [RunStatus::Failed(1), RunStatus::Failed(1)]
+        );
+    }
+}
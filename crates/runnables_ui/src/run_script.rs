@@ -0,0 +1,142 @@
+//! Running the scripts dropped onto the terminal panel as one-shot runnables.
+
+use fs::Fs;
+use gpui::ViewContext;
+use project::SpawnPriority;
+use runnable::{OneshotRunnable, RunnableOverrides};
+use workspace::Workspace;
+use zed_actions::RunScript;
+
+use crate::schedule_runnable;
+
+/// Schedules the script as a one-shot runnable in its directory, recorded in the run history as any other run.
+/// The scripts of the worktrees are not spawned until the user trusts their worktree.
+pub(crate) fn run_script(
+    workspace: &mut Workspace,
+    action: &RunScript,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let path = action.path.clone();
+    let fs = workspace.app_state().fs.clone();
+    let project_root = workspace
+        .project()
+        .read(cx)
+        .find_local_worktree(&path, cx)
+        .map(|(worktree, _)| worktree.read(cx).abs_path().to_path_buf());
+    cx.spawn(|workspace, mut cx| async move {
+        let is_executable = fs
+            .metadata(&path)
+            .await?
+            .map_or(false, |metadata| metadata.is_executable);
+        let Some(mut runnable) = OneshotRunnable::for_script(&path, is_executable) else {
+            anyhow::bail!("{path:?} is neither executable nor a known script");
+        };
+        if let Some(project_root) = project_root {
+            runnable = runnable.with_project_root(project_root);
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            schedule_runnable(
+                workspace,
+                &runnable,
+                RunnableOverrides::default(),
+                SpawnPriority::Interactive,
+                cx,
+            )
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::AppState;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init(app_state.clone(), cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_run_dropped_script(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dropped_scripts",
+            json!({
+                "scripts": { "deploy": "#!/bin/sh", "setup.py": "", "notes.txt": "" },
+            }),
+        )
+        .await;
+        fs.set_executable("/dropped_scripts/scripts/deploy", true);
+        let project = Project::test(fs, ["/dropped_scripts".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let inventory = project.read_with(cx, |project, _| project.runnable_inventory().clone());
+        let drop_script = |path: &str, cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                run_script(
+                    workspace,
+                    &RunScript {
+                        path: PathBuf::from(path),
+                    },
+                    cx,
+                )
+            });
+            cx.run_until_parked();
+        };
+        let runs = |cx: &mut VisualTestContext| {
+            inventory.read_with(cx, |inventory, _| {
+                inventory
+                    .run_history()
+                    .iter()
+                    .map(|run| (run.label.clone(), run.cwd.clone()))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        drop_script("/dropped_scripts/scripts/deploy", cx);
+        assert!(
+            cx.has_pending_prompt(),
+            "The scripts of the worktrees should not run until the worktree is trusted"
+        );
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(
+            runs(cx),
+            [(
+                "/dropped_scripts/scripts/deploy".to_string(),
+                Some(PathBuf::from("/dropped_scripts/scripts"))
+            )]
+        );
+
+        drop_script("/dropped_scripts/scripts/setup.py", cx);
+        assert!(!cx.has_pending_prompt());
+        assert_eq!(
+            runs(cx).last().unwrap(),
+            &(
+                "python3 /dropped_scripts/scripts/setup.py".to_string(),
+                Some(PathBuf::from("/dropped_scripts/scripts"))
+            )
+        );
+
+        drop_script("/dropped_scripts/scripts/notes.txt", cx);
+        assert_eq!(
+            runs(cx).len(),
+            2,
+            "The files neither executable nor scripts should not be run"
+        );
+    }
+}
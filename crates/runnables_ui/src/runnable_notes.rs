@@ -0,0 +1,86 @@
+//! Free-form notes about the runnables, e.g. what they need to be running: written into the definitions
+//! of the runnables from the runnables file, and into the project settings by the runnables' ids for the rest,
+//! e.g. the providers' and the one-shot runnables.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use gpui::{AppContext, ViewContext};
+use project::Project;
+use runnable::{Runnable, RunnablesSettings};
+use settings::Settings;
+use util::paths;
+use workspace::Workspace;
+
+use crate::label_overrides::update_runnable_settings;
+
+/// The note about the runnable in the settings of any of the project's local worktrees or in the user ones,
+/// or the note of its definition. Cleared notes are stored as empty ones, hiding the definitions' notes.
+pub(crate) fn runnable_note(
+    project: &Project,
+    runnable: &dyn Runnable,
+    cx: &AppContext,
+) -> Option<String> {
+    let note = project
+        .directory_worktrees(cx)
+        .filter(|worktree| worktree.read(cx).is_local())
+        .map(|worktree| Some(worktree.read(cx).id().to_usize()))
+        .chain([None])
+        .find_map(|worktree_id| {
+            RunnablesSettings::get(worktree_id.map(|id| (id, Path::new(""))), cx)
+                .note(runnable.id())
+                .map(str::to_string)
+        })
+        .or_else(|| runnable.note().map(str::to_string))?;
+    (!note.is_empty()).then_some(note)
+}
+
+/// Sets the note of the runnable in its definition in the runnables file, keeping the rest of the file as is,
+/// or in the settings of its worktree for the runnables defined elsewhere. An empty note clears it.
+pub(crate) fn edit_note(
+    workspace: &Workspace,
+    runnable: &dyn Runnable,
+    note: String,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(definition_ix) = runnable.definition_ix() else {
+        let id = runnable.id().clone();
+        update_runnable_settings(
+            workspace,
+            runnable,
+            move |settings| {
+                settings
+                    .notes
+                    .get_or_insert_with(Default::default)
+                    .insert(id, note);
+            },
+            cx,
+        );
+        return;
+    };
+    let fs = workspace.app_state().fs.clone();
+    cx.background_executor()
+        .spawn(async move {
+            let runnables_path = paths::RUNNABLES.as_path();
+            let old_text = fs.load(runnables_path).await?;
+            let note = if note.is_empty() {
+                serde_json::Value::Null
+            } else {
+                serde_json::Value::String(note)
+            };
+            let new_text = settings::update_json_array_item(
+                &old_text,
+                &["runnables"],
+                definition_ix,
+                "note",
+                &note,
+            )
+            .with_context(|| {
+                format!("No runnable definition #{definition_ix} in {runnables_path:?}")
+            })?;
+            fs.atomic_write(runnables_path.to_path_buf(), new_text)
+                .await
+                .with_context(|| format!("Failed to write runnables to file {runnables_path:?}"))
+        })
+        .detach_and_log_err(cx);
+}
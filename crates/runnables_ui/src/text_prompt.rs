@@ -0,0 +1,87 @@
+use editor::Editor;
+use gpui::{
+    AppContext, EventEmitter, FocusHandle, FocusableView, InteractiveElement, IntoElement,
+    ParentElement, Render, Styled, View, ViewContext, VisualContext,
+};
+use ui::{prelude::*, v_flex, Label};
+
+pub(crate) enum TextPromptEvent {
+    /// The text typed in got confirmed, trimmed.
+    Confirmed(String),
+    Cancelled,
+}
+
+/// Asks for a line of text about a runnable, e.g. the label to show it with, prefilled with the current one.
+pub(crate) struct TextPrompt {
+    title: SharedString,
+    editor: View<Editor>,
+    /// Whether confirming a blank text is allowed, e.g. to clear a runnable's note.
+    allow_blank: bool,
+}
+
+impl TextPrompt {
+    /// Asks for the label to show the runnable with the name given.
+    pub(crate) fn rename(name: &str, label: &str, cx: &mut ViewContext<Self>) -> Self {
+        Self::new(format!("Rename {name}"), "Label", label, false, cx)
+    }
+
+    /// Asks for the note about the runnable with the name given, cleared if left blank.
+    pub(crate) fn edit_note(name: &str, note: &str, cx: &mut ViewContext<Self>) -> Self {
+        Self::new(format!("Note about {name}"), "Note", note, true, cx)
+    }
+
+    fn new(
+        title: String,
+        placeholder: &'static str,
+        text: &str,
+        allow_blank: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text(placeholder, cx);
+            editor.set_text(text, cx);
+            editor.select_all(&editor::actions::SelectAll, cx);
+            editor
+        });
+        Self {
+            title: SharedString::from(title),
+            editor,
+            allow_blank,
+        }
+    }
+
+    /// Confirms the text typed in, unless it's blank and that's not allowed.
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let text = self.editor.read(cx).text(cx).trim().to_string();
+        if self.allow_blank || !text.is_empty() {
+            cx.emit(TextPromptEvent::Confirmed(text));
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(TextPromptEvent::Cancelled);
+    }
+}
+
+impl EventEmitter<TextPromptEvent> for TextPrompt {}
+
+impl FocusableView for TextPrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for TextPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RunnablePrompt")
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .elevation_3(cx)
+            .p_4()
+            .gap_2()
+            .child(Label::new(self.title.clone()))
+            .child(self.editor.clone())
+    }
+}
@@ -0,0 +1,80 @@
+//! Trust of the worktrees to run the runnables defined by their files: a cloned repository should not get its commands
+//! one keypress away from execution without the user's consent.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+use collections::HashSet;
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{AppContext, Global, PromptLevel, WindowContext};
+use runnable::Runnable;
+use util::ResultExt;
+
+const TRUSTED_WORKTREES_KEY: &str = "runnables_trusted_worktrees";
+/// Index of the answer trusting the worktree in the prompt.
+const TRUST_ANSWER: usize = 0;
+
+/// Roots of the worktrees the user trusts to run the runnables from, persisted between the restarts.
+#[derive(Default)]
+pub(crate) struct TrustedWorktrees(HashSet<PathBuf>);
+
+impl Global for TrustedWorktrees {}
+
+impl TrustedWorktrees {
+    /// Loads the worktrees trusted in the previous sessions.
+    pub(crate) fn init(cx: &mut AppContext) {
+        let trusted_worktrees = KEY_VALUE_STORE
+            .read_kvp(TRUSTED_WORKTREES_KEY)
+            .log_err()
+            .flatten()
+            .and_then(|trusted_worktrees| serde_json::from_str(&trusted_worktrees).log_err())
+            .unwrap_or_default();
+        cx.set_global(Self(trusted_worktrees));
+    }
+
+    pub(crate) fn is_trusted(root: &Path, cx: &AppContext) -> bool {
+        cx.try_global::<Self>().map_or(false, |trusted_worktrees| {
+            trusted_worktrees.0.contains(root)
+        })
+    }
+
+    pub(crate) fn trust(root: PathBuf, cx: &mut AppContext) {
+        let trusted_worktrees = cx.default_global::<Self>();
+        if !trusted_worktrees.0.insert(root) {
+            return;
+        }
+        let Some(serialized) = serde_json::to_string(&trusted_worktrees.0).log_err() else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(TRUSTED_WORKTREES_KEY.to_string(), serialized)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+/// Root of the worktree the runnable comes from, if the worktree is not trusted yet.
+/// The user's own runnables need no trust.
+pub(crate) fn untrusted_root(runnable: &dyn Runnable, cx: &AppContext) -> Option<PathBuf> {
+    runnable
+        .project_root()
+        .filter(|root| !TrustedWorktrees::is_trusted(root, cx))
+        .map(Path::to_path_buf)
+}
+
+/// Asks whether to trust the worktree given, resolving to whether the user did.
+pub(crate) fn ask_for_trust(root: &Path, cx: &mut WindowContext) -> impl Future<Output = bool> {
+    let answer = cx.prompt(
+        PromptLevel::Warning,
+        &format!("Trust {} to run its runnables?", root.display()),
+        Some("Runnables defined by the project's files run whatever commands their authors put in them."),
+        &["Trust and Run", "Cancel"],
+    );
+    async move { answer.await == Ok(TRUST_ANSWER) }
+}
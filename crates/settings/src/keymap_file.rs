@@ -43,7 +43,7 @@ impl KeymapFile {
     pub fn load_asset(asset_path: &str, cx: &mut AppContext) -> Result<()> {
         let content = asset_str::<SettingsAssets>(asset_path);
 
-        Self::parse(content.as_ref())?.add_to_cx(cx)
+        Self::parse(content.as_ref())?.add_to_cx_with_source(Some(asset_path), cx)
     }
 
     pub fn parse(content: &str) -> Result<Self> {
@@ -51,6 +51,12 @@ impl KeymapFile {
     }
 
     pub fn add_to_cx(self, cx: &mut AppContext) -> Result<()> {
+        self.add_to_cx_with_source(None, cx)
+    }
+
+    /// Binds the keymap's keys, recording the source given, e.g. the keymap file's path, on every binding.
+    pub fn add_to_cx_with_source(self, source: Option<&str>, cx: &mut AppContext) -> Result<()> {
+        let source = source.map(|source| SharedString::from(source.to_string()));
         for KeymapBlock { context, bindings } in self.0 {
             let bindings = bindings
                 .into_iter()
@@ -87,7 +93,14 @@ impl KeymapFile {
                         )
                     })
                     .log_err()
-                    .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
+                    .map(|action| {
+                        KeyBinding::load(&keystroke, action, context.as_deref()).map(|binding| {
+                            match &source {
+                                Some(source) => binding.with_source(source.clone()),
+                                None => binding,
+                            }
+                        })
+                    })
                 })
                 .collect::<Result<Vec<_>>>()?;
 
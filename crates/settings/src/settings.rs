@@ -8,7 +8,10 @@ use util::asset_str;
 
 pub use keymap_file::KeymapFile;
 pub use settings_file::*;
-pub use settings_store::{Settings, SettingsJsonSchemaParams, SettingsStore};
+pub use settings_store::{
+    json_key_range, parse_json_with_comments, update_json_array_item, Settings,
+    SettingsJsonSchemaParams, SettingsStore,
+};
 
 #[derive(RustEmbed)]
 #[folder = "../../assets"]
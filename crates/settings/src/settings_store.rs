@@ -888,6 +888,84 @@ fn replace_value_in_json_text(
     }
 }
 
+/// The range of the key at the path given in the JSON text, e.g. `["inlay_hints", "enabled"]`, if it's there.
+pub fn json_key_range(text: &str, key_path: &[&str]) -> Option<Range<usize>> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+
+    let root = syntax_tree.root_node();
+    let mut cursor = root.walk();
+    let mut object = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "object")?;
+    let mut key_range = None;
+    for key in key_path {
+        let mut cursor = object.walk();
+        let pair = object.named_children(&mut cursor).find(|pair| {
+            pair.kind() == "pair"
+                && pair
+                    .child_by_field_name("key")
+                    .and_then(|key_node| text.get(key_node.byte_range()))
+                    .map_or(false, |key_text| key_text == format!("\"{key}\""))
+        })?;
+        key_range = Some(pair.child_by_field_name("key")?.byte_range());
+        object = pair.child_by_field_name("value")?;
+    }
+    key_range
+}
+
+/// Sets the key of the object at the index given in the array at the key path, e.g. the `"note"` of an item
+/// of the `"runnables"` array, inserting the key if the object lacks it.
+/// The comments and the formatting of the rest of the text are kept; returns `None` if there's no such object.
+pub fn update_json_array_item(
+    text: &str,
+    array_key_path: &[&str],
+    index: usize,
+    key: &str,
+    new_value: &serde_json::Value,
+) -> Option<String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+
+    let root = syntax_tree.root_node();
+    let mut cursor = root.walk();
+    let mut value = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "object")?;
+    for key in array_key_path {
+        let mut cursor = value.walk();
+        let pair = value.named_children(&mut cursor).find(|pair| {
+            pair.kind() == "pair"
+                && pair
+                    .child_by_field_name("key")
+                    .and_then(|key_node| text.get(key_node.byte_range()))
+                    .map_or(false, |key_text| key_text == format!("\"{key}\""))
+        })?;
+        value = pair.child_by_field_name("value")?;
+    }
+    if value.kind() != "array" {
+        return None;
+    }
+    let mut cursor = value.walk();
+    let item = value
+        .named_children(&mut cursor)
+        .filter(|node| node.kind() != "comment")
+        .nth(index)
+        .filter(|node| node.kind() == "object")?;
+
+    let item_range = item.byte_range();
+    let (range, replacement) =
+        replace_value_in_json_text(&text[item_range.clone()], &[key], 2, new_value);
+    let mut new_text = text.to_string();
+    new_text.replace_range(
+        item_range.start + range.start..item_range.start + range.end,
+        &replacement,
+    );
+    Some(new_text)
+}
+
 fn to_pretty_json(value: &impl Serialize, indent_size: usize, indent_prefix_len: usize) -> String {
     const SPACES: [u8; 32] = [b' '; 32];
 
@@ -1085,6 +1163,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_json_key_range() {
+        let text = r#"// Zed settings
+{
+  "soft_wrap": "none",
+  "inlay_hints": {
+    // "enabled": false,
+    "enabled": true
+  }
+}"#;
+        let range = |key_path: &[&str]| json_key_range(text, key_path).map(|range| &text[range]);
+        assert_eq!(range(&["soft_wrap"]), Some(r#""soft_wrap""#));
+        let enabled = json_key_range(text, &["inlay_hints", "enabled"]).unwrap();
+        assert_eq!(&text[enabled.clone()], r#""enabled""#);
+        assert_eq!(
+            text[..enabled.start].lines().count(),
+            6,
+            "Keys in the comments should be skipped"
+        );
+        assert_eq!(range(&["enabled"]), None);
+        assert_eq!(range(&["soft_wrap", "enabled"]), None);
+        assert_eq!(range(&["tab_size"]), None);
+    }
+
+    #[test]
+    fn test_update_json_array_item() {
+        let text = r#"// Runnables
+{
+  "version": "1",
+  "runnables": [
+    // { "label": "disabled" },
+    {
+      "label": "build",
+      "command": "make"
+    },
+    { "label": "test", "command": "make", "note": "slow" }
+  ]
+}"#;
+        let note = serde_json::json!("needs docker");
+        assert_eq!(
+            update_json_array_item(text, &["runnables"], 0, "note", &note).unwrap(),
+            r#"// Runnables
+{
+  "version": "1",
+  "runnables": [
+    // { "label": "disabled" },
+    {
+      "note": "needs docker",
+      "label": "build",
+      "command": "make"
+    },
+    { "label": "test", "command": "make", "note": "slow" }
+  ]
+}"#
+        );
+        assert_eq!(
+            update_json_array_item(text, &["runnables"], 1, "note", &serde_json::Value::Null)
+                .unwrap(),
+            text.replace(r#""note": "slow""#, r#""note": null"#)
+        );
+        assert_eq!(
+            update_json_array_item(text, &["runnables"], 2, "note", &note),
+            None
+        );
+        assert_eq!(
+            update_json_array_item(text, &["version"], 0, "note", &note),
+            None
+        );
+    }
+
     #[gpui::test]
     fn test_setting_store_update(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
@@ -0,0 +1,131 @@
+//! The runnables spawned in a PTY with a clean environment: the PTY's process inherits all variables of Zed's process,
+//! the ones set by alacritty_terminal included, so the runnable's command gets started with the ones not in its env unset.
+//! The env itself gets passed with the PTY's options, never on the command line, where the other users could read it.
+
+use alacritty_terminal::tty::setup_env;
+use collections::HashMap;
+
+use crate::terminal_settings::Shell;
+
+/// Variables alacritty_terminal sets for the PTY's process, on top of the inherited ones.
+#[cfg(unix)]
+const PTY_ENV_VARS: &[&str] = &["USER", "HOME", "WINDOWID", "ALACRITTY_WINDOW_ID"];
+
+/// Variables alacritty_terminal sets for the PTY's process, on top of the inherited ones.
+#[cfg(windows)]
+const PTY_ENV_VARS: &[&str] = &[];
+
+/// The environment a runnable with `clean_env` set gets spawned with: the allowlisted variables of Zed's process,
+/// as set up for the terminals, overridden by the runnable's explicit env.
+pub fn clean_runnable_env(explicit: &HashMap<String, String>) -> HashMap<String, String> {
+    setup_env();
+    runnable::clean_env(std::env::vars(), explicit)
+}
+
+/// The shell, started with every variable the PTY's process would get besides the env given unset.
+pub(crate) fn clean_env_shell(shell: Shell, env: &HashMap<String, String>) -> Shell {
+    let (program, args) = match shell {
+        Shell::System => return Shell::System,
+        Shell::Program(program) => (program, Vec::new()),
+        Shell::WithArguments { program, args } => (program, args),
+    };
+    let mut unset_keys = std::env::vars()
+        .map(|(key, _)| key)
+        .chain(PTY_ENV_VARS.iter().map(|key| key.to_string()))
+        .filter(|key| !env.contains_key(key))
+        .collect::<Vec<_>>();
+    unset_keys.sort();
+    unset_keys.dedup();
+
+    #[cfg(unix)]
+    {
+        let mut env_args = Vec::with_capacity(unset_keys.len() * 2 + args.len() + 1);
+        for key in unset_keys {
+            env_args.push("-u".to_string());
+            env_args.push(key);
+        }
+        env_args.push(program);
+        env_args.extend(args);
+        Shell::WithArguments {
+            program: "env".to_string(),
+            args: env_args,
+        }
+    }
+
+    // alacritty_terminal joins the program and its arguments into the command line as they are.
+    #[cfg(windows)]
+    {
+        let mut cmd_args = vec!["/d".to_string(), "/c".to_string()];
+        for key in unset_keys {
+            cmd_args.push(format!("set \"{key}=\""));
+            cmd_args.push("&".to_string());
+        }
+        cmd_args.push(program);
+        cmd_args.extend(args);
+        Shell::WithArguments {
+            program: "cmd.exe".to_string(),
+            args: cmd_args,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_env_shell() {
+        // The shell sets some variables of its own.
+        let inherited_key = std::env::vars()
+            .map(|(key, _)| key)
+            .find(|key| !["PATH", "PWD", "OLDPWD", "SHLVL", "_"].contains(&key.as_str()))
+            .expect("The tests should inherit some variables");
+        let env = HashMap::from_iter([
+            (
+                "PATH".to_string(),
+                std::env::var("PATH").unwrap_or_default(),
+            ),
+            (
+                "CARGO_REGISTRY_TOKEN".to_string(),
+                "cio_hunter2".to_string(),
+            ),
+        ]);
+        let shell = clean_env_shell(
+            Shell::WithArguments {
+                program: "/bin/sh".to_string(),
+                args: vec!["-c".to_string(), "env".to_string()],
+            },
+            &env,
+        );
+        let Shell::WithArguments { program, args } = shell else {
+            panic!("The command should get wrapped");
+        };
+        assert!(
+            args.iter().all(|arg| !arg.contains("cio_hunter2")),
+            "The env values should not get on the command line: {args:?}"
+        );
+
+        // Spawned the way alacritty_terminal does, with the env passed to the process and its own variables set.
+        let output = std::process::Command::new(program)
+            .args(args)
+            .env("WINDOWID", "1")
+            .envs(&env)
+            .output()
+            .unwrap();
+        let printed = String::from_utf8(output.stdout).unwrap();
+        let printed_keys = printed
+            .lines()
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+            .collect::<Vec<_>>();
+        assert!(
+            printed.contains("CARGO_REGISTRY_TOKEN=cio_hunter2"),
+            "The command should get its env: {printed}"
+        );
+        for unset_key in [inherited_key.as_str(), "WINDOWID"] {
+            assert!(
+                !printed_keys.contains(&unset_key),
+                "{unset_key} should be unset: {printed}"
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Exit statuses of the runnables spawned in a PTY: alacritty_terminal reaps its child process
+//! without reporting the status the process exited with.
+
+use anyhow::{Context as _, Result};
+use smol::channel::{bounded, Receiver};
+use util::ResultExt;
+
+/// Waits for the process to exit on a thread of its own, sending the status it exits with, `None` if it got killed
+/// by a signal, or its status could not be read. The process is left for alacritty_terminal to reap, as usual,
+/// so the watching has to start before alacritty_terminal's event loop does.
+pub(crate) fn watch_exit_status(pid: u32) -> Receiver<Option<i32>> {
+    let (status_tx, status_rx) = bounded(1);
+    std::thread::Builder::new()
+        .name("runnable exit status".to_string())
+        .spawn(move || {
+            let exit_status = wait_for_exit_status(pid)
+                .with_context(|| format!("waiting for the exit of the runnable's process {pid}"))
+                .log_err()
+                .flatten();
+            status_tx.send_blocking(exit_status).ok();
+        })
+        .log_err();
+    status_rx
+}
+
+#[cfg(unix)]
+fn wait_for_exit_status(pid: u32) -> Result<Option<i32>> {
+    let mut info = unsafe { std::mem::zeroed::<libc::siginfo_t>() };
+    loop {
+        // `WNOWAIT` leaves the exited process a zombie, for alacritty_terminal to reap it and report the exit.
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result == 0 {
+            break;
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error.into());
+        }
+    }
+    if info.si_code != libc::CLD_EXITED {
+        return Ok(None);
+    }
+    #[cfg(target_os = "macos")]
+    let status = info.si_status;
+    #[cfg(not(target_os = "macos"))]
+    let status = unsafe { info.si_status() };
+    Ok(Some(status))
+}
+
+// todo!(windows)
+#[cfg(windows)]
+fn wait_for_exit_status(_pid: u32) -> Result<Option<i32>> {
+    Ok(None)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_exit_status() {
+        for (script, expected_status) in [
+            ("exit 0", Some(0)),
+            ("exit 3", Some(3)),
+            ("kill -9 $$", None),
+        ] {
+            let mut child = std::process::Command::new("/bin/sh")
+                .args(["-c", script])
+                .spawn()
+                .unwrap();
+            let status_rx = watch_exit_status(child.id());
+            assert_eq!(
+                smol::block_on(status_rx.recv()).unwrap(),
+                expected_status,
+                "Unexpected exit status of {script:?}"
+            );
+            assert_eq!(
+                child.wait().unwrap().code(),
+                expected_status,
+                "The process should be left to be reaped by its owner"
+            );
+        }
+    }
+}
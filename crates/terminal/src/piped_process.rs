@@ -0,0 +1,331 @@
+//! The runnables spawned with plain pipes for their output, in place of a PTY: for the tools behaving differently
+//! in a terminal, e.g. drawing progress bars, and for the scripts choking on its control sequences.
+//! The terminal shows their output all the same, colors included, while the escape sequences get stripped
+//! from the stdout captured for parsing, e.g. the structured output of the tests.
+
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, EventListener},
+    sync::FairMutex,
+    vte::ansi::Processor,
+    Term,
+};
+use collections::HashMap;
+use futures::{future, AsyncRead, AsyncReadExt as _};
+use smol::process::{Child, Command};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Spawns the program with its stdout and stderr piped, and nothing to read from its stdin.
+/// The program gets killed once the returned child gets dropped.
+pub(crate) fn spawn_piped(
+    program: &str,
+    args: &[String],
+    working_directory: Option<&Path>,
+    env: &HashMap<String, String>,
+    clean_env: bool,
+) -> std::io::Result<Child> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(working_directory) = working_directory {
+        command.current_dir(working_directory);
+    }
+    if clean_env {
+        command.env_clear();
+    }
+    command.envs(env).spawn()
+}
+
+/// How the piped process exited, along with what it printed.
+pub(crate) struct PipedOutput {
+    /// The process's exit status, `None` if it got killed by a signal.
+    pub exit_status: Option<i32>,
+    /// What the process printed to its stdout, stripped of the escape sequences, if it got captured.
+    pub stdout: Option<String>,
+}
+
+/// Shows the output of the piped process in the terminal given, as it gets printed, capturing its stdout
+/// if asked to, until the process exits.
+pub(crate) async fn show_output<T: EventListener>(
+    mut child: Child,
+    term: Arc<FairMutex<Term<T>>>,
+    listener: &impl EventListener,
+    capture_stdout: bool,
+) -> PipedOutput {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let (stdout, _) = future::join(
+        show_stream(stdout, &term, listener, capture_stdout),
+        show_stream(stderr, &term, listener, false),
+    )
+    .await;
+    PipedOutput {
+        exit_status: child.status().await.ok().and_then(|status| status.code()),
+        stdout: stdout.map(|stdout| String::from_utf8_lossy(&stdout).into_owned()),
+    }
+}
+
+/// Shows the stream's output in the terminal, returning its text if asked to capture it.
+async fn show_stream<T: EventListener>(
+    stream: Option<impl AsyncRead + Unpin>,
+    term: &FairMutex<Term<T>>,
+    listener: &impl EventListener,
+    capture: bool,
+) -> Option<Vec<u8>> {
+    let mut captured = capture.then(Vec::new);
+    let Some(mut stream) = stream else {
+        return captured;
+    };
+    let mut stripper = AnsiStripper::default();
+    let mut parser: Processor = Processor::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let len = match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        let output = &buffer[..len];
+        if let Some(captured) = &mut captured {
+            captured.extend(stripper.strip(output));
+        }
+        let mut term = term.lock();
+        for &byte in output {
+            // With no PTY translating the line feeds, they would only move the cursor down.
+            if byte == b'\n' {
+                parser.advance(&mut *term, b'\r');
+            }
+            parser.advance(&mut *term, byte);
+        }
+        drop(term);
+        listener.send_event(AlacTermEvent::Wakeup);
+    }
+    captured
+}
+
+/// Drops the escape sequences out of the output read, e.g. the colors and the cursor movements,
+/// along with the sequences split between the reads.
+#[derive(Default)]
+pub(crate) struct AnsiStripper {
+    state: StripperState,
+}
+
+#[derive(Default, Clone, Copy)]
+enum StripperState {
+    #[default]
+    Text,
+    /// After the escape character.
+    Escape,
+    /// Within an escape sequence with intermediate bytes, e.g. `ESC ( B`, up to its final byte.
+    Intermediate,
+    /// Within a control sequence, e.g. `ESC [ 3 1 m`, up to its final byte.
+    ControlSequence,
+    /// Within a control string, e.g. `ESC ] 0 ; title BEL`, up to its terminator.
+    ControlString,
+    /// After the escape character within a control string, which terminates it if followed by a backslash.
+    ControlStringEscape,
+}
+
+impl AnsiStripper {
+    /// The bytes given, with the escape sequences dropped.
+    pub(crate) fn strip(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut text = Vec::with_capacity(bytes.len());
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (StripperState::Text, ESC) => StripperState::Escape,
+                (StripperState::Text, byte) => {
+                    text.push(byte);
+                    StripperState::Text
+                }
+                (StripperState::Escape, b'[') => StripperState::ControlSequence,
+                (StripperState::Escape, b']' | b'P' | b'X' | b'^' | b'_') => {
+                    StripperState::ControlString
+                }
+                (StripperState::Escape, 0x20..=0x2f) => StripperState::Intermediate,
+                (StripperState::Escape, ESC) => StripperState::Escape,
+                (StripperState::Escape, _) => StripperState::Text,
+                (StripperState::Intermediate, 0x30..=0x7e) => StripperState::Text,
+                (StripperState::Intermediate, _) => StripperState::Intermediate,
+                (StripperState::ControlSequence, 0x40..=0x7e) => StripperState::Text,
+                (StripperState::ControlSequence, _) => StripperState::ControlSequence,
+                (StripperState::ControlString, BEL) => StripperState::Text,
+                (StripperState::ControlString, ESC) => StripperState::ControlStringEscape,
+                (StripperState::ControlString, _) => StripperState::ControlString,
+                (StripperState::ControlStringEscape, b'\\') => StripperState::Text,
+                (StripperState::ControlStringEscape, _) => StripperState::ControlString,
+            };
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::VoidListener,
+        grid::Dimensions,
+        index::{Column, Point as AlacPoint},
+        term::Config,
+        vte::ansi::{Color, NamedColor},
+    };
+    use runnable::{StructuredOutput, TestLocation};
+
+    use crate::TerminalSize;
+
+    use super::*;
+
+    /// Text of the terminal's scrollback and screen, with the wrapped lines joined back.
+    fn all_text<T>(term: &Term<T>) -> String {
+        let start = AlacPoint::new(term.grid().topmost_line(), Column(0));
+        let end = AlacPoint::new(term.grid().bottommost_line(), term.grid().last_column());
+        term.bounds_to_string(start, end)
+    }
+
+    fn strip(chunks: &[&str]) -> String {
+        let mut stripper = AnsiStripper::default();
+        let text = chunks
+            .iter()
+            .flat_map(|chunk| stripper.strip(chunk.as_bytes()))
+            .collect::<Vec<_>>();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn test_strip_ansi_escapes() {
+        assert_eq!(
+            strip(&["\x1b[1;31merror\x1b[0m: build failed\r\n"]),
+            "error: build failed\r\n"
+        );
+        assert_eq!(
+            strip(&["\x1b]0;cargo test\x07\x1b]8;;https://zed.dev\x1b\\link\x1b]8;;\x1b\\"]),
+            "link",
+            "The control strings should be dropped up to either of their terminators"
+        );
+        assert_eq!(strip(&["\x1b(B\x1b7\x1b[2K\x1b[1Gdone\x1b8"]), "done");
+        assert_eq!(
+            strip(&["tests: 3 ✓\tnot a \\ sequence ]"]),
+            "tests: 3 ✓\tnot a \\ sequence ]"
+        );
+        assert_eq!(
+            strip(&["\x1b", "[3", "2mok\x1b[", "0m\x1b]0;ti", "tle\x1b", "\\."]),
+            "ok.",
+            "The sequences split between the reads should be dropped as a whole"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_show_piped_output() {
+        let script = "if [ -t 1 ]; then echo tty; else echo pipe; fi; \
+                      printf '\\033[31mred\\033[0m\\n'; \
+                      echo \"$PIPED_OUTPUT_VAR\" >&2; \
+                      exit 3";
+        let child = spawn_piped(
+            "/bin/sh",
+            &["-c".to_string(), script.to_string()],
+            None,
+            &HashMap::from_iter([("PIPED_OUTPUT_VAR".to_string(), "stderr".to_string())]),
+            false,
+        )
+        .unwrap();
+        let term = Arc::new(FairMutex::new(Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            VoidListener,
+        )));
+
+        let output = smol::block_on(show_output(child, term.clone(), &VoidListener, true));
+        assert_eq!(output.exit_status, Some(3));
+        assert_eq!(
+            output.stdout.as_deref(),
+            Some("pipe\nred\n"),
+            "Only stdout should be captured, stripped of its escape sequences"
+        );
+        let term = term.lock();
+        let text = all_text(&term);
+        let lines = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        assert!(
+            lines.contains(&"pipe"),
+            "The output should get piped, not written to a terminal: {lines:?}"
+        );
+        assert!(
+            lines.contains(&"red"),
+            "The escape sequences should not be shown: {lines:?}"
+        );
+        assert!(
+            term.grid()
+                .display_iter()
+                .any(|cell| cell.c == 'r' && cell.fg == Color::Named(NamedColor::Red)),
+            "The terminal should show the colors printed"
+        );
+        assert!(
+            lines.contains(&"stderr"),
+            "Both stdout and stderr should be shown, with the environment given: {lines:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_structured_output_wider_than_terminal() {
+        let failure_message = "x".repeat(300);
+        let failed = serde_json::json!({
+            "type": "test",
+            "event": "failed",
+            "name": "tests::fails",
+            "stdout": format!("thread 'tests::fails' panicked at src/lib.rs:10:5:\n{failure_message}\n"),
+        })
+        .to_string();
+        let passed = r#"{ "type": "test", "event": "ok", "name": "tests::passes" }"#.to_string();
+        let script =
+            "printf '%s\\n' \"$1\"; printf 'warning: ' >&2; printf '%s\\n' \"$2\"; echo unused >&2";
+        let child = spawn_piped(
+            "/bin/sh",
+            &[
+                "-c".to_string(),
+                script.to_string(),
+                "sh".to_string(),
+                failed.clone(),
+                passed,
+            ],
+            None,
+            &HashMap::default(),
+            false,
+        )
+        .unwrap();
+        let term = Arc::new(FairMutex::new(Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            VoidListener,
+        )));
+
+        let output = smol::block_on(show_output(child, term.clone(), &VoidListener, true));
+        assert!(
+            failed.len() > term.lock().columns(),
+            "The JSON line should be wider than the terminal"
+        );
+        let test_results = StructuredOutput::CargoJson
+            .parse(&output.stdout.unwrap())
+            .unwrap();
+        assert_eq!((test_results.passed, test_results.failed), (1, 1));
+        assert_eq!(test_results.failures[0].name, "tests::fails");
+        assert_eq!(
+            test_results.failures[0].location,
+            Some(TestLocation {
+                path: "src/lib.rs".into(),
+                row: 10,
+                column: 5,
+            }),
+            "The lines wrapped in the terminal should be parsed whole"
+        );
+    }
+}
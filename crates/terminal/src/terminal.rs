@@ -1,5 +1,9 @@
+mod clean_env;
+mod exit_status;
 pub mod mappings;
+mod piped_process;
 pub use alacritty_terminal;
+pub use clean_env::clean_runnable_env;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -33,7 +37,7 @@ use mappings::mouse::{
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
 use procinfo::LocalProcessInfo;
-use runnable::RunnableId;
+use runnable::{RunnableId, StructuredOutput, TestResults};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use smol::channel::{Receiver, Sender};
@@ -42,6 +46,7 @@ use theme::{ActiveTheme, Theme};
 use util::truncate_and_trailoff;
 
 use std::{
+    borrow::Cow,
     cmp::{self, min},
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
@@ -89,6 +94,14 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The runnable, spawned in the terminal, has exited.
+    RunnableFinished {
+        id: RunnableId,
+        /// Exit status of the runnable's process, if known.
+        exit_status: Option<i32>,
+        /// Results of the tests, parsed out of the runnable's structured output, if it has any.
+        test_results: Option<TestResults>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -288,26 +301,45 @@ pub struct SpawnRunnable {
     pub command: String,
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
+    pub clean_env: bool,
+    pub structured_output: StructuredOutput,
+    pub tty: bool,
 }
 
 pub struct TerminalBuilder {
     terminal: Terminal,
     events_rx: UnboundedReceiver<AlacTermEvent>,
+    piped_process: Option<(smol::process::Child, ZedListener)>,
 }
 
 impl TerminalBuilder {
+    /// Spawns the shell in a PTY, with the env given on top of the inherited environment,
+    /// or in place of it with `clean_env` set, see [`clean_runnable_env`].
     pub fn new(
         working_directory: Option<PathBuf>,
         runnable: Option<RunableState>,
         shell: Shell,
-        env: HashMap<String, String>,
+        mut env: HashMap<String, String>,
+        clean_env: bool,
         blink_settings: Option<TerminalBlink>,
         alternate_scroll: AlternateScroll,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
     ) -> Result<TerminalBuilder> {
+        // First, setup Alacritty's env
+        setup_env();
+
+        let pty_shell = if clean_env {
+            clean_env::clean_env_shell(shell.clone(), &env)
+        } else {
+            //TODO: Properly set the current locale,
+            env.insert("LC_ALL".to_string(), "en_US.UTF-8".to_string());
+            env.insert("ZED_TERM".to_string(), "true".to_string());
+            shell.clone()
+        };
+
         let pty_options = {
-            let alac_shell = match shell.clone() {
+            let alac_shell = match pty_shell {
                 Shell::System => None,
                 Shell::Program(program) => {
                     Some(alacritty_terminal::tty::Shell::new(program, Vec::new()))
@@ -321,20 +353,10 @@ impl TerminalBuilder {
                 shell: alac_shell,
                 working_directory: working_directory.clone(),
                 hold: false,
+                env: env.into_iter().collect(),
             }
         };
 
-        // First, setup Alacritty's env
-        setup_env();
-
-        // Then setup configured environment variables
-        for (key, value) in env {
-            std::env::set_var(key, value);
-        }
-        //TODO: Properly set the current locale,
-        std::env::set_var("LC_ALL", "en_US.UTF-8");
-        std::env::set_var("ZED_TERM", "true");
-
         let config = Config {
             scrolling_history: 10000,
             ..Config::default()
@@ -385,6 +407,10 @@ impl TerminalBuilder {
         #[cfg(windows)]
         let (fd, shell_pid) = (-1, 0);
 
+        let pty_exit_status = runnable
+            .is_some()
+            .then(|| exit_status::watch_exit_status(shell_pid));
+
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
@@ -398,40 +424,98 @@ impl TerminalBuilder {
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
 
-        let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
-        let word_regex = RegexSearch::new(r#"[\w.\[\]:/@\-~]+"#).unwrap();
-
-        let terminal = Terminal {
-            runnable,
-            pty_tx: Notifier(pty_tx),
-            completion_tx,
+        let mut terminal = Terminal::new(
             term,
-            events: VecDeque::with_capacity(10), //Should never get this high.
-            last_content: Default::default(),
-            last_mouse: None,
-            matches: Vec::new(),
-            selection_head: None,
-            shell_fd: fd as u32,
+            Some(Notifier(pty_tx)),
+            fd as u32,
             shell_pid,
-            foreground_process_info: None,
-            breadcrumb_text: String::new(),
-            scroll_px: px(0.),
-            last_mouse_position: None,
-            next_link_id: 0,
-            selection_phase: SelectionPhase::Ended,
-            cmd_pressed: false,
-            hovered_word: false,
-            url_regex,
-            word_regex,
+            runnable,
+            completion_tx,
+        );
+        terminal.pty_exit_status = pty_exit_status;
+
+        Ok(TerminalBuilder {
+            terminal,
+            events_rx,
+            piped_process: None,
+        })
+    }
+
+    /// Spawns the runnable's command with plain pipes for its stdout and stderr, in place of a PTY.
+    /// The terminal shows the command's output as printed, and takes no input.
+    pub fn new_piped(
+        working_directory: Option<PathBuf>,
+        runnable: RunableState,
+        program: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        clean_env: bool,
+        completion_tx: Sender<()>,
+    ) -> Result<TerminalBuilder> {
+        let child = match piped_process::spawn_piped(
+            &program,
+            &args,
+            working_directory.as_deref(),
+            &env,
+            clean_env,
+        ) {
+            Ok(child) => child,
+            Err(error) => {
+                bail!(TerminalError {
+                    directory: working_directory,
+                    shell: Shell::WithArguments { program, args },
+                    source: error,
+                });
+            }
         };
 
+        let (events_tx, events_rx) = unbounded();
+        let term = Term::new(
+            Config {
+                scrolling_history: 10000,
+                ..Config::default()
+            },
+            &TerminalSize::default(),
+            ZedListener(events_tx.clone()),
+        );
+        // No PTY to ask for the foreground process of, so the process info is always the child's.
+        let shell_fd = -1;
+        let terminal = Terminal::new(
+            Arc::new(FairMutex::new(term)),
+            None,
+            shell_fd as u32,
+            child.id(),
+            Some(runnable),
+            completion_tx,
+        );
+
         Ok(TerminalBuilder {
             terminal,
             events_rx,
+            piped_process: Some((child, ZedListener(events_tx))),
         })
     }
 
     pub fn subscribe(mut self, cx: &mut ModelContext<Terminal>) -> Terminal {
+        if let Some((child, listener)) = self.piped_process.take() {
+            let term = self.terminal.term.clone();
+            // Only the structured output gets parsed from what the command prints.
+            let capture_stdout = self.terminal.runnable.as_ref().map_or(false, |runnable| {
+                runnable.structured_output != StructuredOutput::None
+            });
+            let output = cx.background_executor().spawn(async move {
+                piped_process::show_output(child, term, &listener, capture_stdout).await
+            });
+            self.terminal.piped_output = Some(cx.spawn(|terminal, mut cx| async move {
+                let output = output.await;
+                terminal
+                    .update(&mut cx, |terminal, cx| {
+                        terminal.finish_runnable(output.exit_status, output.stdout, cx)
+                    })
+                    .ok();
+            }));
+        }
+
         //Event loop
         cx.spawn(|terminal, mut cx| async move {
             while let Some(event) = self.events_rx.next().await {
@@ -556,7 +640,12 @@ pub enum SelectionPhase {
 }
 
 pub struct Terminal {
-    pty_tx: Notifier,
+    /// `None` for the runnables spawned with pipes in place of a PTY, which take no input.
+    pty_tx: Option<Notifier>,
+    /// Shows the output of the runnable spawned with pipes, killing it once dropped.
+    piped_output: Option<Task<()>>,
+    /// The exit status of the runnable spawned in the PTY, once its process exits.
+    pty_exit_status: Option<Receiver<Option<i32>>>,
     completion_tx: Sender<()>,
     term: Arc<FairMutex<Term<ZedListener>>>,
     events: VecDeque<InternalEvent>,
@@ -586,9 +675,48 @@ pub struct RunableState {
     pub label: String,
     pub completed: bool,
     pub completion_rx: Receiver<()>,
+    pub structured_output: StructuredOutput,
 }
 
 impl Terminal {
+    fn new(
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        pty_tx: Option<Notifier>,
+        shell_fd: u32,
+        shell_pid: u32,
+        runnable: Option<RunableState>,
+        completion_tx: Sender<()>,
+    ) -> Self {
+        let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
+        let word_regex = RegexSearch::new(r#"[\w.\[\]:/@\-~]+"#).unwrap();
+
+        Terminal {
+            runnable,
+            pty_tx,
+            piped_output: None,
+            pty_exit_status: None,
+            completion_tx,
+            term,
+            events: VecDeque::with_capacity(10), //Should never get this high.
+            last_content: Default::default(),
+            last_mouse: None,
+            matches: Vec::new(),
+            selection_head: None,
+            shell_fd,
+            shell_pid,
+            foreground_process_info: None,
+            breadcrumb_text: String::new(),
+            scroll_px: px(0.),
+            last_mouse_position: None,
+            next_link_id: 0,
+            selection_phase: SelectionPhase::Ended,
+            cmd_pressed: false,
+            hovered_word: false,
+            url_regex,
+            word_regex,
+        }
+    }
+
     fn process_event(&mut self, event: &AlacTermEvent, cx: &mut ModelContext<Self>) {
         match event {
             AlacTermEvent::Title(title) => {
@@ -617,10 +745,21 @@ impl Terminal {
             AlacTermEvent::Bell => {
                 cx.emit(Event::Bell);
             }
-            AlacTermEvent::Exit => match &mut self.runnable {
-                Some(runnable) => {
-                    runnable.completed = true;
-                    self.completion_tx.try_send(()).ok();
+            AlacTermEvent::Exit => match &self.runnable {
+                Some(_) => {
+                    // alacritty_terminal reaps the child process without reporting its exit status,
+                    // which gets watched for on the side instead.
+                    let pty_exit_status = self.pty_exit_status.take();
+                    cx.spawn(|terminal, mut cx| async move {
+                        let exit_status = match pty_exit_status {
+                            Some(pty_exit_status) => pty_exit_status.recv().await.ok().flatten(),
+                            None => None,
+                        };
+                        terminal.update(&mut cx, |terminal, cx| {
+                            terminal.finish_runnable(exit_status, None, cx)
+                        })
+                    })
+                    .detach_and_log_err(cx);
                 }
                 None => cx.emit(Event::CloseTerminal),
             },
@@ -641,6 +780,31 @@ impl Terminal {
         }
     }
 
+    /// Marks the runnable spawned in the terminal as completed, reporting the results of its run,
+    /// parsed from the stdout captured from its process's pipe, rather than the terminal's text, cut at its scrollback
+    /// and mixed with stderr.
+    fn finish_runnable(
+        &mut self,
+        exit_status: Option<i32>,
+        stdout: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(runnable) = &mut self.runnable else {
+            return;
+        };
+        runnable.completed = true;
+        self.completion_tx.try_send(()).ok();
+        let test_results = match runnable.structured_output {
+            StructuredOutput::None => None,
+            structured_output => stdout.and_then(|stdout| structured_output.parse(&stdout)),
+        };
+        cx.emit(Event::RunnableFinished {
+            id: runnable.id.clone(),
+            exit_status,
+            test_results,
+        });
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -699,7 +863,9 @@ impl Terminal {
 
                 self.last_content.size = new_size.clone();
 
-                self.pty_tx.0.send(Msg::Resize(new_size.into())).ok();
+                if let Some(pty_tx) = &self.pty_tx {
+                    pty_tx.0.send(Msg::Resize(new_size.into())).ok();
+                }
 
                 term.resize(new_size);
             }
@@ -982,11 +1148,18 @@ impl Terminal {
 
     ///Write the Input payload to the tty.
     fn write_to_pty(&self, input: String) {
-        self.pty_tx.notify(input.into_bytes());
+        self.notify_pty(input.into_bytes());
+    }
+
+    /// Writes to the PTY, if the terminal has one: the runnables spawned with pipes take no input.
+    fn notify_pty(&self, bytes: impl Into<Cow<'static, [u8]>>) {
+        if let Some(pty_tx) = &self.pty_tx {
+            pty_tx.notify(bytes);
+        }
     }
 
     fn write_bytes_to_pty(&self, input: Vec<u8>) {
-        self.pty_tx.notify(input);
+        self.notify_pty(input);
     }
 
     pub fn input(&mut self, input: String) {
@@ -1120,7 +1293,7 @@ impl Terminal {
 
             if self.mouse_changed(point, side) {
                 if let Some(bytes) = mouse_moved_report(point, e, self.last_content.mode) {
-                    self.pty_tx.notify(bytes);
+                    self.notify_pty(bytes);
                 }
             }
         } else if self.cmd_pressed {
@@ -1194,7 +1367,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, true, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.notify_pty(bytes);
             }
         } else if e.button == MouseButton::Left {
             let position = e.position - origin;
@@ -1241,7 +1414,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, false, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.notify_pty(bytes);
             }
         } else {
             if e.button == MouseButton::Left && setting.copy_on_select {
@@ -1280,7 +1453,7 @@ impl Terminal {
                     scroll_report(point, scroll_lines as i32, e, self.last_content.mode)
                 {
                     for scroll in scrolls {
-                        self.pty_tx.notify(scroll);
+                        self.notify_pty(scroll);
                     }
                 };
             } else if self
@@ -1289,7 +1462,7 @@ impl Terminal {
                 .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
                 && !e.shift
             {
-                self.pty_tx.notify(alt_scroll(scroll_lines))
+                self.notify_pty(alt_scroll(scroll_lines))
             } else {
                 if scroll_lines != 0 {
                     let scroll = AlacScroll::Delta(scroll_lines);
@@ -1384,6 +1557,11 @@ impl Terminal {
         self.cmd_pressed && self.hovered_word
     }
 
+    /// Id of the process spawned in the terminal's pty: either the shell or the runnable command.
+    pub fn shell_pid(&self) -> u32 {
+        self.shell_pid
+    }
+
     pub fn runnable(&self) -> Option<&RunableState> {
         self.runnable.as_ref()
     }
@@ -1407,7 +1585,9 @@ impl Terminal {
 
 impl Drop for Terminal {
     fn drop(&mut self) {
-        self.pty_tx.0.send(Msg::Shutdown).ok();
+        if let Some(pty_tx) = &self.pty_tx {
+            pty_tx.0.send(Msg::Shutdown).ok();
+        }
     }
 }
 
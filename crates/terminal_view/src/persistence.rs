@@ -34,6 +34,9 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
         )];
 }
 
@@ -56,8 +59,10 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                working_directory = ?3
         }
     }
 
@@ -68,4 +73,25 @@ impl TerminalDb {
             WHERE item_id = ? AND workspace_id = ?
         }
     }
+
+    query! {
+        pub async fn save_pinned(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            pinned: bool
+        ) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, pinned)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                pinned = ?3
+        }
+    }
+
+    query! {
+        pub fn get_pinned(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<bool>> {
+            SELECT pinned
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
 }
@@ -0,0 +1,741 @@
+//! Bookkeeping of the processes spawned for runnables.
+//!
+//! Every runnable process is recorded in a small session file, so that the processes left behind by a crashed
+//! or force-quit Zed can be found on the next start and offered for cleanup.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use collections::HashSet;
+use gpui::{
+    AppContext, BackgroundExecutor, DismissEvent, EventEmitter, Global, PromptLevel, Render, Task,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{LineEnding, Rope};
+use procinfo::LocalProcessInfo;
+use project::{Fs, RenameOptions};
+use runnable::{RunnablesOnClose, RunnablesSettings};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::prelude::*;
+use util::{paths::RUNNABLE_PROCESSES, ResultExt};
+use workspace::Workspace;
+
+/// How long to wait for the killed runnables to terminate before closing their workspace anyway,
+/// or before killing the cancelled ones, and the ones left from a previous session, forcibly.
+pub(crate) const TERMINATION_TIMEOUT: Duration = Duration::from_secs(3);
+const TERMINATION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A process spawned for a runnable, as recorded in the session file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedProcess {
+    pub pid: u32,
+    /// Platform-specific process start time, used to tell the process apart from another one that reused its pid.
+    /// `None` if the process could not be inspected when it was recorded.
+    #[serde(default)]
+    pub start_time: Option<u64>,
+    pub command: String,
+}
+
+impl TrackedProcess {
+    /// Whether the process described by `current` is still the one that was recorded.
+    fn is_same_process(&self, current: &TrackedProcess) -> bool {
+        if self.pid != current.pid {
+            return false;
+        }
+        // With no start time to compare, only the command tells the processes apart.
+        if let (Some(recorded), Some(current)) = (self.start_time, current.start_time) {
+            if recorded != current {
+                return false;
+            }
+        }
+        let program = self.command.split_whitespace().next().unwrap_or_default();
+        let program = Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        current.command.contains(program.as_ref())
+    }
+}
+
+/// Platform-dependent access to the processes running in the system.
+pub trait ProcessProbe: Send + Sync {
+    /// Describes the currently running process with the given id, if there is one.
+    fn describe(&self, pid: u32) -> Option<TrackedProcess>;
+    /// Asks the process with the given id to terminate.
+    fn kill(&self, pid: u32);
+    /// Kills the process with the given id, without letting it clean up.
+    fn force_kill(&self, pid: u32);
+}
+
+struct SystemProcessProbe;
+
+impl ProcessProbe for SystemProcessProbe {
+    fn describe(&self, pid: u32) -> Option<TrackedProcess> {
+        let info = LocalProcessInfo::with_root_pid(pid)?;
+        Some(TrackedProcess {
+            pid,
+            start_time: Some(info.start_time),
+            command: info.argv.join(" "),
+        })
+    }
+
+    fn kill(&self, pid: u32) {
+        #[cfg(unix)]
+        unsafe {
+            libc::kill(pid as i32, libc::SIGTERM);
+        }
+        // todo!(windows)
+        #[cfg(windows)]
+        let _ = pid;
+    }
+
+    fn force_kill(&self, pid: u32) {
+        #[cfg(unix)]
+        unsafe {
+            libc::kill(pid as i32, libc::SIGKILL);
+        }
+        // todo!(windows)
+        #[cfg(windows)]
+        let _ = pid;
+    }
+}
+
+/// All runnable processes spawned during the current session.
+pub struct RunnableProcesses {
+    fs: Arc<dyn Fs>,
+    probe: Arc<dyn ProcessProbe>,
+    tracked: Vec<TrackedProcess>,
+    detached: HashSet<u32>,
+    checked_for_orphans: bool,
+    pending_write: Task<Option<()>>,
+}
+
+impl Global for RunnableProcesses {}
+
+impl RunnableProcesses {
+    pub fn new(fs: Arc<dyn Fs>, probe: Arc<dyn ProcessProbe>) -> Self {
+        Self {
+            fs,
+            probe,
+            tracked: Vec::new(),
+            detached: HashSet::default(),
+            checked_for_orphans: false,
+            pending_write: Task::ready(None),
+        }
+    }
+
+    pub(crate) fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+        if !cx.has_global::<Self>() {
+            cx.set_global(Self::new(fs, Arc::new(SystemProcessProbe)));
+        }
+    }
+
+    pub fn tracked(&self) -> &[TrackedProcess] {
+        &self.tracked
+    }
+
+    /// Records a freshly spawned runnable process in the session file.
+    pub fn track(&mut self, pid: u32, command: String, cx: &mut AppContext) {
+        let start_time = self
+            .probe
+            .describe(pid)
+            .and_then(|process| process.start_time);
+        self.tracked.push(TrackedProcess {
+            pid,
+            start_time,
+            command,
+        });
+        self.write(cx);
+    }
+
+    /// Removes a finished runnable process from the session file.
+    /// Detached processes are kept, so that the next session can offer to clean them up.
+    pub fn forget(&mut self, pid: u32, cx: &mut AppContext) {
+        if self.detached.contains(&pid) {
+            return;
+        }
+        let tracked_before = self.tracked.len();
+        self.tracked.retain(|process| process.pid != pid);
+        if self.tracked.len() != tracked_before {
+            self.write(cx);
+        }
+    }
+
+    /// Replaces the session file with the tracked processes.
+    /// The file is written next to the session file first and renamed over it, so that a crash mid-write
+    /// leaves the previous records intact, rather than a truncated file the next session cannot parse.
+    fn write(&mut self, cx: &mut AppContext) {
+        let fs = self.fs.clone();
+        let tracked = self.tracked.clone();
+        self.pending_write = cx.background_executor().spawn(
+            async move {
+                let contents = serde_json::to_string(&tracked)?;
+                let temp_path = temp_path(&RUNNABLE_PROCESSES);
+                fs.save(&temp_path, &Rope::from(contents.as_str()), LineEnding::Unix)
+                    .await?;
+                fs.rename(
+                    &temp_path,
+                    &RUNNABLE_PROCESSES,
+                    RenameOptions {
+                        overwrite: true,
+                        ..RenameOptions::default()
+                    },
+                )
+                .await
+            }
+            .log_err(),
+        );
+    }
+
+    /// Handles the runnable processes still running when their workspace closes, according to the `runnables.on_close` setting.
+    /// Resolves to `false` if the workspace should stay open.
+    pub fn prepare_for_close(pids: Vec<u32>, cx: &mut WindowContext) -> Task<Result<bool>> {
+        let Some(processes) = cx.try_global::<Self>() else {
+            return Task::ready(Ok(true));
+        };
+        let live = processes
+            .tracked
+            .iter()
+            .filter(|process| pids.contains(&process.pid))
+            .cloned()
+            .collect::<Vec<_>>();
+        if live.is_empty() {
+            return Task::ready(Ok(true));
+        }
+
+        let on_close = match RunnablesSettings::get_global(cx).on_close {
+            RunnablesOnClose::Ask => {
+                let detail = live
+                    .iter()
+                    .map(|process| process.command.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let answer = cx.prompt(
+                    PromptLevel::Warning,
+                    "Some runnables are still running",
+                    Some(&detail),
+                    &["Kill All and Close", "Leave Running and Close", "Cancel"],
+                );
+                cx.spawn(|_| async move {
+                    anyhow::Ok(match answer.await? {
+                        0 => Some(RunnablesOnClose::Kill),
+                        1 => Some(RunnablesOnClose::Detach),
+                        _ => None,
+                    })
+                })
+            }
+            on_close => Task::ready(Ok(Some(on_close))),
+        };
+
+        cx.spawn(|mut cx| async move {
+            let pids = live.iter().map(|process| process.pid).collect::<Vec<_>>();
+            match on_close.await? {
+                Some(RunnablesOnClose::Kill) => {
+                    cx.update_global(|processes: &mut Self, cx| processes.terminate(pids, cx))?
+                        .await;
+                    Ok(true)
+                }
+                Some(_) => {
+                    cx.update_global(|processes: &mut Self, _| processes.detached.extend(pids))?;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })
+    }
+
+    /// Terminates the given processes, killing the ones still running after the grace period forcibly.
+    /// Resolves once all of them are gone, or killed forcibly.
+    pub fn cancel(&mut self, pids: Vec<u32>, cx: &mut AppContext) -> Task<()> {
+        cancel(pids, self.probe.clone(), cx.background_executor())
+    }
+
+    /// Kills the given processes, resolving when all of them terminated or the timeout passed, to the ones still running then.
+    fn terminate(&mut self, pids: Vec<u32>, cx: &mut AppContext) -> Task<Vec<u32>> {
+        terminate(pids, self.probe.clone(), cx.background_executor())
+    }
+
+    /// Reads the processes recorded by the previous session and, if any of them are still alive,
+    /// offers to kill them via a workspace notification.
+    /// Only the first call per application run does anything.
+    pub fn report_orphans(workspace: WeakView<Workspace>, cx: &mut WindowContext) {
+        let Some(processes) = cx.try_global::<Self>() else {
+            return;
+        };
+        if processes.checked_for_orphans {
+            return;
+        }
+        let fs = processes.fs.clone();
+        let probe = processes.probe.clone();
+        cx.update_global(|processes: &mut Self, _| processes.checked_for_orphans = true);
+
+        cx.spawn(|mut cx| async move {
+            let recorded = match fs.load(&RUNNABLE_PROCESSES).await {
+                Ok(contents) => serde_json::from_str::<Vec<TrackedProcess>>(&contents)
+                    .log_err()
+                    .unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
+            let orphans = cx
+                .background_executor()
+                .spawn({
+                    let probe = probe.clone();
+                    async move {
+                        recorded
+                            .into_iter()
+                            .filter(|process| {
+                                probe
+                                    .describe(process.pid)
+                                    .map_or(false, |current| process.is_same_process(&current))
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .await;
+
+            // Either way, the previous session's records are not needed anymore.
+            cx.update(|cx| cx.update_global(|processes: &mut Self, cx| processes.write(cx)))?;
+            if orphans.is_empty() {
+                return Ok(());
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.show_notification(0, cx, |cx| {
+                    cx.new_view(|_| OrphanedProcessesNotification::new(orphans, probe))
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Terminates the given processes, killing the ones still running after the grace period forcibly.
+fn cancel(pids: Vec<u32>, probe: Arc<dyn ProcessProbe>, executor: &BackgroundExecutor) -> Task<()> {
+    let terminated = terminate(pids, probe.clone(), executor);
+    executor.spawn(async move {
+        for pid in terminated.await {
+            probe.force_kill(pid);
+        }
+    })
+}
+
+/// Kills the given processes, resolving when all of them terminated or the timeout passed, to the ones still running then.
+fn terminate(
+    pids: Vec<u32>,
+    probe: Arc<dyn ProcessProbe>,
+    executor: &BackgroundExecutor,
+) -> Task<Vec<u32>> {
+    for pid in &pids {
+        probe.kill(*pid);
+    }
+    let timer_executor = executor.clone();
+    executor.spawn(async move {
+        let timed_out = smol::future::or(
+            async {
+                while pids.iter().any(|pid| probe.describe(*pid).is_some()) {
+                    timer_executor.timer(TERMINATION_POLL_INTERVAL).await;
+                }
+                false
+            },
+            async {
+                timer_executor.timer(TERMINATION_TIMEOUT).await;
+                true
+            },
+        )
+        .await;
+        if !timed_out {
+            return Vec::new();
+        }
+        let alive = pids
+            .into_iter()
+            .filter(|pid| probe.describe(*pid).is_some())
+            .collect::<Vec<_>>();
+        // The last of them may have terminated right as the timeout passed.
+        if !alive.is_empty() {
+            log::warn!("Runnable processes {alive:?} did not terminate in time");
+        }
+        alive
+    })
+}
+
+/// The path of the file to write the session file's new contents to, before renaming it over the session file.
+/// Kept in the same directory, for the rename to stay on one file system.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// Lists the runnable processes left from a previous session, offering to kill them.
+pub struct OrphanedProcessesNotification {
+    processes: Vec<TrackedProcess>,
+    probe: Arc<dyn ProcessProbe>,
+}
+
+impl EventEmitter<DismissEvent> for OrphanedProcessesNotification {}
+
+impl OrphanedProcessesNotification {
+    fn new(processes: Vec<TrackedProcess>, probe: Arc<dyn ProcessProbe>) -> Self {
+        Self { processes, probe }
+    }
+
+    /// Kills the processes, forcibly if they are still running after the grace period.
+    pub fn kill(&mut self, cx: &mut ViewContext<Self>) {
+        let pids = self.processes.iter().map(|process| process.pid).collect();
+        cancel(pids, self.probe.clone(), cx.background_executor()).detach();
+        cx.emit(DismissEvent);
+    }
+
+    pub fn ignore(&mut self, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for OrphanedProcessesNotification {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .elevation_3(cx)
+            .p_4()
+            .gap_1()
+            .child(Label::new(
+                "Runnables from a previous session are still running:",
+            ))
+            .children(self.processes.iter().map(|process| {
+                Label::new(format!("{} (pid {})", process.command, process.pid))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("kill", "Kill")
+                            .on_click(cx.listener(|this, _, cx| this.kill(cx))),
+                    )
+                    .child(
+                        Button::new("ignore", "Ignore")
+                            .on_click(cx.listener(|this, _, cx| this.ignore(cx))),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use gpui::{TestAppContext, View, VisualTestContext};
+    use project::{FakeFs, Project};
+    use settings::SettingsStore;
+    use std::sync::Mutex;
+    use workspace::AppState;
+
+    #[derive(Default)]
+    pub(crate) struct FakeProbe {
+        pub(crate) alive: Mutex<Vec<TrackedProcess>>,
+        pub(crate) killed: Mutex<Vec<u32>>,
+        pub(crate) force_killed: Mutex<Vec<u32>>,
+        pub(crate) ignoring_kill: Mutex<Vec<u32>>,
+    }
+
+    impl ProcessProbe for FakeProbe {
+        fn describe(&self, pid: u32) -> Option<TrackedProcess> {
+            self.alive
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|process| process.pid == pid)
+                .cloned()
+        }
+
+        fn kill(&self, pid: u32) {
+            self.killed.lock().unwrap().push(pid);
+            if !self.ignoring_kill.lock().unwrap().contains(&pid) {
+                self.alive
+                    .lock()
+                    .unwrap()
+                    .retain(|process| process.pid != pid);
+            }
+        }
+
+        fn force_kill(&self, pid: u32) {
+            self.force_killed.lock().unwrap().push(pid);
+            self.alive
+                .lock()
+                .unwrap()
+                .retain(|process| process.pid != pid);
+        }
+    }
+
+    pub(crate) fn process(pid: u32, start_time: u64, command: &str) -> TrackedProcess {
+        TrackedProcess {
+            pid,
+            start_time: Some(start_time),
+            command: command.to_string(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_orphaned_processes_are_reported(cx: &mut TestAppContext) {
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let recorded = vec![
+            process(1, 10, "npm run dev"),
+            process(2, 20, "cargo watch"),
+            process(3, 30, "python -m http.server"),
+            // Recorded without a start time, only the command can identify them.
+            TrackedProcess {
+                pid: 4,
+                start_time: None,
+                command: "make serve".to_string(),
+            },
+            TrackedProcess {
+                pid: 5,
+                start_time: None,
+                command: "make serve".to_string(),
+            },
+        ];
+        fs.insert_file(
+            RUNNABLE_PROCESSES.as_path(),
+            serde_json::to_string(&recorded).unwrap(),
+        )
+        .await;
+        let probe = Arc::new(FakeProbe::default());
+        *probe.alive.lock().unwrap() = vec![
+            // Still running.
+            process(1, 10, "node /usr/bin/npm run dev"),
+            // Pid reused by an unrelated process.
+            process(2, 99, "cargo watch"),
+            process(4, 40, "make serve"),
+            process(5, 50, "bash"),
+        ];
+        cx.update(|cx| cx.set_global(RunnableProcesses::new(fs.clone(), probe.clone())));
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        workspace.update(cx, |workspace, cx| {
+            RunnableProcesses::report_orphans(workspace.weak_handle(), cx)
+        });
+        cx.run_until_parked();
+
+        let notification = workspace.update(cx, |workspace, _| {
+            workspace
+                .notification::<OrphanedProcessesNotification>(0)
+                .expect("orphaned processes notification")
+        });
+        notification.update(cx, |notification, _| {
+            assert_eq!(
+                notification.processes,
+                vec![recorded[0].clone(), recorded[3].clone()]
+            );
+        });
+        assert_eq!(
+            fs.load(&RUNNABLE_PROCESSES).await.unwrap(),
+            "[]",
+            "previous session records should be cleaned up"
+        );
+        assert!(!fs.is_file(&temp_path(&RUNNABLE_PROCESSES)).await);
+
+        probe.ignoring_kill.lock().unwrap().push(4);
+        notification.update(cx, |notification, cx| notification.kill(cx));
+        cx.run_until_parked();
+        assert_eq!(*probe.killed.lock().unwrap(), vec![1, 4]);
+        assert!(probe.force_killed.lock().unwrap().is_empty());
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notification::<OrphanedProcessesNotification>(0)
+                .is_none());
+        });
+        cx.executor().advance_clock(TERMINATION_TIMEOUT);
+        cx.run_until_parked();
+        assert_eq!(
+            *probe.force_killed.lock().unwrap(),
+            vec![4],
+            "the orphans still running after the grace period should be killed forcibly"
+        );
+        assert!(probe
+            .alive
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|process| process.pid != 4));
+
+        // The check only happens once per application run.
+        workspace.update(cx, |workspace, cx| {
+            RunnableProcesses::report_orphans(workspace.weak_handle(), cx)
+        });
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notification::<OrphanedProcessesNotification>(0)
+                .is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_tracking_writes_session_file(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let probe = Arc::new(FakeProbe::default());
+        *probe.alive.lock().unwrap() = vec![process(1, 10, "npm run dev")];
+        let mut processes = RunnableProcesses::new(fs.clone(), probe);
+        cx.update(|cx| {
+            processes.track(1, "npm run dev".to_string(), cx);
+            // Exited before it could be inspected.
+            processes.track(2, "true".to_string(), cx);
+        });
+        cx.run_until_parked();
+
+        let written = fs.load(&RUNNABLE_PROCESSES).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<TrackedProcess>>(&written).unwrap(),
+            vec![
+                process(1, 10, "npm run dev"),
+                TrackedProcess {
+                    pid: 2,
+                    start_time: None,
+                    command: "true".to_string(),
+                },
+            ]
+        );
+        assert!(!fs.is_file(&temp_path(&RUNNABLE_PROCESSES)).await);
+
+        cx.update(|cx| processes.forget(2, cx));
+        cx.run_until_parked();
+        let written = fs.load(&RUNNABLE_PROCESSES).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<TrackedProcess>>(&written).unwrap(),
+            vec![process(1, 10, "npm run dev")]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_kill_on_close(cx: &mut TestAppContext) {
+        let (workspace, probe, cx) = init_close_test(RunnablesOnClose::Kill, cx).await;
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![1, 2], cx)
+        });
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(close.await.unwrap());
+        assert_eq!(*probe.killed.lock().unwrap(), vec![1, 2]);
+        assert!(probe.alive.lock().unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_kill_on_close_times_out(cx: &mut TestAppContext) {
+        let (workspace, probe, cx) = init_close_test(RunnablesOnClose::Kill, cx).await;
+        probe.ignoring_kill.lock().unwrap().push(2);
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![1, 2], cx)
+        });
+        cx.executor().advance_clock(TERMINATION_TIMEOUT / 2);
+        cx.run_until_parked();
+        assert_eq!(*probe.killed.lock().unwrap(), vec![1, 2]);
+
+        cx.executor().advance_clock(TERMINATION_TIMEOUT);
+        cx.run_until_parked();
+        assert!(close.await.unwrap());
+        assert_eq!(probe.alive.lock().unwrap().len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_detach_on_close(cx: &mut TestAppContext) {
+        let (workspace, probe, cx) = init_close_test(RunnablesOnClose::Detach, cx).await;
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![1], cx)
+        });
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(close.await.unwrap());
+        assert!(probe.killed.lock().unwrap().is_empty());
+
+        // Terminals torn down with the window do not remove detached processes from the session file.
+        cx.update(|cx| {
+            cx.update_global(|processes: &mut RunnableProcesses, cx| {
+                processes.forget(1, cx);
+                processes.forget(2, cx);
+            })
+        });
+        let tracked = cx.read(|cx| {
+            cx.global::<RunnableProcesses>()
+                .tracked()
+                .iter()
+                .map(|process| process.pid)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(tracked, vec![1]);
+    }
+
+    #[gpui::test]
+    async fn test_ask_on_close(cx: &mut TestAppContext) {
+        let (workspace, probe, cx) = init_close_test(RunnablesOnClose::Ask, cx).await;
+
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![1, 2], cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        assert!(
+            !close.await.unwrap(),
+            "cancelling should keep the window open"
+        );
+        assert!(probe.killed.lock().unwrap().is_empty());
+
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![1, 2], cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert!(close.await.unwrap());
+        assert_eq!(*probe.killed.lock().unwrap(), vec![1, 2]);
+
+        // Nothing to ask about when no tracked runnables are alive.
+        let close = workspace.update(cx, |_, cx| {
+            RunnableProcesses::prepare_for_close(vec![3], cx)
+        });
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(close.await.unwrap());
+    }
+
+    async fn init_close_test(
+        on_close: RunnablesOnClose,
+        cx: &mut TestAppContext,
+    ) -> (View<Workspace>, Arc<FakeProbe>, &mut VisualTestContext) {
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+            runnable::init(cx);
+            cx.update_global(|store: &mut SettingsStore, cx| {
+                store.update_user_settings::<RunnablesSettings>(cx, |settings| {
+                    settings.on_close = Some(on_close);
+                });
+            });
+        });
+        let probe = Arc::new(FakeProbe::default());
+        *probe.alive.lock().unwrap() =
+            vec![process(1, 10, "npm run dev"), process(2, 20, "cargo watch")];
+        cx.update(|cx| {
+            let mut processes = RunnableProcesses::new(app_state.fs.clone(), probe.clone());
+            processes.track(1, "npm run dev".to_string(), cx);
+            processes.track(2, "cargo watch".to_string(), cx);
+            cx.set_global(processes);
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        (workspace, probe, cx)
+    }
+}
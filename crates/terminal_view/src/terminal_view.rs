@@ -1,4 +1,5 @@
 mod persistence;
+pub mod runnable_processes;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -6,9 +7,9 @@ use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    div, impl_actions, overlay, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
-    FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton, MouseDownEvent, Pixels,
-    Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    actions, div, impl_actions, overlay, AnyElement, AppContext, DismissEvent, EventEmitter,
+    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
+    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -64,9 +65,12 @@ pub struct SendKeystroke(String);
 
 impl_actions!(terminal, [SendText, SendKeystroke]);
 
+actions!(runnables, [TogglePinOutput]);
+
 pub fn init(cx: &mut AppContext) {
     terminal_panel::init(cx);
     terminal::init(cx);
+    runnable::init(cx);
 
     register_deserializable_item::<TerminalView>(cx);
 
@@ -90,6 +94,7 @@ pub struct TerminalView {
     blink_epoch: usize,
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
+    pinned: bool,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -165,6 +170,7 @@ impl TerminalView {
             blink_epoch: 0,
             can_navigate_to_selected_word: false,
             workspace_id,
+            pinned: false,
             _subscriptions: vec![focus_in, focus_out],
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -183,14 +189,69 @@ impl TerminalView {
         cx.emit(Event::Wakeup);
     }
 
+    /// Whether the terminal keeps the output of its runnable, instead of being reused for the runnable's next runs.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    fn can_toggle_pin(&self, cx: &AppContext) -> bool {
+        self.pinned || self.terminal.read(cx).runnable().is_some()
+    }
+
+    pub fn toggle_pin_output(&mut self, _: &TogglePinOutput, cx: &mut ViewContext<Self>) {
+        if !self.can_toggle_pin(cx) {
+            return;
+        }
+        self.pinned = !self.pinned;
+        self.save_pinned(cx);
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn save_pinned(&self, cx: &mut ViewContext<Self>) {
+        let item_id = cx.entity_id().as_u64();
+        let workspace_id = self.workspace_id;
+        let pinned = self.pinned;
+        let cwd = self
+            .terminal
+            .read(cx)
+            .foreground_process_info
+            .as_ref()
+            .map(|foreground_info| foreground_info.cwd.clone());
+        cx.background_executor()
+            .spawn(async move {
+                if let Some(cwd) = cwd {
+                    TERMINAL_DB
+                        .save_working_directory(item_id, workspace_id, cwd)
+                        .await
+                        .log_err();
+                }
+                TERMINAL_DB
+                    .save_pinned(item_id, workspace_id, pinned)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
     pub fn deploy_context_menu(
         &mut self,
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
-        let context_menu = ContextMenu::build(cx, |menu, _| {
-            menu.action("Clear", Box::new(Clear))
-                .action("Close", Box::new(CloseActiveItem { save_intent: None }))
+        let pin_label = if self.pinned {
+            Some("Unpin Output")
+        } else if self.terminal.read(cx).runnable().is_some() {
+            Some("Pin Output")
+        } else {
+            None
+        };
+        let context_menu = ContextMenu::build(cx, |mut menu, _| {
+            menu = menu.action("Clear", Box::new(Clear));
+            if let Some(pin_label) = pin_label {
+                menu = menu.action(pin_label, Box::new(TogglePinOutput));
+            }
+            menu.action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
         cx.focus_view(&context_menu);
@@ -441,7 +502,7 @@ fn subscribe_for_terminal_events(
             Event::TitleChanged => {
                 cx.emit(ItemEvent::UpdateTab);
                 let terminal = this.terminal().read(cx);
-                if !terminal.runnable().is_some() {
+                if terminal.runnable().is_none() || this.pinned {
                     if let Some(foreground_info) = &terminal.foreground_process_info {
                         let cwd = foreground_info.cwd.clone();
 
@@ -578,6 +639,24 @@ fn subscribe_for_terminal_events(
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
+            Event::RunnableFinished {
+                id,
+                exit_status,
+                test_results,
+            } => {
+                let id = id.clone();
+                let exit_status = *exit_status;
+                let test_results = test_results.clone();
+                workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.project().update(cx, |project, cx| {
+                            project.runnable_inventory().update(cx, |inventory, cx| {
+                                inventory.runnable_finished(id, exit_status, test_results, cx)
+                            })
+                        })
+                    })
+                    .ok();
+            }
         });
     vec![terminal_subscription, terminal_events_subscription]
 }
@@ -732,6 +811,7 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::clear))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
+            .on_action(cx.listener(TerminalView::toggle_pin_output))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -766,7 +846,12 @@ impl Item for TerminalView {
     type Event = ItemEvent;
 
     fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
-        Some(self.terminal().read(cx).title(false).into())
+        let title = self.terminal().read(cx).title(false);
+        if self.pinned {
+            Some(format!("{title} (pinned)").into())
+        } else {
+            Some(title.into())
+        }
     }
 
     fn tab_content(
@@ -790,6 +875,13 @@ impl Item for TerminalView {
             } else {
                 Color::Muted
             }))
+            .when(self.pinned, |tab| {
+                tab.child(
+                    Label::new("Pinned")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Accent),
+                )
+            })
             .into_any()
     }
 
@@ -873,11 +965,25 @@ impl Item for TerminalView {
                     .flatten()
                 });
 
+            let pinned = TERMINAL_DB
+                .get_pinned(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .unwrap_or(false);
+
             let terminal = project.update(&mut cx, |project, cx| {
                 project.create_terminal(cwd, None, window, cx)
             })??;
             pane.update(&mut cx, |_, cx| {
-                cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
+                cx.new_view(|cx| {
+                    let mut terminal_view =
+                        TerminalView::new(terminal, workspace, workspace_id, cx);
+                    if pinned {
+                        terminal_view.pinned = true;
+                        terminal_view.save_pinned(cx);
+                    }
+                    terminal_view
+                })
             })
         })
     }
@@ -898,6 +1004,24 @@ impl Item for TerminalView {
     fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
         f(*event)
     }
+
+    fn tab_context_menu(&mut self, menu: ContextMenu, cx: &mut ViewContext<Self>) -> ContextMenu {
+        if !self.can_toggle_pin(cx) {
+            return menu;
+        }
+        let terminal_view = cx.view().clone();
+        menu.separator().entry(
+            if self.pinned {
+                "Unpin Output"
+            } else {
+                "Pin Output"
+            },
+            Some(Box::new(TogglePinOutput)),
+            cx.handler_for(&terminal_view, |terminal_view, cx| {
+                terminal_view.toggle_pin_output(&TogglePinOutput, cx)
+            }),
+        )
+    }
 }
 
 impl SearchableItem for TerminalView {
@@ -93,6 +93,7 @@ pub enum IconName {
     Option,
     PageDown,
     PageUp,
+    Pin,
     Play,
     Plus,
     Public,
@@ -103,6 +104,8 @@ pub enum IconName {
     Return,
     Screen,
     SelectAll,
+    Settings,
+    Shield,
     Shift,
     Snip,
     Space,
@@ -186,6 +189,7 @@ impl IconName {
             IconName::Option => "icons/option.svg",
             IconName::PageDown => "icons/page_down.svg",
             IconName::PageUp => "icons/page_up.svg",
+            IconName::Pin => "icons/pin.svg",
             IconName::Play => "icons/play.svg",
             IconName::Plus => "icons/plus.svg",
             IconName::Public => "icons/public.svg",
@@ -196,6 +200,8 @@ impl IconName {
             IconName::Return => "icons/return.svg",
             IconName::Screen => "icons/desktop.svg",
             IconName::SelectAll => "icons/select_all.svg",
+            IconName::Settings => "icons/settings.svg",
+            IconName::Shield => "icons/shield.svg",
             IconName::Shift => "icons/shift.svg",
             IconName::Snip => "icons/snip.svg",
             IconName::Space => "icons/space.svg",
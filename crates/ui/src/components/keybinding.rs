@@ -8,40 +8,29 @@ pub struct KeyBinding {
     ///
     /// This should always contain at least one element.
     key_binding: gpui::KeyBinding,
+
+    /// The maximum number of keystrokes to render. Longer chords have their
+    /// middle keystrokes replaced with an ellipsis.
+    max_keystrokes: Option<usize>,
 }
 
 impl RenderOnce for KeyBinding {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
+        let (head, tail) = self.visible_keystrokes();
+
         h_flex()
             .flex_none()
             .gap_2()
-            .children(self.key_binding.keystrokes().iter().map(|keystroke| {
-                let key_icon = Self::icon_for_key(keystroke);
-
-                h_flex()
-                    .flex_none()
-                    .gap_0p5()
-                    .p_0p5()
-                    .rounded_sm()
-                    .text_color(cx.theme().colors().text_muted)
-                    .when(keystroke.modifiers.function, |el| el.child(Key::new("fn")))
-                    .when(keystroke.modifiers.control, |el| {
-                        el.child(KeyIcon::new(IconName::Control))
-                    })
-                    .when(keystroke.modifiers.alt, |el| {
-                        el.child(KeyIcon::new(IconName::Option))
-                    })
-                    .when(keystroke.modifiers.command, |el| {
-                        el.child(KeyIcon::new(IconName::Command))
-                    })
-                    .when(keystroke.modifiers.shift, |el| {
-                        el.child(KeyIcon::new(IconName::Shift))
-                    })
-                    .when_some(key_icon, |el, icon| el.child(KeyIcon::new(icon)))
-                    .when(key_icon.is_none(), |el| {
-                        el.child(Key::new(keystroke.key.to_uppercase().clone()))
-                    })
-            }))
+            .children(
+                head.iter()
+                    .map(|keystroke| Self::render_keystroke(keystroke, cx)),
+            )
+            .when_some(tail, |el, tail| {
+                el.child(Key::new("…")).children(
+                    tail.iter()
+                        .map(|keystroke| Self::render_keystroke(keystroke, cx)),
+                )
+            })
     }
 }
 
@@ -62,6 +51,84 @@ impl KeyBinding {
         Some(Self::new(key_binding))
     }
 
+    /// Limits the number of rendered keystrokes, eliding the middle of longer chords.
+    /// The first and last keystrokes are always shown.
+    pub fn max_keystrokes(mut self, max_keystrokes: usize) -> Self {
+        self.max_keystrokes = Some(max_keystrokes.max(2));
+        self
+    }
+
+    /// Whether some keystrokes are hidden because of [`Self::max_keystrokes`].
+    pub fn is_truncated(&self) -> bool {
+        self.visible_keystrokes().1.is_some()
+    }
+
+    /// The full keystroke sequence as text, using the current platform's notation.
+    pub fn text(&self) -> String {
+        self.key_binding
+            .keystrokes()
+            .iter()
+            .map(keystroke_text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Splits the keystrokes into the ones rendered before the ellipsis and,
+    /// if the chord is truncated, the ones rendered after it.
+    fn visible_keystrokes(&self) -> (&[Keystroke], Option<&[Keystroke]>) {
+        let keystrokes = self.key_binding.keystrokes();
+        match self.max_keystrokes {
+            Some(max_keystrokes) if keystrokes.len() > max_keystrokes => {
+                let head_len = (max_keystrokes + 1) / 2;
+                let tail_len = max_keystrokes - head_len;
+                (
+                    &keystrokes[..head_len],
+                    Some(&keystrokes[keystrokes.len() - tail_len..]),
+                )
+            }
+            _ => (keystrokes, None),
+        }
+    }
+
+    fn render_keystroke(keystroke: &Keystroke, cx: &WindowContext) -> impl IntoElement {
+        let key_icon = Self::icon_for_key(keystroke);
+
+        h_flex()
+            .flex_none()
+            .gap_0p5()
+            .p_0p5()
+            .rounded_sm()
+            .text_color(cx.theme().colors().text_muted)
+            .when(keystroke.modifiers.function, |el| el.child(Key::new("fn")))
+            .map(|el| {
+                if cfg!(target_os = "macos") {
+                    el.when(keystroke.modifiers.control, |el| {
+                        el.child(KeyIcon::new(IconName::Control))
+                    })
+                    .when(keystroke.modifiers.alt, |el| {
+                        el.child(KeyIcon::new(IconName::Option))
+                    })
+                    .when(keystroke.modifiers.command, |el| {
+                        el.child(KeyIcon::new(IconName::Command))
+                    })
+                    .when(keystroke.modifiers.shift, |el| {
+                        el.child(KeyIcon::new(IconName::Shift))
+                    })
+                } else {
+                    el.when(keystroke.modifiers.control, |el| el.child(Key::new("Ctrl")))
+                        .when(keystroke.modifiers.alt, |el| el.child(Key::new("Alt")))
+                        .when(keystroke.modifiers.command, |el| {
+                            el.child(Key::new("Super"))
+                        })
+                        .when(keystroke.modifiers.shift, |el| el.child(Key::new("Shift")))
+                }
+            })
+            .when_some(key_icon, |el, icon| el.child(KeyIcon::new(icon)))
+            .when(key_icon.is_none(), |el| {
+                el.child(Key::new(keystroke.key.to_uppercase().clone()))
+            })
+    }
+
     fn icon_for_key(keystroke: &Keystroke) -> Option<IconName> {
         match keystroke.key.as_str() {
             "left" => Some(IconName::ArrowLeft),
@@ -82,8 +149,40 @@ impl KeyBinding {
     }
 
     pub fn new(key_binding: gpui::KeyBinding) -> Self {
-        Self { key_binding }
+        Self {
+            key_binding,
+            max_keystrokes: None,
+        }
+    }
+}
+
+fn keystroke_text(keystroke: &Keystroke) -> String {
+    if cfg!(target_os = "macos") {
+        return keystroke.to_string();
+    }
+
+    let mut parts = Vec::new();
+    if keystroke.modifiers.function {
+        parts.push("Fn".to_string());
     }
+    if keystroke.modifiers.control {
+        parts.push("Ctrl".to_string());
+    }
+    if keystroke.modifiers.alt {
+        parts.push("Alt".to_string());
+    }
+    if keystroke.modifiers.command {
+        parts.push("Super".to_string());
+    }
+    if keystroke.modifiers.shift {
+        parts.push("Shift".to_string());
+    }
+    let mut key = keystroke.key.chars();
+    parts.extend(
+        key.next()
+            .map(|first| first.to_uppercase().chain(key).collect::<String>()),
+    );
+    parts.join("+")
 }
 
 #[derive(IntoElement)]
@@ -138,3 +237,53 @@ impl KeyIcon {
         Self { icon }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::NoAction;
+
+    fn absurdly_long_binding() -> gpui::KeyBinding {
+        let chord = (0..200)
+            .map(|ix| format!("ctrl-alt-shift-{}", (b'a' + (ix % 26) as u8) as char))
+            .collect::<Vec<_>>()
+            .join(" ");
+        gpui::KeyBinding::new(&chord, NoAction, None)
+    }
+
+    #[test]
+    fn test_long_chord_is_middle_truncated() {
+        let binding = KeyBinding::new(absurdly_long_binding()).max_keystrokes(3);
+        assert!(binding.is_truncated());
+
+        let (head, tail) = binding.visible_keystrokes();
+        let tail = tail.unwrap();
+        assert_eq!(head.len(), 2);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(head[0].key, "a");
+        assert_eq!(head[1].key, "b");
+        assert_eq!(tail[0].key, "r");
+
+        // The full sequence stays available for the tooltip.
+        assert_eq!(binding.text().split(' ').count(), 200);
+    }
+
+    #[test]
+    fn test_max_keystrokes_keeps_first_and_last() {
+        let binding = KeyBinding::new(absurdly_long_binding()).max_keystrokes(0);
+        let (head, tail) = binding.visible_keystrokes();
+        assert_eq!(head.len(), 1);
+        assert_eq!(tail.map(|tail| tail.len()), Some(1));
+    }
+
+    #[test]
+    fn test_short_chord_is_not_truncated() {
+        let binding =
+            KeyBinding::new(gpui::KeyBinding::new("cmd-k cmd-s", NoAction, None)).max_keystrokes(3);
+        assert!(!binding.is_truncated());
+        assert_eq!(binding.visible_keystrokes().0.len(), 2);
+
+        let unlimited = KeyBinding::new(absurdly_long_binding());
+        assert!(!unlimited.is_truncated());
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use gpui::{HighlightStyle, StyledText};
+use gpui::{FontWeight, HighlightStyle, Hsla, StyledText, UnderlineStyle};
 
 use crate::{prelude::*, LabelCommon, LabelLike, LabelSize, LineHeightStyle};
 
@@ -9,6 +9,8 @@ pub struct HighlightedLabel {
     base: LabelLike,
     label: SharedString,
     highlight_indices: Vec<usize>,
+    bold_highlights: bool,
+    underline_highlights: bool,
 }
 
 impl HighlightedLabel {
@@ -19,6 +21,33 @@ impl HighlightedLabel {
             base: LabelLike::new(),
             label: label.into(),
             highlight_indices,
+            bold_highlights: false,
+            underline_highlights: false,
+        }
+    }
+
+    /// Renders the highlighted characters bold, for them to stand out more than with the accent color alone.
+    pub fn bold_highlights(mut self, bold_highlights: bool) -> Self {
+        self.bold_highlights = bold_highlights;
+        self
+    }
+
+    /// Underlines the highlighted characters, for them to stand out regardless of the colors.
+    pub fn underline_highlights(mut self, underline_highlights: bool) -> Self {
+        self.underline_highlights = underline_highlights;
+        self
+    }
+
+    fn highlight_style(&self, color: Hsla) -> HighlightStyle {
+        HighlightStyle {
+            color: Some(color),
+            font_weight: self.bold_highlights.then_some(FontWeight::BOLD),
+            underline: self.underline_highlights.then(|| UnderlineStyle {
+                thickness: px(1.),
+                color: Some(color),
+                wavy: false,
+            }),
+            ..Default::default()
         }
     }
 }
@@ -47,7 +76,7 @@ impl LabelCommon for HighlightedLabel {
 
 impl RenderOnce for HighlightedLabel {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
-        let highlight_color = cx.theme().colors().text_accent;
+        let highlight_style = self.highlight_style(cx.theme().colors().text_accent);
 
         let mut highlight_indices = self.highlight_indices.iter().copied().peekable();
         let mut highlights: Vec<(Range<usize>, HighlightStyle)> = Vec::new();
@@ -67,13 +96,7 @@ impl RenderOnce for HighlightedLabel {
                 break;
             }
 
-            highlights.push((
-                start_ix..end_ix,
-                HighlightStyle {
-                    color: Some(highlight_color),
-                    ..Default::default()
-                },
-            ));
+            highlights.push((start_ix..end_ix, highlight_style));
         }
 
         let mut text_style = cx.text_style().clone();
@@ -83,3 +106,39 @@ impl RenderOnce for HighlightedLabel {
             .child(StyledText::new(self.label).with_highlights(&text_style, highlights))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::{hsla, px, FontWeight, HighlightStyle, UnderlineStyle};
+
+    use super::HighlightedLabel;
+
+    #[test]
+    fn test_highlight_style() {
+        let color = hsla(0.6, 0.8, 0.5, 1.);
+        let label = || HighlightedLabel::new("go to line", vec![0, 1, 6]);
+        assert_eq!(
+            label().highlight_style(color),
+            HighlightStyle {
+                color: Some(color),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            label()
+                .bold_highlights(true)
+                .underline_highlights(true)
+                .highlight_style(color),
+            HighlightStyle {
+                color: Some(color),
+                font_weight: Some(FontWeight::BOLD),
+                underline: Some(UnderlineStyle {
+                    thickness: px(1.),
+                    color: Some(color),
+                    wavy: false,
+                }),
+                ..Default::default()
+            }
+        );
+    }
+}
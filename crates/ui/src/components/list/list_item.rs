@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use gpui::{px, AnyElement, AnyView, ClickEvent, MouseButton, MouseDownEvent, Pixels};
+use gpui::{
+    px, transparent_black, AnyElement, AnyView, ClickEvent, Hsla, MouseButton, MouseDownEvent,
+    Pixels,
+};
 use smallvec::SmallVec;
 
 use crate::{prelude::*, Disclosure};
@@ -17,6 +20,8 @@ pub struct ListItem {
     id: ElementId,
     disabled: bool,
     selected: bool,
+    /// Whether the selected item gets outlined and an opaque background, for more contrast than the theme's selection.
+    emphasize_selection: bool,
     spacing: ListItemSpacing,
     indent_level: usize,
     indent_step_size: Pixels,
@@ -43,6 +48,7 @@ impl ListItem {
             id: id.into(),
             disabled: false,
             selected: false,
+            emphasize_selection: false,
             spacing: ListItemSpacing::Dense,
             indent_level: 0,
             indent_step_size: px(12.),
@@ -64,6 +70,13 @@ impl ListItem {
         self
     }
 
+    /// Outlines the item when selected and fills it with an opaque background,
+    /// for the selection to stand out with the low contrast themes.
+    pub fn emphasize_selection(mut self, emphasize_selection: bool) -> Self {
+        self.emphasize_selection = emphasize_selection;
+        self
+    }
+
     pub fn on_click(mut self, handler: impl Fn(&ClickEvent, &mut WindowContext) + 'static) -> Self {
         self.on_click = Some(Box::new(handler));
         self
@@ -146,9 +159,34 @@ impl ParentElement for ListItem {
     }
 }
 
+/// Outlines the element painting the item's selection, if it's selected.
+/// Unselected items get a transparent border, not to change their size.
+fn emphasize_selection<E: Styled + InteractiveElement + FluentBuilder>(
+    element: E,
+    selected: bool,
+    cx: &WindowContext,
+) -> E {
+    let border_color = if selected {
+        cx.theme().colors().border_focused
+    } else {
+        transparent_black()
+    };
+    element
+        .border_1()
+        .border_color(border_color)
+        .when(selected, |this| {
+            this.debug_selector(|| "list-item-emphasized-selection".into())
+        })
+}
+
 impl RenderOnce for ListItem {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
-        h_flex()
+        let selected_background: Hsla = if self.emphasize_selection {
+            cx.theme().colors().element_selected
+        } else {
+            cx.theme().colors().ghost_element_selected
+        };
+        let outer = h_flex()
             .id(self.id)
             .w_full()
             .relative()
@@ -166,93 +204,100 @@ impl RenderOnce for ListItem {
                     // })
                     .hover(|style| style.bg(cx.theme().colors().ghost_element_hover))
                     .active(|style| style.bg(cx.theme().colors().ghost_element_active))
-                    .when(self.selected, |this| {
-                        this.bg(cx.theme().colors().ghost_element_selected)
-                    })
-            })
-            .child(
-                h_flex()
-                    .id("inner_list_item")
-                    .w_full()
-                    .relative()
-                    .gap_1()
-                    .px_2()
-                    .map(|this| match self.spacing {
-                        ListItemSpacing::Dense => this,
-                        ListItemSpacing::Sparse => this.py_1(),
-                    })
-                    .group("list_item")
-                    .when(self.inset && !self.disabled, |this| {
-                        this
-                            // TODO: Add focus state
-                            // .when(self.state == InteractionState::Focused, |this| {
-                            //     this.border()
-                            //         .border_color(cx.theme().colors().border_focused)
-                            // })
-                            .hover(|style| style.bg(cx.theme().colors().ghost_element_hover))
-                            .active(|style| style.bg(cx.theme().colors().ghost_element_active))
-                            .when(self.selected, |this| {
-                                this.bg(cx.theme().colors().ghost_element_selected)
-                            })
-                    })
-                    .when_some(self.on_click, |this, on_click| {
-                        this.cursor_pointer().on_click(on_click)
-                    })
-                    .when_some(self.on_secondary_mouse_down, |this, on_mouse_down| {
-                        this.on_mouse_down(MouseButton::Right, move |event, cx| {
-                            (on_mouse_down)(event, cx)
-                        })
-                    })
-                    .when_some(self.tooltip, |this, tooltip| this.tooltip(tooltip))
-                    .map(|this| {
-                        if self.inset {
-                            this.rounded_md()
-                        } else {
-                            // When an item is not inset draw the indent spacing inside of the item
-                            this.ml(self.indent_level as f32 * self.indent_step_size)
-                        }
+                    .when(self.selected, |this| this.bg(selected_background))
+            });
+        let outer = if self.emphasize_selection && !self.inset {
+            emphasize_selection(outer, self.selected, cx)
+        } else {
+            outer
+        };
+        let inner = h_flex()
+            .id("inner_list_item")
+            .w_full()
+            .relative()
+            .gap_1()
+            .px_2();
+        let inner = if self.emphasize_selection && self.inset && !self.disabled {
+            emphasize_selection(inner, self.selected, cx)
+        } else {
+            inner
+        };
+        outer.child(
+            inner
+                .map(|this| match self.spacing {
+                    ListItemSpacing::Dense => this,
+                    ListItemSpacing::Sparse => this.py_1(),
+                })
+                .group("list_item")
+                .when(self.inset && !self.disabled, |this| {
+                    this
+                        // TODO: Add focus state
+                        // .when(self.state == InteractionState::Focused, |this| {
+                        //     this.border()
+                        //         .border_color(cx.theme().colors().border_focused)
+                        // })
+                        .hover(|style| style.bg(cx.theme().colors().ghost_element_hover))
+                        .active(|style| style.bg(cx.theme().colors().ghost_element_active))
+                        .when(self.selected, |this| this.bg(selected_background))
+                })
+                .when_some(self.on_click, |this, on_click| {
+                    this.cursor_pointer().on_click(on_click)
+                })
+                .when_some(self.on_secondary_mouse_down, |this, on_mouse_down| {
+                    this.on_mouse_down(MouseButton::Right, move |event, cx| {
+                        (on_mouse_down)(event, cx)
                     })
-                    .children(self.toggle.map(|is_open| {
-                        div()
-                            .flex()
-                            .absolute()
-                            .left(rems(-1.))
-                            .when(is_open, |this| this.visible_on_hover(""))
-                            .child(Disclosure::new("toggle", is_open).on_toggle(self.on_toggle))
-                    }))
-                    .child(
+                })
+                .when_some(self.tooltip, |this, tooltip| this.tooltip(tooltip))
+                .map(|this| {
+                    if self.inset {
+                        this.rounded_md()
+                    } else {
+                        // When an item is not inset draw the indent spacing inside of the item
+                        this.ml(self.indent_level as f32 * self.indent_step_size)
+                    }
+                })
+                .children(self.toggle.map(|is_open| {
+                    div()
+                        .flex()
+                        .absolute()
+                        .left(rems(-1.))
+                        .when(is_open, |this| this.visible_on_hover(""))
+                        .child(Disclosure::new("toggle", is_open).on_toggle(self.on_toggle))
+                }))
+                .child(
+                    h_flex()
+                        .flex_grow()
+                        .flex_shrink_0()
+                        .flex_basis(relative(0.25))
+                        .gap_1()
+                        .overflow_hidden()
+                        .children(self.start_slot)
+                        .children(self.children),
+                )
+                .when_some(self.end_slot, |this, end_slot| {
+                    this.justify_between().child(
                         h_flex()
-                            .flex_grow()
-                            .flex_shrink_0()
-                            .flex_basis(relative(0.25))
-                            .gap_1()
+                            .flex_shrink()
                             .overflow_hidden()
-                            .children(self.start_slot)
-                            .children(self.children),
+                            .when(self.end_hover_slot.is_some(), |this| {
+                                this.visible()
+                                    .group_hover("list_item", |this| this.invisible())
+                            })
+                            .child(end_slot),
                     )
-                    .when_some(self.end_slot, |this, end_slot| {
-                        this.justify_between().child(
-                            h_flex()
-                                .flex_shrink()
-                                .overflow_hidden()
-                                .when(self.end_hover_slot.is_some(), |this| {
-                                    this.visible()
-                                        .group_hover("list_item", |this| this.invisible())
-                                })
-                                .child(end_slot),
-                        )
-                    })
-                    .when_some(self.end_hover_slot, |this, end_hover_slot| {
-                        this.child(
-                            h_flex()
-                                .h_full()
-                                .absolute()
-                                .right_2()
-                                .top_0()
-                                .visible_on_hover("list_item")
-                                .child(end_hover_slot),
-                        )
-                    }),
-            )
+                })
+                .when_some(self.end_hover_slot, |this, end_hover_slot| {
+                    this.child(
+                        h_flex()
+                            .h_full()
+                            .absolute()
+                            .right_2()
+                            .top_0()
+                            .visible_on_hover("list_item")
+                            .child(end_hover_slot),
+                    )
+                }),
+        )
     }
 }
@@ -40,6 +40,7 @@ lazy_static::lazy_static! {
     pub static ref SETTINGS: PathBuf = CONFIG_DIR.join("settings.json");
     pub static ref KEYMAP: PathBuf = CONFIG_DIR.join("keymap.json");
     pub static ref RUNNABLES: PathBuf = CONFIG_DIR.join("runnables.json");
+    pub static ref RUNNABLE_PROCESSES: PathBuf = SUPPORT_DIR.join("runnable_processes.json");
     pub static ref LAST_USERNAME: PathBuf = CONFIG_DIR.join("last-username.txt");
     pub static ref LOG: PathBuf = LOGS_DIR.join("Zed.log");
     pub static ref OLD_LOG: PathBuf = LOGS_DIR.join("Zed.log.old");
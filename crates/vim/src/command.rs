@@ -31,7 +31,92 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
 }
 
-pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
+/// Full names of the commands offered as completions of the `:` queries, in the order they are listed in.
+/// The `!` variants are left out, typed explicitly when meant.
+const COMPLETED_COMMANDS: &[&str] = &[
+    "write",
+    "wq",
+    "wall",
+    "wqall",
+    "quit",
+    "quitall",
+    "exit",
+    "xall",
+    "update",
+    "split",
+    "vsplit",
+    "new",
+    "vnew",
+    "tabedit",
+    "tabnew",
+    "tabnext",
+    "tabprevious",
+    "tabNext",
+    "tabclose",
+    "clist",
+    "cnext",
+    "cprevious",
+    "cNext",
+    "lprevious",
+    "lNext",
+    "join",
+    "delete",
+    "sort",
+    "Explore",
+    "Hexplore",
+    "Lexplore",
+    "Sexplore",
+    "Vexplore",
+    "term",
+    "Term",
+    "Collab",
+    "Chat",
+    "Notification",
+];
+
+/// The commands for the query: the one it abbreviates first, then, for the queries starting with `:`,
+/// the other commands whose names it is the start of, e.g. `:wall` and `:wqall` for `:wa`.
+pub fn command_interceptor(query: &str, cx: &AppContext) -> Vec<CommandInterceptResult> {
+    let completing = query.starts_with(':');
+    let query = query.trim_start_matches(':');
+    let mut results = Vec::new();
+    if let Some((name, action)) = command_for_query(query, cx) {
+        results.push(intercept_result(name, action, query));
+    }
+    if completing && !query.is_empty() {
+        for command in COMPLETED_COMMANDS {
+            if !command.starts_with(query) {
+                continue;
+            }
+            let Some((name, action)) = command_for_query(command, cx) else {
+                continue;
+            };
+            let string = ":".to_owned() + name;
+            if results.iter().all(|result| result.string != string) {
+                results.push(intercept_result(name, action, query));
+            }
+        }
+    }
+    results
+}
+
+fn intercept_result(name: &str, action: Box<dyn Action>, query: &str) -> CommandInterceptResult {
+    let string = ":".to_owned() + name;
+    let positions = generate_positions(&string, query);
+    // The searches, substitutions and line numbers take their arguments in their names: those get no ranking key.
+    let ranking_key = name
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+        .then(|| format!("vim::ex::{name}"));
+    CommandInterceptResult {
+        action,
+        string,
+        positions,
+        ranking_key,
+    }
+}
+
+/// The name and the action of the command the query (without the `:`) abbreviates, if any.
+fn command_for_query<'a>(query: &'a str, cx: &AppContext) -> Option<(&'a str, Box<dyn Action>)> {
     // Note: this is a very poor simulation of vim's command palette.
     // In the future we should adjust it to handle parsing range syntax,
     // and then calling the appropriate commands with/without ranges.
@@ -41,10 +126,6 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
     //
     // For now, you can only do a replace on the % range, and you can
     // only use a specific line number range to "go to line"
-    while query.starts_with(":") {
-        query = &query[1..];
-    }
-
     let (name, action) = match query {
         // save and quit
         "w" | "wr" | "wri" | "writ" | "write" => (
@@ -317,15 +398,7 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             }
         }
     };
-
-    let string = ":".to_owned() + name;
-    let positions = generate_positions(&string, query);
-
-    Some(CommandInterceptResult {
-        action,
-        string,
-        positions,
-    })
+    Some((name, action))
 }
 
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
@@ -354,6 +427,7 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
+    use super::command_interceptor;
     use crate::test::{NeovimBackedTestContext, VimTestContext};
     use gpui::TestAppContext;
     use indoc::indoc;
@@ -493,4 +567,69 @@ mod test {
         cx.simulate_keystrokes([":", "q", "a", "enter"]);
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[gpui::test]
+    async fn test_command_completions(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let suggestions = |query: &str, cx: &mut VimTestContext| {
+            cx.update(|cx| {
+                command_interceptor(query, cx)
+                    .into_iter()
+                    .map(|result| result.string)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            suggestions(":wa", &mut cx),
+            [":wall"],
+            "The abbreviated command should come first"
+        );
+        assert_eq!(suggestions(":wq", &mut cx), [":wq", ":wqall"]);
+        assert_eq!(
+            suggestions(":tab", &mut cx),
+            [
+                ":tabedit",
+                ":tabnew",
+                ":tabnext",
+                ":tabprevious",
+                ":tabNext",
+                ":tabclose"
+            ],
+            "Queries abbreviating no command should list the commands they start"
+        );
+        assert_eq!(
+            suggestions("tab", &mut cx),
+            Vec::<String>::new(),
+            "Only the queries starting with `:` should be completed"
+        );
+        assert_eq!(suggestions(":12", &mut cx), [":12"]);
+    }
+
+    #[gpui::test]
+    async fn test_command_ranking_keys(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let ranking_keys = |query: &str, cx: &mut VimTestContext| {
+            cx.update(|cx| {
+                command_interceptor(query, cx)
+                    .into_iter()
+                    .map(|result| result.ranking_key)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            ranking_keys(":wq", &mut cx),
+            [
+                Some("vim::ex::wq".to_string()),
+                Some("vim::ex::wqall".to_string())
+            ]
+        );
+        assert_eq!(
+            ranking_keys(":w!", &mut cx),
+            [Some("vim::ex::write!".to_string())]
+        );
+        assert_eq!(ranking_keys(":12", &mut cx), [None]);
+        assert_eq!(ranking_keys(":%s/a/b", &mut cx), [None]);
+    }
 }
@@ -20,6 +20,7 @@ impl VimTestContext {
             let settings = SettingsStore::test(cx);
             cx.set_global(settings);
             release_channel::init("0.0.0", cx);
+            picker::init(cx);
             command_palette::init(cx);
             crate::init(cx);
         });
@@ -16,7 +16,7 @@ mod visual;
 
 use anyhow::Result;
 use collections::HashMap;
-use command_palette::CommandPaletteInterceptor;
+use command_palette::{register_command_variants, CommandPaletteInterceptor, InterceptorId};
 use copilot::CommandPaletteFilter;
 use editor::{movement, Editor, EditorEvent, EditorMode};
 use gpui::{
@@ -76,6 +76,18 @@ pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, cx| register(workspace, cx))
         .detach();
 
+    register_command_variants(
+        [
+            ("normal", Mode::Normal),
+            ("insert", Mode::Insert),
+            ("visual", Mode::Visual),
+            ("visual line", Mode::VisualLine),
+            ("visual block", Mode::VisualBlock),
+        ]
+        .map(|(label, mode)| (label, Box::new(SwitchMode(mode)) as Box<dyn Action>)),
+        cx,
+    );
+
     // Any time settings change, update vim mode to match. The Vim struct
     // will be initialized as disabled by default, so we filter its commands
     // out when starting up.
@@ -178,6 +190,8 @@ struct Vim {
     active_editor: Option<WeakView<Editor>>,
     editor_subscription: Option<Subscription>,
     enabled: bool,
+    /// The interceptor of the `:` commands typed into the command palette, registered while Vim mode is enabled.
+    command_interceptor: Option<InterceptorId>,
     editor_states: HashMap<EntityId, EditorState>,
     workspace_state: WorkspaceState,
     default_state: EditorState,
@@ -510,7 +524,9 @@ impl Vim {
             return;
         }
         if !enabled {
-            let _ = cx.remove_global::<CommandPaletteInterceptor>();
+            if let Some(command_interceptor) = self.command_interceptor.take() {
+                CommandPaletteInterceptor::unregister(command_interceptor, cx);
+            }
             cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
                 filter.hidden_namespaces.insert("vim");
             });
@@ -522,9 +538,10 @@ impl Vim {
         cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
             filter.hidden_namespaces.remove("vim");
         });
-        cx.set_global::<CommandPaletteInterceptor>(CommandPaletteInterceptor(Box::new(
+        self.command_interceptor = Some(CommandPaletteInterceptor::register(
+            cx,
             command::command_interceptor,
-        )));
+        ));
 
         if let Some(active_window) = cx
             .active_window()
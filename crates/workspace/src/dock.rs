@@ -36,6 +36,10 @@ pub trait Panel: FocusableView + EventEmitter<PanelEvent> {
     fn icon_label(&self, _: &WindowContext) -> Option<String> {
         None
     }
+    /// Details shown under the icon's tooltip, e.g. the state of the panel's background work.
+    fn icon_tooltip_meta(&self, _: &WindowContext) -> Option<String> {
+        None
+    }
     fn is_zoomed(&self, _cx: &WindowContext) -> bool {
         false
     }
@@ -61,6 +65,7 @@ pub trait PanelHandle: Send + Sync {
     fn icon_tooltip(&self, cx: &WindowContext) -> Option<&'static str>;
     fn toggle_action(&self, cx: &WindowContext) -> Box<dyn Action>;
     fn icon_label(&self, cx: &WindowContext) -> Option<String>;
+    fn icon_tooltip_meta(&self, cx: &WindowContext) -> Option<String>;
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle;
     fn to_any(&self) -> AnyView;
 }
@@ -125,6 +130,10 @@ where
         self.read(cx).icon_label(cx)
     }
 
+    fn icon_tooltip_meta(&self, cx: &WindowContext) -> Option<String> {
+        self.read(cx).icon_tooltip_meta(cx)
+    }
+
     fn to_any(&self) -> AnyView {
         self.clone().into()
     }
@@ -657,6 +666,8 @@ impl Render for PanelButtons {
             .filter_map(|(i, entry)| {
                 let icon = entry.panel.icon(cx)?;
                 let icon_tooltip = entry.panel.icon_tooltip(cx)?;
+                let icon_label = entry.panel.icon_label(cx);
+                let icon_tooltip_meta = entry.panel.icon_tooltip_meta(cx);
                 let name = entry.panel.persistent_name();
                 let panel = entry.panel.clone();
 
@@ -704,15 +715,36 @@ impl Render for PanelButtons {
                         .anchor(menu_anchor)
                         .attach(menu_attach)
                         .trigger(
-                            IconButton::new(name, icon)
-                                .icon_size(IconSize::Small)
-                                .selected(is_active_button)
-                                .on_click({
-                                    let action = action.boxed_clone();
-                                    move |_, cx| cx.dispatch_action(action.boxed_clone())
-                                })
-                                .tooltip(move |cx| {
-                                    Tooltip::for_action(tooltip.clone(), &*action, cx)
+                            div()
+                                .relative()
+                                .child(
+                                    IconButton::new(name, icon)
+                                        .icon_size(IconSize::Small)
+                                        .selected(is_active_button)
+                                        .on_click({
+                                            let action = action.boxed_clone();
+                                            move |_, cx| cx.dispatch_action(action.boxed_clone())
+                                        })
+                                        .tooltip(move |cx| match &icon_tooltip_meta {
+                                            Some(meta) => Tooltip::with_meta(
+                                                tooltip.clone(),
+                                                Some(&*action),
+                                                meta.clone(),
+                                                cx,
+                                            ),
+                                            None => {
+                                                Tooltip::for_action(tooltip.clone(), &*action, cx)
+                                            }
+                                        }),
+                                )
+                                .when_some(icon_label, |button, icon_label| {
+                                    button.child(
+                                        div().absolute().top_0().right_0().child(
+                                            Label::new(icon_label)
+                                                .size(LabelSize::XSmall)
+                                                .color(Color::Error),
+                                        ),
+                                    )
                                 }),
                         ),
                 )
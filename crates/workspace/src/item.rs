@@ -34,6 +34,7 @@ use std::{
     time::Duration,
 };
 use theme::Theme;
+use ui::ContextMenu;
 
 #[derive(Deserialize)]
 pub struct ItemSettings {
@@ -215,6 +216,10 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
     fn pixel_position_of_cursor(&self, _: &AppContext) -> Option<Point<Pixels>> {
         None
     }
+    /// Adds the item's own entries to the end of its tab's context menu.
+    fn tab_context_menu(&mut self, menu: ContextMenu, _: &mut ViewContext<Self>) -> ContextMenu {
+        menu
+    }
 }
 
 pub trait ItemHandle: 'static + Send {
@@ -279,6 +284,7 @@ pub trait ItemHandle: 'static + Send {
     fn serialized_item_kind(&self) -> Option<&'static str>;
     fn show_toolbar(&self, cx: &AppContext) -> bool;
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>>;
+    fn tab_context_menu(&self, menu: ContextMenu, cx: &mut WindowContext) -> ContextMenu;
 }
 
 pub trait WeakItemHandle: Send + Sync {
@@ -624,6 +630,10 @@ impl<T: Item> ItemHandle for View<T> {
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>> {
         self.read(cx).pixel_position_of_cursor(cx)
     }
+
+    fn tab_context_menu(&self, menu: ContextMenu, cx: &mut WindowContext) -> ContextMenu {
+        self.update(cx, |item, cx| item.tab_context_menu(menu, cx))
+    }
 }
 
 impl From<Box<dyn ItemHandle>> for AnyView {
@@ -40,6 +40,8 @@ pub struct ActiveModal {
 pub struct ModalLayer {
     active_modal: Option<ActiveModal>,
     dismiss_on_focus_lost: bool,
+    /// A modal toggled while the active one was still being dismissed, shown once the dismissal completes.
+    deferred_modal: Option<Box<dyn FnOnce(&mut Self, &mut ViewContext<Self>)>>,
 }
 
 impl ModalLayer {
@@ -47,6 +49,7 @@ impl ModalLayer {
         Self {
             active_modal: None,
             dismiss_on_focus_lost: false,
+            deferred_modal: None,
         }
     }
 
@@ -57,9 +60,15 @@ impl ModalLayer {
     {
         if let Some(active_modal) = &self.active_modal {
             let is_close = active_modal.modal.view().downcast::<V>().is_ok();
-            let did_close = self.hide_modal(cx);
-            if is_close || !did_close {
-                return;
+            match self.hide_modal(cx) {
+                DismissDecision::Dismiss(true) if !is_close => {}
+                DismissDecision::Pending if !is_close => {
+                    let new_modal = cx.new_view(build_view);
+                    self.deferred_modal =
+                        Some(Box::new(move |this, cx| this.show_modal(new_modal, cx)));
+                    return;
+                }
+                _ => return,
             }
         }
         let new_modal = cx.new_view(build_view);
@@ -90,22 +99,24 @@ impl ModalLayer {
         cx.notify();
     }
 
-    fn hide_modal(&mut self, cx: &mut ViewContext<Self>) -> bool {
+    fn hide_modal(&mut self, cx: &mut ViewContext<Self>) -> DismissDecision {
         let Some(active_modal) = self.active_modal.as_mut() else {
             self.dismiss_on_focus_lost = false;
-            return false;
+            return DismissDecision::Dismiss(false);
         };
 
-        match active_modal.modal.on_before_dismiss(cx) {
+        let decision = active_modal.modal.on_before_dismiss(cx);
+        match decision {
             DismissDecision::Dismiss(dismiss) => {
                 self.dismiss_on_focus_lost = !dismiss;
                 if !dismiss {
-                    return false;
+                    self.deferred_modal = None;
+                    return decision;
                 }
             }
             DismissDecision::Pending => {
                 self.dismiss_on_focus_lost = false;
-                return false;
+                return decision;
             }
         }
 
@@ -117,7 +128,10 @@ impl ModalLayer {
             }
             cx.notify();
         }
-        true
+        if let Some(show_deferred_modal) = self.deferred_modal.take() {
+            show_deferred_modal(self, cx);
+        }
+        decision
     }
 
     pub fn active_modal<V>(&self) -> Option<View<V>>
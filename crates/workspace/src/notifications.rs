@@ -125,6 +125,17 @@ impl Workspace {
         });
     }
 
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn notification<V: Notification>(&self, id: usize) -> Option<View<V>> {
+        let type_id = TypeId::of::<V>();
+        self.notifications
+            .iter()
+            .find(|(existing_type_id, existing_id, _)| {
+                (*existing_type_id, *existing_id) == (type_id, id)
+            })
+            .and_then(|(_, _, notification)| notification.to_any().downcast::<V>().ok())
+    }
+
     pub fn dismiss_notification<V: Notification>(&mut self, id: usize, cx: &mut ViewContext<Self>) {
         let type_id = TypeId::of::<V>();
 
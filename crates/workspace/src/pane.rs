@@ -170,6 +170,9 @@ pub struct Pane {
     can_drop_predicate: Option<Arc<dyn Fn(&dyn Any, &mut WindowContext) -> bool>>,
     custom_drop_handle:
         Option<Arc<dyn Fn(&mut Pane, &dyn Any, &mut ViewContext<Pane>) -> ControlFlow<(), ()>>>,
+    /// What dropping the item being dragged would do, shown over the pane while it's dragged over.
+    custom_drop_hint:
+        Option<Arc<dyn Fn(&mut Pane, &mut ViewContext<Pane>) -> Option<SharedString>>>,
     can_split: bool,
     render_tab_bar_buttons: Rc<dyn Fn(&mut Pane, &mut ViewContext<Pane>) -> AnyElement>,
     _subscriptions: Vec<Subscription>,
@@ -271,6 +274,7 @@ impl Pane {
             project,
             can_drop_predicate,
             custom_drop_handle: None,
+            custom_drop_hint: None,
             can_split: true,
             render_tab_bar_buttons: Rc::new(move |pane, cx| {
                 h_flex()
@@ -433,6 +437,15 @@ impl Pane {
         cx.notify();
     }
 
+    /// Sets the hint, shown over the pane while an item gets dragged over it, e.g. to tell what the custom drop handle does with it.
+    pub fn set_custom_drop_hint<F>(&mut self, cx: &mut ViewContext<Self>, hint: F)
+    where
+        F: 'static + Fn(&mut Pane, &mut ViewContext<Pane>) -> Option<SharedString>,
+    {
+        self.custom_drop_hint = Some(Arc::new(hint));
+        cx.notify();
+    }
+
     pub fn nav_history_for_item<T: Item>(&self, item: &View<T>) -> ItemNavHistory {
         ItemNavHistory {
             history: self.nav_history.clone(),
@@ -1382,8 +1395,10 @@ impl Pane {
         };
 
         let pane = cx.view().downgrade();
+        let item = item.boxed_clone();
         right_click_menu(ix).trigger(tab).menu(move |cx| {
             let pane = pane.clone();
+            let item = item.boxed_clone();
             ContextMenu::build(cx, move |mut menu, cx| {
                 if let Some(pane) = pane.upgrade() {
                     menu = menu
@@ -1454,6 +1469,7 @@ impl Pane {
                             }),
                         );
                     }
+                    menu = item.tab_context_menu(menu, cx);
                 }
 
                 menu
@@ -1740,6 +1756,11 @@ impl FocusableView for Pane {
 
 impl Render for Pane {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let drop_hint = self
+            .custom_drop_hint
+            .clone()
+            .filter(|_| cx.has_active_drag())
+            .and_then(|drop_hint| drop_hint(self, cx));
         v_flex()
             .key_context("Pane")
             .track_focus(&self.focus_handle)
@@ -1863,6 +1884,12 @@ impl Render for Pane {
                             .group_drag_over::<DraggedTab>("", |style| style.visible())
                             .group_drag_over::<ProjectEntryId>("", |style| style.visible())
                             .group_drag_over::<ExternalPaths>("", |style| style.visible())
+                            .when_some(drop_hint, |this, drop_hint| {
+                                this.flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .child(Label::new(drop_hint))
+                            })
                             .when_some(self.can_drop_predicate.clone(), |this, p| {
                                 this.can_drop(move |a, cx| p(a, cx))
                             })
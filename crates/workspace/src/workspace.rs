@@ -61,6 +61,7 @@ use std::{
     borrow::Cow,
     cmp, env,
     path::{Path, PathBuf},
+    rc::Rc,
     sync::Weak,
     sync::{atomic::AtomicUsize, Arc},
     time::Duration,
@@ -477,6 +478,7 @@ pub enum OpenVisible {
 pub struct Workspace {
     weak_self: WeakView<Self>,
     workspace_actions: Vec<Box<dyn Fn(Div, &mut ViewContext<Self>) -> Div>>,
+    close_checks: Vec<Rc<dyn Fn(&mut Self, &mut ViewContext<Self>) -> Task<Result<bool>>>>,
     zoomed: Option<AnyWeakView>,
     zoomed_position: Option<DockPosition>,
     center: PaneGroup,
@@ -766,6 +768,7 @@ impl Workspace {
             _subscriptions: subscriptions,
             pane_history_timestamp,
             workspace_actions: Default::default(),
+            close_checks: Default::default(),
             // This data will be incorrect, but it will be overwritten by the time it needs to be used.
             bounds: Default::default(),
         }
@@ -1240,11 +1243,28 @@ impl Workspace {
                 }
             }
 
-            Ok(this
+            let saved = this
                 .update(&mut cx, |this, cx| {
                     this.save_all_internal(SaveIntent::Close, cx)
                 })?
-                .await?)
+                .await?;
+            if !saved {
+                return Ok(false);
+            }
+
+            let close_checks = this.update(&mut cx, |this, cx| {
+                this.close_checks
+                    .clone()
+                    .into_iter()
+                    .map(|check| check(this, cx))
+                    .collect::<Vec<_>>()
+            })?;
+            for check in close_checks {
+                if !check.await? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
         })
     }
 
@@ -3457,6 +3477,16 @@ impl Workspace {
         self
     }
 
+    /// Registers a check to run when the workspace is about to close, after all items were saved.
+    /// The closing is cancelled if any of the checks resolves to `false`.
+    pub fn register_close_check(
+        &mut self,
+        check: impl Fn(&mut Self, &mut ViewContext<Self>) -> Task<Result<bool>> + 'static,
+    ) -> &mut Self {
+        self.close_checks.push(Rc::new(check));
+        self
+    }
+
     fn add_workspace_actions_listeners(&self, div: Div, cx: &mut ViewContext<Self>) -> Div {
         let mut div = div
             .on_action(cx.listener(Self::close_inactive_items_and_panes))
@@ -5287,6 +5317,68 @@ mod tests {
         }
     }
 
+    struct PendingDismissModal {
+        focus_handle: FocusHandle,
+        dismissable: bool,
+    }
+
+    impl EventEmitter<DismissEvent> for PendingDismissModal {}
+
+    impl FocusableView for PendingDismissModal {
+        fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+            self.focus_handle.clone()
+        }
+    }
+
+    impl ModalView for PendingDismissModal {
+        fn on_before_dismiss(&mut self, _: &mut ViewContext<Self>) -> DismissDecision {
+            if self.dismissable {
+                DismissDecision::Dismiss(true)
+            } else {
+                DismissDecision::Pending
+            }
+        }
+    }
+
+    impl Render for PendingDismissModal {
+        fn render(&mut self, _cx: &mut ViewContext<PendingDismissModal>) -> impl IntoElement {
+            div().track_focus(&self.focus_handle)
+        }
+    }
+
+    #[gpui::test]
+    async fn test_toggle_modal_during_pending_dismissal(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| PendingDismissModal {
+                focus_handle: cx.focus_handle(),
+                dismissable: false,
+            });
+            workspace.toggle_modal(cx, TestModal::new);
+        });
+        let pending_modal = workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<TestModal>(cx).is_none());
+            workspace.active_modal::<PendingDismissModal>(cx).unwrap()
+        });
+
+        pending_modal.update(cx, |pending_modal, cx| {
+            pending_modal.dismissable = true;
+            cx.emit(DismissEvent);
+        });
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<PendingDismissModal>(cx).is_none());
+            let test_modal = workspace
+                .active_modal::<TestModal>(cx)
+                .expect("modal toggled during the dismissal should be shown after it");
+            assert!(test_modal.focus_handle(cx).is_focused(cx));
+        });
+    }
+
     #[gpui::test]
     async fn test_panels(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -117,7 +117,7 @@ pub fn app_menus() -> Vec<Menu<'static>> {
                 }),
                 MenuItem::separator(),
                 MenuItem::action("Project Panel", project_panel::ToggleFocus),
-                MenuItem::action("Command Palette", command_palette::Toggle),
+                MenuItem::action("Command Palette", command_palette::Toggle::default()),
                 MenuItem::action("Diagnostics", diagnostics::Deploy),
                 MenuItem::separator(),
             ],
@@ -151,7 +151,7 @@ pub fn app_menus() -> Vec<Menu<'static>> {
         Menu {
             name: "Help",
             items: vec![
-                MenuItem::action("Command Palette", command_palette::Toggle),
+                MenuItem::action("Command Palette", command_palette::Toggle::default()),
                 MenuItem::separator(),
                 MenuItem::action("View Telemetry", crate::OpenTelemetryLog),
                 MenuItem::action("View Dependency Licenses", crate::OpenLicenses),
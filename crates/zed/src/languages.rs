@@ -2,6 +2,7 @@ use anyhow::Context;
 use gpui::AppContext;
 pub use language::*;
 use node_runtime::NodeRuntime;
+use project::Fs;
 use rust_embed::RustEmbed;
 use settings::Settings;
 use std::{str, sync::Arc};
@@ -59,6 +60,11 @@ mod zig;
 #[exclude = "*.rs"]
 struct LanguageDir;
 
+/// Registers the runnable variables, contributed by the languages.
+pub fn register_runnable_variables(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    runnable::register_variable_provider(Arc::new(rust::CargoPackageVariable::new(fs)), cx);
+}
+
 pub fn init(
     languages: Arc<LanguageRegistry>,
     node_runtime: Arc<dyn NodeRuntime>,
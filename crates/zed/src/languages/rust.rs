@@ -1,13 +1,22 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_trait::async_trait;
-use futures::{io::BufReader, StreamExt};
+use futures::{future::BoxFuture, io::BufReader, FutureExt, StreamExt};
 pub use language::*;
 use lazy_static::lazy_static;
 use lsp::LanguageServerBinary;
+use project::Fs;
 use regex::Regex;
+use runnable::{RunnableContext, VariableProvider};
 use smol::fs::{self, File};
-use std::{any::Any, borrow::Cow, env::consts, path::PathBuf, str, sync::Arc};
+use std::{
+    any::Any,
+    borrow::Cow,
+    env::consts,
+    path::{Path, PathBuf},
+    str,
+    sync::Arc,
+};
 use util::{
     async_maybe,
     fs::remove_matching,
@@ -293,6 +302,55 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
     .log_err()
 }
 
+/// Resolves `$CARGO_PACKAGE` into the name of the package, closest to the active file or the worktree root.
+pub struct CargoPackageVariable {
+    fs: Arc<dyn Fs>,
+}
+
+impl CargoPackageVariable {
+    pub fn new(fs: Arc<dyn Fs>) -> Self {
+        Self { fs }
+    }
+}
+
+impl VariableProvider for CargoPackageVariable {
+    fn variable_names(&self) -> &[&'static str] {
+        &["CARGO_PACKAGE"]
+    }
+
+    fn resolve(&self, _: &str, context: &RunnableContext) -> BoxFuture<'static, Result<String>> {
+        let fs = self.fs.clone();
+        let search_start = context
+            .active_file
+            .as_deref()
+            .and_then(Path::parent)
+            .or(context.worktree_root.as_deref())
+            .map(Path::to_path_buf);
+        async move {
+            let search_start = search_start.context("no file is active")?;
+            for dir in search_start.ancestors() {
+                let manifest_path = dir.join("Cargo.toml");
+                let Ok(manifest) = fs.load(&manifest_path).await else {
+                    continue;
+                };
+                let manifest = toml::from_str::<toml::Value>(&manifest)
+                    .with_context(|| format!("parsing {manifest_path:?}"))?;
+                if let Some(name) = manifest
+                    .get("package")
+                    .and_then(|package| package.get("name"))
+                    .and_then(|name| name.as_str())
+                {
+                    return Ok(name.to_string());
+                }
+            }
+            Err(anyhow!(
+                "no Cargo.toml with a package found for {search_start:?}"
+            ))
+        }
+        .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -301,6 +359,8 @@ mod tests {
     use crate::languages::language;
     use gpui::{Context, Hsla, TestAppContext};
     use language::language_settings::AllLanguageSettings;
+    use project::FakeFs;
+    use serde_json::json;
     use settings::SettingsStore;
     use text::BufferId;
     use theme::SyntaxTheme;
@@ -571,4 +631,44 @@ mod tests {
             buffer
         });
     }
+
+    #[gpui::test]
+    async fn test_cargo_package_variable(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/zed",
+            json!({
+                "Cargo.toml": "[workspace]\nmembers = [\"crates/*\"]\n",
+                "crates": {
+                    "editor": {
+                        "Cargo.toml": "[package]\nname = \"editor\"\n",
+                        "src": { "editor.rs": "" },
+                    },
+                },
+                "script": { "bump.rs": "" },
+            }),
+        )
+        .await;
+        let variable = CargoPackageVariable::new(fs);
+
+        let context = |active_file: &str| RunnableContext {
+            worktree_root: Some(PathBuf::from("/zed")),
+            active_file: Some(PathBuf::from(active_file)),
+            ..RunnableContext::default()
+        };
+        assert_eq!(
+            variable
+                .resolve(
+                    "CARGO_PACKAGE",
+                    &context("/zed/crates/editor/src/editor.rs")
+                )
+                .await
+                .unwrap(),
+            "editor"
+        );
+        assert!(variable
+            .resolve("CARGO_PACKAGE", &context("/zed/script/bump.rs"))
+            .await
+            .is_err());
+    }
 }
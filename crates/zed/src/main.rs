@@ -161,6 +161,7 @@ fn main() {
         theme::init(theme::LoadThemes::All(Box::new(Assets)), cx);
         project::Project::init(&client, cx);
         client::init(&client, cx);
+        picker::init(cx);
         command_palette::init(cx);
         language::init(cx);
         editor::init(cx);
@@ -245,6 +246,7 @@ fn main() {
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
         runnables_ui::init(cx);
+        languages::register_runnable_variables(fs.clone(), cx);
         channel::init(&client, user_store.clone(), cx);
         search::init(cx);
         semantic_index::init(fs.clone(), http.clone(), languages.clone(), cx);
@@ -18,6 +18,7 @@ pub use open_listener::*;
 use anyhow::{anyhow, Context as _};
 use assets::Assets;
 use futures::{channel::mpsc, select_biased, StreamExt};
+use project::ScriptsSource;
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
 use release_channel::{AppCommitSha, ReleaseChannel};
@@ -31,7 +32,7 @@ use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
     asset_str,
-    paths::{self, LOCAL_SETTINGS_RELATIVE_PATH},
+    paths::{self, PathExt, LOCAL_SETTINGS_RELATIVE_PATH},
     ResultExt,
 };
 use uuid::Uuid;
@@ -130,6 +131,8 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
         let feedback_button =
             cx.new_view(|_| feedback::deploy_feedback_button::DeployFeedbackButton::new(workspace));
         let cursor_position = cx.new_view(|_| editor::items::CursorPosition::new());
+        let last_command =
+            cx.new_view(|cx| command_palette::LastCommandIndicator::new(workspace, cx));
         workspace.status_bar().update(cx, |status_bar, cx| {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
@@ -138,6 +141,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
+            status_bar.add_right_item(last_command, cx);
         });
 
         auto_update::notify_of_any_new_update(cx);
@@ -162,11 +166,13 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 app_state.fs.clone(),
                 paths::RUNNABLES.clone(),
             );
-            let source = StaticSource::new(runnables_file_rx, cx);
+            let static_source = StaticSource::new(runnables_file_rx, cx);
+            let scripts_source = ScriptsSource::new(project.clone(), cx);
             project.update(cx, |project, cx| {
-                project
-                    .runnable_inventory()
-                    .update(cx, |inventory, cx| inventory.add_source(source, cx))
+                project.runnable_inventory().update(cx, |inventory, cx| {
+                    inventory.add_source(static_source, cx);
+                    inventory.add_source(scripts_source, cx);
+                })
             });
         }
         cx.spawn(|workspace_handle, mut cx| async move {
@@ -565,7 +571,10 @@ pub fn handle_keymap_file_changes(
 fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
-    keymap_content.clone().add_to_cx(cx).log_err();
+    keymap_content
+        .clone()
+        .add_to_cx_with_source(Some(&paths::KEYMAP.compact().to_string_lossy()), cx)
+        .log_err();
     cx.set_menus(app_menus());
 }
 
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use gpui::{actions, impl_actions};
 use serde::Deserialize;
 
@@ -20,6 +22,59 @@ pub struct OpenZedUrl {
     pub url: String,
 }
 
-impl_actions!(zed, [OpenBrowser, OpenZedUrl]);
+/// Executes a command of the command palette, the way confirming it in the palette does.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteCommand {
+    /// Name of the command's action (`"editor::Format"`), of the command (`"editor: format"`) or its synonym.
+    pub name: String,
+    /// Arguments to build the action with, as in the keymap.
+    #[serde(default)]
+    pub args: Option<serde_json::Value>,
+    /// Whether to execute the commands hidden from the palette too.
+    #[serde(default)]
+    pub include_hidden: bool,
+}
+
+/// Runs the script as a one-shot runnable in its directory, once the user trusts the worktree it belongs to.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct RunScript {
+    pub path: PathBuf,
+}
+
+impl_actions!(zed, [OpenBrowser, OpenZedUrl, ExecuteCommand, RunScript]);
+
+/// Opens the runnables modal. With the cwd override given, the runnables scheduled from it run in that directory,
+/// instead of their own or the worktree's one.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+pub struct Spawn {
+    #[serde(default)]
+    pub cwd_override: Option<PathBuf>,
+}
+
+/// Schedules one of the project's runnables the way the runnables modal does:
+/// once the user trusts the worktree it comes from.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct ScheduleRunnable {
+    pub runnable: ProjectRunnable,
+    /// Root of the worktree the one-shot runnables run in, asked trusting before they do.
+    #[serde(default)]
+    pub worktree_root: Option<PathBuf>,
+}
+
+/// The runnable to schedule with [`ScheduleRunnable`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectRunnable {
+    /// The runnable with the label given, among the ones listed in the runnables modal.
+    Label(String),
+    /// A one-shot runnable of the command line given.
+    Command(String),
+}
+
+impl_actions!(runnables, [Spawn, ScheduleRunnable]);
+
+// Stops every runnable running in the workspace's terminals and drops the queued ones.
+actions!(runnables, [CancelAll]);
 
 actions!(zed, [OpenSettings, Quit]);